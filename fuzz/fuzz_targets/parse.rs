@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xdg_desktop_entry::{DesktopEntry, ParseOptions, UnknownEscapes};
+
+fuzz_target!(|input: &str| {
+    let options = ParseOptions {
+        unknown_escapes: UnknownEscapes::Preserve,
+        ..Default::default()
+    };
+
+    for options in [ParseOptions::default(), options] {
+        let Ok(document) = DesktopEntry::parse_with_options(input, &options) else {
+            continue;
+        };
+
+        // A parsed document must be written back in a form that parses to the same output
+        let written = document.to_string();
+        let reparsed = DesktopEntry::parse(&written).expect("written document should parse");
+
+        assert_eq!(written, reparsed.to_string());
+    }
+});
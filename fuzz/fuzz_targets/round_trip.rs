@@ -0,0 +1,73 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xdg_desktop_entry::{DesktopEntry, ParseOptions, Value};
+
+/// Groups with their keys, in the document order.
+fn keys(document: &DesktopEntry) -> Vec<(String, Vec<String>)> {
+    document
+        .groups()
+        .map(|(header, entries)| {
+            let keys = entries.keys().map(ToString::to_string).collect();
+
+            (header.to_string(), keys)
+        })
+        .collect()
+}
+
+/// Checks if the parsed value is the generated one.
+///
+/// A string can be parsed as a boolean or a number, like `true` or `1`, then only its written text
+/// is compared.
+fn same_value(generated: &Value, parsed: &Value) -> bool {
+    match (generated, parsed) {
+        (
+            Value::String(generated) | Value::LocaleString(generated),
+            Value::String(parsed) | Value::LocaleString(parsed),
+        ) => generated == parsed,
+        (Value::String(_) | Value::LocaleString(_), _) => true,
+        (Value::Numeric(generated), Value::Numeric(parsed)) => {
+            generated == parsed || (generated.is_nan() && parsed.is_nan())
+        }
+        (generated, parsed) => generated == parsed,
+    }
+}
+
+fuzz_target!(|document: DesktopEntry<'static>| {
+    let written = document.to_string();
+
+    let options = ParseOptions {
+        keep_comments: true,
+        ..Default::default()
+    };
+    let parsed =
+        DesktopEntry::parse_with_options(&written, &options).expect("written document should parse");
+
+    // The values are typed by their content, a string like `1` is parsed as a number, but they
+    // are written back with their source text
+    assert_eq!(keys(&document), keys(&parsed));
+    assert_eq!(written, parsed.to_string());
+
+    for (header, entries) in document.groups() {
+        assert_eq!(
+            document.group_comments(header),
+            parsed.group_comments(header)
+        );
+
+        for (key, value) in entries {
+            let parsed_value = parsed.group(header).and_then(|entries| entries.get(key));
+
+            assert!(
+                parsed_value.is_some_and(|parsed| same_value(value, parsed)),
+                "{header} {key}: {value:?} != {parsed_value:?}"
+            );
+            assert_eq!(
+                document.entry_comments(header, key),
+                parsed.entry_comments(header, key)
+            );
+        }
+    }
+
+    assert_eq!(document.trailing_comments(), parsed.trailing_comments());
+    assert_eq!(document.has_byte_order_mark(), parsed.has_byte_order_mark());
+});
@@ -0,0 +1,126 @@
+//! Additional application actions, ready to be displayed in a context menu.
+//!
+//! ```
+//! use xdg_desktop_entry::{DesktopEntry, Locale};
+//!
+//! let document = DesktopEntry::parse(
+//!     "[Desktop Entry]\nName=Foo\nActions=new-window;private;\n[Desktop Action new-window]\nName=New Window\nName[de]=Neues Fenster\nExec=foo --new-window\n[Desktop Action private]\nName=New Private Window\nIcon=foo-private\nExec=foo --private\n",
+//! )
+//! .unwrap();
+//!
+//! let locales = [Locale::parse("de_DE").unwrap()];
+//! let names: Vec<String> = document
+//!     .list_actions(&locales)
+//!     .map(|action| action.name.to_string())
+//!     .collect();
+//!
+//! assert_eq!(vec!["Neues Fenster", "New Private Window"], names);
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    action_group_name, gkeyfile::value_text, parse_action_group_name, DesktopEntry, Locale,
+    DESKTOP_ENTRY_GROUP,
+};
+
+/// Action of an application with its name and icon resolved for a locale chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action<'d> {
+    /// Identifier of the action in the `Actions` key.
+    pub id: &'d str,
+    /// Header of the group of the action, like `Desktop Action new-window`.
+    pub group: &'d str,
+    pub name: Cow<'d, str>,
+    pub icon: Option<Cow<'d, str>>,
+    pub exec: Option<Cow<'d, str>>,
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Returns the actions listed in the `Actions` key, in its order, with the `Name` and `Icon`
+    /// of the first locale of the chain that has a translation.
+    ///
+    /// The values fallback to the non localized ones. The actions without a group or a `Name`
+    /// are skipped, since they can't be displayed.
+    pub fn list_actions<'d>(&'d self, locales: &'d [Locale]) -> impl Iterator<Item = Action<'d>> {
+        let actions = self
+            .get(DESKTOP_ENTRY_GROUP, "Actions")
+            .map(value_text)
+            .unwrap_or_default();
+
+        let actions: Vec<Cow<'d, str>> = match actions {
+            Cow::Borrowed(actions) => split(actions).map(Cow::Borrowed).collect(),
+            Cow::Owned(actions) => split(&actions)
+                .map(|action| Cow::Owned(action.to_string()))
+                .collect(),
+        };
+
+        actions.into_iter().filter_map(move |action| {
+            let (group, _) = self
+                .groups
+                .get_key_value(action_group_name(&action).as_str())?;
+            let id = parse_action_group_name(group)?;
+
+            let name = self.localized_in_chain(group, "Name", locales)?;
+
+            Some(Action {
+                id,
+                group,
+                name: value_text(name),
+                icon: self
+                    .localized_in_chain(group, "Icon", locales)
+                    .map(value_text),
+                exec: self.get(group, "Exec").map(value_text),
+            })
+        })
+    }
+}
+
+fn split(actions: &str) -> impl Iterator<Item = &str> {
+    actions.split(';').filter(|action| !action.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_list_actions_for_locale_chain() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nName=Foo\nActions=new;missing;private;unnamed;\n[Desktop Action new]\nName=New Window\nName[it]=Nuova finestra\nName[de]=Neues Fenster\nIcon=foo-new\nIcon[de]=foo-neu\nExec=foo --new\n[Desktop Action private]\nName=New Private Window\nName[it]=Nuova finestra anonima\n[Desktop Action unnamed]\nExec=foo\n",
+        )
+        .unwrap();
+
+        let locales = [
+            Locale::parse("fr_FR").unwrap(),
+            Locale::parse("de").unwrap(),
+            Locale::parse("it").unwrap(),
+        ];
+
+        assert_eq!(
+            vec![
+                Action {
+                    id: "new",
+                    group: "Desktop Action new",
+                    name: Cow::from("Neues Fenster"),
+                    icon: Some(Cow::from("foo-neu")),
+                    exec: Some(Cow::from("foo --new")),
+                },
+                Action {
+                    id: "private",
+                    group: "Desktop Action private",
+                    name: Cow::from("Nuova finestra anonima"),
+                    icon: None,
+                    exec: None,
+                },
+            ],
+            document.list_actions(&locales).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Cow::from("New Window")),
+            document.list_actions(&[]).next().map(|action| action.name)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Interoperability with the [AppStream] metadata of the applications.
+//!
+//! The component ID of a desktop application is its desktop file ID without the `.desktop`
+//! extension, like `org.example.App`. The older components kept the extension in the ID, and
+//! the metainfo files can reference the desktop file with a `<launchable type="desktop-id">`.
+//!
+//! With the `appstream` feature, a minimal desktop entry can be generated from a metainfo file,
+//! for the applications that don't install one.
+//!
+//! [AppStream]: https://www.freedesktop.org/software/appstream/docs/
+
+use crate::loader::DESKTOP_EXTENSION;
+
+/// Returns the AppStream component ID of an application from its desktop file ID, like
+/// `org.example.App` for `org.example.App.desktop`.
+pub fn component_id(desktop_file_id: &str) -> &str {
+    desktop_file_id
+        .strip_suffix(DESKTOP_EXTENSION)
+        .and_then(|id| id.strip_suffix('.'))
+        .unwrap_or(desktop_file_id)
+}
+
+/// Returns the desktop file ID of an application from its AppStream component ID, like
+/// `org.example.App.desktop` for `org.example.App`.
+///
+/// The legacy IDs ending with `.desktop` are kept as they are.
+pub fn desktop_file_id(component_id: &str) -> String {
+    if component_id
+        .strip_suffix(DESKTOP_EXTENSION)
+        .is_some_and(|id| id.ends_with('.'))
+    {
+        return component_id.to_string();
+    }
+
+    format!("{component_id}.{DESKTOP_EXTENSION}")
+}
+
+#[cfg(feature = "appstream")]
+pub use self::metainfo::{from_metainfo, GeneratedEntry, MetainfoError};
+
+#[cfg(feature = "appstream")]
+mod metainfo {
+    use std::borrow::Cow;
+
+    use roxmltree::{Document, Node};
+
+    use super::desktop_file_id;
+    use crate::{DesktopEntry, Locale, Value, DESKTOP_ENTRY_GROUP};
+
+    /// Namespace of the `xml:lang` attribute.
+    const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+    /// Error for an invalid metainfo file.
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    pub enum MetainfoError {
+        #[error("couldn't parse the metainfo XML")]
+        Xml(#[from] roxmltree::Error),
+        #[error("the root element is not a component")]
+        NotComponent,
+        #[error("the component is not a desktop application")]
+        NotDesktopApplication,
+        #[error("missing <{0}> element")]
+        MissingElement(&'static str),
+    }
+
+    /// Desktop entry generated from a metainfo file.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct GeneratedEntry {
+        /// Desktop file ID, from the `desktop-id` launchable or the component ID.
+        pub desktop_file_id: String,
+        pub document: DesktopEntry<'static>,
+    }
+
+    /// Generates a minimal desktop entry from an AppStream metainfo file.
+    ///
+    /// The entry has the translated `Name` and `Comment` from the name and summary of the
+    /// component, the `Icon` from the stock icon, the `Exec` from the first provided binary, and
+    /// the `Categories`, `Keywords` and `MimeType` lists.
+    ///
+    /// # Errors
+    ///
+    /// If the XML is malformed, or the component is not a desktop application with an ID and a
+    /// name.
+    pub fn from_metainfo(input: &str) -> Result<GeneratedEntry, MetainfoError> {
+        let xml = Document::parse(input)?;
+        let component = xml.root_element();
+
+        if !component.has_tag_name("component") {
+            return Err(MetainfoError::NotComponent);
+        }
+
+        if !matches!(
+            component.attribute("type"),
+            Some("desktop-application" | "desktop")
+        ) {
+            return Err(MetainfoError::NotDesktopApplication);
+        }
+
+        let id = child_text(component, "id").ok_or(MetainfoError::MissingElement("id"))?;
+
+        let mut document = DesktopEntry::default();
+
+        let mut set = |key: &str, locale: Option<&str>, value: Value<'static>| {
+            let locale = locale.and_then(Locale::parse).map(Locale::into_owned);
+
+            document.set_localized(DESKTOP_ENTRY_GROUP, key.to_string(), locale, value);
+        };
+
+        set("Type", None, string("Application"));
+
+        let names = translations(component, "name");
+
+        if !names.iter().any(|(locale, _)| locale.is_none()) {
+            return Err(MetainfoError::MissingElement("name"));
+        }
+
+        for (locale, name) in names {
+            set(
+                "Name",
+                locale,
+                Value::LocaleString(Cow::Owned(name.to_string())),
+            );
+        }
+
+        for (locale, summary) in translations(component, "summary") {
+            set(
+                "Comment",
+                locale,
+                Value::LocaleString(Cow::Owned(summary.to_string())),
+            );
+        }
+
+        let icon = children(component, "icon")
+            .find(|icon| icon.attribute("type") == Some("stock"))
+            .and_then(|icon| icon.text());
+
+        if let Some(icon) = icon {
+            set("Icon", None, string(icon.trim()));
+        }
+
+        let provides = children(component, "provides").next();
+
+        let binary = provides
+            .and_then(|provides| child_text(provides, "binary"))
+            .map(str::to_string);
+
+        if let Some(binary) = binary {
+            set("Exec", None, string(&binary));
+        }
+
+        let lists = [
+            ("Categories", "categories", "category"),
+            ("Keywords", "keywords", "keyword"),
+        ];
+
+        for (key, parent, item) in lists {
+            let items = children(component, parent)
+                .flat_map(|list| children(list, item))
+                .filter(|node| lang(*node).is_none())
+                .filter_map(|node| node.text());
+
+            if let Some(list) = join_list(items) {
+                set(key, None, string(&list));
+            }
+        }
+
+        let mime_types = provides
+            .into_iter()
+            .flat_map(|provides| children(provides, "mediatype"))
+            .filter_map(|node| node.text());
+
+        if let Some(list) = join_list(mime_types) {
+            set("MimeType", None, string(&list));
+        }
+
+        let desktop_file_id = children(component, "launchable")
+            .find(|launchable| launchable.attribute("type") == Some("desktop-id"))
+            .and_then(|launchable| launchable.text())
+            .map(|id| id.trim().to_string())
+            .unwrap_or_else(|| desktop_file_id(id));
+
+        Ok(GeneratedEntry {
+            desktop_file_id,
+            document,
+        })
+    }
+
+    fn string(value: &str) -> Value<'static> {
+        Value::String(Cow::Owned(value.to_string()))
+    }
+
+    fn children<'a, 'i>(
+        node: Node<'a, 'i>,
+        name: &'static str,
+    ) -> impl Iterator<Item = Node<'a, 'i>> {
+        node.children()
+            .filter(move |child| child.has_tag_name(name))
+    }
+
+    fn child_text<'a>(node: Node<'a, '_>, name: &'static str) -> Option<&'a str> {
+        children(node, name)
+            .find(|child| lang(*child).is_none())
+            .and_then(|child| child.text())
+            .map(str::trim)
+    }
+
+    fn lang<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+        node.attribute((XML_NAMESPACE, "lang"))
+    }
+
+    /// Returns the untranslated text of the elements, with a [`None`] locale, and its
+    /// translations.
+    fn translations<'a>(node: Node<'a, '_>, name: &'static str) -> Vec<(Option<&'a str>, &'a str)> {
+        children(node, name)
+            .filter_map(|child| Some((lang(child), child.text()?.trim())))
+            .filter(|(_, text)| !text.is_empty())
+            .collect()
+    }
+
+    /// Joins the items in a list terminated by `;`, [`None`] if empty.
+    fn join_list<'a>(items: impl Iterator<Item = &'a str>) -> Option<String> {
+        let list = items
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .fold(String::new(), |list, item| list + item + ";");
+
+        (!list.is_empty()).then_some(list)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_convert_ids() {
+        assert_eq!("org.example.App", component_id("org.example.App.desktop"));
+        assert_eq!("org.example.App", component_id("org.example.App"));
+        assert_eq!(
+            "org.example.App.desktop",
+            desktop_file_id("org.example.App")
+        );
+        assert_eq!("gimp.desktop", desktop_file_id("gimp.desktop"));
+    }
+
+    #[cfg(feature = "appstream")]
+    #[test]
+    fn should_generate_entry_from_metainfo() {
+        use crate::{Locale, Value, DESKTOP_ENTRY_GROUP};
+
+        let metainfo = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>org.example.Foo</id>
+  <name>Foo</name>
+  <name xml:lang="de">Fu</name>
+  <summary>Views foo files</summary>
+  <launchable type="desktop-id">org.example.Foo.desktop</launchable>
+  <icon type="stock">org.example.Foo</icon>
+  <categories>
+    <category>Graphics</category>
+    <category>Viewer</category>
+  </categories>
+  <provides>
+    <binary>foo</binary>
+    <mediatype>image/x-foo</mediatype>
+  </provides>
+</component>
+"#;
+
+        let generated = from_metainfo(metainfo).unwrap();
+
+        assert_eq!("org.example.Foo.desktop", generated.desktop_file_id);
+        assert_eq!(
+            "[Desktop Entry]\nType=Application\nName=Foo\nName[de]=Fu\nComment=Views foo files\nIcon=org.example.Foo\nExec=foo\nCategories=Graphics;Viewer;\nMimeType=image/x-foo;\n",
+            generated.document.to_string()
+        );
+        assert_eq!(
+            Some(&Value::LocaleString("Fu".into())),
+            generated.document.get_localized(
+                DESKTOP_ENTRY_GROUP,
+                "Name",
+                Locale::parse("de_DE").as_ref()
+            )
+        );
+
+        assert_eq!(
+            Err(MetainfoError::NotDesktopApplication),
+            from_metainfo("<component type=\"addon\"><id>foo</id></component>")
+        );
+        assert_eq!(
+            Err(MetainfoError::MissingElement("name")),
+            from_metainfo("<component type=\"desktop-application\"><id>foo</id></component>")
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! [`Arbitrary`] implementations to generate valid documents for fuzzing.
+//!
+//! The generated keys, locales and group headers only contain the characters allowed by the
+//! specification, so the documents can always be written and parsed back.
+
+use std::borrow::Cow;
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::Comment;
+use crate::{DesktopEntry, EntryMap, Key, LineEnding, Locale, Value};
+
+/// Generates a non empty string with only the allowed characters, using the fallback if no
+/// generated character is allowed.
+fn arbitrary_str(
+    u: &mut Unstructured<'_>,
+    allowed: fn(char) -> bool,
+    fallback: &'static str,
+) -> Result<Cow<'static, str>> {
+    let value = String::arbitrary(u)?
+        .chars()
+        .filter(|c| allowed(*c))
+        .collect::<String>();
+
+    if value.is_empty() {
+        return Ok(Cow::Borrowed(fallback));
+    }
+
+    Ok(Cow::Owned(value))
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-'
+}
+
+fn is_header_char(c: char) -> bool {
+    c.is_ascii() && !c.is_control() && c != '[' && c != ']'
+}
+
+fn arbitrary_key_part(u: &mut Unstructured<'_>) -> Result<Cow<'static, str>> {
+    arbitrary_str(u, is_key_char, "Key")
+}
+
+fn arbitrary_opt_key_part(u: &mut Unstructured<'_>) -> Result<Option<Cow<'static, str>>> {
+    if bool::arbitrary(u)? {
+        arbitrary_key_part(u).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Locale<'static> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Locale {
+            lang: arbitrary_str(u, is_key_char, "en")?,
+            country: arbitrary_opt_key_part(u)?,
+            encoding: arbitrary_opt_key_part(u)?,
+            modifier: arbitrary_opt_key_part(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Key<'static> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let key = arbitrary_key_part(u)?;
+
+        if bool::arbitrary(u)? {
+            Ok(Key::Localized {
+                key,
+                locale: Locale::arbitrary(u)?,
+            })
+        } else {
+            Ok(Key::Simple(key))
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for Value<'static> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let value = match u.int_in_range(0..=3)? {
+            0 => Value::String(Cow::Owned(
+                String::arbitrary(u)?
+                    .chars()
+                    .filter(char::is_ascii)
+                    .collect(),
+            )),
+            1 => Value::LocaleString(Cow::Owned(String::arbitrary(u)?)),
+            2 => Value::Boolean(bool::arbitrary(u)?),
+            _ => Value::Numeric(f32::arbitrary(u)?),
+        };
+
+        Ok(value)
+    }
+}
+
+impl<'a> Arbitrary<'a> for DesktopEntry<'static> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut document = DesktopEntry::default();
+
+        for _ in 0..u.arbitrary_len::<(String, Vec<(Key, Value)>)>()? {
+            let header = arbitrary_str(u, is_header_char, "Desktop Entry")?;
+
+            let entries = u
+                .arbitrary_iter::<(Key, Value)>()?
+                .collect::<Result<EntryMap>>()?;
+
+            document.groups.insert(header, entries);
+        }
+
+        for _ in 0..u.arbitrary_len::<(usize, usize, bool, String)>()? {
+            let group = usize::arbitrary(u)?;
+            let entry = usize::arbitrary(u)?;
+
+            let comment = if bool::arbitrary(u)? {
+                let text = String::arbitrary(u)?
+                    .chars()
+                    .filter(|c| !matches!(c, '\n' | '\r'))
+                    .collect::<String>();
+
+                Comment::Comment(Cow::Owned(format!("#{text}")))
+            } else {
+                let white_space = String::arbitrary(u)?
+                    .chars()
+                    .filter(|c| matches!(c, ' ' | '\t'))
+                    .collect::<String>();
+
+                Comment::EmptyLine {
+                    white_space: (!white_space.is_empty()).then_some(Cow::Owned(white_space)),
+                }
+            };
+
+            document
+                .comments
+                .attach(&document.groups, group, entry, comment);
+        }
+
+        document.line_ending = if bool::arbitrary(u)? {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        };
+        document.byte_order_mark = bool::arbitrary(u)?;
+
+        Ok(document)
+    }
+}
@@ -0,0 +1,423 @@
+//! Analysis of the text of a desktop file for the language server features.
+
+use std::borrow::Cow;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Hover, HoverContents,
+    MarkupContent, MarkupKind, NumberOrString, Position, Range,
+};
+use xdg_desktop_entry::{
+    action_group_name,
+    categories::{ADDITIONAL_CATEGORIES, MAIN_CATEGORIES, RESERVED_CATEGORIES},
+    keys::{self, KeyInfo, ValueType, ACTION_KEYS, KEYS},
+    parse_action_group_name,
+    raw::{parse_group_header, parse_key, parse_separator},
+    validate::{self, Severity},
+    DesktopEntry, Key, ParseWarning, DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+/// Line of the document, classified with the parsers of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line<'a> {
+    GroupHeader(Cow<'a, str>),
+    Entry {
+        /// Name of the key, without the locale.
+        key: Cow<'a, str>,
+        /// Byte offset of the end of the key, including the locale.
+        key_end: usize,
+        /// Byte offset of the start of the value.
+        value_start: usize,
+    },
+    Other,
+}
+
+impl<'a> Line<'a> {
+    fn classify(text: &'a str) -> Self {
+        if let Ok((_, header)) = parse_group_header::<()>(text) {
+            return Line::GroupHeader(header);
+        }
+
+        let Ok((rest, key)) = parse_key::<()>(text) else {
+            return Line::Other;
+        };
+
+        let key_end = text.len() - rest.len();
+
+        let Ok((value, _)) = parse_separator::<()>(rest) else {
+            return Line::Other;
+        };
+
+        let key = match key {
+            Key::Simple(key) | Key::Localized { key, .. } => key,
+        };
+
+        Line::Entry {
+            key,
+            key_end,
+            value_start: text.len() - value.len(),
+        }
+    }
+}
+
+/// Lines of the document with the group they are in.
+struct Lines<'a> {
+    lines: Vec<(&'a str, Option<Cow<'a, str>>, Line<'a>)>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut group = None;
+
+        let lines = text
+            .lines()
+            .map(|line| {
+                let line = line.strip_prefix('\u{FEFF}').unwrap_or(line);
+                let classified = Line::classify(line);
+
+                if let Line::GroupHeader(header) = &classified {
+                    group = Some(header.clone());
+                }
+
+                (line, group.clone(), classified)
+            })
+            .collect();
+
+        Self { lines }
+    }
+
+    fn get(&self, line: u32) -> Option<&(&'a str, Option<Cow<'a, str>>, Line<'a>)> {
+        self.lines.get(usize::try_from(line).ok()?)
+    }
+
+    /// Returns the line of a group header.
+    fn find_group(&self, header: &str) -> Option<u32> {
+        self.lines
+            .iter()
+            .position(|(_, _, line)| matches!(line, Line::GroupHeader(h) if h == header))
+            .and_then(|line| u32::try_from(line).ok())
+    }
+
+    /// Returns the line of a key in a group.
+    fn find_key(&self, header: &str, name: &str) -> Option<u32> {
+        self.lines
+            .iter()
+            .position(|(_, group, line)| {
+                group.as_deref() == Some(header)
+                    && matches!(line, Line::Entry { key, .. } if key == name)
+            })
+            .and_then(|line| u32::try_from(line).ok())
+    }
+
+    /// Range of the whole line.
+    fn line_range(&self, line: u32) -> Range {
+        let len = self.get(line).map_or(0, |(text, _, _)| utf16_len(text));
+
+        Range::new(Position::new(line, 0), Position::new(line, len))
+    }
+}
+
+fn utf16_len(text: &str) -> u32 {
+    u32::try_from(text.encode_utf16().count()).unwrap_or(u32::MAX)
+}
+
+/// Converts an UTF-16 column to a byte offset in the line.
+fn byte_offset(text: &str, character: u32) -> usize {
+    let mut utf16 = 0;
+
+    for (i, c) in text.char_indices() {
+        if utf16 >= character {
+            return i;
+        }
+
+        utf16 += u32::try_from(c.len_utf16()).unwrap_or(u32::MAX);
+    }
+
+    text.len()
+}
+
+/// Returns the diagnostics for the parse errors, warnings and validation issues.
+pub fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    let lines = Lines::new(text);
+
+    let document = match DesktopEntry::parse(text) {
+        Ok(document) => document,
+        Err(err) => {
+            let line = u32::try_from(err.line() - 1).unwrap_or(u32::MAX);
+            let mut range = lines.line_range(line);
+
+            if let Some((text, _, _)) = lines.get(line) {
+                let column = text
+                    .chars()
+                    .take(err.column() - 1)
+                    .map(char::len_utf16)
+                    .sum::<usize>();
+
+                range.start.character = u32::try_from(column).unwrap_or(u32::MAX);
+            }
+
+            return vec![diagnostic(
+                range,
+                DiagnosticSeverity::ERROR,
+                err.to_string(),
+            )];
+        }
+    };
+
+    let warnings = document.warnings().iter().map(|warning| {
+        let line = match warning {
+            ParseWarning::UnknownEscape { line, .. }
+            | ParseWarning::InvalidEncoding { line, .. } => *line,
+        };
+
+        diagnostic(
+            lines.line_range(u32::try_from(line - 1).unwrap_or(u32::MAX)),
+            DiagnosticSeverity::WARNING,
+            warning.to_string(),
+        )
+    });
+
+    let issues = validate::validate(&document).into_iter().map(|issue| {
+        let line = match (&issue.group, &issue.key) {
+            (Some(group), Some(key)) => lines
+                .find_key(group, key)
+                .or_else(|| lines.find_group(group)),
+            (Some(group), None) => lines.find_group(group),
+            _ => None,
+        };
+
+        let severity = match issue.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        };
+
+        Diagnostic {
+            code: Some(NumberOrString::String(issue.code.to_string())),
+            ..diagnostic(lines.line_range(line.unwrap_or(0)), severity, issue.message)
+        }
+    });
+
+    warnings.chain(issues).collect()
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("xdg-desktop-entry".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Returns the registry of the keys valid in a group.
+fn group_keys(group: Option<&str>) -> &'static [KeyInfo] {
+    match group {
+        Some(DESKTOP_ENTRY_GROUP) => KEYS,
+        Some(group) if group.starts_with(DESKTOP_ACTION_PREFIX) => ACTION_KEYS,
+        _ => &[],
+    }
+}
+
+fn lookup(group: Option<&str>, key: &str) -> Option<&'static KeyInfo> {
+    match group {
+        Some(DESKTOP_ENTRY_GROUP) => keys::lookup(key),
+        Some(group) if group.starts_with(DESKTOP_ACTION_PREFIX) => keys::lookup_action(key),
+        _ => None,
+    }
+}
+
+/// Returns the documentation of the key under the cursor.
+pub fn hover(text: &str, position: Position) -> Option<Hover> {
+    let lines = Lines::new(text);
+    let (line_text, group, line) = lines.get(position.line)?;
+
+    let value = match line {
+        Line::Entry { key, key_end, .. }
+            if byte_offset(line_text, position.character) <= *key_end =>
+        {
+            let info = lookup(group.as_deref(), key)?;
+
+            let list = if info.list { " list" } else { "" };
+
+            format!(
+                "**{}** (`{}`{list})\n\n{}",
+                info.name,
+                info.value_type.as_str(),
+                info.description
+            )
+        }
+        Line::GroupHeader(header) => match parse_action_group_name(header) {
+            Some(action) => format!("Additional application action `{action}`."),
+            None if header == DESKTOP_ENTRY_GROUP => "Main group of the desktop entry.".to_string(),
+            None => return None,
+        },
+        _ => return None,
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(lines.line_range(position.line)),
+    })
+}
+
+/// Returns the key names, or the values of the key, that can be completed at the cursor.
+pub fn completion(text: &str, position: Position) -> Vec<CompletionItem> {
+    let lines = Lines::new(text);
+
+    let Some((line_text, group, line)) = lines.get(position.line) else {
+        return Vec::new();
+    };
+
+    let cursor = byte_offset(line_text, position.character);
+
+    match line {
+        Line::Entry {
+            key, value_start, ..
+        } if cursor >= *value_start => {
+            let Some(info) = lookup(group.as_deref(), key) else {
+                return Vec::new();
+            };
+
+            let values: Vec<&str> = match (info.name, info.value_type) {
+                ("Type", _) => validate::ENTRY_TYPES.to_vec(),
+                ("Categories", _) => MAIN_CATEGORIES
+                    .iter()
+                    .chain(ADDITIONAL_CATEGORIES)
+                    .chain(RESERVED_CATEGORIES)
+                    .copied()
+                    .collect(),
+                (_, ValueType::Boolean) => vec!["true", "false"],
+                _ => Vec::new(),
+            };
+
+            values
+                .into_iter()
+                .map(|value| CompletionItem {
+                    label: value.to_string(),
+                    kind: Some(CompletionItemKind::VALUE),
+                    ..Default::default()
+                })
+                .collect()
+        }
+        Line::Entry { .. } | Line::Other if !line_text[..cursor].contains('=') => {
+            group_keys(group.as_deref())
+                .iter()
+                .map(|info| CompletionItem {
+                    label: info.name.to_string(),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some(info.value_type.as_str().to_string()),
+                    documentation: Some(lsp_types::Documentation::String(
+                        info.description.to_string(),
+                    )),
+                    insert_text: Some(format!("{}=", info.name)),
+                    ..Default::default()
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the range of the action group for the action under the cursor in the `Actions` key.
+pub fn definition(text: &str, position: Position) -> Option<Range> {
+    let lines = Lines::new(text);
+    let (line_text, group, line) = lines.get(position.line)?;
+
+    let Line::Entry {
+        key, value_start, ..
+    } = line
+    else {
+        return None;
+    };
+
+    if group.as_deref() != Some(DESKTOP_ENTRY_GROUP) || key != "Actions" {
+        return None;
+    }
+
+    let cursor = byte_offset(line_text, position.character);
+    if cursor < *value_start {
+        return None;
+    }
+
+    let value = &line_text[*value_start..];
+    let cursor = cursor - value_start;
+
+    let start = value[..cursor].rfind(';').map_or(0, |i| i + 1);
+    let end = value[cursor..]
+        .find(';')
+        .map_or(value.len(), |i| cursor + i);
+    let action = value[start..end].trim();
+
+    if action.is_empty() {
+        return None;
+    }
+
+    lines
+        .find_group(&action_group_name(action))
+        .map(|line| lines.line_range(line))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../../example/file.desktop");
+
+    #[test]
+    fn should_report_parse_error() {
+        let diagnostics = diagnostics("[Desktop Entry]\nType=Application\nName\n");
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            Range::new(Position::new(2, 0), Position::new(2, 4)),
+            diagnostics[0].range
+        );
+    }
+
+    #[test]
+    fn should_locate_validation_issue() {
+        let diagnostics = diagnostics("[Desktop Entry]\nName=Foo\nType=Foo\n");
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(2, diagnostics[0].range.start.line);
+    }
+
+    #[test]
+    fn should_hover_key() {
+        let hover = hover(EXAMPLE, Position::new(5, 2)).unwrap();
+
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+
+        assert!(content.value.starts_with("**Comment** (`localestring`)"));
+    }
+
+    #[test]
+    fn should_complete_keys_and_values() {
+        let keys = completion("[Desktop Entry]\nNa\n", Position::new(1, 2));
+        assert!(keys.iter().any(|item| item.label == "Name"));
+
+        let values = completion("[Desktop Entry]\nTerminal=\n", Position::new(1, 9));
+        assert_eq!(
+            vec!["true", "false"],
+            values
+                .iter()
+                .map(|item| item.label.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_go_to_action_group() {
+        // Cursor on `Create` in `Actions=Gallery;Create;`
+        let range = definition(EXAMPLE, Position::new(10, 18)).unwrap();
+
+        assert_eq!(16, range.start.line);
+    }
+}
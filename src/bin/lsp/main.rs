@@ -0,0 +1,182 @@
+//! Language server for desktop files, communicating over stdio.
+
+use std::{collections::HashMap, error::Error};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, Request as LspRequest},
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, Location, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Uri,
+};
+
+mod analysis;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["=".to_string(), ";".to_string()]),
+            ..Default::default()
+        }),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    Server::default().run(&connection)?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
+
+/// State of the server, with the text of the open documents.
+#[derive(Debug, Default)]
+struct Server {
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn run(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request);
+
+                    connection.sender.send(response.into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(notification) = self.handle_notification(notification)? {
+                        connection.sender.send(notification.into())?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.respond::<HoverRequest, _>(request, |params| {
+                let position = params.text_document_position_params;
+
+                self.documents
+                    .get(&position.text_document.uri)
+                    .and_then(|text| analysis::hover(text, position.position))
+            }),
+            Completion::METHOD => self.respond::<Completion, _>(request, |params| {
+                let position = params.text_document_position;
+
+                let items = self
+                    .documents
+                    .get(&position.text_document.uri)
+                    .map(|text| analysis::completion(text, position.position))
+                    .unwrap_or_default();
+
+                Some(CompletionResponse::Array(items))
+            }),
+            GotoDefinition::METHOD => self.respond::<GotoDefinition, _>(request, |params| {
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri;
+
+                self.documents
+                    .get(&uri)
+                    .and_then(|text| analysis::definition(text, position.position))
+                    .map(|range| GotoDefinitionResponse::Scalar(Location::new(uri, range)))
+            }),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request {}", request.method),
+            ),
+        }
+    }
+
+    /// Responds to the request with the result of the handler for its parameters.
+    fn respond<R, F>(&self, request: Request, handler: F) -> Response
+    where
+        R: LspRequest,
+        F: FnOnce(R::Params) -> R::Result,
+    {
+        let id = request.id.clone();
+
+        match request.extract::<R::Params>(R::METHOD) {
+            Ok((id, params)) => Response::new_ok(id, handler(params)),
+            Err(err) => Response::new_err(id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    /// Updates the open documents, returning the diagnostics to publish.
+    fn handle_notification(&mut self, notification: Notification) -> Result<Option<Notification>> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification
+                    .extract::<<DidOpenTextDocument as LspNotification>::Params>(
+                        DidOpenTextDocument::METHOD,
+                    )?;
+
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+
+                document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification
+                    .extract::<<DidChangeTextDocument as LspNotification>::Params>(
+                        DidChangeTextDocument::METHOD,
+                    )?;
+
+                // With full synchronization the last change is the whole document
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return Ok(None);
+                };
+
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), change.text);
+
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification
+                    .extract::<<DidCloseTextDocument as LspNotification>::Params>(
+                        DidCloseTextDocument::METHOD,
+                    )?;
+
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+
+                // Clear the diagnostics of the closed document
+                return Ok(Some(publish_diagnostics(uri, Vec::new())));
+            }
+            _ => return Ok(None),
+        };
+
+        let diagnostics = analysis::diagnostics(&self.documents[&uri]);
+
+        Ok(Some(publish_diagnostics(uri, diagnostics)))
+    }
+}
+
+fn publish_diagnostics(uri: Uri, diagnostics: Vec<lsp_types::Diagnostic>) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        PublishDiagnosticsParams::new(uri, diagnostics, None),
+    )
+}
@@ -0,0 +1,184 @@
+//! Registry of the categories defined by the Desktop Menu Specification.
+
+/// Main categories, an entry should have at least one of them.
+pub const MAIN_CATEGORIES: &[&str] = &[
+    "AudioVideo",
+    "Audio",
+    "Video",
+    "Development",
+    "Education",
+    "Game",
+    "Graphics",
+    "Network",
+    "Office",
+    "Science",
+    "Settings",
+    "System",
+    "Utility",
+];
+
+/// Additional categories, to be used together with a main category.
+pub const ADDITIONAL_CATEGORIES: &[&str] = &[
+    "Building",
+    "Debugger",
+    "IDE",
+    "GUIDesigner",
+    "Profiling",
+    "RevisionControl",
+    "Translation",
+    "Calendar",
+    "ContactManagement",
+    "Database",
+    "Dictionary",
+    "Chart",
+    "Email",
+    "Finance",
+    "FlowChart",
+    "PDA",
+    "ProjectManagement",
+    "Presentation",
+    "Spreadsheet",
+    "WordProcessor",
+    "2DGraphics",
+    "VectorGraphics",
+    "RasterGraphics",
+    "3DGraphics",
+    "Scanning",
+    "OCR",
+    "Photography",
+    "Publishing",
+    "Viewer",
+    "TextTools",
+    "DesktopSettings",
+    "HardwareSettings",
+    "Printing",
+    "PackageManager",
+    "Dialup",
+    "InstantMessaging",
+    "Chat",
+    "IRCClient",
+    "Feed",
+    "FileTransfer",
+    "HamRadio",
+    "News",
+    "P2P",
+    "RemoteAccess",
+    "Telephony",
+    "TelephonyTools",
+    "VideoConference",
+    "WebBrowser",
+    "WebDevelopment",
+    "Midi",
+    "Mixer",
+    "Sequencer",
+    "Tuner",
+    "TV",
+    "AudioVideoEditing",
+    "Player",
+    "Recorder",
+    "DiscBurning",
+    "ActionGame",
+    "AdventureGame",
+    "ArcadeGame",
+    "BoardGame",
+    "BlocksGame",
+    "CardGame",
+    "KidsGame",
+    "LogicGame",
+    "RolePlaying",
+    "Shooter",
+    "Simulation",
+    "SportsGame",
+    "StrategyGame",
+    "Art",
+    "Construction",
+    "Music",
+    "Languages",
+    "ArtificialIntelligence",
+    "Astronomy",
+    "Biology",
+    "Chemistry",
+    "ComputerScience",
+    "DataVisualization",
+    "Economy",
+    "Electricity",
+    "Geography",
+    "Geology",
+    "Geoscience",
+    "History",
+    "Humanities",
+    "ImageProcessing",
+    "Literature",
+    "Maps",
+    "Math",
+    "NumericalAnalysis",
+    "MedicalSoftware",
+    "Physics",
+    "Robotics",
+    "Spirituality",
+    "Sports",
+    "ParallelComputing",
+    "Amusement",
+    "Archiving",
+    "Compression",
+    "Electronics",
+    "Emulator",
+    "Engineering",
+    "FileTools",
+    "FileManager",
+    "TerminalEmulator",
+    "Filesystem",
+    "Monitor",
+    "Security",
+    "Accessibility",
+    "Calculator",
+    "Clock",
+    "TextEditor",
+    "Documentation",
+    "Adult",
+    "Core",
+    "KDE",
+    "GNOME",
+    "XFCE",
+    "DDE",
+    "GTK",
+    "Qt",
+    "Motif",
+    "Java",
+    "ConsoleOnly",
+];
+
+/// Reserved categories, that can only be used together with `OnlyShowIn`.
+pub const RESERVED_CATEGORIES: &[&str] = &["Screensaver", "TrayIcon", "Applet", "Shell"];
+
+/// Deprecated categories, with the category replacing them if there is one.
+pub const DEPRECATED_CATEGORIES: &[(&str, Option<&str>)] =
+    &[("Application", None), ("Applications", None)];
+
+/// Returns true if the category is one of the [`MAIN_CATEGORIES`].
+pub fn is_main(category: &str) -> bool {
+    MAIN_CATEGORIES.contains(&category)
+}
+
+/// Returns true if the category is one of the [`RESERVED_CATEGORIES`].
+pub fn is_reserved(category: &str) -> bool {
+    RESERVED_CATEGORIES.contains(&category)
+}
+
+/// Returns the replacement of a deprecated category, [`None`] if the category is not deprecated and
+/// `Some(None)` if it should be removed.
+pub fn replacement(category: &str) -> Option<Option<&'static str>> {
+    DEPRECATED_CATEGORIES
+        .iter()
+        .find(|(deprecated, _)| *deprecated == category)
+        .map(|(_, replacement)| *replacement)
+}
+
+/// Returns true if the category is defined by the specification.
+pub fn is_registered(category: &str) -> bool {
+    MAIN_CATEGORIES
+        .iter()
+        .chain(ADDITIONAL_CATEGORIES)
+        .chain(RESERVED_CATEGORIES)
+        .any(|registered| *registered == category)
+}
@@ -0,0 +1,151 @@
+//! Conformance runner parsing all the desktop files in a directory tree.
+//!
+//! It's meant for QA tools checking the files installed by a distribution, like the ones in
+//! `/usr/share/applications`, and as an integration test of the parser.
+//!
+//! ```no_run
+//! use xdg_desktop_entry::{corpus::check_corpus, ParseOptions};
+//!
+//! let report = check_corpus("/usr/share/applications", &ParseOptions::default())?;
+//!
+//! print!("{report}");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    error::Error as _,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{loader::DesktopFiles, DesktopEntry, Error, ParseOptions, ParseWarning};
+
+/// Result of parsing a single file of the corpus.
+#[derive(Debug)]
+pub struct FileReport {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Error reading or parsing the file.
+    pub error: Option<Error>,
+    /// Warnings reported while parsing the file.
+    pub warnings: Vec<ParseWarning>,
+    /// Time spent parsing the file, excluding the read.
+    pub elapsed: Duration,
+}
+
+impl FileReport {
+    /// Returns true if the file was read and parsed.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results of parsing all the files of a corpus, sorted by path.
+#[derive(Debug, Default)]
+pub struct CorpusReport {
+    pub files: Vec<FileReport>,
+}
+
+impl CorpusReport {
+    /// Returns the files that couldn't be read or parsed.
+    pub fn failures(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.is_ok())
+    }
+
+    /// Returns the files parsed with warnings.
+    pub fn with_warnings(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.warnings.is_empty())
+    }
+
+    /// Returns true if all the files were parsed.
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(FileReport::is_ok)
+    }
+
+    /// Total time spent parsing the files.
+    pub fn elapsed(&self) -> Duration {
+        self.files.iter().map(|file| file.elapsed).sum()
+    }
+}
+
+impl Display for CorpusReport {
+    /// Writes a line for each failure and warning, followed by a summary.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for file in &self.files {
+            if let Some(error) = &file.error {
+                write!(f, "FAIL {}: {error}", file.path.display())?;
+
+                if let Some(source) = error.source() {
+                    write!(f, ": {source}")?;
+                }
+
+                writeln!(f)?;
+            }
+
+            for warning in &file.warnings {
+                writeln!(f, "WARN {}: {warning}", file.path.display())?;
+            }
+        }
+
+        writeln!(
+            f,
+            "{} files, {} failed, {} warnings in {:?}",
+            self.files.len(),
+            self.failures().count(),
+            self.files
+                .iter()
+                .map(|file| file.warnings.len())
+                .sum::<usize>(),
+            self.elapsed()
+        )
+    }
+}
+
+/// Parses all the desktop files under the root directory, recursively.
+///
+/// The files are walked like [`DesktopFiles`], a directory reached again through a symbolic link
+/// is checked once and a missing root is an empty corpus.
+///
+/// # Errors
+///
+/// If a directory of the tree can't be read. The errors for the single files are in the report.
+pub fn check_corpus(root: impl AsRef<Path>, options: &ParseOptions) -> io::Result<CorpusReport> {
+    let files = DesktopFiles::new(root.as_ref())
+        .map(|(path, result)| result.map(|()| check_file(path, options)))
+        .collect::<io::Result<_>>()?;
+
+    Ok(CorpusReport { files })
+}
+
+/// Reads and parses a single desktop file.
+pub fn check_file(path: PathBuf, options: &ParseOptions) -> FileReport {
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            return FileReport {
+                path,
+                error: Some(Error::Io(err)),
+                warnings: Vec::new(),
+                elapsed: Duration::ZERO,
+            }
+        }
+    };
+
+    let start = Instant::now();
+    let result = DesktopEntry::parse_with_options(&content, options);
+    let elapsed = start.elapsed();
+
+    let (error, warnings) = match result {
+        Ok(document) => (None, document.warnings),
+        Err(err) => (Some(Error::Parse(err)), Vec::new()),
+    };
+
+    FileReport {
+        path,
+        error,
+        warnings,
+        elapsed,
+    }
+}
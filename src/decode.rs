@@ -0,0 +1,135 @@
+use std::{borrow::Cow, io::Read};
+
+use crate::{utf8, Error, ParseError, ParseErrorKind, ParseWarning, BYTE_ORDER_MARK};
+
+/// Decoding of the bytes of a desktop file, see [`DesktopEntry::from_bytes`].
+///
+/// [`DesktopEntry::from_bytes`]: crate::DesktopEntry::from_bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Decoding {
+    /// The input must be valid UTF-8, as required by the specification.
+    #[default]
+    Strict,
+    /// The invalid UTF-8 sequences are replaced with `U+FFFD`, reporting a
+    /// [`ParseWarning::InvalidEncoding`] for each line with replacements.
+    Lossy,
+    /// The input is transcoded from the encoding, replacing the malformed sequences like
+    /// [`Decoding::Lossy`].
+    #[cfg(feature = "encoding")]
+    Encoding(&'static encoding_rs::Encoding),
+    /// The encoding is detected from the byte order mark, or the input is UTF-8 if valid. A
+    /// `Legacy-Mixed` file is [decoded](crate::encoding::decode_legacy_mixed), otherwise the
+    /// input is transcoded from the fallback encoding.
+    #[cfg(feature = "encoding")]
+    Detect(&'static encoding_rs::Encoding),
+}
+
+/// Decodes the input, returning the text and the warnings for the replaced characters.
+pub(crate) fn decode(
+    input: &[u8],
+    decoding: Decoding,
+) -> Result<(Cow<'_, str>, Vec<ParseWarning>), ParseError> {
+    match decoding {
+        Decoding::Strict => utf8::from_utf8(input)
+            .map(|text| (Cow::Borrowed(text), Vec::new()))
+            .map_err(|err| {
+                let valid = &input[..err.valid_up_to()];
+                // The prefix is valid UTF-8
+                let valid = std::str::from_utf8(valid).unwrap_or_default();
+
+                ParseError::new(valid, valid.len(), ParseErrorKind::InvalidEncoding)
+            }),
+        Decoding::Lossy => {
+            let text = String::from_utf8_lossy(input);
+            let had_errors = matches!(text, Cow::Owned(_));
+
+            Ok(with_warnings(text, had_errors, "UTF-8"))
+        }
+        #[cfg(feature = "encoding")]
+        Decoding::Encoding(encoding) => {
+            let (text, had_errors) = encoding.decode_without_bom_handling(input);
+
+            Ok(with_warnings(text, had_errors, encoding.name()))
+        }
+        #[cfg(feature = "encoding")]
+        Decoding::Detect(fallback) => {
+            if let Some((encoding, len)) = encoding_rs::Encoding::for_bom(input) {
+                // The parser skips the UTF-8 byte order mark itself
+                let input = if encoding == encoding_rs::UTF_8 {
+                    input
+                } else {
+                    &input[len..]
+                };
+
+                return decode(input, Decoding::Encoding(encoding));
+            }
+
+            if let Ok(text) = utf8::from_utf8(input) {
+                return Ok((Cow::Borrowed(text), Vec::new()));
+            }
+
+            if crate::encoding::is_legacy_mixed(input) {
+                if let Ok(text) = crate::encoding::decode_legacy_mixed(input) {
+                    return Ok((Cow::Owned(text), Vec::new()));
+                }
+            }
+
+            decode(input, Decoding::Encoding(fallback))
+        }
+    }
+}
+
+/// Reads the input up to the file size limit, failing without reading the rest of a larger one.
+///
+/// The byte order mark is not counted in the size, like in the parser.
+pub(crate) fn read_limited(
+    mut reader: impl Read,
+    max_file_size: Option<usize>,
+) -> Result<Vec<u8>, Error> {
+    let mut input = Vec::new();
+
+    let Some(max_file_size) = max_file_size else {
+        reader.read_to_end(&mut input)?;
+
+        return Ok(input);
+    };
+
+    // Reads one more byte to know if the input is larger
+    let limit = max_file_size.saturating_add(BYTE_ORDER_MARK.len_utf8() + 1);
+    reader
+        .take(u64::try_from(limit).unwrap_or(u64::MAX))
+        .read_to_end(&mut input)?;
+
+    let content = input
+        .strip_prefix(BYTE_ORDER_MARK.encode_utf8(&mut [0; 4]).as_bytes())
+        .unwrap_or(&input);
+
+    if content.len() > max_file_size {
+        return Err(ParseError::new("", 0, ParseErrorKind::LimitExceeded).into());
+    }
+
+    Ok(input)
+}
+
+/// Reports the lines with the replacement character if the decoding had errors.
+fn with_warnings<'a>(
+    text: Cow<'a, str>,
+    had_errors: bool,
+    encoding: &str,
+) -> (Cow<'a, str>, Vec<ParseWarning>) {
+    if !had_errors {
+        return (text, Vec::new());
+    }
+
+    let warnings = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains(char::REPLACEMENT_CHARACTER))
+        .map(|(index, _)| ParseWarning::InvalidEncoding {
+            line: index + 1,
+            encoding: encoding.to_string(),
+        })
+        .collect();
+
+    (text, warnings)
+}
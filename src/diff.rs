@@ -0,0 +1,207 @@
+//! Unified diff of the changes to a desktop file.
+//!
+//! The [edits](crate::edit), the [migration](crate::migrate) and the [fixes](crate::lint::fix)
+//! return the changed source, the diff shows what would change so the changes can be reviewed
+//! before writing them.
+//!
+//! ```
+//! use xdg_desktop_entry::{diff::unified_diff, migrate::migrate};
+//!
+//! let input = "[Desktop Entry]\nEncoding=UTF-8\nName=Foo\n";
+//! let migrated = migrate(input);
+//!
+//! assert_eq!(
+//!     "--- foo.desktop\n+++ foo.desktop\n@@ -1,3 +1,2 @@\n [Desktop Entry]\n-Encoding=UTF-8\n Name=Foo\n",
+//!     unified_diff(input, &migrated.output, "foo.desktop", "foo.desktop")
+//! );
+//! ```
+
+use std::fmt::Write;
+
+/// Lines of context around the changes.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Returns the unified diff between the old and new text, empty if they are equal.
+///
+/// The lines are compared with their line ending, a missing line ending at the end of the text
+/// is marked like `diff` does.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let mut output = format!("--- {old_name}\n+++ {new_name}\n");
+
+    for hunk in hunks(&ops) {
+        write_hunk(&mut output, &ops, hunk, &old_lines, &new_lines);
+    }
+
+    output
+}
+
+/// Computes the operations to change the old lines in the new ones, with the longest common
+/// subsequence of the lines between the common prefix and suffix.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // Length of the common subsequence of the suffixes starting at each position
+    let width = new_mid.len() + 1;
+    let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = vec![Op::Equal; prefix];
+    let (mut i, mut j) = (0, 0);
+
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if j == new_mid.len()
+            || (i < old_mid.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            ops.push(Op::Delete);
+            i += 1;
+        } else {
+            ops.push(Op::Insert);
+            j += 1;
+        }
+    }
+
+    ops.extend(std::iter::repeat_n(Op::Equal, suffix));
+
+    ops
+}
+
+/// Groups the changes closer than twice the context, returning the ranges of the operations.
+fn hunks(ops: &[Op]) -> Vec<std::ops::Range<usize>> {
+    let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+
+    for (index, _) in ops.iter().enumerate().filter(|(_, op)| **op != Op::Equal) {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + 1 + CONTEXT).min(ops.len());
+
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+
+    hunks
+}
+
+fn write_hunk(
+    output: &mut String,
+    ops: &[Op],
+    hunk: std::ops::Range<usize>,
+    old: &[&str],
+    new: &[&str],
+) {
+    // Positions in the old and new lines at the start of the hunk
+    let before = &ops[..hunk.start];
+    let mut i = before.iter().filter(|op| **op != Op::Insert).count();
+    let mut j = before.iter().filter(|op| **op != Op::Delete).count();
+
+    let hunk = &ops[hunk];
+    let old_len = hunk.iter().filter(|op| **op != Op::Insert).count();
+    let new_len = hunk.iter().filter(|op| **op != Op::Delete).count();
+
+    // An empty range starts at the line before it
+    let start = |position: usize, len: usize| if len == 0 { position } else { position + 1 };
+
+    let _ = writeln!(
+        output,
+        "@@ -{},{old_len} +{},{new_len} @@",
+        start(i, old_len),
+        start(j, new_len)
+    );
+
+    for op in hunk {
+        let (marker, line) = match op {
+            Op::Equal => {
+                i += 1;
+                j += 1;
+
+                (' ', old[i - 1])
+            }
+            Op::Delete => {
+                i += 1;
+
+                ('-', old[i - 1])
+            }
+            Op::Insert => {
+                j += 1;
+
+                ('+', new[j - 1])
+            }
+        };
+
+        output.push(marker);
+        output.push_str(line);
+
+        if !line.ends_with('\n') {
+            output.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_diff_with_context() {
+        let old = "[Desktop Entry]\nType=Application\nName=Foo\nComment=Foo\nIcon=foo\nExec=foo\nTerminal=false\nCategories=Utility;\nKeywords=foo;\nMimeType=text/plain;\n";
+        let new = "[Desktop Entry]\nType=Application\nName=Bar\nComment=Foo\nIcon=foo\nExec=foo\nTerminal=false\nCategories=Utility;\nKeywords=foo;\nMimeType=text/plain;\nNoDisplay=true";
+
+        assert_eq!(
+            "--- a\n+++ b\n@@ -1,6 +1,6 @@\n [Desktop Entry]\n Type=Application\n-Name=Foo\n+Name=Bar\n Comment=Foo\n Icon=foo\n Exec=foo\n@@ -8,3 +8,4 @@\n Categories=Utility;\n Keywords=foo;\n MimeType=text/plain;\n+NoDisplay=true\n\\ No newline at end of file\n",
+            unified_diff(old, new, "a", "b")
+        );
+    }
+
+    #[test]
+    fn should_diff_empty_ranges() {
+        assert_eq!("", unified_diff("a\n", "a\n", "a", "b"));
+        assert_eq!(
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+[Desktop Entry]\n",
+            unified_diff("", "[Desktop Entry]\n", "a", "b")
+        );
+        assert_eq!(
+            "--- a\n+++ b\n@@ -1,1 +0,0 @@\n-[Desktop Entry]\n",
+            unified_diff("[Desktop Entry]\n", "", "a", "b")
+        );
+    }
+}
@@ -0,0 +1,535 @@
+//! Editing of desktop files keeping their formatting.
+//!
+//! Like the [fixes](crate::lint::fix) of the lints, the changes are applied to the source text:
+//! only the edited lines are changed, the comments and the layout of the file are kept.
+//!
+//! ```
+//! use xdg_desktop_entry::{edit::{self, Change}, Value};
+//!
+//! let input = "[Desktop Entry]\n# The command\nExec=foo\n";
+//!
+//! let output = edit::apply(
+//!     input,
+//!     &[
+//!         Change::set("Desktop Entry", "Exec", Value::String("foo --bar".into())),
+//!         Change::set("Desktop Entry", "NoDisplay", Value::Boolean(true)),
+//!     ],
+//! );
+//!
+//! assert_eq!("[Desktop Entry]\n# The command\nExec=foo --bar\nNoDisplay=true\n", output);
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    diff::unified_diff,
+    escape::{escape_list_item, split_list},
+    lint::{rewrite_lines, Edit},
+    loader::DesktopFiles,
+    token::{tokenize, TokenKind},
+    user::write_atomic,
+    LineEnding, Value, DESKTOP_ENTRY_GROUP,
+};
+
+/// Change to an entry of a desktop file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Sets the value of the key, adding it at the end of the group if missing.
+    ///
+    /// The group is added at the end of the file if missing.
+    Set {
+        group: String,
+        key: String,
+        locale: Option<String>,
+        value: Value<'static>,
+    },
+    /// Removes the key.
+    Remove {
+        group: String,
+        key: String,
+        locale: Option<String>,
+    },
+    /// Adds an item to a list of strings, like `Categories`, if not already present.
+    ///
+    /// The key is added if missing.
+    AddToList {
+        group: String,
+        key: String,
+        item: String,
+    },
+    /// Removes an item from a list of strings, removing the key if the list becomes empty.
+    RemoveFromList {
+        group: String,
+        key: String,
+        item: String,
+    },
+}
+
+impl Change {
+    /// Sets the default value of a key.
+    pub fn set(group: impl Into<String>, key: impl Into<String>, value: Value<'static>) -> Self {
+        Change::Set {
+            group: group.into(),
+            key: key.into(),
+            locale: None,
+            value,
+        }
+    }
+
+    /// Sets the value of a key for a locale, like `de_DE`.
+    pub fn set_localized(
+        group: impl Into<String>,
+        key: impl Into<String>,
+        locale: impl Into<String>,
+        value: Value<'static>,
+    ) -> Self {
+        Change::Set {
+            group: group.into(),
+            key: key.into(),
+            locale: Some(locale.into()),
+            value,
+        }
+    }
+
+    /// Removes the default value of a key.
+    pub fn remove(group: impl Into<String>, key: impl Into<String>) -> Self {
+        Change::Remove {
+            group: group.into(),
+            key: key.into(),
+            locale: None,
+        }
+    }
+
+    /// Sets the `Name` of the main group, for a locale if given.
+    pub fn set_name(name: impl Into<String>, locale: Option<&str>) -> Self {
+        Change::Set {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: "Name".to_string(),
+            locale: locale.map(str::to_string),
+            value: Value::LocaleString(Cow::Owned(name.into())),
+        }
+    }
+
+    /// Adds a category to the main group.
+    pub fn add_category(category: impl Into<String>) -> Self {
+        Self::add_to_list("Categories", category)
+    }
+
+    /// Removes a category from the main group.
+    pub fn remove_category(category: impl Into<String>) -> Self {
+        Self::remove_from_list("Categories", category)
+    }
+
+    /// Adds a MIME type to the main group.
+    pub fn add_mime_type(mime_type: impl Into<String>) -> Self {
+        Self::add_to_list("MimeType", mime_type)
+    }
+
+    /// Removes a MIME type from the main group.
+    pub fn remove_mime_type(mime_type: impl Into<String>) -> Self {
+        Self::remove_from_list("MimeType", mime_type)
+    }
+
+    fn add_to_list(key: &str, item: impl Into<String>) -> Self {
+        Change::AddToList {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: key.to_string(),
+            item: item.into(),
+        }
+    }
+
+    fn remove_from_list(key: &str, item: impl Into<String>) -> Self {
+        Change::RemoveFromList {
+            group: DESKTOP_ENTRY_GROUP.to_string(),
+            key: key.to_string(),
+            item: item.into(),
+        }
+    }
+
+    fn target(&self) -> Target<'_> {
+        match self {
+            Change::Set {
+                group, key, locale, ..
+            }
+            | Change::Remove { group, key, locale } => (group, key, locale.as_deref()),
+            Change::AddToList { group, key, .. } | Change::RemoveFromList { group, key, .. } => {
+                (group, key, None)
+            }
+        }
+    }
+
+    /// Applies the change to the escaped value of the entry, [`None`] if missing or removed.
+    fn apply_to(&self, value: Option<String>) -> Option<String> {
+        match self {
+            Change::Set { value, .. } => Some(value.to_string()),
+            Change::Remove { .. } => None,
+            Change::AddToList { item, .. } => {
+                let item = escape_list_item(item);
+                let mut value = value.unwrap_or_default();
+
+                if !split_list(&value).any(|current| current == item) {
+                    if !value.is_empty() && !value.ends_with(';') {
+                        value.push(';');
+                    }
+
+                    value.push_str(&item);
+                    value.push(';');
+                }
+
+                Some(value)
+            }
+            Change::RemoveFromList { item, .. } => {
+                let item = escape_list_item(item);
+                let value = value?;
+
+                if !split_list(&value).any(|current| current == item) {
+                    return Some(value);
+                }
+
+                let value = split_list(&value)
+                    .filter(|current| *current != item)
+                    .fold(String::new(), |list, current| list + current + ";");
+
+                (!value.is_empty()).then_some(value)
+            }
+        }
+    }
+}
+
+/// Group, key and locale of an entry.
+type Target<'c> = (&'c str, &'c str, Option<&'c str>);
+
+/// Applies the changes to the source, in order.
+///
+/// The changes of the same entry are applied one after the other, so a [`Change::Set`] replaces
+/// the result of the previous changes. All the lines with the key of a change are edited, the
+/// added entries use the line ending of the file.
+pub fn apply(input: &str, changes: &[Change]) -> String {
+    let mut targets = IndexMap::<Target, Vec<&Change>>::new();
+
+    for change in changes {
+        targets.entry(change.target()).or_default().push(change);
+    }
+
+    let resolve = |target: &Target, value: Option<&str>| {
+        targets[target]
+            .iter()
+            .fold(value.map(str::to_string), |value, change| {
+                change.apply_to(value)
+            })
+    };
+
+    let line_ending = LineEnding::detect(input).as_str();
+
+    // Last line of each group, after which the missing entries are added
+    let mut last_lines = HashMap::new();
+    let mut found = vec![false; targets.len()];
+
+    rewrite_lines(input, |number, group, line| {
+        let group = group?;
+
+        if !line.trim().is_empty() && !line.starts_with('#') {
+            last_lines.insert(group.to_string(), number);
+        }
+
+        if let Some(index) = entry_target(line)
+            .and_then(|(key, locale, _)| targets.get_index_of(&(group, key, locale)))
+        {
+            found[index] = true;
+        }
+
+        None
+    });
+
+    let missing = |group: &str| {
+        targets
+            .keys()
+            .zip(&found)
+            .filter(move |((target_group, ..), found)| !**found && *target_group == group)
+            .filter_map(|(target, _)| Some(entry_line(target, &resolve(target, None)?)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut output = rewrite_lines(input, |number, group, line| {
+        let group = group?;
+
+        let edit = entry_target(line).and_then(|(key, locale, prefix)| {
+            let target = (group, key, locale);
+
+            if !targets.contains_key(&target) {
+                return None;
+            }
+
+            let current = &line[prefix.len()..];
+
+            let edit = match resolve(&target, Some(current)) {
+                Some(value) if value == current => return None,
+                Some(value) => Edit::Replace(format!("{prefix}{value}")),
+                None => Edit::Remove,
+            };
+
+            Some(edit)
+        });
+
+        if last_lines.get(group) != Some(&number) {
+            return edit;
+        }
+
+        let entries = missing(group);
+
+        if entries.is_empty() {
+            return edit;
+        }
+
+        let mut text = match edit {
+            Some(Edit::Replace(text)) => text,
+            Some(Edit::Remove) => String::new(),
+            None => line.to_string(),
+        };
+
+        for entry in entries {
+            if !text.is_empty() {
+                text.push_str(line_ending);
+            }
+
+            text.push_str(&entry);
+        }
+
+        Some(Edit::Replace(text))
+    });
+
+    let mut added = HashSet::new();
+
+    for (group, ..) in targets.keys() {
+        if last_lines.contains_key(*group) || !added.insert(*group) {
+            continue;
+        }
+
+        let entries = missing(group);
+
+        if entries.is_empty() {
+            continue;
+        }
+
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push_str(line_ending);
+        }
+
+        output.push_str(&format!("[{group}]{line_ending}"));
+
+        for entry in entries {
+            output.push_str(&entry);
+            output.push_str(line_ending);
+        }
+    }
+
+    output
+}
+
+/// Applies the changes to a desktop file, returning true if it was changed.
+///
+/// The file is written only if changed, replacing it at once.
+///
+/// # Errors
+///
+/// If the file can't be read or written.
+pub fn apply_to_file(path: &Path, changes: &[Change]) -> io::Result<bool> {
+    let input = fs::read_to_string(path)?;
+    let output = apply(&input, changes);
+
+    if output == input {
+        return Ok(false);
+    }
+
+    write_atomic(path, &output)?;
+
+    Ok(true)
+}
+
+/// Applies the changes to all the desktop files in the directory and its sub-directories.
+///
+/// Returns the result of each file, see [`apply_to_file`], in order of path.
+pub fn apply_to_dir(dir: &Path, changes: &[Change]) -> Vec<(PathBuf, io::Result<bool>)> {
+    DesktopFiles::new(dir)
+        .map(|(path, result)| {
+            let result = result.and_then(|()| apply_to_file(&path, changes));
+
+            (path, result)
+        })
+        .collect()
+}
+
+/// Returns the unified diff of the changes to a desktop file, without writing it.
+///
+/// The diff is empty if the file wouldn't change, see [`unified_diff`].
+///
+/// # Errors
+///
+/// If the file can't be read.
+pub fn preview_file(path: &Path, changes: &[Change]) -> io::Result<String> {
+    let input = fs::read_to_string(path)?;
+    let output = apply(&input, changes);
+
+    let name = path.to_string_lossy();
+
+    Ok(unified_diff(&input, &output, &name, &name))
+}
+
+/// Returns the unified diff of the changes to all the desktop files in the directory and its
+/// sub-directories, without writing them.
+///
+/// See [`preview_file`] and [`apply_to_dir`].
+pub fn preview_dir(dir: &Path, changes: &[Change]) -> Vec<(PathBuf, io::Result<String>)> {
+    DesktopFiles::new(dir)
+        .map(|(path, result)| {
+            let result = result.and_then(|()| preview_file(&path, changes));
+
+            (path, result)
+        })
+        .collect()
+}
+
+/// Returns the key and locale of an entry line, with the text up to the end of the separator.
+fn entry_target(line: &str) -> Option<(&str, Option<&str>, &str)> {
+    let tokens = tokenize(line);
+
+    let key = tokens
+        .first()
+        .filter(|token| token.kind == TokenKind::Key)?;
+    let separator = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Separator)?;
+    let locale = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Locale)
+        .filter(|token| token.span.start < separator.span.start)
+        .map(|token| token.text(line));
+
+    Some((key.text(line), locale, &line[..separator.span.end]))
+}
+
+fn entry_line((_, key, locale): &Target, value: &str) -> String {
+    match locale {
+        Some(locale) => format!("{key}[{locale}]={value}"),
+        None => format!("{key}={value}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_apply_changes_keeping_the_layout() {
+        let input = "# Header\r\n[Desktop Entry]\r\nName=Foo\r\nName[de]=Fuh\r\nExec = foo\r\n\r\n# Actions\r\n[Desktop Action New]\r\nExec=foo --new\r\n";
+
+        let output = apply(
+            input,
+            &[
+                Change::set("Desktop Entry", "Exec", Value::String("bar".into())),
+                Change::set_localized("Desktop Entry", "Name", "fr", Value::String("Fou".into())),
+                Change::Remove {
+                    group: "Desktop Entry".to_string(),
+                    key: "Name".to_string(),
+                    locale: Some("de".to_string()),
+                },
+                Change::remove("Desktop Action New", "Exec"),
+                Change::set("X-Foo", "Bar", Value::Boolean(true)),
+                Change::set("Desktop Entry", "Type", Value::String("Application".into())),
+                Change::set("X-Foo", "Baz", Value::Numeric(1.0)),
+            ],
+        );
+
+        assert_eq!(
+            "# Header\r\n[Desktop Entry]\r\nName=Foo\r\nExec = bar\r\nName[fr]=Fou\r\nType=Application\r\n\r\n# Actions\r\n[Desktop Action New]\r\n[X-Foo]\r\nBar=true\r\nBaz=1.0\r\n",
+            output
+        );
+    }
+
+    #[test]
+    fn should_add_group_to_file_without_final_newline() {
+        let output = apply(
+            "[Desktop Entry]\nName=Foo",
+            &[
+                Change::set("Desktop Entry", "Name", Value::String("Bar".into())),
+                Change::remove("Desktop Entry", "Missing"),
+                Change::set("X-Foo", "Baz", Value::String(" baz".into())),
+            ],
+        );
+
+        assert_eq!("[Desktop Entry]\nName=Bar\n[X-Foo]\nBaz=\\sbaz\n", output);
+    }
+
+    #[test]
+    fn should_edit_lists() {
+        let input =
+            "[Desktop Entry]\nName=Foo\nCategories=Utility;Development\nMimeType=text/plain;\n";
+
+        let output = apply(
+            input,
+            &[
+                Change::add_category("Development"),
+                Change::add_category("IDE"),
+                Change::remove_category("Utility"),
+                Change::remove_mime_type("text/plain"),
+                Change::add_mime_type("text/x-rust"),
+                Change::remove_mime_type("text/x-rust"),
+                Change::set_name("Fuh", Some("de")),
+            ],
+        );
+
+        assert_eq!(
+            "[Desktop Entry]\nName=Foo\nCategories=Development;IDE;\nName[de]=Fuh\n",
+            output
+        );
+        assert_eq!(
+            vec!["a", "b\\;c", "d"],
+            split_list("a;b\\;c;;d;").collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_edit_files_in_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+
+        fs::write(dir.join("foo.desktop"), "[Desktop Entry]\nName=Foo\n").unwrap();
+        fs::write(
+            dir.join("sub/bar.desktop"),
+            "[Desktop Entry]\nName=Bar\nCategories=Game;\n",
+        )
+        .unwrap();
+
+        let results = apply_to_dir(dir, &[Change::add_category("Game")])
+            .into_iter()
+            .map(|(path, result)| (path, result.unwrap()))
+            .collect::<Vec<_>>();
+
+        let foo = fs::read_to_string(dir.join("foo.desktop")).unwrap();
+        let previews = preview_dir(dir, &[Change::remove_category("Game")])
+            .into_iter()
+            .map(|(_, diff)| diff.unwrap().lines().count())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (dir.join("foo.desktop"), true),
+                (dir.join("sub/bar.desktop"), false),
+            ],
+            results
+        );
+        assert_eq!("[Desktop Entry]\nName=Foo\nCategories=Game;\n", foo);
+        // The files are not written by the preview
+        assert_eq!(vec![6, 6], previews);
+    }
+}
@@ -0,0 +1,276 @@
+//! Decoding of desktop files in the legacy encodings.
+//!
+//! Before version 1.0 of the specification, a desktop file with `Encoding=Legacy-Mixed` stored
+//! each translation in the legacy encoding of its locale, given by the encoding in the locale of
+//! the key or by a table of the specification. [`decode_legacy_mixed`] converts these files to
+//! UTF-8 so they can be parsed.
+//!
+//! ```
+//! use xdg_desktop_entry::{encoding::decode_legacy_mixed, DesktopEntry, Locale, Value};
+//!
+//! let input = b"[Desktop Entry]\nEncoding=Legacy-Mixed\nName=Foo\nName[de]=F\xfc\xfc\n";
+//! let decoded = decode_legacy_mixed(input)?;
+//!
+//! let entry = DesktopEntry::parse(&decoded)?;
+//! let locale = Locale::parse("de");
+//!
+//! assert_eq!(
+//!     Some(&Value::LocaleString("Füü".into())),
+//!     entry.get_localized("Desktop Entry", "Name", locale.as_ref())
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::borrow::Cow;
+
+use encoding_rs::{Encoding, UTF_8};
+
+use crate::{Locale, DESKTOP_ENTRY_GROUP};
+
+/// Value of the `Encoding` key for the legacy mixed encodings.
+pub const LEGACY_MIXED: &str = "Legacy-Mixed";
+
+/// Encodings of the locales in the `Legacy-Mixed` files, from the table of the specification.
+///
+/// The names are the labels of the encodings, `EUC-CN` is decoded as its superset GBK. The
+/// `ARMSCII-8`, `GEORGIAN-PS` and `TCVN-5712` encodings aren't supported.
+const LEGACY_ENCODINGS: &[(&str, &str)] = &[
+    ("be", "CP1251"),
+    ("bg", "CP1251"),
+    ("br", "ISO-8859-1"),
+    ("ca", "ISO-8859-1"),
+    ("cs", "ISO-8859-2"),
+    ("cy", "ISO-8859-14"),
+    ("da", "ISO-8859-1"),
+    ("de", "ISO-8859-1"),
+    ("el", "ISO-8859-7"),
+    ("en", "ISO-8859-1"),
+    ("eo", "ISO-8859-3"),
+    ("es", "ISO-8859-1"),
+    ("et", "ISO-8859-15"),
+    ("eu", "ISO-8859-1"),
+    ("fi", "ISO-8859-1"),
+    ("fr", "ISO-8859-1"),
+    ("ga", "ISO-8859-14"),
+    ("gl", "ISO-8859-1"),
+    ("hr", "ISO-8859-2"),
+    ("hu", "ISO-8859-2"),
+    ("hy", "ARMSCII-8"),
+    ("it", "ISO-8859-1"),
+    ("ja", "EUC-JP"),
+    ("ka", "GEORGIAN-PS"),
+    ("ko", "EUC-KR"),
+    ("lt", "ISO-8859-13"),
+    ("lv", "ISO-8859-13"),
+    ("mi", "ISO-8859-13"),
+    ("mk", "ISO-8859-5"),
+    ("nl", "ISO-8859-1"),
+    ("no", "ISO-8859-1"),
+    ("pl", "ISO-8859-2"),
+    ("pt", "ISO-8859-1"),
+    ("ro", "ISO-8859-2"),
+    ("ru", "KOI8-R"),
+    ("sk", "ISO-8859-2"),
+    ("sl", "ISO-8859-2"),
+    ("sp", "ISO-8859-5"),
+    ("sq", "ISO-8859-2"),
+    ("sr", "ISO-8859-2"),
+    ("sv", "ISO-8859-1"),
+    ("th", "TIS-620"),
+    ("tr", "ISO-8859-9"),
+    ("uk", "KOI8-U"),
+    ("vi", "TCVN-5712"),
+    ("wa", "ISO-8859-1"),
+    ("zh_CN", "GB2312"),
+    ("zh_TW", "BIG5"),
+];
+
+/// Error decoding a `Legacy-Mixed` desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LegacyMixedError {
+    #[error("unsupported encoding {encoding} at line {line}")]
+    UnsupportedEncoding { line: usize, encoding: String },
+    #[error("invalid {encoding} value at line {line}")]
+    Malformed { line: usize, encoding: &'static str },
+}
+
+/// Returns the name of the legacy encoding of a locale, if the specification defines one.
+///
+/// The encoding in the locale has precedence over the table of the specification.
+pub fn legacy_encoding_name<'l>(locale: &'l Locale) -> Option<&'l str> {
+    if let Some(encoding) = locale.encoding() {
+        return Some(encoding);
+    }
+
+    let lang = locale.lang();
+    let lang_country = locale.country().map(|country| format!("{lang}_{country}"));
+
+    let encoding = [lang_country.as_deref(), Some(lang)]
+        .into_iter()
+        .flatten()
+        .find_map(|name| {
+            LEGACY_ENCODINGS
+                .iter()
+                .find(|(locale, _)| *locale == name)
+                .map(|(_, encoding)| *encoding)
+        });
+
+    encoding
+}
+
+/// Checks if the `Encoding` key of the main group is `Legacy-Mixed`.
+pub fn is_legacy_mixed(input: &[u8]) -> bool {
+    let mut group = None;
+
+    input.split(|b| *b == b'\n').any(|line| {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix('[') {
+            group = header.strip_suffix(']').map(str::to_string);
+
+            return false;
+        }
+
+        group.as_deref() == Some(DESKTOP_ENTRY_GROUP)
+            && line.split_once('=').is_some_and(|(key, value)| {
+                key.trim() == "Encoding" && value.trim() == LEGACY_MIXED
+            })
+    })
+}
+
+/// Converts a `Legacy-Mixed` desktop file to UTF-8.
+///
+/// The values of the localized keys are decoded with the [encoding of their
+/// locale](legacy_encoding_name), the other lines must be UTF-8, except the comments that are
+/// decoded replacing the invalid characters. The `Encoding` key is set to `UTF-8`.
+///
+/// # Errors
+///
+/// If a value is invalid in its encoding, or the encoding is not supported.
+pub fn decode_legacy_mixed(input: &[u8]) -> Result<String, LegacyMixedError> {
+    let mut output = String::with_capacity(input.len());
+
+    for (index, line) in input.split_inclusive(|b| *b == b'\n').enumerate() {
+        let number = index + 1;
+        let text = line.trim_ascii_start();
+
+        if text.starts_with(b"#") {
+            output.push_str(&String::from_utf8_lossy(line));
+
+            continue;
+        }
+
+        let Some(separator) = line.iter().position(|b| *b == b'=') else {
+            output.push_str(&decode(line, UTF_8, number)?);
+
+            continue;
+        };
+
+        let (key, value) = line.split_at(separator + 1);
+        let key = decode(key, UTF_8, number)?;
+
+        let encoding = match locale_of_key(&key) {
+            Some(locale) => match legacy_encoding_name(&locale) {
+                Some(name) => Encoding::for_label(name.as_bytes()).ok_or_else(|| {
+                    LegacyMixedError::UnsupportedEncoding {
+                        line: number,
+                        encoding: name.to_string(),
+                    }
+                })?,
+                None => UTF_8,
+            },
+            None => UTF_8,
+        };
+
+        output.push_str(&key);
+
+        if key.trim_end_matches(['=', ' ', '\t']) == "Encoding"
+            && value.trim_ascii() == LEGACY_MIXED.as_bytes()
+        {
+            let line_ending = &value[value.trim_ascii_end().len()..];
+
+            output.push_str("UTF-8");
+            output.push_str(&decode(line_ending, UTF_8, number)?);
+        } else {
+            output.push_str(&decode(value, encoding, number)?);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Returns the locale of the key, before the separator.
+fn locale_of_key(key: &str) -> Option<Locale<'_>> {
+    let (_, locale) = key.split_once('[')?;
+    let (locale, _) = locale.split_once(']')?;
+
+    Locale::parse(locale)
+}
+
+fn decode<'a>(
+    bytes: &'a [u8],
+    encoding: &'static Encoding,
+    line: usize,
+) -> Result<Cow<'a, str>, LegacyMixedError> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or(LegacyMixedError::Malformed {
+            line,
+            encoding: encoding.name(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_decode_legacy_mixed() {
+        let mut input = b"[Desktop Entry]\r\nEncoding=Legacy-Mixed\r\nName=Viewer\r\n".to_vec();
+
+        for (key, value) in [
+            ("Name[ru]", "Просмотр"),
+            ("Name[pl]", "Przeglądarka"),
+            ("Name[ja.EUC-JP]", "ビューア"),
+            ("Name[zh_TW]", "檢視器"),
+        ] {
+            let locale = locale_of_key(key).unwrap();
+            let name = legacy_encoding_name(&locale).unwrap();
+            let (encoded, _, _) = Encoding::for_label(name.as_bytes()).unwrap().encode(value);
+
+            input.extend(format!("{key}=").as_bytes());
+            input.extend(encoded.as_ref());
+            input.extend(b"\r\n");
+        }
+
+        input.extend(b"# Comment \xff\r\n");
+
+        assert!(is_legacy_mixed(&input));
+        assert_eq!(
+            "[Desktop Entry]\r\nEncoding=UTF-8\r\nName=Viewer\r\nName[ru]=Просмотр\r\nName[pl]=Przeglądarka\r\nName[ja.EUC-JP]=ビューア\r\nName[zh_TW]=檢視器\r\n# Comment \u{fffd}\r\n",
+            decode_legacy_mixed(&input).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_legacy_values() {
+        assert!(!is_legacy_mixed(b"[Desktop Entry]\nEncoding=UTF-8\n"));
+        assert_eq!(
+            Err(LegacyMixedError::UnsupportedEncoding {
+                line: 3,
+                encoding: "TCVN-5712".to_string()
+            }),
+            decode_legacy_mixed(b"[Desktop Entry]\nName=Foo\nName[vi]=Foo\n")
+        );
+        assert_eq!(
+            Err(LegacyMixedError::Malformed {
+                line: 2,
+                encoding: "UTF-8"
+            }),
+            decode_legacy_mixed(b"[Desktop Entry]\nName=F\xfc\n")
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+use nom::{error::ErrorKind, Offset};
+
+/// Error returned by the crate.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("couldn't parse the desktop entry")]
+    Parse(#[from] ParseError),
+    #[error("couldn't read the desktop entry")]
+    Io(#[from] std::io::Error),
+    #[error("couldn't run the desktop entry command")]
+    Exec(#[from] crate::exec::ExecError),
+    #[error("couldn't launch the desktop entry")]
+    Launch(#[from] crate::launch::LaunchError),
+    #[error("invalid search provider")]
+    SearchProvider(#[from] crate::search_provider::SearchProviderError),
+    #[error("couldn't write the desktop entry")]
+    Write(#[from] WriteError),
+    #[cfg(feature = "dbus")]
+    #[error("couldn't activate the application over D-Bus")]
+    DBus(#[from] zbus::Error),
+}
+
+/// Error writing a document with the [`WriteOptions`](crate::WriteOptions).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WriteError {
+    #[error("the string value of `{key}` in the group `{group}` contains the non ASCII character `{character}` at byte {offset}, only the localestring values can")]
+    NonAsciiString {
+        group: String,
+        key: String,
+        character: char,
+        offset: usize,
+    },
+}
+
+/// Error for an invalid or malformed desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{kind} at line {line}, column {column}")]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    /// Creates the error from the input and the error returned by the nom parsers.
+    pub fn from_nom(input: &str, error: nom::Err<nom::error::Error<&str>>) -> Self {
+        match error {
+            nom::Err::Incomplete(_) => Self::new(input, input.len(), ParseErrorKind::Incomplete),
+            // The error input is a sub-slice of the input, but not always a suffix
+            nom::Err::Error(error) | nom::Err::Failure(error) => Self::new(
+                input,
+                input.offset(error.input),
+                ParseErrorKind::from(error.code),
+            ),
+        }
+    }
+
+    /// Creates the error at the byte offset in the input.
+    pub fn new(input: &str, offset: usize, kind: ParseErrorKind) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            kind,
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Byte offset of the error in the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Line of the error, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column of the error in characters, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// Category of a [`ParseError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// Line that is not a valid comment, group header or entry.
+    Syntax,
+    /// Value with an unknown escape sequence.
+    InvalidEscape,
+    /// Key repeated in the same group, rejected by the
+    /// [`DuplicateKeys`](crate::DuplicateKeys) policy of the dialect.
+    DuplicateKey,
+    /// One of the configured [`Limits`](crate::Limits) was exceeded.
+    LimitExceeded,
+    /// Input ended unexpectedly.
+    Incomplete,
+    /// Input that is not valid UTF-8.
+    InvalidEncoding,
+}
+
+impl From<ErrorKind> for ParseErrorKind {
+    fn from(value: ErrorKind) -> Self {
+        match value {
+            ErrorKind::Escaped => ParseErrorKind::InvalidEscape,
+            ErrorKind::TooLarge => ParseErrorKind::LimitExceeded,
+            ErrorKind::Verify => ParseErrorKind::DuplicateKey,
+            _ => ParseErrorKind::Syntax,
+        }
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::Syntax => write!(f, "invalid syntax"),
+            ParseErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            ParseErrorKind::DuplicateKey => write!(f, "duplicate key"),
+            ParseErrorKind::LimitExceeded => write!(f, "limit exceeded"),
+            ParseErrorKind::Incomplete => write!(f, "unexpected end of input"),
+            ParseErrorKind::InvalidEncoding => write!(f, "invalid UTF-8"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error as _;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::DesktopEntry;
+
+    use super::*;
+
+    #[test]
+    fn should_locate_parse_error() {
+        let err = DesktopEntry::parse("[Desktop Entry]\nName=Caffè \\x\n").unwrap_err();
+
+        assert_eq!(ParseErrorKind::InvalidEscape, err.kind());
+        assert_eq!(2, err.line());
+        assert_eq!(12, err.column());
+        assert_eq!(
+            "invalid escape sequence at line 2, column 12",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_have_parse_error_as_source() {
+        let err = Error::from(DesktopEntry::parse("[Desktop Entry").unwrap_err());
+
+        let source = err.source().unwrap().downcast_ref::<ParseError>().unwrap();
+
+        assert_eq!(ParseErrorKind::Syntax, source.kind());
+    }
+}
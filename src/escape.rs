@@ -0,0 +1,164 @@
+//! Escaping of the values written in a desktop file.
+//!
+//! The escape sequences are the ones read by the parser: `\s` for the leading spaces that would
+//! be trimmed after the `=`, `\n`, `\t`, `\r` and `\\`, and `\;` for the semicolons inside the
+//! items of a list. The [`Display`](std::fmt::Display) of a [`Value`](crate::Value) and the
+//! [edits](crate::edit) use the same rules, so the written values are read back as they were.
+//!
+//! ```
+//! use xdg_desktop_entry::escape;
+//!
+//! assert_eq!(r"\sa\\b\nc", escape::escape_value(" a\\b\nc"));
+//! assert_eq!(r"Foo\;Bar;Baz;", escape::join_list(["Foo;Bar", "Baz"]));
+//! ```
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use crate::raw::ESCAPE_CHAR;
+
+/// Separator of the items of a list.
+pub const LIST_SEPARATOR: char = ';';
+
+/// Escapes a value, borrowing it if no character needs to be escaped.
+pub fn escape_value(value: &str) -> Cow<'_, str> {
+    escape(value, false)
+}
+
+/// Escapes an item of a list, including the [`LIST_SEPARATOR`].
+pub fn escape_list_item(item: &str) -> Cow<'_, str> {
+    escape(item, true)
+}
+
+/// Joins the escaped items in a list, each one terminated by the [`LIST_SEPARATOR`].
+pub fn join_list<'i>(items: impl IntoIterator<Item = &'i str>) -> String {
+    items.into_iter().fold(String::new(), |mut list, item| {
+        list.push_str(&escape_list_item(item));
+        list.push(LIST_SEPARATOR);
+
+        list
+    })
+}
+
+/// Splits an escaped list on the separators not escaped, skipping the empty items.
+///
+/// The items are still escaped.
+pub fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    let mut escaped = false;
+
+    value
+        .split(move |c| {
+            let separator = c == LIST_SEPARATOR && !escaped;
+
+            escaped = c == ESCAPE_CHAR && !escaped;
+
+            separator
+        })
+        .filter(|item| !item.is_empty())
+}
+
+fn escape(value: &str, list_item: bool) -> Cow<'_, str> {
+    let needs_escape =
+        value.starts_with(' ') || value.chars().any(|c| escaped_char(c, list_item).is_some());
+
+    if !needs_escape {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = String::with_capacity(value.len() + 2);
+
+    // Writing to a string can't fail
+    let _ = write_escaped(&mut escaped, value, list_item);
+
+    Cow::Owned(escaped)
+}
+
+/// Writes a value escaping the backslash, the control characters and the leading spaces, and
+/// the [`LIST_SEPARATOR`] of a list item.
+pub(crate) fn write_escaped(out: &mut impl Write, value: &str, list_item: bool) -> fmt::Result {
+    let leading = value.len() - value.trim_start_matches([' ', '\t']).len();
+
+    for (i, c) in value.char_indices() {
+        let escaped = match c {
+            ' ' if i < leading => 's',
+            c => match escaped_char(c, list_item) {
+                Some(escaped) => escaped,
+                None => {
+                    out.write_char(c)?;
+
+                    continue;
+                }
+            },
+        };
+
+        out.write_char(ESCAPE_CHAR)?;
+        out.write_char(escaped)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the character of the escape sequence, if the character must be escaped.
+fn escaped_char(c: char, list_item: bool) -> Option<char> {
+    match c {
+        '\t' => Some('t'),
+        '\n' => Some('n'),
+        '\r' => Some('r'),
+        ESCAPE_CHAR => Some(ESCAPE_CHAR),
+        LIST_SEPARATOR if list_item => Some(LIST_SEPARATOR),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{raw::parse_escaped_string_with, UnknownEscapes};
+
+    fn unescape(value: &str) -> String {
+        let (rest, unescaped) =
+            parse_escaped_string_with::<()>(UnknownEscapes::Reject)(value).unwrap();
+
+        assert_eq!("", rest);
+
+        unescaped.into_owned()
+    }
+
+    #[test]
+    fn should_escape_values() {
+        assert_eq!(Cow::Borrowed("foo bar;"), escape_value("foo bar;"));
+        assert_eq!(r"\s\sa\\b\n\tc d\r", escape_value("  a\\b\n\tc d\r"));
+        assert_eq!(r"\ta", escape_value("\ta"));
+        assert_eq!(r"\sa\;b ", escape_list_item(" a;b "));
+    }
+
+    #[test]
+    fn should_round_trip_through_the_parser() {
+        let values = [
+            "",
+            "foo",
+            "  leading",
+            "\ttab",
+            "a\\b",
+            "line\nbreak\r",
+            "a;b;",
+        ];
+
+        for value in values {
+            assert_eq!(value, unescape(&escape_value(value)));
+        }
+
+        let items = ["Foo;Bar", " Baz", "a\\;b", "c\nd"];
+        let list = join_list(items);
+
+        assert_eq!(r"Foo\;Bar;\sBaz;a\\\;b;c\nd;", list);
+        assert_eq!(
+            items.to_vec(),
+            split_list(&list).map(unescape).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,666 @@
+//! Parsing and expansion of the `Exec` key.
+//!
+//! The command line is split in arguments following the quoting rules of the specification, then
+//! the field codes like `%f` are expanded with the files to open.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    ops::Range,
+    path::Path,
+};
+
+/// Field code of an `Exec` command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldCode {
+    /// `%f`, a single file name.
+    File,
+    /// `%F`, a list of files.
+    Files,
+    /// `%u`, a single URL.
+    Url,
+    /// `%U`, a list of URLs.
+    Urls,
+    /// `%i`, the `Icon` key expanded as the two arguments `--icon <icon>`.
+    Icon,
+    /// `%c`, the translated name of the application.
+    Name,
+    /// `%k`, the location of the desktop file.
+    Location,
+    /// Deprecated field codes `%d`, `%D`, `%n`, `%N`, `%v` and `%m`, expanded to nothing.
+    Deprecated(char),
+}
+
+impl FieldCode {
+    /// Returns the field code for the character following the `%`.
+    pub fn from_char(code: char) -> Option<Self> {
+        let code = match code {
+            'f' => FieldCode::File,
+            'F' => FieldCode::Files,
+            'u' => FieldCode::Url,
+            'U' => FieldCode::Urls,
+            'i' => FieldCode::Icon,
+            'c' => FieldCode::Name,
+            'k' => FieldCode::Location,
+            'd' | 'D' | 'n' | 'N' | 'v' | 'm' => FieldCode::Deprecated(code),
+            _ => return None,
+        };
+
+        Some(code)
+    }
+
+    /// Returns true for the codes expanded to a list of arguments, `%F` and `%U`.
+    pub fn is_list(&self) -> bool {
+        matches!(self, FieldCode::Files | FieldCode::Urls)
+    }
+}
+
+impl Display for FieldCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            FieldCode::File => 'f',
+            FieldCode::Files => 'F',
+            FieldCode::Url => 'u',
+            FieldCode::Urls => 'U',
+            FieldCode::Icon => 'i',
+            FieldCode::Name => 'c',
+            FieldCode::Location => 'k',
+            FieldCode::Deprecated(code) => *code,
+        };
+
+        write!(f, "%{code}")
+    }
+}
+
+/// Part of an argument of the command line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExecPart {
+    Text(String),
+    FieldCode(FieldCode),
+}
+
+/// Argument of the command line, made of text and field codes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExecArg {
+    pub parts: Vec<ExecPart>,
+    /// The argument, or part of it, was quoted.
+    pub quoted: bool,
+}
+
+impl ExecArg {
+    /// Returns the argument if it's only text, without field codes.
+    pub fn as_text(&self) -> Option<&str> {
+        match self.parts.as_slice() {
+            [] => Some(""),
+            [ExecPart::Text(text)] => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the field code if it's the only part of the argument.
+    pub fn as_field_code(&self) -> Option<FieldCode> {
+        match self.parts.as_slice() {
+            [ExecPart::FieldCode(code)] => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Returns the field codes in the argument.
+    pub fn field_codes(&self) -> impl Iterator<Item = FieldCode> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            ExecPart::FieldCode(code) => Some(*code),
+            ExecPart::Text(_) => None,
+        })
+    }
+
+    fn push_char(&mut self, c: char) {
+        match self.parts.last_mut() {
+            Some(ExecPart::Text(text)) => text.push(c),
+            _ => self.parts.push(ExecPart::Text(c.to_string())),
+        }
+    }
+}
+
+/// Error for an invalid `Exec` key.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExecError {
+    #[error("the desktop entry is not an application")]
+    NotApplication,
+    #[error("missing Exec key")]
+    MissingExec,
+    #[error("empty command line")]
+    EmptyCommand,
+    #[error("the program must not contain field codes")]
+    InvalidProgram,
+    #[error("unterminated quoted argument")]
+    UnterminatedQuote,
+    #[error("unknown field code `%{0}`")]
+    UnknownFieldCode(char),
+    #[error("missing field code after `%`")]
+    MissingFieldCode,
+    #[error("the field code {0} must be a standalone argument")]
+    ListNotStandalone(FieldCode),
+    #[error("reserved character `{0}` outside of quotes requires a shell")]
+    RequiresShell(char),
+    #[error("the field code {0} is interpreted by the shell script")]
+    ShellFieldCode(FieldCode),
+}
+
+/// Kind of an [`ExecIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecIssueKind {
+    /// `%` followed by a letter that is not a field code.
+    UnknownFieldCode(char),
+    /// One of the deprecated field codes, like `%d`.
+    DeprecatedFieldCode(char),
+    /// More than one of the `%f`, `%F`, `%u` and `%U` field codes.
+    MultipleFileCodes,
+    /// Literal `%` not escaped as `%%`.
+    UnescapedPercent,
+    /// Reserved character outside of a quoted argument.
+    UnquotedReservedChar(char),
+}
+
+impl Display for ExecIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecIssueKind::UnknownFieldCode(code) => write!(f, "unknown field code `%{code}`"),
+            ExecIssueKind::DeprecatedFieldCode(code) => {
+                write!(f, "deprecated field code `%{code}`")
+            }
+            ExecIssueKind::MultipleFileCodes => {
+                write!(f, "more than one of the %f, %F, %u and %U field codes")
+            }
+            ExecIssueKind::UnescapedPercent => write!(f, "literal `%` must be escaped as `%%`"),
+            ExecIssueKind::UnquotedReservedChar(c) => {
+                write!(f, "reserved character `{c}` must be quoted")
+            }
+        }
+    }
+}
+
+/// Reserved characters of the command line, that must be in a quoted argument.
+///
+/// The space, tab and newline are reserved too, but outside of the quotes they separate the
+/// arguments.
+pub const RESERVED_CHARS: &[char] = &[
+    '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '`', '(', ')',
+];
+
+/// Programs that run the script passed with `-c` in a shell.
+const SHELLS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "ksh", "mksh", "csh", "tcsh", "fish",
+];
+
+/// Issue with the field codes of an `Exec` command line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecIssue {
+    pub kind: ExecIssueKind,
+    /// Byte range of the field code in the command line.
+    pub span: Range<usize>,
+}
+
+/// Checks the field codes of the value of an `Exec` key, returning the issues in order.
+///
+/// Differently from [`Exec::parse`] it doesn't stop at the first issue.
+pub fn check_field_codes(command: &str) -> Vec<ExecIssue> {
+    let mut issues = Vec::new();
+    let mut has_file_code = false;
+
+    let mut chars = command.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '%' => {
+                let next = chars.next_if(|(_, next)| !next.is_whitespace());
+
+                let Some((j, code)) = next else {
+                    issues.push(ExecIssue {
+                        kind: ExecIssueKind::UnescapedPercent,
+                        span: i..i + 1,
+                    });
+
+                    continue;
+                };
+
+                let code_span = i..j + code.len_utf8();
+
+                let (kind, span) = match FieldCode::from_char(code) {
+                    Some(FieldCode::File | FieldCode::Files | FieldCode::Url | FieldCode::Urls) => {
+                        if !std::mem::replace(&mut has_file_code, true) {
+                            continue;
+                        }
+
+                        (ExecIssueKind::MultipleFileCodes, code_span)
+                    }
+                    Some(FieldCode::Deprecated(code)) => {
+                        (ExecIssueKind::DeprecatedFieldCode(code), code_span)
+                    }
+                    Some(_) => continue,
+                    None if code == '%' => continue,
+                    None if code.is_ascii_alphabetic() => {
+                        (ExecIssueKind::UnknownFieldCode(code), code_span)
+                    }
+                    None => (ExecIssueKind::UnescapedPercent, i..i + 1),
+                };
+
+                issues.push(ExecIssue { kind, span });
+            }
+            _ => {}
+        }
+    }
+
+    issues
+}
+
+/// Checks the quoting of the command line, returning the reserved characters outside of the quotes.
+///
+/// The double quotes delimiting the quoted arguments are not reported, but a backslash outside of
+/// them is, since the specification only defines it inside the quotes.
+pub fn check_quoting(command: &str) -> Vec<ExecIssue> {
+    let mut issues = Vec::new();
+    let mut quoted = false;
+
+    let mut chars = command.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                chars.next();
+            }
+            c if !quoted && RESERVED_CHARS.contains(&c) => issues.push(ExecIssue {
+                kind: ExecIssueKind::UnquotedReservedChar(c),
+                span: i..i + c.len_utf8(),
+            }),
+            _ => {}
+        }
+    }
+
+    issues
+}
+
+/// Parsed command line of the `Exec` key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Exec {
+    args: Vec<ExecArg>,
+}
+
+impl Exec {
+    /// Parses the value of the `Exec` key, after the general escape sequences were replaced.
+    ///
+    /// The arguments are separated by spaces and can be quoted with double quotes. Inside the
+    /// quotes, and outside of them, a backslash escapes the next character.
+    ///
+    /// # Errors
+    ///
+    /// Unterminated quotes, unknown field codes, a program with field codes or a `%F`/`%U` field
+    /// code that is not a standalone argument.
+    pub fn parse(command: &str) -> Result<Self, ExecError> {
+        let mut args = Vec::new();
+        let mut arg = ExecArg::default();
+        let mut has_arg = false;
+        let mut quoted = false;
+
+        let mut chars = command.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                ' ' | '\t' | '\n' if !quoted => {
+                    if has_arg {
+                        args.push(std::mem::take(&mut arg));
+                        has_arg = false;
+                    }
+                }
+                '"' => {
+                    quoted = !quoted;
+                    arg.quoted = true;
+                    has_arg = true;
+                }
+                '\\' => {
+                    // A trailing backslash is kept as is
+                    arg.push_char(chars.next().unwrap_or('\\'));
+                    has_arg = true;
+                }
+                '%' => {
+                    match chars.next() {
+                        Some('%') => arg.push_char('%'),
+                        Some(code) => arg.parts.push(ExecPart::FieldCode(
+                            FieldCode::from_char(code).ok_or(ExecError::UnknownFieldCode(code))?,
+                        )),
+                        None => return Err(ExecError::MissingFieldCode),
+                    }
+
+                    has_arg = true;
+                }
+                c => {
+                    arg.push_char(c);
+                    has_arg = true;
+                }
+            }
+        }
+
+        if quoted {
+            return Err(ExecError::UnterminatedQuote);
+        }
+
+        if has_arg {
+            args.push(arg);
+        }
+
+        let program = args.first().ok_or(ExecError::EmptyCommand)?;
+
+        if program.as_text().is_none() {
+            return Err(ExecError::InvalidProgram);
+        }
+
+        for arg in &args {
+            if let Some(code) = arg.field_codes().find(FieldCode::is_list) {
+                if arg.parts.len() > 1 {
+                    return Err(ExecError::ListNotStandalone(code));
+                }
+            }
+        }
+
+        Ok(Self { args })
+    }
+
+    /// Parses the value of the `Exec` key, rejecting the command lines that rely on a shell.
+    ///
+    /// The expansion never goes through a shell, so a reserved character like `|` or `>` outside
+    /// of the quotes doesn't have the meaning intended by the author. A field code in the script of
+    /// a shell invoked with `-c` would instead be interpreted by the shell, allowing a crafted
+    /// file name to inject commands.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`Exec::parse`], reserved characters outside of the quotes and field codes in
+    /// a shell script.
+    pub fn parse_hardened(command: &str) -> Result<Self, ExecError> {
+        let exec = Self::parse(command)?;
+
+        if let Some(issue) = check_quoting(command).first() {
+            if let ExecIssueKind::UnquotedReservedChar(c) = issue.kind {
+                return Err(ExecError::RequiresShell(c));
+            }
+        }
+
+        if let Some(code) = exec
+            .shell_script()
+            .and_then(|script| script.field_codes().next())
+        {
+            return Err(ExecError::ShellFieldCode(code));
+        }
+
+        Ok(exec)
+    }
+
+    /// Returns the program to execute, the first argument.
+    pub fn program(&self) -> &str {
+        self.args[0].as_text().unwrap_or_default()
+    }
+
+    /// Returns the arguments, including the program.
+    pub fn args(&self) -> &[ExecArg] {
+        &self.args
+    }
+
+    /// Returns the script argument, if the program is a shell invoked with `-c`.
+    ///
+    /// The shell is recognized by the name of the program, also with an absolute path, like
+    /// `/bin/sh`. The `-c` can be combined with other options, like `-ec`.
+    pub fn shell_script(&self) -> Option<&ExecArg> {
+        let name = Path::new(self.program()).file_name()?.to_str()?;
+
+        if !SHELLS.contains(&name) {
+            return None;
+        }
+
+        let mut args = self.args[1..].iter();
+
+        for arg in args.by_ref() {
+            // The first argument that is not an option is a script file
+            let option = arg.as_text().and_then(|arg| arg.strip_prefix('-'))?;
+
+            if !option.starts_with('-') && option.contains('c') {
+                break;
+            }
+        }
+
+        args.next()
+    }
+
+    /// Expands the field codes, returning the program followed by its arguments.
+    ///
+    /// The single file codes `%f` and `%u` are expanded to the first file, the arguments made
+    /// only of field codes expanded to nothing are removed.
+    ///
+    /// The expansion is not recursive and is done after splitting the arguments: each file is
+    /// part of exactly one argument, whatever characters it contains, and the arguments are meant
+    /// to be passed to the program without a shell.
+    pub fn expand(&self, context: &ExecContext) -> Vec<OsString> {
+        let mut argv = Vec::with_capacity(self.args.len() + context.files.len());
+
+        for arg in &self.args {
+            match arg.as_field_code() {
+                Some(code) if code.is_list() => {
+                    argv.extend(context.files.iter().cloned());
+
+                    continue;
+                }
+                Some(FieldCode::Icon) => {
+                    if let Some(icon) = context.icon {
+                        argv.push(OsString::from("--icon"));
+                        argv.push(OsString::from(icon));
+                    }
+
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut value = OsString::new();
+            let mut only_codes = !arg.parts.is_empty();
+
+            for part in &arg.parts {
+                match part {
+                    ExecPart::Text(text) => {
+                        value.push(text);
+                        only_codes = false;
+                    }
+                    ExecPart::FieldCode(code) => value.push(context.expand(*code)),
+                }
+            }
+
+            if only_codes && value.is_empty() {
+                continue;
+            }
+
+            argv.push(value);
+        }
+
+        argv
+    }
+}
+
+/// Values to expand the field codes with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecContext<'a> {
+    /// Files or URLs to open.
+    pub files: &'a [OsString],
+    /// Value of the `Icon` key, for `%i`.
+    pub icon: Option<&'a str>,
+    /// Translated name of the application, for `%c`.
+    pub name: Option<&'a str>,
+    /// Location of the desktop file, for `%k`.
+    pub location: Option<&'a Path>,
+}
+
+impl<'a> ExecContext<'a> {
+    /// Expands a single field code.
+    fn expand(&self, code: FieldCode) -> &'a OsStr {
+        let value = match code {
+            FieldCode::File | FieldCode::Url => self.files.first().map(OsString::as_os_str),
+            FieldCode::Icon => self.icon.map(OsStr::new),
+            FieldCode::Name => self.name.map(OsStr::new),
+            FieldCode::Location => self.location.map(Path::as_os_str),
+            FieldCode::Files | FieldCode::Urls | FieldCode::Deprecated(_) => None,
+        };
+
+        value.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn expand(command: &str, files: &[&str]) -> Vec<String> {
+        let files = files.iter().map(OsString::from).collect::<Vec<_>>();
+        let context = ExecContext {
+            files: &files,
+            icon: Some("foo"),
+            name: Some("Foo Viewer"),
+            location: Some(Path::new("/usr/share/applications/foo.desktop")),
+        };
+
+        Exec::parse(command)
+            .unwrap()
+            .expand(&context)
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn should_split_quoted_arguments() {
+        assert_eq!(
+            vec!["foo", "a b", "c\"d", "", "e$f", "g h"],
+            expand(r#"foo "a b" "c\"d" "" "e\$f" g\ h"#, &[])
+        );
+    }
+
+    #[test]
+    fn should_expand_field_codes() {
+        assert_eq!(
+            vec![
+                "fooview",
+                "--icon",
+                "foo",
+                "--name=Foo Viewer",
+                "/usr/share/applications/foo.desktop",
+                "a",
+                "100%"
+            ],
+            expand("fooview %i --name=%c %k %f 100%%", &["a", "b"])
+        );
+        assert_eq!(
+            vec!["fooview", "a", "b"],
+            expand("fooview %U %d", &["a", "b"])
+        );
+        assert_eq!(vec!["fooview", "--"], expand("fooview -- %f", &[]));
+    }
+
+    #[test]
+    fn should_reject_invalid_commands() {
+        assert_eq!(Err(ExecError::EmptyCommand), Exec::parse("  "));
+        assert_eq!(Err(ExecError::UnterminatedQuote), Exec::parse("foo \"bar"));
+        assert_eq!(Err(ExecError::UnknownFieldCode('x')), Exec::parse("foo %x"));
+        assert_eq!(Err(ExecError::InvalidProgram), Exec::parse("%f"));
+        assert_eq!(
+            Err(ExecError::ListNotStandalone(FieldCode::Files)),
+            Exec::parse("foo --files=%F")
+        );
+    }
+
+    #[test]
+    fn should_check_field_codes() {
+        let issues = check_field_codes("foo %x %d %f %U 100% %%");
+
+        assert_eq!(
+            vec![
+                ExecIssue {
+                    kind: ExecIssueKind::UnknownFieldCode('x'),
+                    span: 4..6
+                },
+                ExecIssue {
+                    kind: ExecIssueKind::DeprecatedFieldCode('d'),
+                    span: 7..9
+                },
+                ExecIssue {
+                    kind: ExecIssueKind::MultipleFileCodes,
+                    span: 13..15
+                },
+                ExecIssue {
+                    kind: ExecIssueKind::UnescapedPercent,
+                    span: 19..20
+                },
+            ],
+            issues
+        );
+    }
+
+    #[test]
+    fn should_check_quoting() {
+        let issues = check_quoting(r#"foo "a > b \" c" a|b \x"#);
+
+        assert_eq!(
+            vec![
+                ExecIssue {
+                    kind: ExecIssueKind::UnquotedReservedChar('|'),
+                    span: 18..19
+                },
+                ExecIssue {
+                    kind: ExecIssueKind::UnquotedReservedChar('\\'),
+                    span: 21..22
+                },
+            ],
+            issues
+        );
+    }
+
+    #[test]
+    fn should_find_shell_script() {
+        let script = |command: &str| {
+            Exec::parse(command)
+                .unwrap()
+                .shell_script()
+                .and_then(|arg| arg.as_text().map(str::to_string))
+        };
+
+        assert_eq!(Some("foo".to_string()), script("sh -c foo"));
+        assert_eq!(
+            Some("foo \"$1\"".to_string()),
+            script(r#"/usr/bin/bash -e -c "foo \"\$1\"" bash %f"#)
+        );
+        assert_eq!(None, script("sh script.sh -c foo"));
+        assert_eq!(None, script("sh --norc foo"));
+        assert_eq!(None, script("foo -c bar"));
+    }
+
+    #[test]
+    fn should_expand_files_to_single_arguments() {
+        let file = "a b\" ;rm -rf ~ %f $(id)";
+
+        assert_eq!(
+            vec!["foo", "--file=a b\" ;rm -rf ~ %f $(id)", file, "b"],
+            expand("foo --file=%f %F", &[file, "b"])
+        );
+    }
+
+    #[test]
+    fn should_reject_shell_commands_when_hardened() {
+        assert!(Exec::parse_hardened(r#"foo "a|b" --x=%f"#).is_ok());
+        assert!(Exec::parse_hardened(r#"sh -c "foo \"\$1\"" sh %f"#).is_ok());
+        assert_eq!(
+            Err(ExecError::RequiresShell('>')),
+            Exec::parse_hardened("foo %f > log")
+        );
+        assert_eq!(
+            Err(ExecError::ShellFieldCode(FieldCode::File)),
+            Exec::parse_hardened(r#"sh -c "foo %f""#)
+        );
+    }
+}
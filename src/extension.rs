@@ -0,0 +1,274 @@
+//! Typed access to the extension keys of the `[Desktop Entry]` group.
+//!
+//! The specification reserves the keys starting with `X-` to the vendors, like
+//! `X-MyApp-Foo`. The values are converted with the [`ExtensionValue`] trait.
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//!
+//! let mut document =
+//!     DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nX-Foo-Count=3\n").unwrap();
+//!
+//! assert_eq!(Some(3), document.get_extension::<u32>("X-Foo-Count").unwrap());
+//!
+//! document.set_extension("X-Foo-Enabled", true).unwrap();
+//! assert!(document.set_extension("Foo", true).is_err());
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{DesktopEntry, Value, DESKTOP_ENTRY_GROUP};
+
+/// Prefix of the extension keys.
+pub const EXTENSION_PREFIX: &str = "X-";
+
+/// Error for an invalid extension key or value.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExtensionError {
+    #[error("the key `{0}` doesn't have the `X-` prefix")]
+    MissingPrefix(String),
+    #[error("the key `{0}` contains characters other than `A-Za-z0-9-`")]
+    InvalidKey(String),
+    #[error("the value of `{key}` is not a valid {expected}")]
+    InvalidValue { key: String, expected: &'static str },
+}
+
+/// Conversion between the value of a key and a Rust type.
+///
+/// The conversion from the value is lenient on the type of the parsed value, since it depends
+/// on the content for the keys not defined by the specification: a `X-Foo=1` is parsed as a
+/// number, but can be read as a string.
+pub trait ExtensionValue: Sized {
+    /// Name of the type in the errors, like `boolean`.
+    const TYPE_NAME: &'static str;
+
+    /// Converts the value, [`None`] if invalid.
+    fn from_value(value: &Value) -> Option<Self>;
+
+    /// Converts into a value.
+    fn into_value(self) -> Value<'static>;
+}
+
+impl ExtensionValue for String {
+    const TYPE_NAME: &'static str = "string";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) | Value::LocaleString(value) => Some(value.to_string()),
+            Value::Boolean(value) => Some(value.to_string()),
+            Value::Numeric(_) => Some(value.to_string()),
+        }
+    }
+
+    fn into_value(self) -> Value<'static> {
+        Value::String(Cow::Owned(self))
+    }
+}
+
+impl ExtensionValue for bool {
+    const TYPE_NAME: &'static str = "boolean";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Boolean(value) => Some(*value),
+            Value::String(value) | Value::LocaleString(value) => value.parse().ok(),
+            Value::Numeric(_) => None,
+        }
+    }
+
+    fn into_value(self) -> Value<'static> {
+        Value::Boolean(self)
+    }
+}
+
+impl ExtensionValue for f32 {
+    const TYPE_NAME: &'static str = "number";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Numeric(value) => Some(*value),
+            Value::String(value) | Value::LocaleString(value) => value.parse().ok(),
+            Value::Boolean(_) => None,
+        }
+    }
+
+    fn into_value(self) -> Value<'static> {
+        Value::Numeric(self)
+    }
+}
+
+/// Implements the conversion of an integer, from a number without a fractional part.
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl ExtensionValue for $ty {
+                const TYPE_NAME: &'static str = "integer";
+
+                fn from_value(value: &Value) -> Option<Self> {
+                    match value {
+                        Value::Numeric(value) if value.fract() == 0.0 => {
+                            <$ty>::try_from(*value as i64).ok()
+                        }
+                        Value::String(value) | Value::LocaleString(value) => value.parse().ok(),
+                        Value::Numeric(_) | Value::Boolean(_) => None,
+                    }
+                }
+
+                fn into_value(self) -> Value<'static> {
+                    // Written without the decimal point of the numeric values
+                    Value::String(Cow::Owned(self.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i32, i64, u32, u64);
+
+impl ExtensionValue for Vec<String> {
+    const TYPE_NAME: &'static str = "list";
+
+    /// Splits the list on the `;`, skipping the empty items.
+    fn from_value(value: &Value) -> Option<Self> {
+        let list = String::from_value(value)?;
+
+        let items = list
+            .split(';')
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Some(items)
+    }
+
+    /// Joins the items terminating each one with a `;`.
+    fn into_value(self) -> Value<'static> {
+        let list = self
+            .into_iter()
+            .fold(String::new(), |list, item| list + &item + ";");
+
+        Value::String(Cow::Owned(list))
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Returns the value of an extension key of the `[Desktop Entry]` group, converted to the
+    /// type.
+    ///
+    /// # Errors
+    ///
+    /// If the key is not a valid extension key or the value can't be converted.
+    pub fn get_extension<T: ExtensionValue>(&self, key: &str) -> Result<Option<T>, ExtensionError> {
+        check_key(key)?;
+
+        let Some(value) = self.get(DESKTOP_ENTRY_GROUP, key) else {
+            return Ok(None);
+        };
+
+        T::from_value(value)
+            .map(Some)
+            .ok_or_else(|| ExtensionError::InvalidValue {
+                key: key.to_string(),
+                expected: T::TYPE_NAME,
+            })
+    }
+
+    /// Sets the value of an extension key of the `[Desktop Entry]` group.
+    ///
+    /// Returns the previous value, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the key is not a valid extension key.
+    pub fn set_extension<T: ExtensionValue>(
+        &mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: T,
+    ) -> Result<Option<Value<'a>>, ExtensionError> {
+        let key = key.into();
+
+        check_key(&key)?;
+
+        Ok(self.set_localized(DESKTOP_ENTRY_GROUP, key, None, value.into_value()))
+    }
+}
+
+/// Checks that the key has the `X-` prefix and only the characters allowed in the keys.
+fn check_key(key: &str) -> Result<(), ExtensionError> {
+    if !key.starts_with(EXTENSION_PREFIX) || key.len() == EXTENSION_PREFIX.len() {
+        return Err(ExtensionError::MissingPrefix(key.to_string()));
+    }
+
+    if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ExtensionError::InvalidKey(key.to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_get_and_set_extension_keys() {
+        let mut document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nX-Foo-Count=3\nX-Foo-Ratio=0.5\nX-Foo-Schemes=http;https;\nX-Foo-Enabled=true\n",
+        )
+        .unwrap();
+
+        assert_eq!(Ok(Some(3)), document.get_extension::<u32>("X-Foo-Count"));
+        assert_eq!(
+            Ok(Some("3.0".to_string())),
+            document.get_extension::<String>("X-Foo-Count")
+        );
+        assert_eq!(Ok(Some(0.5)), document.get_extension::<f32>("X-Foo-Ratio"));
+        assert_eq!(
+            Ok(Some(vec!["http".to_string(), "https".to_string()])),
+            document.get_extension::<Vec<String>>("X-Foo-Schemes")
+        );
+        assert_eq!(
+            Ok(Some(true)),
+            document.get_extension::<bool>("X-Foo-Enabled")
+        );
+        assert_eq!(Ok(None), document.get_extension::<bool>("X-Foo-Missing"));
+        assert_eq!(
+            Err(ExtensionError::InvalidValue {
+                key: "X-Foo-Ratio".to_string(),
+                expected: "integer"
+            }),
+            document.get_extension::<i64>("X-Foo-Ratio")
+        );
+
+        document
+            .set_extension("X-Foo-Schemes", vec!["ftp".to_string()])
+            .unwrap();
+        document.set_extension("X-Foo-Count", -4_i32).unwrap();
+
+        assert_eq!(
+            Some(&Value::String("ftp;".into())),
+            document.get(DESKTOP_ENTRY_GROUP, "X-Foo-Schemes")
+        );
+        assert_eq!(Ok(Some(-4)), document.get_extension::<i32>("X-Foo-Count"));
+    }
+
+    #[test]
+    fn should_reject_keys_without_prefix() {
+        let mut document = DesktopEntry::parse("[Desktop Entry]\nName=Foo\n").unwrap();
+
+        assert_eq!(
+            Err(ExtensionError::MissingPrefix("Name".to_string())),
+            document.get_extension::<String>("Name")
+        );
+        assert_eq!(
+            Err(ExtensionError::MissingPrefix("X-".to_string())),
+            document.set_extension("X-", true)
+        );
+        assert_eq!(
+            Err(ExtensionError::InvalidKey("X-Foo Bar".to_string())),
+            document.set_extension("X-Foo Bar", true)
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Hand-written single pass parser of the desktop files, used with the `fast-parser` feature.
+//!
+//! It parses the files of the [`Dialect::DESKTOP_ENTRY`] a line at a time without the
+//! combinators, folding the lines in the document like the [`nom`] parser. It gives up on the
+//! first line it can't parse, on the exceeded limits and on the options it doesn't support, and
+//! the input is parsed again by the [`nom`] parser, so the errors and the warnings are the same
+//! with or without the feature.
+
+use std::borrow::Cow;
+
+use crate::{
+    estimate_capacity, finish_document, fold_line,
+    raw::{escaped_chars, parse_numeric, ESCAPE_CHAR},
+    start_document, value_source, DesktopEntry, Dialect, Key, Limits, Line, Locale, ParseOptions,
+    UnknownEscapes, Value, BYTE_ORDER_MARK,
+};
+
+/// Parses a desktop file, returning [`None`] if the input must be parsed by the [`nom`] parser.
+pub(crate) fn parse_document<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Option<DesktopEntry<'a>> {
+    if options.dialect != Dialect::DESKTOP_ENTRY
+        || options.unknown_escapes != UnknownEscapes::Reject
+    {
+        return None;
+    }
+
+    let (input, byte_order_mark) = match input.strip_prefix(BYTE_ORDER_MARK) {
+        Some(input) => (input, true),
+        None => (input, false),
+    };
+
+    let limits = &options.limits;
+
+    if Limits::exceeds(limits.max_file_size, input.len()) {
+        return None;
+    }
+
+    let capacities = if options.reserve_capacity {
+        estimate_capacity(input)
+    } else {
+        Vec::new()
+    };
+
+    let mut acc = start_document(capacities.len());
+    let mut groups = 0usize;
+    let mut entries = 0usize;
+    let mut keep_group = true;
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let (line, next) = match rest.find('\n') {
+            Some(end) => (&rest[..end], &rest[end + 1..]),
+            None => (rest, ""),
+        };
+
+        rest = next;
+
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        if Limits::exceeds(limits.max_line_length, line.len()) {
+            return None;
+        }
+
+        let line = parse_line(line, options, keep_group)?;
+
+        match &line {
+            Line::GroupHeader(header) => {
+                groups += 1;
+                keep_group = options.keep_group(header);
+
+                if Limits::exceeds(limits.max_groups, groups) {
+                    return None;
+                }
+            }
+            // The entries must be in a group
+            Line::Entry { .. } | Line::Skipped if groups == 0 => return None,
+            Line::Entry { .. } => {
+                entries += 1;
+
+                if Limits::exceeds(limits.max_entries, entries) {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+
+        let capacity = capacities.get(groups.wrapping_sub(1));
+
+        acc = fold_line(acc, line, options, capacity.copied().unwrap_or_default());
+    }
+
+    Some(finish_document(acc, input, byte_order_mark, Vec::new()))
+}
+
+/// Parses a line without the line ending.
+fn parse_line<'a>(line: &'a str, options: &ParseOptions, keep_group: bool) -> Option<Line<'a>> {
+    match line.as_bytes().first() {
+        Some(b'#') => Some(Line::Comment(Cow::Borrowed(line))),
+        Some(b'[') => parse_group_header(line),
+        Some(b' ' | b'\t') | None => parse_empty_line(line),
+        Some(_) => parse_entry(line, options, keep_group),
+    }
+}
+
+fn parse_group_header(line: &str) -> Option<Line<'_>> {
+    let header = line.strip_prefix('[')?.strip_suffix(']')?;
+
+    let valid = !header.is_empty()
+        && header
+            .bytes()
+            .all(|c| c.is_ascii() && !c.is_ascii_control() && c != b'[' && c != b']');
+
+    valid.then_some(Line::GroupHeader(Cow::Borrowed(header)))
+}
+
+fn parse_empty_line(line: &str) -> Option<Line<'_>> {
+    if !line.bytes().all(|c| c == b' ' || c == b'\t') {
+        return None;
+    }
+
+    let white_space = (!line.is_empty()).then_some(Cow::Borrowed(line));
+
+    Some(Line::EmptyLine { white_space })
+}
+
+fn parse_entry<'a>(line: &'a str, options: &ParseOptions, keep_group: bool) -> Option<Line<'a>> {
+    let (key, rest) = parse_key(line)?;
+
+    let value = rest
+        .trim_start_matches([' ', '\t'])
+        .strip_prefix('=')?
+        .trim_start_matches([' ', '\t']);
+
+    // The value parsers of nom strip another `\r` at the end of the line
+    let value = value.strip_suffix('\r').unwrap_or(value);
+
+    if !keep_group || !options.keep_key(&key) {
+        return Some(Line::Skipped);
+    }
+
+    let parsed = parse_value(value)?;
+    let source = value_source(value, &parsed);
+
+    Some(Line::Entry {
+        key,
+        value: parsed,
+        source,
+    })
+}
+
+/// Parses a key with the optional locale, returning the rest of the line.
+fn parse_key(line: &str) -> Option<(Key<'_>, &str)> {
+    let (name, rest) = parse_key_part(line)?;
+    let name = Cow::Borrowed(name);
+
+    let localized = rest.strip_prefix('[').and_then(|rest| {
+        let (locale, rest) = parse_locale(rest)?;
+
+        Some((locale, rest.strip_prefix(']')?))
+    });
+
+    match localized {
+        Some((locale, rest)) => Some((Key::Localized { key: name, locale }, rest)),
+        None => Some((Key::Simple(name), rest)),
+    }
+}
+
+/// Parses a locale in the form `lang_COUNTRY.ENCODING@MODIFIER`, returning the rest.
+fn parse_locale(input: &str) -> Option<(Locale<'_>, &str)> {
+    let (lang, mut rest) = parse_key_part(input)?;
+
+    let mut part = |prefix: char| {
+        let (part, after) = parse_key_part(rest.strip_prefix(prefix)?)?;
+
+        rest = after;
+
+        Some(Cow::Borrowed(part))
+    };
+
+    let locale = Locale {
+        lang: Cow::Borrowed(lang),
+        country: part('_'),
+        encoding: part('.'),
+        modifier: part('@'),
+    };
+
+    Some((locale, rest))
+}
+
+/// Splits the ASCII alphanumeric characters and `-` at the start of the input.
+fn parse_key_part(input: &str) -> Option<(&str, &str)> {
+    let end = input
+        .bytes()
+        .position(|c| !c.is_ascii_alphanumeric() && c != b'-')
+        .unwrap_or(input.len());
+
+    (end > 0).then(|| input.split_at(end))
+}
+
+/// Types the value by its content like [`raw::parse_value`](crate::raw::parse_value).
+fn parse_value(value: &str) -> Option<Value<'_>> {
+    match value {
+        "true" => return Some(Value::Boolean(true)),
+        "false" => return Some(Value::Boolean(false)),
+        _ => {}
+    }
+
+    // Only the values starting like a number, `nan` or `inf` are parsed as a float
+    let numeric = matches!(
+        value.as_bytes().first(),
+        Some(b'0'..=b'9' | b'+' | b'-' | b'.' | b'n' | b'N' | b'i' | b'I')
+    );
+
+    if numeric {
+        if let Ok((_, number)) = parse_numeric::<()>(value) {
+            return Some(Value::Numeric(number));
+        }
+    }
+
+    let value = unescape(value)?;
+
+    if value.is_ascii() {
+        Some(Value::String(value))
+    } else {
+        Some(Value::LocaleString(value))
+    }
+}
+
+/// Replaces the escape sequences, [`None`] for an unknown one.
+fn unescape(value: &str) -> Option<Cow<'_, str>> {
+    let Some(start) = value.find(ESCAPE_CHAR) else {
+        return Some(Cow::Borrowed(value));
+    };
+
+    let mut unescaped = String::with_capacity(value.len());
+    unescaped.push_str(&value[..start]);
+
+    let mut chars = value[start..].chars();
+
+    while let Some(c) = chars.next() {
+        if c != ESCAPE_CHAR {
+            unescaped.push(c);
+
+            continue;
+        }
+
+        unescaped.push_str(escaped_chars(chars.next()?)?);
+    }
+
+    Some(Cow::Owned(unescaped))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parse_desktop_entry_verbose;
+
+    fn assert_same_document(input: &str, options: &ParseOptions) {
+        let expected = parse_desktop_entry_verbose(input, options)
+            .ok()
+            .map(|(_, document)| document);
+
+        match parse_document(input, options) {
+            Some(document) => assert_eq!(expected, Some(document), "{input:?}"),
+            // Only the inputs with an error fallback to the nom parser with the default options
+            None => assert_eq!(None, expected, "{input:?}"),
+        }
+    }
+
+    #[test]
+    fn should_parse_like_the_combinators() {
+        let inputs = [
+            include_str!("../example/file.desktop"),
+            "",
+            "\n",
+            "\u{FEFF}[Desktop Entry]\r\nName=Foo\r\n",
+            "# Comment\n  \n[Desktop Entry]\nName = Foo\nName[de_DE.UTF-8@euro]=Fü\nVersion=1.5\nTerminal=false\nExec=foo\\s--bar\\\\\n\t\r",
+            "[Desktop Entry]\nNumber=-1e3\nInf=inf\nInfinity=Infinity\nWord=9Eu\nEmpty=\nSpaces=   \n",
+            "[Desktop Entry]\nName[de_]=Foo\n",
+            "[Desktop Entry]\nName=\\x\n",
+            "[Desktop Entry]\nName=Foo\\\n",
+            "Name=Foo\n[Desktop Entry]\n",
+            "[Desktop Entry\nName=Foo\n",
+            "[Desktop Entry]x\n",
+            "[]\n",
+            "[Desktop Entry]\nName Foo\n",
+            "[Desktop Entry]\n Name=Foo\n",
+            "[Desktop Entry]\nName=Foo\r\r\n",
+            "[Desktop Entry]\nName=Foo\rBar",
+        ];
+
+        let options = ParseOptions::default();
+
+        for input in inputs {
+            assert_same_document(input, &options);
+        }
+    }
+
+    #[test]
+    fn should_filter_like_the_combinators() {
+        let options = ParseOptions {
+            locales: Some(vec![Locale::parse("de").unwrap()]),
+            groups: Some(vec![Cow::from("Desktop Entry")]),
+            keys: Some(vec![Cow::from("Name"), Cow::from("Exec")]),
+            keep_comments: false,
+            ..Default::default()
+        };
+
+        let input = "# Head\n[Desktop Entry]\nName=Foo\nName[de]=Fu\nName[it]=Fuu\nIcon=foo\nExec=foo\n# Tail\n[Desktop Action new]\nExec=foo --new\n";
+
+        assert_same_document(input, &options);
+        assert_eq!(
+            "[Desktop Entry]\nName=Foo\nName[de]=Fu\nExec=foo\n",
+            parse_document(input, &options).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fallback_for_other_dialects() {
+        let options = ParseOptions {
+            dialect: Dialect::GKEY_FILE,
+            ..Default::default()
+        };
+
+        assert!(parse_document("[Desktop Entry]\nName=Foo\n", &options).is_none());
+    }
+}
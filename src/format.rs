@@ -0,0 +1,290 @@
+//! Formatting of the layout of a desktop file.
+//!
+//! Differently from writing a parsed [`DesktopEntry`](crate::DesktopEntry), the formatter works on
+//! the source text: comments, key order and escape sequences are kept as written, only the
+//! whitespace between them is normalized.
+
+use crate::{
+    raw::{not_line_ending, parse_line_ending},
+    token::{tokenize, Token, TokenKind},
+    LineEnding, BYTE_ORDER_MARK,
+};
+
+/// Options to customize the layout of the formatted file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatOptions {
+    /// Surround the `=` of the entries with a space, like `Name = Foo`.
+    pub space_around_separator: bool,
+    /// Number of empty lines before each group header, except the first.
+    pub blank_lines_between_groups: usize,
+    /// Line ending of the formatted file, if [`None`] the one of the input is kept.
+    pub line_ending: Option<LineEnding>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            space_around_separator: false,
+            blank_lines_between_groups: 1,
+            line_ending: None,
+        }
+    }
+}
+
+/// Formats the layout of a desktop file.
+///
+/// The spaces around the `=` are normalized, the trailing whitespace is removed and the empty
+/// lines are collapsed, with [`FormatOptions::blank_lines_between_groups`] before each group.
+/// Trailing whitespace in a value is significant, so it's escaped instead of being removed.
+/// Comments stay attached to the line following them, and lines that are not valid are kept as
+/// they are.
+pub fn format(input: &str, options: &FormatOptions) -> String {
+    let line_ending = options
+        .line_ending
+        .unwrap_or_else(|| LineEnding::detect(input));
+
+    let mut formatter = Formatter {
+        output: String::with_capacity(input.len()),
+        options,
+        line_ending: line_ending.as_str(),
+        lines: 0,
+        after_header: false,
+        blank: false,
+        comments: Vec::new(),
+        comments_after_blank: false,
+    };
+
+    let mut rest = match input.strip_prefix(BYTE_ORDER_MARK) {
+        Some(rest) => {
+            formatter.output.push(BYTE_ORDER_MARK);
+
+            rest
+        }
+        None => input,
+    };
+
+    while !rest.is_empty() {
+        let Ok((after, line)) = not_line_ending::<()>(rest) else {
+            break;
+        };
+
+        formatter.line(line);
+
+        rest = parse_line_ending::<()>(after).map_or("", |(after, _)| after);
+    }
+
+    formatter.flush_comments();
+
+    formatter.output
+}
+
+struct Formatter<'a, 'o> {
+    output: String,
+    options: &'o FormatOptions,
+    line_ending: &'static str,
+    /// Number of lines written.
+    lines: usize,
+    /// The last line written is a group header.
+    after_header: bool,
+    /// There are empty lines before the current one.
+    blank: bool,
+    /// Comments waiting for the line they are attached to.
+    comments: Vec<&'a str>,
+    /// There are empty lines before the pending comments.
+    comments_after_blank: bool,
+}
+
+impl<'a> Formatter<'a, '_> {
+    fn line(&mut self, line: &'a str) {
+        let tokens = tokenize(line);
+
+        let Some(first) = tokens.first() else {
+            self.blank = true;
+
+            return;
+        };
+
+        match first.kind {
+            TokenKind::Comment => {
+                if self.comments.is_empty() {
+                    self.comments_after_blank = self.blank;
+                } else if self.blank {
+                    // Comments separated by an empty line are not attached
+                    self.flush_comments();
+                    self.comments_after_blank = true;
+                }
+
+                self.blank = false;
+                self.comments.push(trim_end(line));
+            }
+            _ if is_header(line, &tokens) => {
+                if self.blank {
+                    self.flush_comments();
+                }
+
+                let separation = if self.lines == 0 {
+                    0
+                } else {
+                    self.options.blank_lines_between_groups
+                };
+
+                self.write_blank_lines(separation);
+                self.write_comments();
+                self.write_line(trim_end(line));
+
+                self.after_header = true;
+                self.blank = false;
+            }
+            _ => {
+                self.flush_comments();
+
+                if self.blank && !self.after_header && self.lines > 0 {
+                    self.write_blank_lines(1);
+                }
+
+                let entry = format_entry(line, &tokens, self.options);
+                match entry {
+                    Some(entry) => self.write_line(&entry),
+                    None => self.write_line(trim_end(line)),
+                }
+
+                self.after_header = false;
+                self.blank = false;
+            }
+        }
+    }
+
+    /// Writes the pending comments not attached to a group header.
+    fn flush_comments(&mut self) {
+        if self.comments.is_empty() {
+            return;
+        }
+
+        if self.comments_after_blank && !self.after_header && self.lines > 0 {
+            self.write_blank_lines(1);
+        }
+
+        self.write_comments();
+        self.after_header = false;
+    }
+
+    fn write_comments(&mut self) {
+        for comment in std::mem::take(&mut self.comments) {
+            self.write_line(comment);
+        }
+    }
+
+    fn write_blank_lines(&mut self, count: usize) {
+        for _ in 0..count {
+            self.output.push_str(self.line_ending);
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        self.output.push_str(line);
+        self.output.push_str(self.line_ending);
+        self.lines += 1;
+    }
+}
+
+fn trim_end(line: &str) -> &str {
+    line.trim_end_matches([' ', '\t'])
+}
+
+/// Checks if the line is a group header, ignoring the trailing whitespace.
+fn is_header(line: &str, tokens: &[Token]) -> bool {
+    match tokens {
+        [open, name, _, rest @ ..] if open.kind == TokenKind::Bracket => {
+            name.kind == TokenKind::GroupName
+                && rest
+                    .iter()
+                    .all(|token| trim_end(token.text(line)).is_empty())
+        }
+        _ => false,
+    }
+}
+
+/// Formats an entry line, returns [`None`] if the line is not a valid entry.
+fn format_entry(line: &str, tokens: &[Token], options: &FormatOptions) -> Option<String> {
+    if tokens.iter().any(|token| token.kind == TokenKind::Invalid) {
+        return None;
+    }
+
+    let separator = tokens
+        .iter()
+        .position(|token| token.kind == TokenKind::Separator)?;
+
+    let mut entry = String::with_capacity(line.len());
+
+    for token in &tokens[..separator] {
+        entry.push_str(token.text(line));
+    }
+
+    entry.push_str(if options.space_around_separator {
+        " = "
+    } else {
+        "="
+    });
+
+    let value = &tokens[separator + 1..];
+
+    for (i, token) in value.iter().enumerate() {
+        let text = token.text(line);
+
+        if i + 1 < value.len() || token.kind != TokenKind::Value {
+            entry.push_str(text);
+
+            continue;
+        }
+
+        let trimmed = trim_end(text);
+        entry.push_str(trimmed);
+
+        for c in text[trimmed.len()..].chars() {
+            entry.push_str(if c == '\t' { "\\t" } else { "\\s" });
+        }
+    }
+
+    Some(entry)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_keep_formatted_example() {
+        let input = include_str!("../example/file.desktop");
+
+        assert_eq!(
+            input.trim_end(),
+            format(input, &FormatOptions::default()).trim_end()
+        );
+    }
+
+    #[test]
+    fn should_normalize_layout() {
+        let input = "# Header comment\n\n\n[Desktop Entry]  \n\nName = Foo \nIcon=foo\\s\n\n\n# Keys\nType  =Application\n# Action\n[Desktop Action New]\nName[de]=Neu\t\n\n";
+
+        assert_eq!(
+            "# Header comment\n\n[Desktop Entry]\nName=Foo\\s\nIcon=foo\\s\n\n# Keys\nType=Application\n\n# Action\n[Desktop Action New]\nName[de]=Neu\\t\n",
+            format(input, &FormatOptions::default())
+        );
+    }
+
+    #[test]
+    fn should_format_with_options() {
+        let options = FormatOptions {
+            space_around_separator: true,
+            blank_lines_between_groups: 2,
+            line_ending: Some(LineEnding::CrLf),
+        };
+
+        assert_eq!(
+            "[A]\r\nKey = Value\r\n\r\n\r\n[B]\r\nbroken line\r\n",
+            format("[A]\nKey=Value\n[B]\nbroken line \n", &options)
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Translations of the entries from a gettext domain.
+//!
+//! Some desktop files don't contain the translations of their keys, but name a gettext domain
+//! in the `X-GNOME-Gettext-Domain` or `X-Ubuntu-Gettext-Domain` key. GNOME Shell and the other
+//! GLib based launchers look up the untranslated value in the compiled catalog of the domain
+//! when no inline translation matches the locale, the [`Translator`] does the same.
+//!
+//! ```no_run
+//! use xdg_desktop_entry::{gettext::Translator, DesktopEntry, Locale, DESKTOP_ENTRY_GROUP};
+//!
+//! let entry = DesktopEntry::parse("[Desktop Entry]\nName=Files\nX-GNOME-Gettext-Domain=nautilus\n")?;
+//! let locale = Locale::parse("de_DE.UTF-8");
+//!
+//! let translator = Translator::from_env();
+//! let name = translator.get_localized(&entry, DESKTOP_ENTRY_GROUP, "Name", locale.as_ref());
+//! # Ok::<(), xdg_desktop_entry::ParseError>(())
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{loader, utf8, DesktopEntry, Locale, Value, DESKTOP_ENTRY_GROUP};
+
+/// Keys of the main group naming the gettext domain, in order of precedence.
+pub const GETTEXT_DOMAIN_KEYS: [&str; 2] = ["X-GNOME-Gettext-Domain", "X-Ubuntu-Gettext-Domain"];
+
+/// Magic number at the start of a MO file, in the byte order of the file.
+const MO_MAGIC: u32 = 0x9504_12de;
+
+/// Separator between the context and the id of a message in a MO file.
+const CONTEXT_SEPARATOR: char = '\u{4}';
+
+/// Returns the gettext domain of the entry, if any.
+pub fn gettext_domain<'e>(entry: &'e DesktopEntry) -> Option<&'e str> {
+    GETTEXT_DOMAIN_KEYS
+        .into_iter()
+        .find_map(|key| match entry.get(DESKTOP_ENTRY_GROUP, key)? {
+            Value::String(domain) | Value::LocaleString(domain) => Some(domain.as_ref()),
+            Value::Boolean(_) | Value::Numeric(_) => None,
+        })
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Error reading a MO file.
+#[derive(Debug, thiserror::Error)]
+pub enum MoError {
+    #[error("couldn't read the catalog")]
+    Io(#[from] io::Error),
+    #[error("invalid MO file")]
+    Invalid,
+}
+
+/// Compiled gettext catalog, read from a MO file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageCatalog {
+    /// Translations by id, prefixed by the context and [`CONTEXT_SEPARATOR`] if present.
+    messages: HashMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// Parses a catalog in the MO format, in either byte order.
+    ///
+    /// Only the singular form of the plural messages is kept.
+    pub fn parse(bytes: &[u8]) -> Result<Self, MoError> {
+        let read_u32 = |offset: usize, big_endian: bool| -> Result<u32, MoError> {
+            let bytes = bytes
+                .get(offset..offset + 4)
+                .and_then(|bytes| <[u8; 4]>::try_from(bytes).ok())
+                .ok_or(MoError::Invalid)?;
+
+            Ok(if big_endian {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            })
+        };
+
+        let big_endian = match read_u32(0, false)? {
+            MO_MAGIC => false,
+            magic if magic == MO_MAGIC.swap_bytes() => true,
+            _ => return Err(MoError::Invalid),
+        };
+
+        let read = |offset: usize| read_u32(offset, big_endian).map(|value| value as usize);
+
+        // Only the minor revision can change in a compatible way
+        if read(4)? >> 16 > 1 {
+            return Err(MoError::Invalid);
+        }
+
+        let len = read(8)?;
+        let ids = read(12)?;
+        let translations = read(16)?;
+
+        // Returns the first string, before the plural forms
+        let string = |table: usize, index: usize| -> Result<&str, MoError> {
+            let descriptor = table + index * 8;
+            let string_len = read(descriptor)?;
+            let offset = read(descriptor + 4)?;
+
+            let bytes = bytes
+                .get(offset..offset + string_len)
+                .ok_or(MoError::Invalid)?;
+            let string = utf8::from_utf8(bytes).map_err(|_| MoError::Invalid)?;
+
+            Ok(string.split('\0').next().unwrap_or_default())
+        };
+
+        let mut messages = HashMap::with_capacity(len);
+
+        for index in 0..len {
+            let id = string(ids, index)?;
+
+            // Skip the header
+            if id.is_empty() {
+                continue;
+            }
+
+            messages.insert(id.to_string(), string(translations, index)?.to_string());
+        }
+
+        Ok(Self { messages })
+    }
+
+    /// Reads a catalog from a MO file.
+    pub fn open(path: &Path) -> Result<Self, MoError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// Returns the translation of a message without context.
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.messages.get(id).map(String::as_str)
+    }
+
+    /// Returns the translation of a message with a context.
+    pub fn get_with_context(&self, context: &str, id: &str) -> Option<&str> {
+        self.get(&format!("{context}{CONTEXT_SEPARATOR}{id}"))
+    }
+
+    /// Returns the number of messages, without the header.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Catalogs by domain and normalized locale, [`None`] if not found.
+type CatalogCache = HashMap<(String, String), Option<Arc<MessageCatalog>>>;
+
+/// Looks up the translations of the entries in the catalogs of their gettext domain.
+///
+/// The catalogs are searched in the `LANG/LC_MESSAGES/DOMAIN.mo` files of the locale
+/// directories, and cached once read.
+#[derive(Debug, Default)]
+pub struct Translator {
+    dirs: Vec<PathBuf>,
+    catalogs: Mutex<CatalogCache>,
+}
+
+impl Translator {
+    /// Creates a translator searching the catalogs in the locale directories.
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        Self {
+            dirs,
+            catalogs: Mutex::default(),
+        }
+    }
+
+    /// Creates a translator searching the `locale` directories of the XDG data directories.
+    pub fn from_env() -> Self {
+        let dirs = loader::base_dirs()
+            .all_data_dirs()
+            .into_iter()
+            .map(|dir| dir.join("locale"))
+            .collect();
+
+        Self::new(dirs)
+    }
+
+    /// Returns the locale directories.
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Returns the catalog of the domain that best matches the locale.
+    ///
+    /// Like gettext, the catalogs are tried in the order `lang_COUNTRY@MODIFIER`,
+    /// `lang_COUNTRY`, `lang@MODIFIER` and `lang`, ignoring the encoding. An unreadable catalog
+    /// is skipped.
+    pub fn catalog(&self, domain: &str, locale: &Locale) -> Option<Arc<MessageCatalog>> {
+        let locale = locale.normalize();
+        let cache_key = (domain.to_string(), locale.to_string());
+
+        let mut catalogs = self.catalogs.lock().unwrap_or_else(|err| err.into_inner());
+
+        catalogs
+            .entry(cache_key)
+            .or_insert_with(|| {
+                let file = format!("{domain}.mo");
+                let file = file.as_str();
+
+                locale_variants(&locale)
+                    .iter()
+                    .flat_map(|variant| {
+                        self.dirs
+                            .iter()
+                            .map(move |dir| dir.join(variant).join("LC_MESSAGES").join(file))
+                    })
+                    .find_map(|path| MessageCatalog::open(&path).ok())
+                    .map(Arc::new)
+            })
+            .clone()
+    }
+
+    /// Returns the value of a key that best matches the locale, translated with the gettext
+    /// domain of the entry if needed.
+    ///
+    /// An inline translation matching the locale has precedence, otherwise the non localized
+    /// value is looked up in the catalog of the domain. Fallbacks to the non localized value if
+    /// it has no translation, like [`DesktopEntry::get_localized`].
+    pub fn get_localized<'e>(
+        &self,
+        entry: &'e DesktopEntry,
+        header: &str,
+        key: &str,
+        locale: Option<&Locale>,
+    ) -> Option<Cow<'e, str>> {
+        let value = |value: &'e Value| match value {
+            Value::String(value) | Value::LocaleString(value) => Some(value.as_ref()),
+            Value::Boolean(_) | Value::Numeric(_) => None,
+        };
+
+        let Some(locale) = locale else {
+            return entry.get(header, key).and_then(value).map(Cow::Borrowed);
+        };
+
+        let has_translation = entry
+            .localized_values(header, key)
+            .keys()
+            .flatten()
+            .any(|key_locale| locale.match_key(key_locale).is_some());
+
+        let localized = value(entry.get_localized(header, key, Some(locale))?)?;
+
+        if has_translation {
+            return Some(Cow::Borrowed(localized));
+        }
+
+        let translation = gettext_domain(entry)
+            .and_then(|domain| self.catalog(domain, locale))
+            .and_then(|catalog| catalog.get(localized).map(str::to_string));
+
+        Some(translation.map_or(Cow::Borrowed(localized), Cow::Owned))
+    }
+}
+
+/// Returns the names of the catalog directories for the locale, from the most specific.
+fn locale_variants(locale: &Locale) -> Vec<String> {
+    let lang = locale.lang();
+    let country = locale.country().map(|country| format!("{lang}_{country}"));
+
+    let mut variants = Vec::with_capacity(4);
+
+    if let Some(modifier) = locale.modifier() {
+        variants.extend(
+            country
+                .iter()
+                .map(|country| format!("{country}@{modifier}")),
+        );
+    }
+
+    variants.extend(country.clone());
+
+    if let Some(modifier) = locale.modifier() {
+        variants.push(format!("{lang}@{modifier}"));
+    }
+
+    variants.push(lang.to_string());
+
+    variants
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Builds a little endian MO file with the messages.
+    fn mo(messages: &[(&str, &str)]) -> Vec<u8> {
+        let header = 28;
+        let table_len = messages.len() * 8;
+        let mut strings = Vec::new();
+        let mut ids = Vec::new();
+        let mut translations = Vec::new();
+
+        let mut add = |table: &mut Vec<u8>, string: &str| {
+            let offset = header + 2 * table_len + strings.len();
+
+            table.extend((string.len() as u32).to_le_bytes());
+            table.extend((offset as u32).to_le_bytes());
+
+            strings.extend(string.as_bytes());
+            strings.push(0);
+        };
+
+        for (id, translation) in messages {
+            add(&mut ids, id);
+            add(&mut translations, translation);
+        }
+
+        let mut bytes = Vec::new();
+
+        for value in [
+            MO_MAGIC,
+            0,
+            messages.len() as u32,
+            28,
+            (28 + table_len) as u32,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes.extend([0; 8]);
+        bytes.extend(ids);
+        bytes.extend(translations);
+        bytes.extend(strings);
+
+        bytes
+    }
+
+    #[test]
+    fn should_parse_mo_catalog() {
+        let bytes = mo(&[
+            ("", "Language: de\n"),
+            ("Foo", "Fuh"),
+            ("Name\u{4}New", "Neu"),
+            ("file\0files", "Datei\0Dateien"),
+        ]);
+
+        let catalog = MessageCatalog::parse(&bytes).unwrap();
+
+        assert_eq!(3, catalog.len());
+        assert_eq!(Some("Fuh"), catalog.get("Foo"));
+        assert_eq!(Some("Neu"), catalog.get_with_context("Name", "New"));
+        assert_eq!(Some("Datei"), catalog.get("file"));
+        assert_eq!(None, catalog.get("New"));
+        assert!(matches!(
+            MessageCatalog::parse(&bytes[..20]),
+            Err(MoError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn should_translate_with_gettext_domain() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let messages = dir.join("de/LC_MESSAGES");
+
+        fs::create_dir_all(&messages).unwrap();
+        fs::write(
+            messages.join("foo.mo"),
+            mo(&[("Foo", "Fuh"), ("Say hi", "Sag hallo")]),
+        )
+        .unwrap();
+
+        let entry = DesktopEntry::parse(
+            "[Desktop Entry]\nName=Foo\nName[fr]=Fou\nComment=Say hi\nX-Ubuntu-Gettext-Domain=foo\n",
+        )
+        .unwrap();
+
+        let translator = Translator::new(vec![dir.join("missing"), dir.to_path_buf()]);
+        let get = |key: &str, locale: &str| {
+            let locale = Locale::parse(locale);
+
+            translator
+                .get_localized(&entry, DESKTOP_ENTRY_GROUP, key, locale.as_ref())
+                .map(Cow::into_owned)
+        };
+
+        let translated = [
+            get("Name", "de_DE.UTF-8"),
+            get("Comment", "de"),
+            get("Name", "fr_FR"),
+            get("Name", "it"),
+            get("Name", "C"),
+            get("Icon", "de"),
+        ];
+
+        assert_eq!(Some("foo"), gettext_domain(&entry));
+        assert_eq!(
+            [
+                Some("Fuh".to_string()),
+                Some("Sag hallo".to_string()),
+                Some("Fou".to_string()),
+                Some("Foo".to_string()),
+                Some("Foo".to_string()),
+                None,
+            ],
+            translated
+        );
+    }
+}
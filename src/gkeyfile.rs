@@ -0,0 +1,315 @@
+//! Access to the values like the getters of GLib's `GKeyFile`.
+//!
+//! The functions follow the rules of `g_key_file_get_boolean`, `g_key_file_get_integer` and the
+//! others, so an application ported from C reads the same values. They are meant for the
+//! documents parsed with [`Dialect::GKEY_FILE`](crate::Dialect::GKEY_FILE), where the values are
+//! kept as strings, but also accept the typed values of the other syntaxes.
+//!
+//! The escape sequences are replaced by the parser, so differently from `GKeyFile` an escaped
+//! `\;` in a list is read as a separator.
+//!
+//! ```
+//! use xdg_desktop_entry::{gkeyfile, DesktopEntry, Dialect, ParseOptions};
+//!
+//! let options = ParseOptions {
+//!     dialect: Dialect::GKEY_FILE,
+//!     ..Default::default()
+//! };
+//! let document =
+//!     DesktopEntry::parse_with_options("[Settings]\nFlags=1;0;true;\nSizes=3;-4\n", &options)
+//!         .unwrap();
+//!
+//! assert_eq!(
+//!     vec![true, false, true],
+//!     gkeyfile::get_boolean_list(&document, "Settings", "Flags").unwrap()
+//! );
+//! assert_eq!(
+//!     vec![3, -4],
+//!     gkeyfile::get_integer_list(&document, "Settings", "Sizes").unwrap()
+//! );
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{DesktopEntry, Value};
+
+/// Separator of the items of the lists.
+pub const LIST_SEPARATOR: char = ';';
+
+/// Error reading a value, like the `GKeyFileError` of GLib.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GKeyFileError {
+    #[error("the group `{0}` was not found")]
+    GroupNotFound(String),
+    #[error("the key `{key}` was not found in the group `{group}`")]
+    KeyNotFound { group: String, key: String },
+    #[error("the value `{value}` of `{key}` can't be interpreted as {expected}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+/// Returns the value as a string, like `g_key_file_get_string`.
+///
+/// # Errors
+///
+/// If the group or key is missing.
+pub fn get_string(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<String, GKeyFileError> {
+    get_text(document, group, key).map(Cow::into_owned)
+}
+
+/// Returns the value as a boolean, like `g_key_file_get_boolean`.
+///
+/// The value can be `true` or `1`, `false` or `0`, followed by spaces.
+///
+/// # Errors
+///
+/// If the group or key is missing, or the value is not a boolean.
+pub fn get_boolean(document: &DesktopEntry, group: &str, key: &str) -> Result<bool, GKeyFileError> {
+    let value = get_text(document, group, key)?;
+
+    parse_boolean(&value).ok_or_else(|| invalid(key, &value, "a boolean"))
+}
+
+/// Returns the value as an integer, like `g_key_file_get_integer`.
+///
+/// The value is a decimal number with an optional sign, surrounded by spaces.
+///
+/// # Errors
+///
+/// If the group or key is missing, or the value is not an integer or out of range.
+pub fn get_integer(document: &DesktopEntry, group: &str, key: &str) -> Result<i32, GKeyFileError> {
+    let value = get_text(document, group, key)?;
+
+    parse_integer(&value).ok_or_else(|| invalid(key, &value, "an integer"))
+}
+
+/// Returns the value as a double, like `g_key_file_get_double`.
+///
+/// # Errors
+///
+/// If the group or key is missing, or the value is not a number.
+pub fn get_double(document: &DesktopEntry, group: &str, key: &str) -> Result<f64, GKeyFileError> {
+    let value = get_text(document, group, key)?;
+
+    parse_double(&value).ok_or_else(|| invalid(key, &value, "a float number"))
+}
+
+/// Returns the value as a list of strings, like `g_key_file_get_string_list`.
+///
+/// The items are separated by `;`, the empty items are kept except the one after the trailing
+/// separator. An empty value is an empty list.
+///
+/// # Errors
+///
+/// If the group or key is missing.
+pub fn get_string_list(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<Vec<String>, GKeyFileError> {
+    let value = get_text(document, group, key)?;
+
+    Ok(split_list(&value).map(str::to_string).collect())
+}
+
+/// Returns the value as a list of booleans, like `g_key_file_get_boolean_list`.
+///
+/// # Errors
+///
+/// If the group or key is missing, or an item is not a boolean.
+pub fn get_boolean_list(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<Vec<bool>, GKeyFileError> {
+    get_list(document, group, key, parse_boolean, "a boolean")
+}
+
+/// Returns the value as a list of integers, like `g_key_file_get_integer_list`.
+///
+/// # Errors
+///
+/// If the group or key is missing, or an item is not an integer.
+pub fn get_integer_list(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<Vec<i32>, GKeyFileError> {
+    get_list(document, group, key, parse_integer, "an integer")
+}
+
+/// Returns the value as a list of doubles, like `g_key_file_get_double_list`.
+///
+/// # Errors
+///
+/// If the group or key is missing, or an item is not a number.
+pub fn get_double_list(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<Vec<f64>, GKeyFileError> {
+    get_list(document, group, key, parse_double, "a float number")
+}
+
+fn get_list<T>(
+    document: &DesktopEntry,
+    group: &str,
+    key: &str,
+    parse: fn(&str) -> Option<T>,
+    expected: &'static str,
+) -> Result<Vec<T>, GKeyFileError> {
+    let value = get_text(document, group, key)?;
+
+    split_list(&value)
+        .map(|item| parse(item).ok_or_else(|| invalid(key, item, expected)))
+        .collect()
+}
+
+/// Returns the text of the value in the group.
+fn get_text<'d>(
+    document: &'d DesktopEntry,
+    group: &str,
+    key: &str,
+) -> Result<Cow<'d, str>, GKeyFileError> {
+    if document.group(group).is_none() {
+        return Err(GKeyFileError::GroupNotFound(group.to_string()));
+    }
+
+    let value = document
+        .get(group, key)
+        .ok_or_else(|| GKeyFileError::KeyNotFound {
+            group: group.to_string(),
+            key: key.to_string(),
+        })?;
+
+    Ok(value_text(value))
+}
+
+/// Returns the text of a value, the numbers without the decimal point of the integers, like
+/// `3`.
+pub(crate) fn value_text<'v>(value: &'v Value) -> Cow<'v, str> {
+    match value {
+        Value::String(value) | Value::LocaleString(value) => Cow::Borrowed(value.as_ref()),
+        Value::Boolean(value) => Cow::Owned(value.to_string()),
+        Value::Numeric(value) => Cow::Owned(value.to_string()),
+    }
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    let value = value.strip_suffix(LIST_SEPARATOR).unwrap_or(value);
+
+    value
+        .split(LIST_SEPARATOR)
+        .take(if value.is_empty() { 0 } else { usize::MAX })
+}
+
+fn parse_boolean(value: &str) -> Option<bool> {
+    match value.trim_end_matches(|c: char| c.is_ascii_whitespace()) {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_integer(value: &str) -> Option<i32> {
+    value
+        .trim_matches(|c: char| c.is_ascii_whitespace())
+        .parse()
+        .ok()
+}
+
+fn parse_double(value: &str) -> Option<f64> {
+    value
+        .trim_matches(|c: char| c.is_ascii_whitespace())
+        .parse()
+        .ok()
+}
+
+fn invalid(key: &str, value: &str, expected: &'static str) -> GKeyFileError {
+    GKeyFileError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        expected,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Dialect, ParseOptions};
+
+    #[test]
+    fn should_read_values_like_gkeyfile() {
+        let options = ParseOptions {
+            dialect: Dialect::GKEY_FILE,
+            ..Default::default()
+        };
+        let document = DesktopEntry::parse_with_options(
+            "# comment\n  [Général]\nCount = 3\nEnabled=1\nRatio=0.5\nNames=a;;b;\nEmpty=\nFlags=true;0;yes\n",
+            &options,
+        )
+        .unwrap();
+
+        let group = "Général";
+
+        assert_eq!(Ok("3".to_string()), get_string(&document, group, "Count"));
+        assert_eq!(Ok(3), get_integer(&document, group, "Count"));
+        assert_eq!(Ok(true), get_boolean(&document, group, "Enabled"));
+        assert_eq!(Ok(0.5), get_double(&document, group, "Ratio"));
+        assert_eq!(
+            Ok(vec!["a".to_string(), String::new(), "b".to_string()]),
+            get_string_list(&document, group, "Names")
+        );
+        assert_eq!(Ok(Vec::new()), get_string_list(&document, group, "Empty"));
+        assert_eq!(
+            Err(GKeyFileError::InvalidValue {
+                key: "Flags".to_string(),
+                value: "yes".to_string(),
+                expected: "a boolean"
+            }),
+            get_boolean_list(&document, group, "Flags")
+        );
+        assert_eq!(
+            Err(GKeyFileError::InvalidValue {
+                key: "Ratio".to_string(),
+                value: "0.5".to_string(),
+                expected: "an integer"
+            }),
+            get_integer(&document, group, "Ratio")
+        );
+        assert_eq!(
+            Err(GKeyFileError::GroupNotFound("Foo".to_string())),
+            get_string(&document, "Foo", "Count")
+        );
+        assert_eq!(
+            Err(GKeyFileError::KeyNotFound {
+                group: group.to_string(),
+                key: "Missing".to_string()
+            }),
+            get_string(&document, group, "Missing")
+        );
+    }
+
+    #[test]
+    fn should_read_typed_values() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nX-Count=3\nX-Sizes=1;2;\nX-Flag=false\n")
+                .unwrap();
+
+        assert_eq!(Ok(3), get_integer(&document, "Desktop Entry", "X-Count"));
+        assert_eq!(
+            Ok(vec![1.0, 2.0]),
+            get_double_list(&document, "Desktop Entry", "X-Sizes")
+        );
+        assert_eq!(Ok(false), get_boolean(&document, "Desktop Entry", "X-Flag"));
+    }
+}
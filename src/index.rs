@@ -0,0 +1,825 @@
+//! Index of the applications installed in the XDG data directories.
+//!
+//! The index holds the parsed desktop files by desktop file ID, following the precedence of the
+//! data directories, and reverse maps to find the application of a running window or process.
+//!
+//! ```no_run
+//! use xdg_desktop_entry::index::AppIndex;
+//!
+//! let index = AppIndex::scan();
+//!
+//! for app in index.find_by_wm_class("firefox") {
+//!     println!("{}", app.id);
+//! }
+//! ```
+
+#[cfg(feature = "cache")]
+mod cache;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+    thread::{self, JoinHandle},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    exec::Exec,
+    intern::Interner,
+    loader::{self, Provenance, DESKTOP_EXTENSION},
+    naming, DesktopEntry, ParseOptions, Value, DESKTOP_ENTRY_GROUP,
+};
+
+#[cfg(feature = "cache")]
+pub use cache::{CacheError, CACHE_VERSION};
+
+/// Application in the [`AppIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppEntry {
+    /// Desktop file ID, like `org.example.App.desktop`.
+    pub id: String,
+    pub document: DesktopEntry<'static>,
+    /// Origin of the desktop file, [`None`] for the entries not loaded from a file.
+    pub provenance: Option<Provenance>,
+}
+
+impl AppEntry {
+    /// Returns the `StartupWMClass` of the application.
+    pub fn wm_class(&self) -> Option<&str> {
+        self.string("StartupWMClass")
+    }
+
+    /// Returns the file name of the program of the `Exec` key, like `foo` for `/usr/bin/foo %f`.
+    pub fn binary(&self) -> Option<String> {
+        let exec = Exec::parse(self.string("Exec")?).ok()?;
+
+        let name = Path::new(exec.program()).file_name()?.to_str()?;
+
+        Some(name.to_string())
+    }
+
+    /// Returns the interfaces of the `Implements` key, like `org.freedesktop.FileManager1`.
+    pub fn implements(&self) -> impl Iterator<Item = &str> {
+        self.string("Implements")
+            .unwrap_or_default()
+            .split(';')
+            .filter(|interface| !interface.is_empty())
+    }
+
+    /// Returns the D-Bus name derived from the desktop file ID, like `org.example.App`.
+    ///
+    /// It's [`None`] if the ID doesn't follow the naming convention, see [`naming::check_id`].
+    pub fn dbus_name(&self) -> Option<&str> {
+        if !naming::is_valid_id(&self.id) {
+            return None;
+        }
+
+        self.id
+            .strip_suffix(DESKTOP_EXTENSION)
+            .and_then(|name| name.strip_suffix('.'))
+    }
+
+    fn string(&self, key: &str) -> Option<&str> {
+        match self.document.get(DESKTOP_ENTRY_GROUP, key)? {
+            Value::String(value) | Value::LocaleString(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Identifiers of a window, to find its application with [`AppIndex::find_window`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WindowIds<'a> {
+    /// Wayland `app_id` of the window.
+    pub app_id: Option<&'a str>,
+    /// Class of the X11 `WM_CLASS` property.
+    pub wm_class: Option<&'a str>,
+    /// Instance of the X11 `WM_CLASS` property.
+    pub wm_instance: Option<&'a str>,
+}
+
+impl<'a> WindowIds<'a> {
+    /// Returns the identifiers of a Wayland window.
+    pub fn wayland(app_id: &'a str) -> Self {
+        Self {
+            app_id: Some(app_id),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the identifiers of an X11 window, from the class and instance of `WM_CLASS`.
+    pub fn x11(wm_class: &'a str, wm_instance: &'a str) -> Self {
+        Self {
+            wm_class: Some(wm_class),
+            wm_instance: Some(wm_instance),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the identifiers in order of priority.
+    fn names(&self) -> impl Iterator<Item = &'a str> + Clone {
+        [self.app_id, self.wm_class, self.wm_instance]
+            .into_iter()
+            .flatten()
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// Number of files parsed and reused by a scan of the [`AppIndex`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScanStats {
+    /// Files read and parsed.
+    pub parsed: usize,
+    /// Unchanged files whose entry was kept from the previous scan.
+    pub reused: usize,
+}
+
+/// Applications by desktop file ID, with reverse maps by window class, binary, D-Bus name and
+/// implemented interface.
+#[derive(Debug, Clone, Default)]
+pub struct AppIndex {
+    entries: IndexMap<String, AppEntry>,
+    by_wm_class: HashMap<String, Vec<usize>>,
+    by_binary: HashMap<String, Vec<usize>>,
+    by_dbus_name: HashMap<String, usize>,
+    by_interface: HashMap<String, Vec<usize>>,
+    interner: Interner,
+}
+
+impl AppIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scans the `applications` directories of the XDG data directories.
+    ///
+    /// See [`AppIndex::scan_dirs`].
+    pub fn scan() -> Self {
+        Self::scan_dirs(&loader::application_dirs())
+    }
+
+    /// Scans the desktop files in the `applications` directories, in order of preference.
+    ///
+    /// When more directories have a desktop file with the same ID, the one in the first
+    /// directory is used, and the others are listed in its [`Provenance::overrides`]. The entries
+    /// with `Hidden=true` are treated as deleted, hiding the files with the same ID in the
+    /// following directories. The files that can't be read or parsed are skipped.
+    pub fn scan_dirs(dirs: &[PathBuf]) -> Self {
+        let mut index = Self::new();
+        index.scan_with_cache(dirs, HashMap::new());
+
+        index
+    }
+
+    /// Scans again the `applications` directories, in order of preference.
+    ///
+    /// The files with the same path, size and modification time of the last scan are not parsed
+    /// again, their entries are kept. A file changed without changing its size, in the
+    /// granularity of the modification time of the file system, is not detected.
+    pub fn refresh(&mut self, dirs: &[PathBuf]) -> ScanStats {
+        let previous = std::mem::take(self);
+
+        self.interner = previous.interner;
+
+        let cache = previous
+            .entries
+            .into_values()
+            .filter_map(|entry| {
+                let provenance = entry.provenance.as_ref()?;
+
+                Some((provenance.path.clone(), entry))
+            })
+            .collect();
+
+        self.scan_with_cache(dirs, cache)
+    }
+
+    /// Scans the directories, reusing the cached entries of the unchanged files.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(dirs = dirs.len(), cached = cache.len()))
+    )]
+    fn scan_with_cache(
+        &mut self,
+        dirs: &[PathBuf],
+        mut cache: HashMap<PathBuf, AppEntry>,
+    ) -> ScanStats {
+        let mut stats = ScanStats::default();
+        let mut seen = HashSet::new();
+
+        for (rank, dir) in dirs.iter().enumerate() {
+            let paths = loader::DesktopFiles::new(dir).filter_map(|(path, result)| {
+                result.ok()?;
+
+                Some(path)
+            });
+
+            for path in paths {
+                let Some(id) = loader::desktop_file_id(dir, &path) else {
+                    continue;
+                };
+
+                if seen.contains(&id) {
+                    if let Some(provenance) = self
+                        .entries
+                        .get_mut(&id)
+                        .and_then(|entry| entry.provenance.as_mut())
+                    {
+                        provenance.overrides.push(path);
+                    }
+
+                    continue;
+                }
+
+                let provenance = Provenance::new(&path, rank);
+
+                #[cfg(feature = "tracing")]
+                if let Err(err) = &provenance {
+                    tracing::debug!(path = %path.display(), %err, "skipping unreadable desktop file");
+                }
+
+                let Ok(provenance) = provenance else {
+                    continue;
+                };
+
+                let cached = cache.remove(&path).filter(|entry| {
+                    entry.provenance.as_ref().is_some_and(|cached| {
+                        cached.size == provenance.size
+                            && cached.modified.is_some()
+                            && cached.modified == provenance.modified
+                    })
+                });
+
+                let document = match cached {
+                    Some(entry) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::trace!(path = %path.display(), "reusing cached desktop file");
+
+                        stats.reused += 1;
+
+                        entry.document
+                    }
+                    None => {
+                        let Ok(document) = loader::load(&path, &ParseOptions::default()) else {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(path = %path.display(), "skipping invalid desktop file");
+
+                            continue;
+                        };
+
+                        stats.parsed += 1;
+
+                        self.interner.intern_document(document)
+                    }
+                };
+
+                seen.insert(id.clone());
+
+                let hidden = matches!(
+                    document.get(DESKTOP_ENTRY_GROUP, "Hidden"),
+                    Some(Value::Boolean(true))
+                );
+
+                if !hidden {
+                    self.insert_entry(AppEntry {
+                        id,
+                        document,
+                        provenance: Some(provenance),
+                    });
+                }
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            parsed = stats.parsed,
+            reused = stats.reused,
+            entries = self.entries.len(),
+            "scanned applications"
+        );
+
+        stats
+    }
+
+    /// Returns the interner shared by the documents of the scanned files.
+    ///
+    /// The strings interned by the application, like the keys of the entries it inserts with
+    /// [`Interner::intern_document`], share the allocations of the index.
+    pub fn interner(&mut self) -> &mut Interner {
+        &mut self.interner
+    }
+
+    /// Adds an application to the index, replacing the one with the same ID.
+    pub fn insert(&mut self, id: impl Into<String>, document: DesktopEntry<'static>) {
+        self.insert_entry(AppEntry {
+            id: id.into(),
+            document,
+            provenance: None,
+        });
+    }
+
+    /// Adds an application with its provenance to the index, replacing the one with the same ID.
+    pub fn insert_entry(&mut self, entry: AppEntry) {
+        let id = entry.id.clone();
+
+        let index = match self.entries.get_index_of(&id) {
+            Some(index) => {
+                self.unlink(index);
+                self.entries[index] = entry;
+
+                index
+            }
+            None => self.entries.insert_full(id, entry).0,
+        };
+
+        self.link(index);
+    }
+
+    /// Returns the application with the given desktop file ID.
+    pub fn get(&self, id: &str) -> Option<&AppEntry> {
+        self.entries.get(id)
+    }
+
+    /// Returns the applications, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &AppEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the applications with the given `StartupWMClass`.
+    pub fn find_by_wm_class(&self, wm_class: &str) -> impl Iterator<Item = &AppEntry> {
+        self.find(&self.by_wm_class, wm_class)
+    }
+
+    /// Returns the applications running the given binary, by file name like `foo`.
+    pub fn find_by_binary(&self, binary: &str) -> impl Iterator<Item = &AppEntry> {
+        self.find(&self.by_binary, binary)
+    }
+
+    /// Returns the application with the D-Bus name, like `org.example.App`.
+    pub fn find_by_dbus_name(&self, name: &str) -> Option<&AppEntry> {
+        let index = *self.by_dbus_name.get(name)?;
+
+        self.entries.get_index(index).map(|(_, entry)| entry)
+    }
+
+    /// Returns the applications implementing the interface, like `org.freedesktop.FileManager1`.
+    pub fn apps_implementing(&self, interface: &str) -> impl Iterator<Item = &AppEntry> {
+        self.find(&self.by_interface, interface)
+    }
+
+    /// Returns the application of a window, from its Wayland `app_id` or X11 `WM_CLASS`.
+    ///
+    /// The identifiers are tried in order, first the `app_id`, then the class and the instance
+    /// of `WM_CLASS`, matching them in order against:
+    ///
+    /// 1. the `StartupWMClass` of the applications;
+    /// 2. the desktop file ID, like `org.example.App.desktop` for `org.example.App`, then
+    ///    ignoring the case;
+    /// 3. the `StartupWMClass` ignoring the case;
+    /// 4. the file name of the program of the `Exec` key, then in lower case.
+    ///
+    /// The first match is returned, in the order the applications were added.
+    pub fn find_window(&self, window: &WindowIds) -> Option<&AppEntry> {
+        let names = window.names();
+
+        let by_wm_class = |name: &str| self.find_by_wm_class(name).next();
+
+        let by_id = |name: &str| {
+            let id = format!("{name}.{DESKTOP_EXTENSION}");
+
+            self.get(&id)
+                .or_else(|| self.iter().find(|entry| entry.id.eq_ignore_ascii_case(&id)))
+        };
+
+        let by_wm_class_ignore_case = |name: &str| {
+            self.iter().find(|entry| {
+                entry
+                    .wm_class()
+                    .is_some_and(|wm_class| wm_class.eq_ignore_ascii_case(name))
+            })
+        };
+
+        let by_binary = |name: &str| {
+            self.find_by_binary(name)
+                .next()
+                .or_else(|| self.find_by_binary(&name.to_lowercase()).next())
+        };
+
+        names
+            .clone()
+            .find_map(by_wm_class)
+            .or_else(|| names.clone().find_map(by_id))
+            .or_else(|| names.clone().find_map(by_wm_class_ignore_case))
+            .or_else(|| names.clone().find_map(by_binary))
+    }
+
+    fn find<'a>(
+        &'a self,
+        map: &'a HashMap<String, Vec<usize>>,
+        key: &str,
+    ) -> impl Iterator<Item = &'a AppEntry> {
+        map.get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|index| self.entries.get_index(*index).map(|(_, entry)| entry))
+    }
+
+    /// Adds the entry at the index to the reverse maps.
+    fn link(&mut self, index: usize) {
+        let entry = &self.entries[index];
+
+        if let Some(wm_class) = entry.wm_class() {
+            self.by_wm_class
+                .entry(wm_class.to_string())
+                .or_default()
+                .push(index);
+        }
+
+        if let Some(binary) = entry.binary() {
+            self.by_binary.entry(binary).or_default().push(index);
+        }
+
+        if let Some(name) = entry.dbus_name() {
+            self.by_dbus_name.insert(name.to_string(), index);
+        }
+
+        for interface in entry.implements() {
+            self.by_interface
+                .entry(interface.to_string())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    /// Removes the entry at the index from the reverse maps.
+    fn unlink(&mut self, index: usize) {
+        let entry = &self.entries[index];
+
+        let remove = |map: &mut HashMap<String, Vec<usize>>, key: &str| {
+            if let Some(indexes) = map.get_mut(key) {
+                indexes.retain(|i| *i != index);
+
+                if indexes.is_empty() {
+                    map.remove(key);
+                }
+            }
+        };
+
+        if let Some(wm_class) = entry.wm_class() {
+            remove(&mut self.by_wm_class, wm_class);
+        }
+
+        if let Some(binary) = entry.binary() {
+            remove(&mut self.by_binary, &binary);
+        }
+
+        if let Some(name) = entry.dbus_name() {
+            self.by_dbus_name.remove(name);
+        }
+
+        for interface in entry.implements() {
+            remove(&mut self.by_interface, interface);
+        }
+    }
+}
+
+/// Application index shared between threads, refreshed in the background.
+///
+/// The readers get a snapshot of the index, that stays valid while a refresh scans the
+/// directories again. The new index is swapped in at the end of the scan, so the queries never
+/// see a partially updated index and are not blocked during the scan.
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use xdg_desktop_entry::{index::SharedIndex, loader};
+///
+/// let dirs = loader::application_dirs();
+/// let shared = Arc::new(SharedIndex::scan_dirs(&dirs));
+///
+/// // On a change of the directories
+/// let handle = shared.spawn_refresh(dirs);
+///
+/// let snapshot = shared.snapshot();
+/// println!("{} applications", snapshot.len());
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedIndex {
+    index: RwLock<Arc<AppIndex>>,
+}
+
+impl SharedIndex {
+    pub fn new(index: AppIndex) -> Self {
+        Self {
+            index: RwLock::new(Arc::new(index)),
+        }
+    }
+
+    /// Scans the directories, see [`AppIndex::scan_dirs`].
+    pub fn scan_dirs(dirs: &[PathBuf]) -> Self {
+        Self::new(AppIndex::scan_dirs(dirs))
+    }
+
+    /// Returns the current index.
+    pub fn snapshot(&self) -> Arc<AppIndex> {
+        self.index
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the index, returning the previous one.
+    pub fn replace(&self, index: AppIndex) -> Arc<AppIndex> {
+        let mut current = self.index.write().unwrap_or_else(PoisonError::into_inner);
+
+        std::mem::replace(&mut current, Arc::new(index))
+    }
+
+    /// Scans the directories again and swaps in the new index, see [`AppIndex::refresh`].
+    ///
+    /// The scan works on a copy of the current index, the lock is held only to swap it.
+    pub fn refresh(&self, dirs: &[PathBuf]) -> ScanStats {
+        let mut index = AppIndex::clone(&self.snapshot());
+
+        let stats = index.refresh(dirs);
+
+        self.replace(index);
+
+        stats
+    }
+
+    /// Refreshes the index in a background thread.
+    pub fn spawn_refresh(self: &Arc<Self>, dirs: Vec<PathBuf>) -> JoinHandle<ScanStats> {
+        let shared = Arc::clone(self);
+
+        thread::spawn(move || shared.refresh(&dirs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn entry(input: &str) -> DesktopEntry<'static> {
+        DesktopEntry::parse(input).unwrap().into_owned()
+    }
+
+    fn ids<'a>(entries: impl Iterator<Item = &'a AppEntry>) -> Vec<&'a str> {
+        entries.map(|entry| entry.id.as_str()).collect()
+    }
+
+    #[test]
+    fn should_find_by_reverse_maps() {
+        let mut index = AppIndex::new();
+        index.insert(
+            "org.example.Foo.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Foo\nExec=/usr/bin/foo %f\nStartupWMClass=foo-window\n"),
+        );
+        index.insert(
+            "bar.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Bar\nExec=foo --bar\n"),
+        );
+
+        assert_eq!(
+            vec!["org.example.Foo.desktop"],
+            ids(index.find_by_wm_class("foo-window"))
+        );
+        assert_eq!(
+            vec!["org.example.Foo.desktop", "bar.desktop"],
+            ids(index.find_by_binary("foo"))
+        );
+        assert_eq!(
+            Some("org.example.Foo.desktop"),
+            index
+                .find_by_dbus_name("org.example.Foo")
+                .map(|entry| entry.id.as_str())
+        );
+        assert!(index.find_by_dbus_name("bar").is_none());
+
+        index.insert(
+            "org.example.Foo.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Foo\nExec=baz\n"),
+        );
+
+        assert_eq!(2, index.len());
+        assert!(index.find_by_wm_class("foo-window").next().is_none());
+        assert_eq!(vec!["bar.desktop"], ids(index.find_by_binary("foo")));
+        assert_eq!(
+            vec!["org.example.Foo.desktop"],
+            ids(index.find_by_binary("baz"))
+        );
+    }
+
+    #[test]
+    fn should_find_apps_implementing_an_interface() {
+        let mut index = AppIndex::new();
+
+        index.insert(
+            "org.example.Files.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Files\nExec=files\nImplements=org.freedesktop.FileManager1;org.gnome.Shell.SearchProvider2;\n"),
+        );
+        index.insert(
+            "org.example.Search.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Search\nExec=search\nImplements=org.gnome.Shell.SearchProvider2\n"),
+        );
+
+        assert_eq!(
+            vec!["org.example.Files.desktop"],
+            ids(index.apps_implementing("org.freedesktop.FileManager1"))
+        );
+        assert_eq!(
+            vec!["org.example.Files.desktop", "org.example.Search.desktop"],
+            ids(index.apps_implementing("org.gnome.Shell.SearchProvider2"))
+        );
+
+        index.insert(
+            "org.example.Files.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Files\nExec=files\n"),
+        );
+
+        assert!(index
+            .apps_implementing("org.freedesktop.FileManager1")
+            .next()
+            .is_none());
+        assert_eq!(
+            vec!["org.example.Search.desktop"],
+            ids(index.apps_implementing("org.gnome.Shell.SearchProvider2"))
+        );
+    }
+
+    #[test]
+    fn should_find_the_application_of_a_window() {
+        let mut index = AppIndex::new();
+
+        index.insert(
+            "org.example.Foo.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n"),
+        );
+        index.insert(
+            "bar.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Bar\nExec=/opt/bar/bin/bar-bin\nStartupWMClass=Bar-Window\n"),
+        );
+        index.insert(
+            "baz.desktop",
+            entry("[Desktop Entry]\nType=Application\nName=Baz\nExec=baz\nStartupWMClass=org.example.Foo\n"),
+        );
+
+        let id = |window: WindowIds| index.find_window(&window).map(|entry| entry.id.as_str());
+
+        // The StartupWMClass has priority over the desktop file ID
+        assert_eq!(
+            Some("baz.desktop"),
+            id(WindowIds::wayland("org.example.Foo"))
+        );
+        assert_eq!(
+            Some("org.example.Foo.desktop"),
+            id(WindowIds::wayland("ORG.EXAMPLE.FOO"))
+        );
+        assert_eq!(
+            Some("bar.desktop"),
+            id(WindowIds::x11("bar-window", "bar-window"))
+        );
+        assert_eq!(
+            Some("bar.desktop"),
+            id(WindowIds::x11("Bar-bin", "whatever"))
+        );
+        assert_eq!(None, id(WindowIds::wayland("qux")));
+        assert_eq!(None, id(WindowIds::default()));
+    }
+
+    #[test]
+    fn should_scan_dirs_with_precedence() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let user = root.join("user/applications");
+        let system = root.join("system/applications");
+
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(system.join("kde")).unwrap();
+
+        let app =
+            |name: &str| format!("[Desktop Entry]\nType=Application\nName={name}\nExec=foo\n");
+
+        fs::write(user.join("org.example.Foo.desktop"), app("User")).unwrap();
+        fs::write(
+            user.join("kde-hidden.desktop"),
+            "[Desktop Entry]\nHidden=true\n",
+        )
+        .unwrap();
+        fs::write(system.join("org.example.Foo.desktop"), app("System")).unwrap();
+        fs::write(system.join("kde/hidden.desktop"), app("Hidden")).unwrap();
+        fs::write(system.join("kde/kate.desktop"), app("Kate")).unwrap();
+        fs::write(system.join("broken.desktop"), "Name\n").unwrap();
+
+        let index = AppIndex::scan_dirs(&[user.clone(), system.clone()]);
+
+        assert_eq!(
+            vec!["org.example.Foo.desktop", "kde-kate.desktop"],
+            ids(index.iter())
+        );
+        let foo = index.get("org.example.Foo.desktop").unwrap();
+        let provenance = foo.provenance.as_ref().unwrap();
+
+        assert_eq!(
+            Some(&Value::String("User".into())),
+            foo.document.get(DESKTOP_ENTRY_GROUP, "Name")
+        );
+        assert_eq!(user.join("org.example.Foo.desktop"), provenance.path);
+        assert_eq!(0, provenance.rank);
+        assert!(provenance.modified.is_some());
+        assert_eq!(
+            vec![system.join("org.example.Foo.desktop")],
+            provenance.overrides
+        );
+        assert_eq!(
+            1,
+            index
+                .get("kde-kate.desktop")
+                .and_then(|entry| entry.provenance.as_ref())
+                .map(|provenance| provenance.rank)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_swap_shared_index_on_refresh() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        fs::write(
+            dir.join("foo.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n",
+        )
+        .unwrap();
+
+        let dirs = vec![dir.to_path_buf()];
+        let shared = Arc::new(SharedIndex::scan_dirs(&dirs));
+        let before = shared.snapshot();
+
+        fs::write(
+            dir.join("bar.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Bar\nExec=bar\n",
+        )
+        .unwrap();
+
+        let stats = shared.spawn_refresh(dirs).join().unwrap();
+
+        assert_eq!(
+            ScanStats {
+                parsed: 1,
+                reused: 1
+            },
+            stats
+        );
+        assert_eq!(vec!["foo.desktop"], ids(before.iter()));
+        assert_eq!(
+            vec!["bar.desktop", "foo.desktop"],
+            ids(shared.snapshot().iter())
+        );
+    }
+
+    #[test]
+    fn should_refresh_only_changed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let app =
+            |name: &str| format!("[Desktop Entry]\nType=Application\nName={name}\nExec=foo\n");
+
+        fs::write(dir.join("foo.desktop"), app("Foo")).unwrap();
+        fs::write(dir.join("bar.desktop"), app("Bar")).unwrap();
+
+        let dirs = [dir.to_path_buf()];
+        let mut index = AppIndex::new();
+
+        let first = index.refresh(&dirs);
+        let unchanged = index.refresh(&dirs);
+
+        fs::write(dir.join("bar.desktop"), app("Barbaz")).unwrap();
+        fs::remove_file(dir.join("foo.desktop")).unwrap();
+        fs::write(dir.join("baz.desktop"), app("Baz")).unwrap();
+
+        let changed = index.refresh(&dirs);
+
+        let stats = |parsed, reused| ScanStats { parsed, reused };
+
+        assert_eq!(stats(2, 0), first);
+        assert_eq!(stats(0, 2), unchanged);
+        assert_eq!(stats(2, 0), changed);
+        assert_eq!(vec!["bar.desktop", "baz.desktop"], ids(index.iter()));
+    }
+}
@@ -0,0 +1,355 @@
+//! Persistent cache of the [`AppIndex`], behind the `cache` feature.
+//!
+//! The cache starts with the version of the format and the scanned directories, followed by the
+//! entries with their [`Provenance`]. A launcher can start from the cache and then
+//! [`refresh`](AppIndex::refresh) the index in the background: the files not changed since the
+//! cache was written are not parsed again.
+//!
+//! The comments and the formatting of the documents are not stored in the cache.
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AppEntry, AppIndex};
+use crate::{loader::Provenance, DesktopEntry, EntryMap, GroupMap, Key, Locale, Value};
+
+/// Version of the format of the cache, changed when the format is not compatible.
+pub const CACHE_VERSION: u32 = 1;
+
+/// Error reading or writing the cache of the [`AppIndex`].
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    #[error("couldn't read or write the cache")]
+    Io(#[from] io::Error),
+    #[error("couldn't encode or decode the cache")]
+    Encoding(#[from] bincode::Error),
+    #[error("unsupported cache version {found}, expected {CACHE_VERSION}")]
+    Version { found: u32 },
+    #[error("the cache was written for different directories")]
+    Dirs,
+}
+
+impl AppIndex {
+    /// Writes the index scanned from the directories to the cache file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn save_cache(&self, path: impl AsRef<Path>, dirs: &[PathBuf]) -> Result<(), CacheError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        self.write_cache(&mut writer, dirs)?;
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the index scanned from the directories to the cache.
+    ///
+    /// # Errors
+    ///
+    /// If the cache can't be written.
+    pub fn write_cache(&self, mut writer: impl Write, dirs: &[PathBuf]) -> Result<(), CacheError> {
+        let entries = self
+            .entries
+            .values()
+            .map(CachedEntry::from)
+            .collect::<Vec<_>>();
+
+        bincode::serialize_into(&mut writer, &CACHE_VERSION)?;
+        bincode::serialize_into(&mut writer, dirs)?;
+        bincode::serialize_into(&mut writer, &entries)?;
+
+        Ok(())
+    }
+
+    /// Reads the index from the cache file written for the same directories.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or it was written by a different version or for other
+    /// directories.
+    pub fn load_cache(path: impl AsRef<Path>, dirs: &[PathBuf]) -> Result<Self, CacheError> {
+        Self::read_cache(BufReader::new(File::open(path)?), dirs)
+    }
+
+    /// Reads the index from the cache written for the same directories.
+    ///
+    /// # Errors
+    ///
+    /// If the cache can't be read, or it was written by a different version or for other
+    /// directories.
+    pub fn read_cache(mut reader: impl Read, dirs: &[PathBuf]) -> Result<Self, CacheError> {
+        let found: u32 = bincode::deserialize_from(&mut reader)?;
+
+        if found != CACHE_VERSION {
+            return Err(CacheError::Version { found });
+        }
+
+        let cached_dirs: Vec<PathBuf> = bincode::deserialize_from(&mut reader)?;
+
+        if cached_dirs != dirs {
+            return Err(CacheError::Dirs);
+        }
+
+        let entries: Vec<CachedEntry> = bincode::deserialize_from(&mut reader)?;
+
+        let mut index = Self::new();
+
+        for entry in entries {
+            index.insert_entry(entry.into());
+        }
+
+        Ok(index)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedEntry {
+    id: String,
+    provenance: Option<CachedProvenance>,
+    groups: Vec<(String, Vec<(CachedKey, CachedValue)>)>,
+}
+
+impl From<&AppEntry> for CachedEntry {
+    fn from(entry: &AppEntry) -> Self {
+        let groups = entry
+            .document
+            .groups
+            .iter()
+            .map(|(header, entries)| {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| (CachedKey::from(key), CachedValue::from(value)))
+                    .collect();
+
+                (header.to_string(), entries)
+            })
+            .collect();
+
+        Self {
+            id: entry.id.clone(),
+            provenance: entry.provenance.as_ref().map(CachedProvenance::from),
+            groups,
+        }
+    }
+}
+
+impl From<CachedEntry> for AppEntry {
+    fn from(entry: CachedEntry) -> Self {
+        let groups = entry
+            .groups
+            .into_iter()
+            .map(|(header, entries)| {
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into(), value.into()))
+                    .collect::<EntryMap>();
+
+                (Cow::Owned(header), entries)
+            })
+            .collect::<GroupMap>();
+
+        Self {
+            id: entry.id,
+            document: DesktopEntry {
+                groups,
+                ..DesktopEntry::default()
+            },
+            provenance: entry.provenance.map(Provenance::from),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedProvenance {
+    path: PathBuf,
+    rank: usize,
+    user: bool,
+    modified: Option<SystemTime>,
+    size: u64,
+    overrides: Vec<PathBuf>,
+}
+
+impl From<&Provenance> for CachedProvenance {
+    fn from(provenance: &Provenance) -> Self {
+        Self {
+            path: provenance.path.clone(),
+            rank: provenance.rank,
+            user: provenance.user,
+            modified: provenance.modified,
+            size: provenance.size,
+            overrides: provenance.overrides.clone(),
+        }
+    }
+}
+
+impl From<CachedProvenance> for Provenance {
+    fn from(provenance: CachedProvenance) -> Self {
+        Self {
+            path: provenance.path,
+            rank: provenance.rank,
+            user: provenance.user,
+            modified: provenance.modified,
+            size: provenance.size,
+            overrides: provenance.overrides,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedKey {
+    key: String,
+    locale: Option<CachedLocale>,
+}
+
+impl From<&Key<'_>> for CachedKey {
+    fn from(key: &Key<'_>) -> Self {
+        match key {
+            Key::Simple(key) => Self {
+                key: key.to_string(),
+                locale: None,
+            },
+            Key::Localized { key, locale } => Self {
+                key: key.to_string(),
+                locale: Some(CachedLocale {
+                    lang: locale.lang.to_string(),
+                    country: locale.country.as_deref().map(str::to_string),
+                    encoding: locale.encoding.as_deref().map(str::to_string),
+                    modifier: locale.modifier.as_deref().map(str::to_string),
+                }),
+            },
+        }
+    }
+}
+
+impl From<CachedKey> for Key<'static> {
+    fn from(key: CachedKey) -> Self {
+        let Some(locale) = key.locale else {
+            return Key::Simple(Cow::Owned(key.key));
+        };
+
+        Key::Localized {
+            key: Cow::Owned(key.key),
+            locale: Locale {
+                lang: Cow::Owned(locale.lang),
+                country: locale.country.map(Cow::Owned),
+                encoding: locale.encoding.map(Cow::Owned),
+                modifier: locale.modifier.map(Cow::Owned),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedLocale {
+    lang: String,
+    country: Option<String>,
+    encoding: Option<String>,
+    modifier: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedValue {
+    String(String),
+    LocaleString(String),
+    Boolean(bool),
+    Numeric(f32),
+}
+
+impl From<&Value<'_>> for CachedValue {
+    fn from(value: &Value<'_>) -> Self {
+        match value {
+            Value::String(value) => CachedValue::String(value.to_string()),
+            Value::LocaleString(value) => CachedValue::LocaleString(value.to_string()),
+            Value::Boolean(value) => CachedValue::Boolean(*value),
+            Value::Numeric(value) => CachedValue::Numeric(*value),
+        }
+    }
+}
+
+impl From<CachedValue> for Value<'static> {
+    fn from(value: CachedValue) -> Self {
+        match value {
+            CachedValue::String(value) => Value::String(Cow::Owned(value)),
+            CachedValue::LocaleString(value) => Value::LocaleString(Cow::Owned(value)),
+            CachedValue::Boolean(value) => Value::Boolean(value),
+            CachedValue::Numeric(value) => Value::Numeric(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::index::ScanStats;
+
+    #[test]
+    fn should_round_trip_and_refresh_from_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let applications = dir.join("applications");
+        fs::create_dir_all(&applications).unwrap();
+
+        fs::write(
+            applications.join("org.example.Foo.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Foo\nName[de_DE@euro]=Fuh\nExec=foo %f\nTerminal=false\nStartupWMClass=foo-window\n",
+        )
+        .unwrap();
+        fs::write(
+            applications.join("bar.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Bar\nExec=bar\n",
+        )
+        .unwrap();
+
+        let dirs = [applications.clone()];
+        let index = AppIndex::scan_dirs(&dirs);
+
+        let cache = dir.join("index.cache");
+        index.save_cache(&cache, &dirs).unwrap();
+
+        let mut loaded = AppIndex::load_cache(&cache, &dirs).unwrap();
+        let cached = loaded.iter().cloned().collect::<Vec<_>>();
+        let other = AppIndex::load_cache(&cache, &[dir.to_path_buf()]);
+        let stats = loaded.refresh(&dirs);
+
+        assert_eq!(index.iter().cloned().collect::<Vec<_>>(), cached);
+        assert_eq!(
+            Some("org.example.Foo.desktop"),
+            loaded
+                .find_by_wm_class("foo-window")
+                .next()
+                .map(|entry| entry.id.as_str())
+        );
+        assert!(matches!(other, Err(CacheError::Dirs)));
+        assert_eq!(
+            ScanStats {
+                parsed: 0,
+                reused: 2
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn should_reject_other_version() {
+        let mut buf = Vec::new();
+        bincode::serialize_into(&mut buf, &(CACHE_VERSION + 1)).unwrap();
+
+        let result = AppIndex::read_cache(buf.as_slice(), &[]);
+
+        assert!(matches!(result, Err(CacheError::Version { found }) if found == CACHE_VERSION + 1));
+    }
+}
@@ -0,0 +1,220 @@
+//! Installation of desktop files, like `desktop-file-install`.
+//!
+//! The file is edited, validated and written at once in the target `applications` directory, so
+//! build tools can install the desktop files of an application without the `desktop-file-utils`
+//! binaries.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use xdg_desktop_entry::{
+//!     edit::Change,
+//!     install::{install, InstallOptions},
+//!     DESKTOP_ENTRY_GROUP,
+//! };
+//!
+//! let options = InstallOptions {
+//!     vendor: Some("org.example".to_string()),
+//!     changes: vec![Change::remove(DESKTOP_ENTRY_GROUP, "X-Build-Id")],
+//!     ..InstallOptions::default()
+//! };
+//!
+//! let path = install(
+//!     Path::new("data/app.desktop"),
+//!     Path::new("/usr/share/applications"),
+//!     &options,
+//! )?;
+//! # Ok::<(), xdg_desktop_entry::install::InstallError>(())
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    edit::{self, Change},
+    user::write_atomic,
+    validate::{validate_str_with_file_id, Diagnostic, Severity, ValidateOptions},
+};
+
+/// Options of [`install`].
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    /// Prefix added to the file name with a `-`, unless already present.
+    pub vendor: Option<String>,
+    /// Changes applied to the file before the validation.
+    pub changes: Vec<Change>,
+    /// Validate the installed file, failing on errors.
+    pub validate: bool,
+    /// Options of the validation.
+    pub validate_options: ValidateOptions,
+    /// Permissions of the installed file, on Unix.
+    pub mode: u32,
+    /// Removes the source file after the installation.
+    pub delete_original: bool,
+}
+
+impl Default for InstallOptions {
+    /// Returns the options to validate the file and install it readable by all the users.
+    fn default() -> Self {
+        Self {
+            vendor: None,
+            changes: Vec::new(),
+            validate: true,
+            validate_options: ValidateOptions::default(),
+            mode: 0o644,
+            delete_original: false,
+        }
+    }
+}
+
+/// Error returned by [`install`].
+#[derive(Debug, thiserror::Error)]
+pub enum InstallError {
+    #[error("couldn't install the desktop file")]
+    Io(#[from] io::Error),
+    /// The validation found errors.
+    #[error("invalid desktop file, {} errors found", .0.len())]
+    Invalid(Vec<Diagnostic>),
+}
+
+/// Installs a desktop file in the target `applications` directory.
+///
+/// The changes are applied to the source text, keeping the formatting, and the result is
+/// validated with the desktop file ID of the installed file. Nothing is written if the
+/// validation has errors. Returns the path of the installed file.
+///
+/// # Errors
+///
+/// If the file can't be read or written, or it's invalid.
+pub fn install(
+    source: &Path,
+    target_dir: &Path,
+    options: &InstallOptions,
+) -> Result<PathBuf, InstallError> {
+    let name = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?;
+
+    let id = match &options.vendor {
+        Some(vendor) if !name.starts_with(&format!("{vendor}-")) => format!("{vendor}-{name}"),
+        _ => name.to_string(),
+    };
+
+    let content = fs::read_to_string(source)?;
+    let content = edit::apply(&content, &options.changes);
+
+    if options.validate {
+        let errors = validate_str_with_file_id(&content, Some(&id), &options.validate_options)
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect::<Vec<_>>();
+
+        if !errors.is_empty() {
+            return Err(InstallError::Invalid(errors));
+        }
+    }
+
+    let target = target_dir.join(&id);
+
+    write_atomic(&target, &content)?;
+    set_mode(&target, options.mode)?;
+
+    if options.delete_original && fs::canonicalize(source)? != fs::canonicalize(&target)? {
+        fs::remove_file(source)?;
+    }
+
+    Ok(target)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Value, DESKTOP_ENTRY_GROUP};
+
+    #[test]
+    fn should_install_with_vendor_and_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let source = dir.join("src/foo.desktop");
+        let target_dir = dir.join("applications");
+
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(
+            &source,
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=@BINDIR@/foo\nX-Build-Id=1\n",
+        )
+        .unwrap();
+
+        let options = InstallOptions {
+            vendor: Some("example".to_string()),
+            changes: vec![
+                Change::set(
+                    DESKTOP_ENTRY_GROUP,
+                    "Exec",
+                    Value::String("/usr/bin/foo".into()),
+                ),
+                Change::remove(DESKTOP_ENTRY_GROUP, "X-Build-Id"),
+            ],
+            delete_original: true,
+            ..InstallOptions::default()
+        };
+
+        let path = install(&source, &target_dir, &options).unwrap();
+        let installed = fs::read_to_string(&path).unwrap();
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777
+        };
+        let source_exists = source.exists();
+
+        assert_eq!(target_dir.join("example-foo.desktop"), path);
+        assert_eq!(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=/usr/bin/foo\n",
+            installed
+        );
+        #[cfg(unix)]
+        assert_eq!(0o644, mode);
+        assert!(!source_exists);
+    }
+
+    #[test]
+    fn should_not_install_invalid_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let source = dir.join("foo.desktop");
+        let target_dir = dir.join("applications");
+        fs::write(&source, "[Desktop Entry]\nType=Application\nExec=foo\n").unwrap();
+
+        let result = install(&source, &target_dir, &InstallOptions::default());
+        let installed = target_dir.join("foo.desktop").exists();
+
+        let Err(InstallError::Invalid(errors)) = result else {
+            panic!("expected validation errors");
+        };
+
+        assert_eq!(
+            vec!["DE103"],
+            errors.iter().map(|d| d.code).collect::<Vec<_>>()
+        );
+        assert!(!installed);
+    }
+}
@@ -0,0 +1,208 @@
+//! Interning of the strings repeated across many documents, like the key names.
+//!
+//! The interned documents borrow the shared strings instead of owning a copy each, so an index
+//! of thousands of entries stores the `Name` or `Exec` keys once.
+//!
+//! ```
+//! use xdg_desktop_entry::{intern::Interner, DesktopEntry};
+//!
+//! let mut interner = Interner::new();
+//!
+//! let first = interner.intern_document(DesktopEntry::parse("[Desktop Entry]\nName=Foo\n").unwrap());
+//! let second = interner.intern_document(DesktopEntry::parse("[Desktop Entry]\nName=Bar\n").unwrap());
+//!
+//! let header = |document: &DesktopEntry| document.groups().next().unwrap().0.as_ptr();
+//!
+//! assert_eq!(header(&first), header(&second));
+//! ```
+
+use std::{borrow::Cow, collections::HashSet};
+
+use crate::{
+    keys::{ACTION_KEYS, KEYS},
+    main_group::EntryType,
+    DesktopEntry, Key, Locale, Value, DESKTOP_ENTRY_GROUP,
+};
+
+/// Set of the interned strings.
+///
+/// The strings are leaked to be shared by all the documents for the rest of the program, so only
+/// a bounded vocabulary should be interned: the interner starts with the standard keys and
+/// values, and [`Interner::intern_document`] adds the headers, the key names and the locales, but
+/// only reuses the values already interned.
+#[derive(Debug, Clone)]
+pub struct Interner {
+    strings: HashSet<&'static str>,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        let types = [
+            EntryType::Application,
+            EntryType::Link,
+            EntryType::Directory,
+        ];
+
+        let strings = KEYS
+            .iter()
+            .chain(ACTION_KEYS)
+            .map(|info| info.name)
+            .chain(types.iter().map(EntryType::as_str))
+            .chain([DESKTOP_ENTRY_GROUP])
+            .collect();
+
+        Self { strings }
+    }
+}
+
+impl Interner {
+    /// Creates an interner with the standard keys and values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of the string, interning it if missing.
+    pub fn intern(&mut self, value: &str) -> &'static str {
+        if let Some(interned) = self.get(value) {
+            return interned;
+        }
+
+        let interned: &'static str = Box::leak(value.into());
+
+        self.strings.insert(interned);
+
+        interned
+    }
+
+    /// Returns the shared copy of the string, if interned.
+    pub fn get(&self, value: &str) -> Option<&'static str> {
+        self.strings.get(value).copied()
+    }
+
+    /// Number of interned strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Converts the document into one borrowing the interned strings, like
+    /// [`DesktopEntry::into_owned`].
+    ///
+    /// The values and the comments not already interned are owned by the document.
+    pub fn intern_document(&mut self, document: DesktopEntry) -> DesktopEntry<'static> {
+        let DesktopEntry {
+            groups,
+            comments,
+            sources,
+            line_ending,
+            byte_order_mark,
+            warnings,
+        } = document;
+
+        let groups = groups
+            .into_iter()
+            .map(|(header, entries)| {
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| (self.intern_key(key), self.intern_value(value)))
+                    .collect();
+
+                (Cow::Borrowed(self.intern(&header)), entries)
+            })
+            .collect();
+
+        DesktopEntry {
+            groups,
+            comments: comments.into_owned(),
+            sources: sources.into_owned(),
+            line_ending,
+            byte_order_mark,
+            warnings,
+        }
+    }
+
+    fn intern_key(&mut self, key: Key) -> Key<'static> {
+        match key {
+            Key::Simple(key) => Key::Simple(Cow::Borrowed(self.intern(&key))),
+            Key::Localized { key, locale } => Key::Localized {
+                key: Cow::Borrowed(self.intern(&key)),
+                locale: self.intern_locale(locale),
+            },
+        }
+    }
+
+    fn intern_locale(&mut self, locale: Locale) -> Locale<'static> {
+        let lang = Cow::Borrowed(self.intern(&locale.lang));
+        let mut intern =
+            |part: Option<Cow<str>>| part.map(|part| Cow::Borrowed(self.intern(&part)));
+
+        Locale {
+            lang,
+            country: intern(locale.country),
+            encoding: intern(locale.encoding),
+            modifier: intern(locale.modifier),
+        }
+    }
+
+    fn intern_value(&self, value: Value) -> Value<'static> {
+        let interned = |value: Cow<str>| match self.get(&value) {
+            Some(interned) => Cow::Borrowed(interned),
+            None => Cow::Owned(value.into_owned()),
+        };
+
+        match value {
+            Value::String(value) => Value::String(interned(value)),
+            Value::LocaleString(value) => Value::LocaleString(interned(value)),
+            Value::Boolean(value) => Value::Boolean(value),
+            Value::Numeric(value) => Value::Numeric(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_share_interned_strings() {
+        let mut interner = Interner::new();
+        let len = interner.len();
+
+        let input = "[Desktop Entry]\nType=Application\nName=Foo\nName[de]=Fu\nX-Foo=Bar\n";
+        let documents = [
+            interner.intern_document(DesktopEntry::parse(input).unwrap()),
+            interner.intern_document(DesktopEntry::parse(input).unwrap()),
+        ];
+
+        // The header and the type are standard, the locale and the extension key are added
+        assert_eq!(len + 2, interner.len());
+        assert_eq!(documents[0], DesktopEntry::parse(input).unwrap());
+
+        let pointers = |document: &DesktopEntry| {
+            document
+                .groups()
+                .flat_map(|(_, entries)| entries.iter())
+                .map(|(key, value)| (key.name().as_ptr(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pointers(&documents[0]), pointers(&documents[1]));
+        assert!(matches!(
+            documents[0].get(DESKTOP_ENTRY_GROUP, "Type"),
+            Some(Value::String(Cow::Borrowed(_)))
+        ));
+        assert!(matches!(
+            documents[0].get(DESKTOP_ENTRY_GROUP, "X-Foo"),
+            Some(Value::String(Cow::Owned(_)))
+        ));
+        assert_eq!(
+            interner.intern("Foo").as_ptr(),
+            interner.intern("Foo").as_ptr()
+        );
+    }
+}
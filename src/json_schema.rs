@@ -0,0 +1,307 @@
+//! [JSON Schema] of the desktop files serialized as JSON, generated from the [`keys`] registry.
+//!
+//! The schema describes the document as serialized with the `serde` feature: an object of the
+//! groups, each one an object from the key, with the locale in square brackets, to the value.
+//! Since the values are typed by their content, a string key can also be a number or a boolean,
+//! like `Version=1.5`. The keys not defined by the specification are allowed.
+//!
+//! [JSON Schema]: https://json-schema.org/
+
+use std::fmt::Write;
+
+use crate::{
+    keys::{self, EntryTypes, KeyInfo, ValueType},
+    validate::ENTRY_TYPES,
+    DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+/// Dialect of the generated schema.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Node of the generated JSON document.
+enum Json {
+    Object(Vec<(String, Json)>),
+    Array(Vec<Json>),
+    String(String),
+}
+
+impl Json {
+    fn object<'a>(members: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        Json::Object(
+            members
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn string(value: impl Into<String>) -> Self {
+        Json::String(value.into())
+    }
+
+    fn strings<'a>(values: impl IntoIterator<Item = &'a str>) -> Self {
+        Json::Array(values.into_iter().map(Json::string).collect())
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        let pad = |out: &mut String, indent: usize| out.push_str(&"  ".repeat(indent));
+
+        match self {
+            Json::Object(members) if members.is_empty() => out.push_str("{}"),
+            Json::Object(members) => {
+                out.push_str("{\n");
+
+                for (i, (name, value)) in members.iter().enumerate() {
+                    pad(out, indent + 1);
+                    write_string(out, name);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+
+                    if i + 1 < members.len() {
+                        out.push(',');
+                    }
+
+                    out.push('\n');
+                }
+
+                pad(out, indent);
+                out.push('}');
+            }
+            Json::Array(items) => {
+                out.push('[');
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+
+                    item.write(out, indent);
+                }
+
+                out.push(']');
+            }
+            Json::String(value) => write_string(out, value),
+        }
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+/// Returns the JSON Schema of the desktop files, pretty printed.
+pub fn json_schema() -> String {
+    let required = |keys: &[KeyInfo], entry_types: &[EntryTypes]| {
+        keys.iter()
+            .filter(|info| info.required && entry_types.contains(&info.entry_types))
+            .map(|info| info.name)
+            .collect::<Vec<_>>()
+    };
+
+    let mut desktop_entry = group_schema(keys::KEYS, required(keys::KEYS, &[EntryTypes::All]));
+
+    if let Json::Object(members) = &mut desktop_entry {
+        members.push(("allOf".to_string(), required_by_type()));
+    }
+
+    // The keys of the actions are required in all of them
+    let action = group_schema(
+        keys::ACTION_KEYS,
+        required(
+            keys::ACTION_KEYS,
+            &[EntryTypes::All, EntryTypes::Application],
+        ),
+    );
+
+    let action_pattern = format!("^{}.+$", regex_escape(DESKTOP_ACTION_PREFIX));
+
+    let schema = Json::object([
+        ("$schema", Json::string(JSON_SCHEMA_DIALECT)),
+        ("title", Json::string("Desktop Entry")),
+        (
+            "description",
+            Json::string("Desktop file of the freedesktop.org Desktop Entry Specification."),
+        ),
+        ("type", Json::string("object")),
+        ("required", Json::strings([DESKTOP_ENTRY_GROUP])),
+        (
+            "properties",
+            Json::object([(DESKTOP_ENTRY_GROUP, desktop_entry)]),
+        ),
+        (
+            "patternProperties",
+            Json::object([(action_pattern.as_str(), action)]),
+        ),
+        (
+            "additionalProperties",
+            Json::object([("type", Json::string("object"))]),
+        ),
+    ]);
+
+    let mut out = String::new();
+    schema.write(&mut out, 0);
+    out.push('\n');
+
+    out
+}
+
+/// Returns the schema of a group with the keys.
+fn group_schema(keys: &[KeyInfo], required: Vec<&str>) -> Json {
+    let properties = keys.iter().map(|info| (info.name, key_schema(info)));
+
+    let localized = keys
+        .iter()
+        .filter(|info| {
+            matches!(
+                info.value_type,
+                ValueType::LocaleString | ValueType::IconString
+            )
+        })
+        .map(|info| info.name)
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let localized_pattern = format!("^({localized})\\[[^\\]]+\\]$");
+    let extension_pattern = "^X-[A-Za-z0-9-]+(\\[[^\\]]+\\])?$";
+
+    Json::object([
+        ("type", Json::string("object")),
+        ("required", Json::strings(required)),
+        ("properties", Json::object(properties)),
+        (
+            "patternProperties",
+            Json::object([
+                (localized_pattern.as_str(), scalar_schema()),
+                (extension_pattern, scalar_schema()),
+            ]),
+        ),
+    ])
+}
+
+/// Returns the conditions on the `Type` requiring the keys of the entry type.
+fn required_by_type() -> Json {
+    let conditions = [
+        ("Application", EntryTypes::Application),
+        ("Link", EntryTypes::Link),
+    ]
+    .into_iter()
+    .filter_map(|(entry_type, entry_types)| {
+        let required: Vec<&str> = keys::KEYS
+            .iter()
+            .filter(|info| info.required && info.entry_types == entry_types)
+            .map(|info| info.name)
+            .collect();
+
+        if required.is_empty() {
+            return None;
+        }
+
+        Some(Json::object([
+            (
+                "if",
+                Json::object([(
+                    "properties",
+                    Json::object([("Type", Json::object([("const", Json::string(entry_type))]))]),
+                )]),
+            ),
+            (
+                "then",
+                Json::object([("required", Json::strings(required))]),
+            ),
+        ]))
+    });
+
+    Json::Array(conditions.collect())
+}
+
+fn key_schema(info: &KeyInfo) -> Json {
+    let mut members = vec![("description", Json::string(info.description))];
+
+    if info.name == "Type" {
+        members.push(("type", Json::string("string")));
+        members.push(("enum", Json::strings(ENTRY_TYPES)));
+
+        return Json::object(members);
+    }
+
+    match (info.list, info.value_type) {
+        (true, _) => members.push(("type", Json::string("string"))),
+        (false, ValueType::Boolean) => members.push(("type", Json::string("boolean"))),
+        (false, ValueType::Numeric) => members.push(("type", Json::string("number"))),
+        (false, _) => members.push(("type", Json::strings(["string", "number", "boolean"]))),
+    }
+
+    if info.list {
+        members.push(("x-list-separator", Json::string(";")));
+    }
+
+    Json::object(members)
+}
+
+/// Schema of a value of any type.
+fn scalar_schema() -> Json {
+    Json::object([("type", Json::strings(["string", "number", "boolean"]))])
+}
+
+fn regex_escape(value: &str) -> String {
+    value.chars().fold(String::new(), |mut escaped, c| {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+
+        escaped
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_generate_json_schema() {
+        let schema: serde_json::Value = serde_json::from_str(&json_schema()).unwrap();
+
+        assert_eq!(JSON_SCHEMA_DIALECT, schema["$schema"]);
+
+        let desktop_entry = &schema["properties"]["Desktop Entry"];
+
+        assert_eq!(
+            serde_json::json!(["Type", "Name"]),
+            desktop_entry["required"]
+        );
+        assert_eq!("boolean", desktop_entry["properties"]["Terminal"]["type"]);
+        assert_eq!(
+            serde_json::json!(["Application", "Link", "Directory"]),
+            desktop_entry["properties"]["Type"]["enum"]
+        );
+        assert_eq!(
+            serde_json::json!({
+                "if": {"properties": {"Type": {"const": "Link"}}},
+                "then": {"required": ["URL"]}
+            }),
+            desktop_entry["allOf"][0]
+        );
+        assert_eq!(
+            serde_json::json!(["Name"]),
+            schema["patternProperties"]["^Desktop Action .+$"]["required"]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Keys of the KDE service files, the KDE extensions to the desktop entries.
+//!
+//! KService reads some keys without the `X-` prefix, like `InitialPreference`, and falls back to
+//! the `X-KDE-` keys of older files when the standard key is missing.
+
+use crate::{extension::ExtensionValue, DesktopEntry, Value, DESKTOP_ENTRY_GROUP};
+
+/// Name of KDE in `XDG_CURRENT_DESKTOP`, `OnlyShowIn` and `NotShowIn`.
+pub const KDE_DESKTOP: &str = "KDE";
+
+/// Returns if the application sends the startup notification, from `StartupNotify` or the
+/// older `X-KDE-StartupNotify`.
+///
+/// It's [`None`] if neither key is set, KDE then assumes the notification is sent.
+pub fn startup_notify(document: &DesktopEntry) -> Option<bool> {
+    boolean(document, "StartupNotify").or_else(|| boolean(document, "X-KDE-StartupNotify"))
+}
+
+/// Returns the `InitialPreference` of the application, used to rank the applications
+/// associated with the same MIME type. The higher is preferred.
+pub fn initial_preference(document: &DesktopEntry) -> Option<i32> {
+    document
+        .get(DESKTOP_ENTRY_GROUP, "InitialPreference")
+        .and_then(i32::from_value)
+}
+
+/// Returns the URL schemes supported by the application in `X-KDE-Protocols`, like `http`.
+///
+/// The protocols are separated by `,` in the KDE files, or by `;` like the other lists.
+pub fn protocols(document: &DesktopEntry) -> Vec<String> {
+    let Some(protocols) = document
+        .get(DESKTOP_ENTRY_GROUP, "X-KDE-Protocols")
+        .and_then(String::from_value)
+    else {
+        return Vec::new();
+    };
+
+    protocols
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|protocol| !protocol.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Checks if the application should run on the discrete GPU, with `X-KDE-RunOnDiscreteGpu`.
+pub fn run_on_discrete_gpu(document: &DesktopEntry) -> bool {
+    boolean(document, "X-KDE-RunOnDiscreteGpu") == Some(true)
+}
+
+/// Checks if the entry is hidden from the menus of KDE, like KService does.
+///
+/// The entry is hidden with `NoDisplay=true`, if `OnlyShowIn` doesn't list `KDE`, or if
+/// `NotShowIn` lists it.
+pub fn no_display(document: &DesktopEntry) -> bool {
+    if boolean(document, "NoDisplay") == Some(true) {
+        return true;
+    }
+
+    let lists_kde = |key: &str| {
+        document
+            .get(DESKTOP_ENTRY_GROUP, key)
+            .and_then(Vec::<String>::from_value)
+            .map(|desktops| desktops.iter().any(|desktop| desktop == KDE_DESKTOP))
+    };
+
+    lists_kde("OnlyShowIn") == Some(false) || lists_kde("NotShowIn") == Some(true)
+}
+
+fn boolean(document: &DesktopEntry, key: &str) -> Option<bool> {
+    match document.get(DESKTOP_ENTRY_GROUP, key)? {
+        Value::Boolean(value) => Some(*value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_read_kde_keys() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo %u\nX-KDE-StartupNotify=false\nInitialPreference=9\nX-KDE-Protocols=http,https, ftp\nX-KDE-RunOnDiscreteGpu=true\n",
+        )
+        .unwrap();
+
+        assert_eq!(Some(false), startup_notify(&document));
+        assert_eq!(Some(9), initial_preference(&document));
+        assert_eq!(vec!["http", "https", "ftp"], protocols(&document));
+        assert!(run_on_discrete_gpu(&document));
+        assert!(!no_display(&document));
+
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\nStartupNotify=true\nX-KDE-StartupNotify=false\n",
+        )
+        .unwrap();
+
+        assert_eq!(Some(true), startup_notify(&document));
+        assert_eq!(None, initial_preference(&document));
+        assert!(protocols(&document).is_empty());
+    }
+
+    #[test]
+    fn should_hide_entries_not_shown_in_kde() {
+        let hidden = |keys: &str| {
+            let input = format!("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n{keys}");
+
+            no_display(&DesktopEntry::parse(&input).unwrap())
+        };
+
+        assert!(hidden("NoDisplay=true\nOnlyShowIn=KDE;\n"));
+        assert!(hidden("OnlyShowIn=GNOME;XFCE;\n"));
+        assert!(hidden("NotShowIn=KDE;\n"));
+        assert!(!hidden("OnlyShowIn=GNOME;KDE;\n"));
+        assert!(!hidden("NotShowIn=GNOME;\n"));
+    }
+}
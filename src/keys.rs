@@ -0,0 +1,359 @@
+//! Registry of the keys defined by the specification for the `[Desktop Entry]` group.
+
+use phf::phf_map;
+
+/// Type of the value of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// ASCII string.
+    String,
+    /// UTF-8 string that can be localized.
+    LocaleString,
+    /// Icon name or absolute path, that can be localized.
+    IconString,
+    Boolean,
+    Numeric,
+}
+
+impl ValueType {
+    /// Returns the name of the type used by the specification.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::String => "string",
+            ValueType::LocaleString => "localestring",
+            ValueType::IconString => "iconstring",
+            ValueType::Boolean => "boolean",
+            ValueType::Numeric => "numeric",
+        }
+    }
+}
+
+/// Types of desktop entries a key can be used in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryTypes {
+    All,
+    Application,
+    Link,
+}
+
+impl EntryTypes {
+    /// Checks if the key can be used in an entry with the given `Type`.
+    pub fn allows(&self, entry_type: &str) -> bool {
+        match self {
+            EntryTypes::All => true,
+            EntryTypes::Application => entry_type == "Application",
+            EntryTypes::Link => entry_type == "Link",
+        }
+    }
+}
+
+/// Description of a standard key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyInfo {
+    pub name: &'static str,
+    pub value_type: ValueType,
+    /// The value is a list of values separated by `;`.
+    pub list: bool,
+    pub required: bool,
+    pub entry_types: EntryTypes,
+    /// Short description from the specification.
+    pub description: &'static str,
+}
+
+const fn key(
+    name: &'static str,
+    value_type: ValueType,
+    list: bool,
+    entry_types: EntryTypes,
+    description: &'static str,
+) -> KeyInfo {
+    KeyInfo {
+        name,
+        value_type,
+        list,
+        required: false,
+        entry_types,
+        description,
+    }
+}
+
+const fn required(info: KeyInfo) -> KeyInfo {
+    KeyInfo {
+        required: true,
+        ..info
+    }
+}
+
+/// Standard keys of the `[Desktop Entry]` group, in the order of the specification.
+pub const KEYS: &[KeyInfo] = &[
+    required(key(
+        "Type",
+        ValueType::String,
+        false,
+        EntryTypes::All,
+        "Type of the desktop entry: `Application`, `Link` or `Directory`.",
+    )),
+    key(
+        "Version",
+        ValueType::String,
+        false,
+        EntryTypes::All,
+        "Version of the Desktop Entry Specification that the desktop entry conforms with.",
+    ),
+    required(key(
+        "Name",
+        ValueType::LocaleString,
+        false,
+        EntryTypes::All,
+        "Specific name of the application, for example \"Mozilla\".",
+    )),
+    key(
+        "GenericName",
+        ValueType::LocaleString,
+        false,
+        EntryTypes::All,
+        "Generic name of the application, for example \"Web Browser\".",
+    ),
+    key(
+        "NoDisplay",
+        ValueType::Boolean,
+        false,
+        EntryTypes::All,
+        "The application exists, but shouldn't be displayed in the menus.",
+    ),
+    key(
+        "Comment",
+        ValueType::LocaleString,
+        false,
+        EntryTypes::All,
+        "Tooltip for the entry, for example \"View sites on the Internet\".",
+    ),
+    key(
+        "Icon",
+        ValueType::IconString,
+        false,
+        EntryTypes::All,
+        "Icon to display in file manager, menus, etc. Either an absolute path or a name looked up in the icon theme.",
+    ),
+    key(
+        "Hidden",
+        ValueType::Boolean,
+        false,
+        EntryTypes::All,
+        "The user deleted the entry, it's equivalent to the file not existing at all.",
+    ),
+    key(
+        "OnlyShowIn",
+        ValueType::String,
+        true,
+        EntryTypes::All,
+        "Desktop environments that should display the entry.",
+    ),
+    key(
+        "NotShowIn",
+        ValueType::String,
+        true,
+        EntryTypes::All,
+        "Desktop environments that should not display the entry.",
+    ),
+    key(
+        "DBusActivatable",
+        ValueType::Boolean,
+        false,
+        EntryTypes::Application,
+        "The application is launched with D-Bus activation instead of the `Exec` key.",
+    ),
+    key(
+        "TryExec",
+        ValueType::String,
+        false,
+        EntryTypes::Application,
+        "Executable used to determine if the program is actually installed.",
+    ),
+    key(
+        "Exec",
+        ValueType::String,
+        false,
+        EntryTypes::Application,
+        "Program to execute, possibly with arguments and field codes like `%f`.",
+    ),
+    key(
+        "Path",
+        ValueType::String,
+        false,
+        EntryTypes::Application,
+        "Working directory to run the program in.",
+    ),
+    key(
+        "Terminal",
+        ValueType::Boolean,
+        false,
+        EntryTypes::Application,
+        "Whether the program runs in a terminal window.",
+    ),
+    key(
+        "Actions",
+        ValueType::String,
+        true,
+        EntryTypes::Application,
+        "Identifiers of the application actions, each one with a `[Desktop Action <id>]` group.",
+    ),
+    key(
+        "MimeType",
+        ValueType::String,
+        true,
+        EntryTypes::Application,
+        "The MIME types supported by the application.",
+    ),
+    key(
+        "Categories",
+        ValueType::String,
+        true,
+        EntryTypes::Application,
+        "Categories in which the entry should be shown in a menu.",
+    ),
+    key(
+        "Implements",
+        ValueType::String,
+        true,
+        EntryTypes::All,
+        "Interfaces that the application implements.",
+    ),
+    key(
+        "Keywords",
+        ValueType::LocaleString,
+        true,
+        EntryTypes::Application,
+        "Strings used in addition to other metadata to describe the entry, for example when searching.",
+    ),
+    key(
+        "StartupNotify",
+        ValueType::Boolean,
+        false,
+        EntryTypes::Application,
+        "The application sends a \"remove\" message when started with `DESKTOP_STARTUP_ID` set.",
+    ),
+    key(
+        "StartupWMClass",
+        ValueType::String,
+        false,
+        EntryTypes::Application,
+        "WM class or WM name hint of at least one window mapped by the application.",
+    ),
+    required(key(
+        "URL",
+        ValueType::String,
+        false,
+        EntryTypes::Link,
+        "URL to access.",
+    )),
+    key(
+        "PrefersNonDefaultGPU",
+        ValueType::Boolean,
+        false,
+        EntryTypes::Application,
+        "The application prefers to be run on a more powerful discrete GPU if available.",
+    ),
+    key(
+        "SingleMainWindow",
+        ValueType::Boolean,
+        false,
+        EntryTypes::Application,
+        "The application has a single main window and doesn't support opening another one.",
+    ),
+];
+
+/// Keys of the `[Desktop Action <id>]` groups.
+pub const ACTION_KEYS: &[KeyInfo] = &[
+    required(key(
+        "Name",
+        ValueType::LocaleString,
+        false,
+        EntryTypes::Application,
+        "Label of the action displayed to the user.",
+    )),
+    key(
+        "Icon",
+        ValueType::IconString,
+        false,
+        EntryTypes::Application,
+        "Icon of the action.",
+    ),
+    key(
+        "Exec",
+        ValueType::String,
+        false,
+        EntryTypes::Application,
+        "Program to execute for the action, possibly with arguments and field codes like `%f`.",
+    ),
+];
+
+/// Index of the [`KEYS`] by name.
+static KEYS_BY_NAME: phf::Map<&'static str, &'static KeyInfo> = phf_map! {
+    "Type" => &KEYS[0],
+    "Version" => &KEYS[1],
+    "Name" => &KEYS[2],
+    "GenericName" => &KEYS[3],
+    "NoDisplay" => &KEYS[4],
+    "Comment" => &KEYS[5],
+    "Icon" => &KEYS[6],
+    "Hidden" => &KEYS[7],
+    "OnlyShowIn" => &KEYS[8],
+    "NotShowIn" => &KEYS[9],
+    "DBusActivatable" => &KEYS[10],
+    "TryExec" => &KEYS[11],
+    "Exec" => &KEYS[12],
+    "Path" => &KEYS[13],
+    "Terminal" => &KEYS[14],
+    "Actions" => &KEYS[15],
+    "MimeType" => &KEYS[16],
+    "Categories" => &KEYS[17],
+    "Implements" => &KEYS[18],
+    "Keywords" => &KEYS[19],
+    "StartupNotify" => &KEYS[20],
+    "StartupWMClass" => &KEYS[21],
+    "URL" => &KEYS[22],
+    "PrefersNonDefaultGPU" => &KEYS[23],
+    "SingleMainWindow" => &KEYS[24],
+};
+
+/// Index of the [`ACTION_KEYS`] by name.
+static ACTION_KEYS_BY_NAME: phf::Map<&'static str, &'static KeyInfo> = phf_map! {
+    "Name" => &ACTION_KEYS[0],
+    "Icon" => &ACTION_KEYS[1],
+    "Exec" => &ACTION_KEYS[2],
+};
+
+/// Returns the standard key of the `[Desktop Entry]` group with the given name.
+pub fn lookup(name: &str) -> Option<&'static KeyInfo> {
+    KEYS_BY_NAME.get(name).copied()
+}
+
+/// Returns the standard key of a `[Desktop Action <id>]` group with the given name.
+pub fn lookup_action(name: &str) -> Option<&'static KeyInfo> {
+    ACTION_KEYS_BY_NAME.get(name).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_index_all_the_keys() {
+        assert_eq!(KEYS.len(), KEYS_BY_NAME.len());
+        assert_eq!(ACTION_KEYS.len(), ACTION_KEYS_BY_NAME.len());
+
+        for info in KEYS {
+            assert_eq!(Some(info), lookup(info.name));
+        }
+
+        for info in ACTION_KEYS {
+            assert_eq!(Some(info), lookup_action(info.name));
+        }
+
+        assert_eq!(None, lookup("X-Foo"));
+        assert_eq!(None, lookup_action("Type"));
+    }
+}
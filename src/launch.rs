@@ -0,0 +1,983 @@
+//! Launch of the applications described by a desktop file.
+
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod flatpak;
+#[cfg(feature = "systemd")]
+pub mod systemd;
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt::{self, Debug},
+    io,
+    path::{Path, PathBuf},
+    process::{self, Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    exec::{Exec, ExecContext, ExecError, FieldCode},
+    kde, uri, DesktopEntry, Error, Locale, Value, DESKTOP_ENTRY_GROUP,
+};
+
+/// Options to launch an application.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions<'o> {
+    /// Files or URLs to open with the application.
+    pub files: Vec<OsString>,
+    /// Path of the desktop file, for the `%k` field code.
+    pub location: Option<PathBuf>,
+    /// Locale of the translated name for the `%c` field code.
+    pub locale: Option<Locale<'o>>,
+    /// Rejects the entries with a command line that relies on a shell, see
+    /// [`Exec::parse_hardened`].
+    pub hardened: bool,
+    /// Terminal emulators tried in order for the `Terminal=true` entries, if
+    /// [`XDG_TERMINAL_EXEC`] is not installed.
+    ///
+    /// If empty the [default terminals](TerminalEmulator::defaults) are used.
+    pub terminals: Vec<TerminalEmulator>,
+    /// Token of the XDG Activation protocol, passed to the application so the compositor can
+    /// give it the focus on Wayland.
+    ///
+    /// If [`None`] the token of the launcher isn't inherited, since it's valid only once.
+    pub activation_token: Option<String>,
+    /// Startup notification ID for the `StartupNotify=true` entries.
+    ///
+    /// If [`None`] an ID is generated with [`startup_id`]. The launcher sends the `new:` and
+    /// `remove:` messages of the startup notification protocol itself.
+    pub startup_id: Option<String>,
+    /// X server time of the event that caused the launch, used in the generated startup
+    /// notification ID. Zero if unknown.
+    pub event_time: u32,
+    /// Environment to run the `PrefersNonDefaultGPU=true` entries on the discrete GPU, like the
+    /// one of the GPU from `switcheroo-control` or the [default one](gpu_offload_env).
+    ///
+    /// If empty the entries are run on the default GPU.
+    pub gpu_offload_env: Vec<(String, String)>,
+    /// Runs the applications exported by Flatpak with `flatpak run`, instead of their `Exec`
+    /// command, see [`flatpak::app_id`].
+    pub flatpak_run: bool,
+    /// Working directory of the application if the entry has no `Path` key.
+    pub working_dir: Option<PathBuf>,
+    /// Additional environment variables of the application.
+    pub env: Vec<(OsString, OsString)>,
+    /// Hooks run in the child process before executing the application.
+    #[cfg(unix)]
+    pub pre_exec: Vec<PreExecHook>,
+    /// Detaches the application from the launcher, so it doesn't die with it.
+    ///
+    /// The standard streams of the application are redirected to `/dev/null`. On Unix the
+    /// child process starts a new session and forks the application, exiting right away: the
+    /// returned [`Child`] is the intermediate process and must be waited to reap it, see
+    /// [`launch_detached`].
+    pub detach: bool,
+}
+
+/// Hook run in the child process after the fork, before executing the program.
+#[cfg(unix)]
+#[derive(Clone)]
+pub struct PreExecHook(Arc<dyn Fn() -> io::Result<()> + Send + Sync>);
+
+#[cfg(unix)]
+impl PreExecHook {
+    /// Creates a hook, returning an error from the hook fails the spawn.
+    ///
+    /// # Safety
+    ///
+    /// The hook runs in the forked process, it has the same requirements as
+    /// [`CommandExt::pre_exec`](std::os::unix::process::CommandExt::pre_exec).
+    pub unsafe fn new(hook: impl Fn() -> io::Result<()> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+#[cfg(unix)]
+impl Debug for PreExecHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PreExecHook")
+    }
+}
+
+#[cfg(unix)]
+impl PartialEq for PreExecHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(unix)]
+impl Eq for PreExecHook {}
+
+/// Environment variable with the startup notification ID of the launched application.
+pub const DESKTOP_STARTUP_ID: &str = "DESKTOP_STARTUP_ID";
+
+/// Sequence number of the generated startup notification IDs.
+static STARTUP_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Environment variable with the XDG Activation token of the launched application.
+pub const XDG_ACTIVATION_TOKEN: &str = "XDG_ACTIVATION_TOKEN";
+
+/// Launcher of the terminal applications, from the `xdg-terminal-exec` proposal.
+///
+/// It runs the command passed as arguments in the terminal emulator preferred by the user.
+pub const XDG_TERMINAL_EXEC: &str = "xdg-terminal-exec";
+
+/// Terminal emulator to run the `Terminal=true` applications.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalEmulator {
+    /// Program of the terminal, searched in `PATH` if not a path.
+    pub program: String,
+    /// Arguments before the command to run, like `-e`.
+    pub exec_args: Vec<String>,
+}
+
+impl TerminalEmulator {
+    pub fn new(program: impl Into<String>, exec_args: &[&str]) -> Self {
+        Self {
+            program: program.into(),
+            exec_args: exec_args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// Returns the common terminal emulators, with the flags to run a command.
+    pub fn defaults() -> Vec<Self> {
+        [
+            ("x-terminal-emulator", &["-e"][..]),
+            ("gnome-terminal", &["--"]),
+            ("konsole", &["-e"]),
+            ("xfce4-terminal", &["-x"]),
+            ("kitty", &[]),
+            ("alacritty", &["-e"]),
+            ("foot", &[]),
+            ("wezterm", &["start", "--"]),
+            ("xterm", &["-e"]),
+        ]
+        .into_iter()
+        .map(|(program, exec_args)| Self::new(program, exec_args))
+        .collect()
+    }
+}
+
+/// Error launching an application.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LaunchError {
+    #[error(transparent)]
+    Exec(#[from] ExecError),
+    #[error("no terminal emulator found to run the application")]
+    MissingTerminal,
+    #[error("the application is not D-Bus activatable")]
+    NotDBusActivatable,
+    #[error("the application can't open the remote URI `{0}`")]
+    RemoteUri(String),
+}
+
+/// Returns the command to launch the application, without spawning it.
+///
+/// The command runs the program directly, without a shell, and each file is passed in a single
+/// argument. Only the first file is passed if the command line accepts a single file with `%f`
+/// or `%u`, see [`commands`] to launch an instance for each file.
+///
+/// # Errors
+///
+/// If the entry is not an application, the `Exec` key is missing or invalid, or no terminal
+/// emulator is found for a `Terminal=true` application.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(files = options.files.len()))
+)]
+pub fn command(document: &DesktopEntry, options: &LaunchOptions) -> Result<Command, LaunchError> {
+    check_application(document)?;
+
+    let text = |value: Option<&Value>| match value {
+        Some(Value::String(value) | Value::LocaleString(value)) => Some(value.to_string()),
+        _ => None,
+    };
+
+    let icon = text(document.get(DESKTOP_ENTRY_GROUP, "Icon"));
+    let name = text(document.get_localized(DESKTOP_ENTRY_GROUP, "Name", options.locale.as_ref()));
+
+    let flatpak_app_id = options
+        .flatpak_run
+        .then(|| flatpak::app_id(document, options.location.as_deref()))
+        .flatten();
+
+    let mut argv = match flatpak_app_id {
+        Some(app_id) => flatpak::run_args(&app_id, &options.files),
+        None => {
+            let exec = parse_exec(document, options.hardened)?;
+
+            let context = ExecContext {
+                files: &options.files,
+                icon: icon.as_deref(),
+                name: name.as_deref(),
+                location: options.location.as_deref(),
+            };
+
+            exec.expand(&context)
+        }
+    };
+
+    if document.get(DESKTOP_ENTRY_GROUP, "Terminal") == Some(&Value::Boolean(true)) {
+        let terminal = terminal_command(&options.terminals, find_program)
+            .ok_or(LaunchError::MissingTerminal)?;
+
+        argv.splice(0..0, terminal);
+    }
+
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]);
+
+    let working_dir = match document.get(DESKTOP_ENTRY_GROUP, "Path") {
+        Some(Value::String(path) | Value::LocaleString(path)) if !path.is_empty() => {
+            Some(Path::new(path.as_ref()))
+        }
+        _ => options.working_dir.as_deref(),
+    };
+
+    if let Some(dir) = working_dir {
+        command.current_dir(dir);
+    }
+
+    command.envs(options.env.iter().map(|(key, value)| (key, value)));
+
+    if options.detach {
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            // SAFETY: only async-signal-safe functions are called in the forked process
+            unsafe {
+                command.pre_exec(detach);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    for hook in &options.pre_exec {
+        use std::os::unix::process::CommandExt;
+
+        let hook = Arc::clone(&hook.0);
+
+        // SAFETY: the requirements are the ones of the constructor of the hook
+        unsafe {
+            command.pre_exec(move || hook());
+        }
+    }
+
+    match &options.activation_token {
+        Some(token) => command.env(XDG_ACTIVATION_TOKEN, token),
+        None => command.env_remove(XDG_ACTIVATION_TOKEN),
+    };
+
+    if prefers_non_default_gpu(document) {
+        command.envs(
+            options
+                .gpu_offload_env
+                .iter()
+                .map(|(key, value)| (key, value)),
+        );
+    }
+
+    match notify_startup_id(document, options, name.as_deref()) {
+        Some(id) => command.env(DESKTOP_STARTUP_ID, id),
+        None => command.env_remove(DESKTOP_STARTUP_ID),
+    };
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(?command, "built launch command");
+
+    Ok(command)
+}
+
+/// Returns the commands to launch the application, one instance for each file if the command
+/// line accepts a single file with `%f` or `%u`.
+///
+/// As for the spec, an application accepting a single file is launched multiple times when
+/// opening more files. The activation token and the startup ID of the options are passed only
+/// to the first instance, since they are valid once, the other instances get a new startup ID.
+///
+/// # Errors
+///
+/// If the command can't be built, see [`command`].
+pub fn commands(
+    document: &DesktopEntry,
+    options: &LaunchOptions,
+) -> Result<Vec<Command>, LaunchError> {
+    let single_file = parse_exec(document, options.hardened).is_ok_and(|exec| {
+        let mut codes = exec.args().iter().flat_map(|arg| arg.field_codes());
+
+        codes.any(|code| matches!(code, FieldCode::File | FieldCode::Url))
+    });
+
+    if !single_file || options.files.len() < 2 {
+        return command(document, options).map(|command| vec![command]);
+    }
+
+    options
+        .files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let first = i == 0;
+
+            let instance = LaunchOptions {
+                files: vec![file.clone()],
+                activation_token: options.activation_token.clone().filter(|_| first),
+                startup_id: options.startup_id.clone().filter(|_| first),
+                ..options.clone()
+            };
+
+            command(document, &instance)
+        })
+        .collect()
+}
+
+/// Returns the commands to launch the application with the URIs.
+///
+/// The URIs are passed as they are to the applications accepting URIs with `%u` or `%U`, the
+/// local paths are converted to `file://` URIs. The applications accepting only files with `%f`
+/// or `%F` receive the local path of the `file://` URIs, since nothing is downloaded the other
+/// URIs are rejected. The URIs are dropped if the command line has no field code for them.
+///
+/// An instance is launched for each URI if the command line accepts a single one, see
+/// [`commands`].
+///
+/// # Errors
+///
+/// If the command is invalid, see [`command`], or a remote URI is passed to an application
+/// accepting only files.
+pub fn commands_with_uris(
+    document: &DesktopEntry,
+    uris: &[&str],
+    options: &LaunchOptions,
+) -> Result<Vec<Command>, LaunchError> {
+    check_application(document)?;
+
+    let exec = parse_exec(document, options.hardened)?;
+
+    let mut accepts_uris = false;
+    let mut accepts_files = false;
+
+    for code in exec.args().iter().flat_map(|arg| arg.field_codes()) {
+        match code {
+            FieldCode::Url | FieldCode::Urls => accepts_uris = true,
+            FieldCode::File | FieldCode::Files => accepts_files = true,
+            _ => {}
+        }
+    }
+
+    let files = if accepts_uris {
+        uris.iter()
+            .map(|value| OsString::from(to_uri(value)))
+            .collect()
+    } else if accepts_files {
+        uris.iter()
+            .map(|value| to_path(value).map(OsString::from))
+            .collect::<Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    commands(
+        document,
+        &LaunchOptions {
+            files,
+            ..options.clone()
+        },
+    )
+}
+
+/// Returns the URI of a local path, the URIs are kept.
+pub(crate) fn to_uri(value: &str) -> String {
+    if uri::is_uri(value) {
+        return value.to_string();
+    }
+
+    let path = Path::new(value);
+
+    std::path::absolute(path)
+        .ok()
+        .and_then(|path| uri::path_to_uri(&path))
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Returns the local path of an URI, the paths are kept.
+fn to_path(value: &str) -> Result<PathBuf, LaunchError> {
+    if !uri::is_uri(value) {
+        return Ok(PathBuf::from(value));
+    }
+
+    uri::uri_to_path(value).ok_or_else(|| LaunchError::RemoteUri(value.to_string()))
+}
+
+fn check_application(document: &DesktopEntry) -> Result<(), ExecError> {
+    match document.get(DESKTOP_ENTRY_GROUP, "Type") {
+        Some(Value::String(entry_type)) if entry_type == "Application" => Ok(()),
+        _ => Err(ExecError::NotApplication),
+    }
+}
+
+fn parse_exec(document: &DesktopEntry, hardened: bool) -> Result<Exec, ExecError> {
+    match document.get(DESKTOP_ENTRY_GROUP, "Exec") {
+        Some(Value::String(exec) | Value::LocaleString(exec)) if hardened => {
+            Exec::parse_hardened(exec)
+        }
+        Some(Value::String(exec) | Value::LocaleString(exec)) => Exec::parse(exec),
+        _ => Err(ExecError::MissingExec),
+    }
+}
+
+/// Returns the startup notification ID of the launch, if the entry has `StartupNotify=true`, or
+/// `X-KDE-StartupNotify=true` without the standard key.
+pub(crate) fn notify_startup_id(
+    document: &DesktopEntry,
+    options: &LaunchOptions,
+    name: Option<&str>,
+) -> Option<String> {
+    if kde::startup_notify(document) != Some(true) {
+        return None;
+    }
+
+    let id = options
+        .startup_id
+        .clone()
+        .unwrap_or_else(|| startup_id(name.unwrap_or_default(), options.event_time));
+
+    Some(id)
+}
+
+/// Returns the environment to run an application on the discrete GPU with the Mesa and NVIDIA
+/// drivers, used when the GPUs can't be queried.
+pub fn gpu_offload_env() -> Vec<(String, String)> {
+    [
+        ("DRI_PRIME", "1"),
+        ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+        ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect()
+}
+
+/// Checks if the application should run on the discrete GPU, also with the KDE key.
+fn prefers_non_default_gpu(document: &DesktopEntry) -> bool {
+    document.prefers_non_default_gpu() || kde::run_on_discrete_gpu(document)
+}
+
+/// Generates a unique startup notification ID for launching an application.
+///
+/// The ID has the form `NAME/PID-SEQUENCE-HOSTNAME_TIMETIMESTAMP` like the ones of the other
+/// launchers, the `_TIME` suffix is the X server time used to prevent focus stealing.
+pub fn startup_id(name: &str, event_time: u32) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c == '/' {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let sequence = STARTUP_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+
+    format!(
+        "{name}/{}-{sequence}-{}_TIME{event_time}",
+        process::id(),
+        hostname()
+    )
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|hostname| hostname.trim().to_string())
+        .or_else(|| env::var("HOSTNAME").ok())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// Returns the arguments to run a command in a terminal, before the command.
+///
+/// [`XDG_TERMINAL_EXEC`] has precedence over the terminal emulators.
+fn terminal_command(
+    terminals: &[TerminalEmulator],
+    find: impl Fn(&str) -> Option<PathBuf>,
+) -> Option<Vec<OsString>> {
+    if let Some(path) = find(XDG_TERMINAL_EXEC) {
+        return Some(vec![path.into_os_string()]);
+    }
+
+    let defaults;
+    let terminals = if terminals.is_empty() {
+        defaults = TerminalEmulator::defaults();
+
+        &defaults
+    } else {
+        terminals
+    };
+
+    terminals.iter().find_map(|terminal| {
+        let path = find(&terminal.program)?;
+
+        Some(
+            std::iter::once(path.into_os_string())
+                .chain(terminal.exec_args.iter().map(OsString::from))
+                .collect(),
+        )
+    })
+}
+
+/// Returns the path of an executable, searching the directories in `PATH` if it's not a path.
+pub(crate) fn find_program(program: &str) -> Option<PathBuf> {
+    let program = Path::new(program);
+
+    if program.components().count() > 1 {
+        return is_executable(program).then(|| program.to_path_buf());
+    }
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Launches the application, returning the spawned process.
+///
+/// # Errors
+///
+/// If the command is invalid or the process can't be spawned.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn launch(document: &DesktopEntry, options: &LaunchOptions) -> Result<Child, Error> {
+    spawn(command(document, options)?)
+}
+
+/// Launches the application, an instance for each file if it accepts a single one, see
+/// [`commands`].
+///
+/// # Errors
+///
+/// If the command is invalid or a process can't be spawned, the instances already spawned keep
+/// running.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn launch_all(document: &DesktopEntry, options: &LaunchOptions) -> Result<Vec<Child>, Error> {
+    spawn_all(commands(document, options)?)
+}
+
+/// Launches the application with the URIs, see [`commands_with_uris`].
+///
+/// # Errors
+///
+/// If the command is invalid or a process can't be spawned, the instances already spawned keep
+/// running.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(uris = uris.len()))
+)]
+pub fn launch_with_uris(
+    document: &DesktopEntry,
+    uris: &[&str],
+    options: &LaunchOptions,
+) -> Result<Vec<Child>, Error> {
+    spawn_all(commands_with_uris(document, uris, options)?)
+}
+
+/// Launches the application detached from the launcher, without a handle to the process.
+///
+/// The instances are launched like [`launch_all`] with [`LaunchOptions::detach`], and the
+/// intermediate processes are reaped.
+///
+/// # Errors
+///
+/// If the command is invalid or a process can't be spawned.
+pub fn launch_detached(document: &DesktopEntry, options: &LaunchOptions) -> Result<(), Error> {
+    let options = LaunchOptions {
+        detach: true,
+        ..options.clone()
+    };
+
+    let children = launch_all(document, &options)?;
+
+    // The intermediate processes exit after forking the application
+    #[cfg(unix)]
+    for mut child in children {
+        child.wait()?;
+    }
+
+    #[cfg(not(unix))]
+    drop(children);
+
+    Ok(())
+}
+
+/// Starts a new session and forks, the parent exits so the application is reparented to init.
+///
+/// The session has no controlling terminal and since the application is not the session
+/// leader it can't acquire one.
+#[cfg(unix)]
+fn detach() -> io::Result<()> {
+    // SAFETY: the functions are async-signal-safe
+    unsafe {
+        if libc::setsid() == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        match libc::fork() {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Ok(()),
+            _ => libc::_exit(0),
+        }
+    }
+}
+
+fn spawn_all(commands: Vec<Command>) -> Result<Vec<Child>, Error> {
+    commands.into_iter().map(spawn).collect()
+}
+
+fn spawn(mut command: Command) -> Result<Child, Error> {
+    let result = command.spawn();
+
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(child) => tracing::debug!(
+            program = ?command.get_program(),
+            pid = child.id(),
+            "spawned application"
+        ),
+        Err(err) => {
+            tracing::debug!(program = ?command.get_program(), %err, "failed to spawn application")
+        }
+    }
+
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_build_command() {
+        let example_file = include_str!("../example/file.desktop");
+        let document = DesktopEntry::parse(example_file).unwrap();
+
+        let options = LaunchOptions {
+            files: vec![OsString::from("a.foo"), OsString::from("b.foo")],
+            ..Default::default()
+        };
+
+        let command = command(&document, &options).unwrap();
+
+        assert_eq!("fooview", command.get_program());
+        assert_eq!(
+            vec!["a.foo", "b.foo"],
+            command.get_args().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_run_terminal_applications_in_a_terminal() {
+        // Only uses the installed terminals
+        let installed = |installed: &'static [&'static str]| {
+            move |program: &str| {
+                installed
+                    .contains(&program)
+                    .then(|| Path::new("/usr/bin").join(program))
+            }
+        };
+
+        assert_eq!(
+            Some(vec![OsString::from("/usr/bin/xdg-terminal-exec")]),
+            terminal_command(&[], installed(&["xdg-terminal-exec", "xterm"]))
+        );
+        assert_eq!(
+            Some(vec![OsString::from("/usr/bin/xterm"), OsString::from("-e")]),
+            terminal_command(&[], installed(&["xterm"]))
+        );
+        assert_eq!(
+            Some(vec![
+                OsString::from("/usr/bin/foot"),
+                OsString::from("--hold")
+            ]),
+            terminal_command(
+                &[
+                    TerminalEmulator::new("kitty", &[]),
+                    TerminalEmulator::new("foot", &["--hold"])
+                ],
+                installed(&["foot", "xterm"])
+            )
+        );
+        assert_eq!(None, terminal_command(&[], installed(&[])));
+    }
+
+    #[test]
+    fn should_pass_activation_token() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n").unwrap();
+
+        let env = |token: Option<&str>| {
+            let options = LaunchOptions {
+                activation_token: token.map(str::to_string),
+                ..Default::default()
+            };
+
+            command(&document, &options)
+                .unwrap()
+                .get_envs()
+                .filter(|(key, _)| *key == XDG_ACTIVATION_TOKEN)
+                .map(|(key, value)| (key.to_os_string(), value.map(|value| value.to_os_string())))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![(
+                OsString::from(XDG_ACTIVATION_TOKEN),
+                Some(OsString::from("token"))
+            )],
+            env(Some("token"))
+        );
+        assert_eq!(
+            vec![(OsString::from(XDG_ACTIVATION_TOKEN), None)],
+            env(None)
+        );
+    }
+
+    #[test]
+    fn should_offload_to_the_discrete_gpu() {
+        let env = |input: &str, gpu_offload_env: Vec<(String, String)>| {
+            let document = DesktopEntry::parse(input).unwrap();
+            let options = LaunchOptions {
+                gpu_offload_env,
+                ..Default::default()
+            };
+
+            command(&document, &options)
+                .unwrap()
+                .get_envs()
+                .filter_map(|(key, value)| {
+                    Some((key.to_str()?.to_string(), value?.to_str()?.to_string()))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let input =
+            "[Desktop Entry]\nType=Application\nName=Game\nExec=game\nPrefersNonDefaultGPU=true\n";
+
+        assert_eq!(
+            vec![
+                ("DRI_PRIME".to_string(), "1".to_string()),
+                (
+                    "__GLX_VENDOR_LIBRARY_NAME".to_string(),
+                    "nvidia".to_string()
+                ),
+                ("__NV_PRIME_RENDER_OFFLOAD".to_string(), "1".to_string()),
+            ],
+            env(input, gpu_offload_env())
+        );
+        assert_eq!(Vec::<(String, String)>::new(), env(input, Vec::new()));
+        assert_eq!(
+            Vec::<(String, String)>::new(),
+            env(
+                "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n",
+                gpu_offload_env()
+            )
+        );
+    }
+
+    #[test]
+    fn should_set_working_dir_and_env() {
+        let options = LaunchOptions {
+            working_dir: Some(PathBuf::from("/tmp")),
+            env: vec![(OsString::from("FOO"), OsString::from("1"))],
+            ..Default::default()
+        };
+
+        let command = |input: &str| {
+            let document = DesktopEntry::parse(input).unwrap();
+
+            command(&document, &options).unwrap()
+        };
+
+        let with_path =
+            command("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\nPath=/srv/foo\n");
+        let without_path = command("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n");
+
+        assert_eq!(Some(Path::new("/srv/foo")), with_path.get_current_dir());
+        assert_eq!(Some(Path::new("/tmp")), without_path.get_current_dir());
+        assert!(without_path
+            .get_envs()
+            .any(|(key, value)| key == "FOO" && value == Some("1".as_ref())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_run_pre_exec_hooks() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=env\n").unwrap();
+
+        // SAFETY: the hook doesn't allocate or lock
+        let hook = unsafe { PreExecHook::new(|| Err(io::Error::from_raw_os_error(1))) };
+
+        let options = LaunchOptions {
+            pre_exec: vec![hook],
+            ..Default::default()
+        };
+
+        let err = launch(&document, &options).unwrap_err();
+
+        assert!(matches!(err, Error::Io(err) if err.raw_os_error() == Some(1)));
+    }
+
+    #[test]
+    fn should_pass_uris_or_paths() {
+        let args = |exec: &str, uris: &[&str]| {
+            let input = format!("[Desktop Entry]\nType=Application\nName=Foo\nExec={exec}\n");
+            let document = DesktopEntry::parse(&input).unwrap();
+
+            commands_with_uris(&document, uris, &LaunchOptions::default()).map(|commands| {
+                commands
+                    .iter()
+                    .flat_map(|command| command.get_args())
+                    .map(|arg| arg.to_os_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            Ok(vec![
+                OsString::from("file:///tmp/a%20b"),
+                OsString::from("https://example.com")
+            ]),
+            args("foo %U", &["/tmp/a b", "https://example.com"])
+        );
+        assert_eq!(
+            Ok(vec![OsString::from("/tmp/a b"), OsString::from("/tmp/c")]),
+            args("foo %F", &["file:///tmp/a%20b", "/tmp/c"])
+        );
+        assert_eq!(
+            Err(LaunchError::RemoteUri("https://example.com".to_string())),
+            args("foo %f", &["https://example.com"])
+        );
+        assert_eq!(Ok(Vec::new()), args("foo", &["https://example.com"]));
+    }
+
+    #[test]
+    fn should_launch_an_instance_for_each_file() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo %f\n")
+                .unwrap();
+
+        let options = LaunchOptions {
+            files: vec![OsString::from("a.foo"), OsString::from("b.foo")],
+            activation_token: Some("token".to_string()),
+            ..Default::default()
+        };
+
+        let instances = commands(&document, &options).unwrap();
+
+        let args = instances
+            .iter()
+            .map(|command| command.get_args().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let tokens = instances
+            .iter()
+            .map(|command| {
+                command
+                    .get_envs()
+                    .find(|(key, _)| *key == XDG_ACTIVATION_TOKEN)
+                    .and_then(|(_, value)| value)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![vec!["a.foo"], vec!["b.foo"]], args);
+        assert_eq!(vec![Some("token".as_ref()), None], tokens);
+
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo %F\n")
+                .unwrap();
+
+        assert_eq!(1, commands(&document, &options).unwrap().len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_launch_detached() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=env\n").unwrap();
+        let missing = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=/nonexistent/foo\n",
+        )
+        .unwrap();
+
+        let options = LaunchOptions {
+            detach: true,
+            ..Default::default()
+        };
+
+        let mut child = launch(&document, &options).unwrap();
+
+        assert!(child.wait().unwrap().success());
+        assert!(child.stdout.is_none());
+
+        launch_detached(&document, &LaunchOptions::default()).unwrap();
+
+        // The exec error of the application is reported through the intermediate process
+        let err = launch_detached(&missing, &LaunchOptions::default());
+
+        assert!(matches!(err, Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn should_not_launch_links() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Link\nURL=https://example.com\n").unwrap();
+
+        assert_eq!(
+            LaunchError::Exec(ExecError::NotApplication),
+            command(&document, &LaunchOptions::default()).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_reject_shell_commands_when_hardened() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=sh -c \"foo %f\"\n",
+        )
+        .unwrap();
+
+        let options = LaunchOptions {
+            files: vec![OsString::from("$(id).foo")],
+            hardened: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            LaunchError::Exec(ExecError::ShellFieldCode(FieldCode::File)),
+            command(&document, &options).unwrap_err()
+        );
+    }
+}
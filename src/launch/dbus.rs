@@ -0,0 +1,266 @@
+//! Activation of the applications with the `org.freedesktop.Application` D-Bus interface,
+//! behind the `dbus` feature.
+//!
+//! The entries with `DBusActivatable=true` are launched by calling the interface on the bus name
+//! derived from the desktop file ID, instead of running the `Exec` command. The platform data
+//! of the calls carries the activation token and the startup notification ID.
+
+use std::collections::HashMap;
+
+use zbus::{blocking::Connection, zvariant::Value as Variant};
+
+use super::{notify_startup_id, to_uri, LaunchError, LaunchOptions};
+use crate::{loader::DESKTOP_EXTENSION, naming, DesktopEntry, Error, Value, DESKTOP_ENTRY_GROUP};
+
+/// Interface implemented by the D-Bus activatable applications.
+pub const APPLICATION_INTERFACE: &str = "org.freedesktop.Application";
+
+/// Platform data passed to the methods of the interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformData {
+    /// XDG Activation token, for the focus on Wayland.
+    pub activation_token: Option<String>,
+    /// Startup notification ID, for the launch feedback on X11.
+    pub startup_id: Option<String>,
+}
+
+impl PlatformData {
+    /// Returns the platform data with the activation token and the startup ID of the options.
+    pub fn from_options(options: &LaunchOptions) -> Self {
+        Self {
+            activation_token: options.activation_token.clone(),
+            startup_id: options.startup_id.clone(),
+        }
+    }
+
+    /// Returns the `a{sv}` dictionary of the platform data.
+    pub fn to_dict(&self) -> HashMap<&'static str, Variant<'_>> {
+        let mut dict = HashMap::new();
+
+        if let Some(token) = &self.activation_token {
+            dict.insert("activation-token", Variant::from(token.as_str()));
+        }
+
+        if let Some(id) = &self.startup_id {
+            dict.insert("desktop-startup-id", Variant::from(id.as_str()));
+        }
+
+        dict
+    }
+}
+
+/// Returns the bus name of an application from its desktop file ID, like `org.example.App`.
+///
+/// It's [`None`] if the ID doesn't follow the naming convention, see [`naming::check_id`].
+pub fn bus_name(desktop_file_id: &str) -> Option<&str> {
+    if !naming::is_valid_id(desktop_file_id) {
+        return None;
+    }
+
+    desktop_file_id
+        .strip_suffix(DESKTOP_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+}
+
+/// Returns the object path of an application from its bus name, like `/org/example/App`.
+pub fn object_path(bus_name: &str) -> String {
+    let path = bus_name.replace('.', "/").replace('-', "_");
+
+    format!("/{path}")
+}
+
+/// Calls the `Activate` method of the application.
+///
+/// # Errors
+///
+/// If the call fails.
+pub fn activate(
+    connection: &Connection,
+    bus_name: &str,
+    platform_data: &PlatformData,
+) -> zbus::Result<()> {
+    connection.call_method(
+        Some(bus_name),
+        object_path(bus_name).as_str(),
+        Some(APPLICATION_INTERFACE),
+        "Activate",
+        &(platform_data.to_dict(),),
+    )?;
+
+    Ok(())
+}
+
+/// Calls the `Open` method of the application with the URIs.
+///
+/// # Errors
+///
+/// If the call fails.
+pub fn open(
+    connection: &Connection,
+    bus_name: &str,
+    uris: &[&str],
+    platform_data: &PlatformData,
+) -> zbus::Result<()> {
+    connection.call_method(
+        Some(bus_name),
+        object_path(bus_name).as_str(),
+        Some(APPLICATION_INTERFACE),
+        "Open",
+        &(uris, platform_data.to_dict()),
+    )?;
+
+    Ok(())
+}
+
+/// Calls the `ActivateAction` method of the application with the parameters of the action.
+///
+/// # Errors
+///
+/// If the call fails.
+pub fn activate_action(
+    connection: &Connection,
+    bus_name: &str,
+    action: &str,
+    parameters: &[Variant],
+    platform_data: &PlatformData,
+) -> zbus::Result<()> {
+    connection.call_method(
+        Some(bus_name),
+        object_path(bus_name).as_str(),
+        Some(APPLICATION_INTERFACE),
+        "ActivateAction",
+        &(action, parameters, platform_data.to_dict()),
+    )?;
+
+    Ok(())
+}
+
+/// Launches a `DBusActivatable` application on the session bus.
+///
+/// The application is activated, or asked to open the files of the options. The startup
+/// notification ID is generated like for [`command`](super::command) if the entry has
+/// `StartupNotify=true`.
+///
+/// # Errors
+///
+/// If the entry is not D-Bus activatable, the ID is not a valid bus name, or the call fails.
+pub fn launch_dbus(
+    document: &DesktopEntry,
+    desktop_file_id: &str,
+    options: &LaunchOptions,
+) -> Result<(), Error> {
+    let (connection, bus_name, platform_data) = prepare(document, desktop_file_id, options)?;
+
+    if options.files.is_empty() {
+        activate(&connection, bus_name, &platform_data)?;
+    } else {
+        let uris = options
+            .files
+            .iter()
+            .map(|file| to_uri(&file.to_string_lossy()))
+            .collect::<Vec<_>>();
+        let uris = uris.iter().map(String::as_str).collect::<Vec<_>>();
+
+        open(&connection, bus_name, &uris, &platform_data)?;
+    }
+
+    Ok(())
+}
+
+/// Activates an action of a `DBusActivatable` application on the session bus.
+///
+/// See [`launch_dbus`].
+///
+/// # Errors
+///
+/// If the entry is not D-Bus activatable, the ID is not a valid bus name, or the call fails.
+pub fn launch_action_dbus(
+    document: &DesktopEntry,
+    desktop_file_id: &str,
+    action: &str,
+    options: &LaunchOptions,
+) -> Result<(), Error> {
+    let (connection, bus_name, platform_data) = prepare(document, desktop_file_id, options)?;
+
+    activate_action(&connection, bus_name, action, &[], &platform_data)?;
+
+    Ok(())
+}
+
+fn prepare<'i>(
+    document: &DesktopEntry,
+    desktop_file_id: &'i str,
+    options: &LaunchOptions,
+) -> Result<(Connection, &'i str, PlatformData), Error> {
+    if !document.dbus_activatable() {
+        return Err(LaunchError::NotDBusActivatable.into());
+    }
+
+    let bus_name = bus_name(desktop_file_id).ok_or(LaunchError::NotDBusActivatable)?;
+
+    let mut platform_data = PlatformData::from_options(options);
+
+    if platform_data.startup_id.is_none() {
+        let name =
+            match document.get_localized(DESKTOP_ENTRY_GROUP, "Name", options.locale.as_ref()) {
+                Some(Value::String(name) | Value::LocaleString(name)) => Some(name.as_ref()),
+                _ => None,
+            };
+
+        platform_data.startup_id = notify_startup_id(document, options, name);
+    }
+
+    let connection = Connection::session()?;
+
+    Ok((connection, bus_name, platform_data))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_derive_bus_name_and_path() {
+        assert_eq!(
+            Some("org.example.Foo_Bar"),
+            bus_name("org.example.Foo_Bar.desktop")
+        );
+        assert_eq!(None, bus_name("foo.desktop"));
+        assert_eq!("/org/example/foo_bar", object_path("org.example.foo-bar"));
+    }
+
+    #[test]
+    fn should_build_platform_data() {
+        let data = PlatformData {
+            activation_token: Some("token".to_string()),
+            startup_id: None,
+        };
+
+        assert_eq!(
+            HashMap::from([("activation-token", Variant::from("token"))]),
+            data.to_dict()
+        );
+        assert_eq!("file:///tmp/a%20b.txt", to_uri("/tmp/a b.txt"));
+        assert_eq!("https://example.com", to_uri("https://example.com"));
+    }
+
+    #[test]
+    fn should_not_activate_entries_without_dbus_activatable() {
+        let document =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n").unwrap();
+
+        let err = launch_dbus(
+            &document,
+            "org.example.Foo.desktop",
+            &LaunchOptions::default(),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::Launch(LaunchError::NotDBusActivatable)
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+//! Applications exported by Flatpak.
+//!
+//! Flatpak exports the desktop files of the installed applications in its `exports` directories,
+//! rewriting the `Exec` key to run the sandbox and adding the `X-Flatpak` key with the
+//! application ID. A launcher aware of the sandbox can run the application with `flatpak run`
+//! instead, forwarding the files through the document portal.
+
+use std::{ffi::OsString, path::Path};
+
+use crate::{DesktopEntry, Value, DESKTOP_ENTRY_GROUP};
+
+/// Key with the ID of the Flatpak application.
+pub const FLATPAK_KEY: &str = "X-Flatpak";
+
+/// Directory of the desktop files exported by Flatpak, in the system and user installations.
+const EXPORTS_DIR: &str = "flatpak/exports/share/applications";
+
+/// Returns the ID of the Flatpak application of the entry, if exported by Flatpak.
+///
+/// The ID is the value of the `X-Flatpak` key, or the name of the desktop file if it's in
+/// one of the `exports` directories.
+pub fn app_id(document: &DesktopEntry, location: Option<&Path>) -> Option<String> {
+    if let Some(Value::String(app_id) | Value::LocaleString(app_id)) =
+        document.get(DESKTOP_ENTRY_GROUP, FLATPAK_KEY)
+    {
+        return Some(app_id.to_string());
+    }
+
+    let location = location?;
+
+    if !location.parent()?.ends_with(EXPORTS_DIR) {
+        return None;
+    }
+
+    location
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+}
+
+/// Returns the command line to run the Flatpak application with the files.
+///
+/// The files are forwarded with `--file-forwarding`, as URIs if any of them is an URI.
+pub fn run_args(app_id: &str, files: &[OsString]) -> Vec<OsString> {
+    let mut args = ["flatpak", "run"].map(OsString::from).to_vec();
+
+    if files.is_empty() {
+        args.push(OsString::from(app_id));
+
+        return args;
+    }
+
+    let is_uri = files
+        .iter()
+        .any(|file| file.to_str().is_some_and(|file| file.contains("://")));
+
+    args.push(OsString::from("--file-forwarding"));
+    args.push(OsString::from(app_id));
+    args.push(OsString::from(if is_uri { "@@u" } else { "@@" }));
+    args.extend(files.iter().cloned());
+    args.push(OsString::from("@@"));
+
+    args
+}
+
+#[cfg(test)]
+mod test {
+    use std::process::Command;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::launch::{command, LaunchOptions};
+
+    #[test]
+    fn should_detect_flatpak_apps() {
+        let exported = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=/usr/bin/flatpak run --branch=stable org.example.Foo\nX-Flatpak=org.example.Foo\n",
+        )
+        .unwrap();
+        let plain =
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\n").unwrap();
+
+        assert_eq!(Some("org.example.Foo".to_string()), app_id(&exported, None));
+        assert_eq!(
+            Some("org.example.Bar".to_string()),
+            app_id(
+                &plain,
+                Some(Path::new(
+                    "/var/lib/flatpak/exports/share/applications/org.example.Bar.desktop"
+                ))
+            )
+        );
+        assert_eq!(
+            None,
+            app_id(
+                &plain,
+                Some(Path::new("/usr/share/applications/foo.desktop"))
+            )
+        );
+    }
+
+    #[test]
+    fn should_run_with_flatpak() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=/usr/bin/flatpak run org.example.Foo %U\nX-Flatpak=org.example.Foo\n",
+        )
+        .unwrap();
+
+        let args = |command: Command| {
+            std::iter::once(command.get_program().to_os_string())
+                .chain(command.get_args().map(|arg| arg.to_os_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let options = LaunchOptions {
+            files: vec![OsString::from("/tmp/a b.txt")],
+            flatpak_run: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            [
+                "flatpak",
+                "run",
+                "--file-forwarding",
+                "org.example.Foo",
+                "@@",
+                "/tmp/a b.txt",
+                "@@"
+            ]
+            .map(OsString::from)
+            .to_vec(),
+            args(command(&document, &options).unwrap())
+        );
+        assert_eq!(
+            [
+                "flatpak",
+                "run",
+                "--file-forwarding",
+                "org.example.Foo",
+                "@@u",
+                "https://example.com",
+                "@@"
+            ]
+            .map(OsString::from)
+            .to_vec(),
+            run_args("org.example.Foo", &[OsString::from("https://example.com")])
+        );
+        assert_eq!(
+            ["flatpak", "run", "org.example.Foo"]
+                .map(OsString::from)
+                .to_vec(),
+            run_args("org.example.Foo", &[])
+        );
+    }
+}
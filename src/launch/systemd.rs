@@ -0,0 +1,186 @@
+//! Launch of the applications in a systemd transient scope, behind the `systemd` feature.
+//!
+//! Desktop environments managed by systemd run each application in its own scope under
+//! `app.slice`, named as in the [XDG integration] of systemd, so the resources of the
+//! applications can be tracked and limited. The scope is created with `systemd-run --user
+//! --scope`, which then runs the command of the application.
+//!
+//! [XDG integration]: https://systemd.io/DESKTOP_ENVIRONMENTS/
+
+use std::{
+    process::{Child, Command},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{command, LaunchOptions};
+use crate::{DesktopEntry, Error};
+
+/// Options of the transient scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeOptions {
+    /// Name of the launcher, added to the name of the unit.
+    pub launcher: Option<String>,
+    /// Slice of the scope.
+    pub slice: String,
+    /// Description of the unit, like the name of the application.
+    pub description: Option<String>,
+}
+
+impl Default for ScopeOptions {
+    /// Returns the options to run the scope in `app.slice`.
+    fn default() -> Self {
+        Self {
+            launcher: None,
+            slice: "app.slice".to_string(),
+            description: None,
+        }
+    }
+}
+
+/// Returns the name of a new scope for the application, in the form
+/// `app-LAUNCHER-ID-RANDOM.scope`.
+///
+/// The ID is the desktop file ID without the `.desktop` extension, escaped like
+/// `systemd-escape` does.
+pub fn scope_name(desktop_file_id: &str, launcher: Option<&str>) -> String {
+    let id = desktop_file_id
+        .strip_suffix(".desktop")
+        .unwrap_or(desktop_file_id);
+
+    let random = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos())
+        ^ std::process::id();
+
+    match launcher {
+        Some(launcher) => format!("app-{}-{}-{random:x}.scope", escape(launcher), escape(id)),
+        None => format!("app-{}-{random:x}.scope", escape(id)),
+    }
+}
+
+/// Wraps the command to run it in a transient scope of the user.
+///
+/// The arguments, the environment and the working directory of the command are kept.
+pub fn scope_command(command: &Command, unit: &str, options: &ScopeOptions) -> Command {
+    let mut scope = Command::new("systemd-run");
+
+    scope.args(["--user", "--scope", "--quiet", "--collect"]);
+    scope.arg(format!("--unit={unit}"));
+    scope.arg(format!("--slice={}", options.slice));
+
+    if let Some(description) = &options.description {
+        scope.arg(format!("--description={description}"));
+    }
+
+    scope
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => scope.env(key, value),
+            None => scope.env_remove(key),
+        };
+    }
+
+    if let Some(dir) = command.get_current_dir() {
+        scope.current_dir(dir);
+    }
+
+    scope
+}
+
+/// Launches the application in a new transient scope, returning the `systemd-run` process.
+///
+/// # Errors
+///
+/// If the command is invalid or `systemd-run` can't be spawned.
+pub fn launch_in_scope(
+    document: &DesktopEntry,
+    desktop_file_id: &str,
+    options: &LaunchOptions,
+    scope: &ScopeOptions,
+) -> Result<Child, Error> {
+    let command = command(document, options)?;
+    let unit = scope_name(desktop_file_id, scope.launcher.as_deref());
+
+    let child = scope_command(&command, &unit, scope).spawn()?;
+
+    Ok(child)
+}
+
+/// Escapes a part of a unit name, the `-` is reserved as separator.
+fn escape(part: &str) -> String {
+    let mut escaped = String::with_capacity(part.len());
+
+    for (i, byte) in part.bytes().enumerate() {
+        let keep = byte.is_ascii_alphanumeric() || byte == b'_' || (byte == b'.' && i > 0);
+
+        if keep {
+            escaped.push(char::from(byte));
+        } else {
+            escaped.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_name_scope() {
+        let name = scope_name("org.example.Foo-Bar.desktop", Some("my-shell"));
+
+        assert!(
+            name.starts_with("app-my\\x2dshell-org.example.Foo\\x2dBar-"),
+            "{name}"
+        );
+        assert!(name.ends_with(".scope"), "{name}");
+        assert!(scope_name("foo.desktop", None).starts_with("app-foo-"));
+    }
+
+    #[test]
+    fn should_wrap_command_in_scope() {
+        let mut command = Command::new("foo");
+        command.args(["--new", "a b"]).env("FOO", "1");
+
+        let scope = scope_command(
+            &command,
+            "app-foo-1.scope",
+            &ScopeOptions {
+                description: Some("Foo".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!("systemd-run", scope.get_program());
+        assert_eq!(
+            vec![
+                "--user",
+                "--scope",
+                "--quiet",
+                "--collect",
+                "--unit=app-foo-1.scope",
+                "--slice=app.slice",
+                "--description=Foo",
+                "--",
+                "foo",
+                "--new",
+                "a b"
+            ],
+            scope.get_args().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(OsStr::new("FOO"), Some(OsStr::new("1")))],
+            scope.get_envs().collect::<Vec<_>>()
+        );
+    }
+}
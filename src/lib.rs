@@ -1,18 +1,114 @@
-use std::{borrow::Cow, cell::Cell};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    io::Read,
+    path::Path,
+};
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, line_ending, not_line_ending, satisfy, space0, space1},
-    combinator::{cut, eof, map, map_parser, opt, peek, recognize, value, verify},
-    multi::{fold_many0, many1_count},
-    number::complete::float,
-    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    combinator::{eof, map, value, verify},
+    error::{context, ContextError, ErrorKind, ParseError as NomParseError, VerboseError},
+    multi::fold_many0,
+    sequence::terminated,
     IResult,
 };
 
-const ESCAPE_CHAR: char = '\\';
+use raw::{
+    not_line_ending, parse_dialect_comment, parse_dialect_entry_with, parse_dialect_group_header,
+    parse_dialect_key, parse_dialect_separator, parse_empty_line, parse_end_of_line,
+    strip_inline_comment, unknown_escape_sequences_in,
+};
+
+pub mod action;
+pub mod appstream;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+pub mod categories;
+pub mod corpus;
+mod decode;
+pub mod diff;
+pub mod edit;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+mod error;
+pub mod escape;
+pub mod exec;
+pub mod extension;
+#[cfg(feature = "fast-parser")]
+mod fast_parser;
+pub mod format;
+#[cfg(feature = "gettext")]
+pub mod gettext;
+pub mod gkeyfile;
+pub mod index;
+pub mod install;
+pub mod intern;
+pub mod json_schema;
+pub mod kde;
+pub mod keys;
+pub mod launch;
+pub mod lint;
+pub mod loader;
+mod locale;
+pub mod main_group;
+pub mod map;
+pub mod migrate;
+pub mod naming;
+mod options;
+pub mod po;
+pub mod query;
+pub mod raw;
+pub mod schema;
+pub mod search_provider;
+#[cfg(feature = "serde")]
+mod serde;
+pub mod shared;
+pub mod stats;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod token;
+pub mod uri;
+pub mod user;
+mod utf8;
+pub mod validate;
+mod warning;
+mod writer;
+
+pub use decode::Decoding;
+pub use error::{Error, ParseError, ParseErrorKind, WriteError};
+pub use locale::Locale;
+pub use options::{
+    Dialect, DuplicateKeys, KeyOrder, Limits, NonAsciiPolicy, ParseOptions, UnknownEscapes,
+    WriteOptions,
+};
+pub use warning::ParseWarning;
+
+/// Header of the main group of a desktop file.
+pub const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+
+/// Prefix of the header of the groups of the additional application actions.
+pub const DESKTOP_ACTION_PREFIX: &str = "Desktop Action ";
+
+/// Returns the header of the group of an action listed in the `Actions` key, like
+/// `Desktop Action gallery` for `gallery`.
+pub fn action_group_name(action: &str) -> String {
+    format!("{DESKTOP_ACTION_PREFIX}{action}")
+}
+
+/// Returns the identifier of the action of a group header, like `gallery` for
+/// `Desktop Action gallery`.
+///
+/// It's [`None`] if the header is not of an action group or the identifier is empty.
+pub fn parse_action_group_name(header: &str) -> Option<&str> {
+    header
+        .strip_prefix(DESKTOP_ACTION_PREFIX)
+        .filter(|action| !action.is_empty())
+}
+
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Key<'a> {
@@ -23,14 +119,6 @@ pub enum Key<'a> {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Locale<'a> {
-    lang: Cow<'a, str>,
-    country: Option<Cow<'a, str>>,
-    encoding: Option<Cow<'a, str>>,
-    modifier: Option<Cow<'a, str>>,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value<'a> {
     String(Cow<'a, str>),
@@ -43,12 +131,60 @@ pub enum Value<'a> {
 
 impl<'a> Eq for Value<'a> {}
 
+impl<'a> Key<'a> {
+    /// Returns the name of the key, without the locale.
+    pub fn name(&self) -> &str {
+        match self {
+            Key::Simple(key) | Key::Localized { key, .. } => key,
+        }
+    }
+
+    /// Returns the locale of a localized key.
+    pub fn locale(&self) -> Option<&Locale<'a>> {
+        match self {
+            Key::Simple(_) => None,
+            Key::Localized { locale, .. } => Some(locale),
+        }
+    }
+
+    pub fn into_owned(self) -> Key<'static> {
+        match self {
+            Key::Simple(key) => Key::Simple(Cow::Owned(key.into_owned())),
+            Key::Localized { key, locale } => Key::Localized {
+                key: Cow::Owned(key.into_owned()),
+                locale: locale.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::String(value) => Value::String(Cow::Owned(value.into_owned())),
+            Value::LocaleString(value) => Value::LocaleString(Cow::Owned(value.into_owned())),
+            Value::Boolean(value) => Value::Boolean(value),
+            Value::Numeric(value) => Value::Numeric(value),
+        }
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Line<'a> {
     Comment(Cow<'a, str>),
-    EmptyLine { white_space: Option<Cow<'a, str>> },
+    EmptyLine {
+        white_space: Option<Cow<'a, str>>,
+    },
     GroupHeader(Cow<'a, str>),
-    Entry { key: Key<'a>, value: Value<'a> },
+    Entry {
+        key: Key<'a>,
+        value: Value<'a>,
+        /// Text of the value, if it's not written back as it was.
+        source: Option<Cow<'a, str>>,
+    },
+    /// Entry filtered out by the [`ParseOptions`].
+    Skipped,
 }
 
 struct Group<'a> {
@@ -56,474 +192,1861 @@ struct Group<'a> {
     entries: EntryMap<'a, 'a>,
 }
 
-#[cfg(feature = "keep-comments")]
+/// Comment or empty line of a document.
+///
+/// The comments are attached to the group header or entry following them, or to the end of the
+/// document, and are written back before it.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Comment<'a> {
+pub enum Comment<'a> {
+    /// Comment line, including the starting `#`.
     Comment(Cow<'a, str>),
+    /// Empty line, with the optional white space in it.
     EmptyLine { white_space: Option<Cow<'a, str>> },
 }
 
+impl<'a> Comment<'a> {
+    /// Converts the comment into one owning its text.
+    pub fn into_owned(self) -> Comment<'static> {
+        match self {
+            Comment::Comment(comment) => Comment::Comment(Cow::Owned(comment.into_owned())),
+            Comment::EmptyLine { white_space } => Comment::EmptyLine {
+                white_space: white_space.map(|white_space| Cow::Owned(white_space.into_owned())),
+            },
+        }
+    }
+}
+
+/// Comments and empty lines of a document, attached to the group header or entry following
+/// them, so they move with it when the document is modified.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
-pub struct DesktopEntry<'a> {
-    groups: IndexMap<Cow<'a, str>, EntryMap<'a, 'a>>,
-    #[cfg(feature = "keep-comments")]
-    comments: IndexMap<usize, Comment<'a>>,
+struct Comments<'a> {
+    /// Comments before the group headers.
+    groups: IndexMap<Cow<'a, str>, Vec<Comment<'a>>>,
+    /// Comments before the entries, by group.
+    entries: IndexMap<Cow<'a, str>, IndexMap<Key<'a>, Vec<Comment<'a>>>>,
+    /// Comments after the last entry of the document.
+    tail: Vec<Comment<'a>>,
 }
 
-pub type EntryMap<'a, 'b> = IndexMap<Key<'a>, Value<'b>>;
+impl<'a> Comments<'a> {
+    fn before_group(&self, header: &str) -> &[Comment<'a>] {
+        self.groups.get(header).map_or(&[], Vec::as_slice)
+    }
 
-/// Parses a desktop file.
-///
-/// # Errors
-///
-/// Invalid or malformed desktop file.
-pub fn parse_desktop_entry(input: &str) -> IResult<&str, DesktopEntry> {
-    let has_entry = Cell::new(true);
+    fn before_entry(&self, header: &str, key: &Key) -> &[Comment<'a>] {
+        let Some(entries) = self.entries.get(header) else {
+            return &[];
+        };
 
-    terminated(
-        map(
-            fold_many0(
-                verify(parse_line, move |line| match line {
-                    Line::GroupHeader(_) => {
-                        has_entry.set(true);
-
-                        true
-                    }
-                    Line::Entry { .. } => has_entry.get(),
-                    _ => true,
-                }),
-                || (DesktopEntry::default(), None::<Group>, 0usize),
-                map_document_line,
-            ),
-            |(mut document, group, _)| {
-                if let Some(group) = group {
-                    document.groups.insert(group.header, group.entries);
-                }
+        // Reborrow the entries with the shorter lifetime of the key
+        let index = {
+            let entries: &IndexMap<Key, Vec<Comment>> = entries;
 
-                document
-            },
-        ),
-        eof,
-    )(input)
-}
+            entries.get_index_of(key)
+        };
 
-#[cfg(feature = "keep-comments")]
-fn map_document_line<'a>(
-    (mut document, mut group, count): (DesktopEntry<'a>, Option<Group<'a>>, usize),
-    line: Line<'a>,
-) -> (DesktopEntry<'a>, Option<Group<'a>>, usize) {
-    match line {
-        Line::Comment(comment) => {
-            document.comments.insert(count, Comment::Comment(comment));
-        }
-        Line::EmptyLine { white_space } => {
-            document
-                .comments
-                .insert(count, Comment::EmptyLine { white_space });
-        }
-        Line::GroupHeader(header) => {
-            let old_group = group.replace(Group {
-                header,
-                entries: EntryMap::new(),
-            });
+        index
+            .and_then(|index| entries.get_index(index))
+            .map_or(&[], |(_, comments)| comments.as_slice())
+    }
 
-            if let Some(group) = old_group {
-                document.groups.insert(group.header, group.entries);
-            }
-        }
-        Line::Entry { key, value } => {
-            group.as_mut().unwrap().entries.insert(key, value);
-        }
+    fn tail(&self) -> &[Comment<'a>] {
+        &self.tail
     }
 
-    (document, group, count + 1)
-}
+    /// Attaches a comment to the entry of the group at the indexes, to the group header if the
+    /// entry is out of range, or to the tail if the group is.
+    #[cfg(feature = "arbitrary")]
+    fn attach(&mut self, groups: &GroupMap<'a>, group: usize, entry: usize, comment: Comment<'a>) {
+        let comments = match groups.get_index(group) {
+            Some((header, entries)) => match entries.get_index(entry) {
+                Some((key, _)) => self
+                    .entries
+                    .entry(header.clone())
+                    .or_default()
+                    .entry(key.clone())
+                    .or_default(),
+                None => self.groups.entry(header.clone()).or_default(),
+            },
+            None => &mut self.tail,
+        };
 
-#[cfg(not(feature = "keep-comments"))]
-fn map_document_line<'a>(
-    (mut document, mut group, count): (DesktopEntry<'a>, Option<Group<'a>>, usize),
-    line: Line<'a>,
-) -> (DesktopEntry<'a>, Option<Group<'a>>, usize) {
-    match line {
-        Line::GroupHeader(header) => {
-            let old_group = group.replace(Group {
-                header,
-                entries: EntryMap::new(),
-            });
+        comments.push(comment);
+    }
 
-            if let Some(group) = old_group {
-                document.groups.insert(group.header, group.entries);
-            }
-        }
-        Line::Entry { key, value } => {
-            group.as_mut().unwrap().entries.insert(key, value);
-        }
-        Line::Comment(_) | Line::EmptyLine { .. } => {}
+    /// Drops the comments of the groups and entries no longer in the document.
+    fn retain(&mut self, groups: &GroupMap<'a>) {
+        self.groups.retain(|header, _| groups.contains_key(header));
+        self.entries.retain(|header, comments| {
+            let Some(entries) = groups.get(header) else {
+                return false;
+            };
+
+            comments.retain(|key, _| entries.contains_key(key));
+
+            !comments.is_empty()
+        });
     }
 
-    (document, group, count + 1)
-}
+    fn into_owned(self) -> Comments<'static> {
+        let owned = |comments: Vec<Comment>| {
+            comments
+                .into_iter()
+                .map(Comment::into_owned)
+                .collect::<Vec<_>>()
+        };
 
-fn parse_line(input: &str) -> IResult<&str, Line> {
-    terminated(
-        alt((
-            map(parse_comment, Line::Comment),
-            map(parse_group_header, Line::GroupHeader),
-            map(parse_entry, |(key, value)| Line::Entry { key, value }),
-            map(parse_empty_line, |white_space| Line::EmptyLine {
-                white_space,
-            }),
-        )),
-        parse_end_of_line,
-    )(input)
+        Comments {
+            groups: self
+                .groups
+                .into_iter()
+                .map(|(header, comments)| (Cow::Owned(header.into_owned()), owned(comments)))
+                .collect(),
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(header, entries)| {
+                    let entries = entries
+                        .into_iter()
+                        .map(|(key, comments)| (key.into_owned(), owned(comments)))
+                        .collect();
+
+                    (Cow::Owned(header.into_owned()), entries)
+                })
+                .collect(),
+            tail: owned(self.tail),
+        }
+    }
 }
 
-fn parse_end_of_line(input: &str) -> IResult<&str, &str> {
-    alt((line_ending, eof))(input)
+/// Source text of the parsed values that are written differently, like `1` for the number
+/// `1.0` or `a\;b` for the string `a;b`, so they are written back as they were.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Sources<'a> {
+    entries: IndexMap<Cow<'a, str>, IndexMap<Key<'a>, Source<'a>>>,
 }
 
-/// Parse the comment until the end of the line
-fn parse_comment(input: &str) -> IResult<&str, Cow<str>> {
-    map(recognize(pair(char('#'), not_line_ending)), Cow::from)(input)
+/// Text of a parsed value, with the value it was parsed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Source<'a> {
+    text: Cow<'a, str>,
+    value: Value<'a>,
 }
 
-/// Parses an empty line, peeks since the line is handled by [`parse_line`].
-///
-/// It will consider lines with only whitespace as empty lines.
-fn parse_empty_line(input: &str) -> IResult<&str, Option<Cow<str>>> {
-    alt((
-        terminated(
-            map(space1, |white_space| Some(Cow::from(white_space))),
-            peek(parse_end_of_line),
-        ),
-        map(peek(line_ending), |_| None),
-    ))(input)
-}
-
-fn parse_group_header(input: &str) -> IResult<&str, Cow<str>> {
-    map(
-        delimited(
-            char('['),
-            // Fail for missing header content
-            recognize(cut(many1_count(satisfy(|c| {
-                c.is_ascii() && !c.is_control() && c != '[' && c != ']'
-            })))),
-            // If an ope `[` is not close fail the parser
-            cut(char(']')),
-        ),
-        Cow::from,
-    )(input)
-}
+impl<'a> Sources<'a> {
+    /// Returns the text of the entry, if it still has the value parsed from it.
+    fn text(&self, header: &str, key: &Key, value: &Value) -> Option<&str> {
+        let entries = self.entries.get(header)?;
 
-fn parse_entry(input: &str) -> IResult<&str, (Key, Value)> {
-    separated_pair(parse_key, tuple((space0, char('='), space0)), parse_value)(input)
-}
+        // Reborrow the entries with the shorter lifetime of the key
+        let index = {
+            let entries: &IndexMap<Key, Source> = entries;
 
-fn parse_key(input: &str) -> IResult<&str, Key> {
-    map(
-        pair(
-            parse_key_part,
-            opt(delimited(char('['), parse_key_locale, char(']'))),
-        ),
-        |(key, opt_locale)| match opt_locale {
-            Some(locale) => Key::Localized { key, locale },
-            None => Key::Simple(key),
-        },
-    )(input)
-}
+            entries.get_index_of(key)
+        };
 
-fn parse_key_locale(input: &str) -> IResult<&str, Locale> {
-    map(
-        tuple((
-            parse_key_part,
-            opt(preceded(char('_'), parse_key_part)),
-            opt(preceded(char('.'), parse_key_part)),
-            opt(preceded(char('@'), parse_key_part)),
-        )),
-        |(lang, country, encoding, modifier)| Locale {
-            lang,
-            country,
-            encoding,
-            modifier,
-        },
-    )(input)
-}
+        let (_, source) = entries.get_index(index?)?;
 
-fn parse_key_part(input: &str) -> IResult<&str, Cow<str>> {
-    map(
-        recognize(many1_count(satisfy(|c| {
-            c.is_ascii_alphanumeric() || c == '-'
-        }))),
-        Cow::from,
-    )(input)
-}
+        let unchanged = match (&source.value, value) {
+            // The NaN is not equal to itself
+            (Value::Numeric(parsed), Value::Numeric(value)) => parsed.to_bits() == value.to_bits(),
+            (parsed, value) => parsed == value,
+        };
 
-/// Parse all the characters until the line ending
-fn parse_value(input: &str) -> IResult<&str, Value> {
-    alt((
-        map(parse_boolean, Value::Boolean),
-        map(parse_numeric, Value::Numeric),
-        map(parse_string, Value::String),
-        map(parse_local_string, Value::LocaleString),
-    ))(input)
-}
-
-fn escaped_chars(input: char) -> Option<&'static str> {
-    let escaped = match input {
-        's' => " ",
-        'n' => "\n",
-        't' => "\t",
-        'r' => "\r",
-        '\\' => "\\",
-        ';' => ";",
-        _ => {
-            return None;
-        }
-    };
+        unchanged.then_some(source.text.as_ref())
+    }
 
-    Some(escaped)
-}
-
-fn parse_escaped_string(input: &str) -> IResult<&str, Cow<str>> {
-    let mut iter = input.chars().enumerate();
-
-    while let Some((i, c)) = iter.next() {
-        if c == ESCAPE_CHAR {
-            let escaped = iter
-                .next()
-                .and_then(|(_, escaped)| escaped_chars(escaped))
-                .ok_or_else(|| {
-                    nom::Err::Error(nom::error::Error::new(
-                        input,
-                        nom::error::ErrorKind::Escaped,
-                    ))
-                })?;
-
-            let mut escaped_string = String::with_capacity(input.len());
-            escaped_string.push_str(&input[..i]);
-            escaped_string.push_str(escaped);
-
-            let mut iter = input[i + 2..].chars();
-            while let Some(c) = iter.next() {
-                if c == ESCAPE_CHAR {
-                    let escaped = iter.next().and_then(escaped_chars).ok_or_else(|| {
-                        nom::Err::Error(nom::error::Error::new(
-                            input,
-                            nom::error::ErrorKind::Escaped,
-                        ))
-                    })?;
-
-                    escaped_string.push_str(escaped);
-                } else {
-                    escaped_string.push(c);
-                }
-            }
+    /// Drops the sources of the groups and entries no longer in the document.
+    fn retain(&mut self, groups: &GroupMap<'a>) {
+        self.entries.retain(|header, sources| {
+            let Some(entries) = groups.get(header) else {
+                return false;
+            };
+
+            sources.retain(|key, _| entries.contains_key(key));
+
+            !sources.is_empty()
+        });
+    }
 
-            return Ok(("", Cow::Owned(escaped_string)));
+    fn into_owned(self) -> Sources<'static> {
+        Sources {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(header, entries)| {
+                    let entries = entries
+                        .into_iter()
+                        .map(|(key, source)| {
+                            let source = Source {
+                                text: Cow::Owned(source.text.into_owned()),
+                                value: source.value.into_owned(),
+                            };
+
+                            (key.into_owned(), source)
+                        })
+                        .collect();
+
+                    (Cow::Owned(header.into_owned()), entries)
+                })
+                .collect(),
         }
     }
+}
 
-    Ok(("", Cow::Borrowed(input)))
+/// Returns the text of a parsed value, if the value is not written back as it.
+fn value_source<'a>(text: &'a str, value: &Value) -> Option<Cow<'a, str>> {
+    (!writer::writes_as(value, text)).then_some(Cow::Borrowed(text))
 }
 
-fn parse_string(input: &str) -> IResult<&str, Cow<str>> {
-    map(
-        verify(
-            map_parser(not_line_ending, cut(parse_escaped_string)),
-            str::is_ascii,
-        ),
-        Cow::from,
-    )(input)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesktopEntry<'a> {
+    groups: GroupMap<'a>,
+    comments: Comments<'a>,
+    sources: Sources<'a>,
+    line_ending: LineEnding,
+    byte_order_mark: bool,
+    warnings: Vec<ParseWarning>,
 }
 
-fn parse_local_string(input: &str) -> IResult<&str, Cow<str>> {
-    map(
-        map_parser(not_line_ending, cut(parse_escaped_string)),
-        Cow::from,
-    )(input)
+impl TryFrom<&Path> for DesktopEntry<'static> {
+    type Error = Error;
+
+    /// Reads and parses a desktop file, see [`DesktopEntry::from_path`].
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        DesktopEntry::from_path(path)
+    }
 }
 
-fn parse_boolean(input: &str) -> IResult<&str, bool> {
-    map_parser(
-        not_line_ending,
-        alt((value(true, tag("true")), value(false, tag("false")))),
-    )(input)
+/// Line ending style of a desktop file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// Unix style `\n` line ending.
+    #[default]
+    Lf,
+    /// Windows style `\r\n` line ending.
+    CrLf,
 }
 
-fn parse_numeric(input: &str) -> IResult<&str, f32> {
-    map_parser(not_line_ending, float)(input)
+impl LineEnding {
+    /// Detects the line ending style from the first line of the input.
+    pub fn detect(input: &str) -> Self {
+        match input.find('\n') {
+            Some(i) if input[..i].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use indexmap::indexmap;
-    use pretty_assertions::assert_eq;
+/// Entries of a group, by key.
+#[cfg(not(feature = "vec-map"))]
+pub type EntryMap<'a, 'b> = IndexMap<Key<'a>, Value<'b>>;
+/// Entries of a group, by key.
+#[cfg(feature = "vec-map")]
+pub type EntryMap<'a, 'b> = map::VecMap<Key<'a>, Value<'b>>;
+
+/// Groups of a document, by header.
+#[cfg(not(feature = "vec-map"))]
+pub type GroupMap<'a> = IndexMap<Cow<'a, str>, EntryMap<'a, 'a>>;
+/// Groups of a document, by header.
+#[cfg(feature = "vec-map")]
+pub type GroupMap<'a> = map::VecMap<Cow<'a, str>, EntryMap<'a, 'a>>;
+
+impl<'a> DesktopEntry<'a> {
+    /// Parses a desktop file.
+    ///
+    /// # Errors
+    ///
+    /// Invalid or malformed desktop file.
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        Self::parse_with_options(input, &ParseOptions::default())
+    }
 
-    use super::*;
+    /// Parses a desktop file with the given options.
+    ///
+    /// # Errors
+    ///
+    /// Invalid or malformed desktop file.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = input.len()))
+    )]
+    pub fn parse_with_options(input: &'a str, options: &ParseOptions) -> Result<Self, ParseError> {
+        let result = parse_desktop_entry_with_options(input, options)
+            .map(|(_, document)| document)
+            .map_err(|err| ParseError::from_nom(input, err));
+
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(document) => tracing::trace!(
+                groups = document.groups.len(),
+                warnings = document.warnings.len(),
+                "parsed desktop entry"
+            ),
+            Err(err) => tracing::debug!(%err, "failed to parse desktop entry"),
+        }
 
-    #[test]
-    fn shoul_parse_comment() {
-        assert_eq!(Ok(("\n", Cow::from("# Code"))), parse_comment("# Code\n"))
+        result
     }
 
-    #[test]
-    fn shoul_parse_empty_comment() {
-        assert_eq!(Ok(("", Cow::from("#"))), parse_comment("#"))
+    /// Parses a desktop file from bytes, decoding them to UTF-8.
+    ///
+    /// The document owns its strings, since the decoded text may not borrow the input. The
+    /// characters replaced while decoding are reported in the [warnings](Self::warnings).
+    ///
+    /// # Errors
+    ///
+    /// Invalid UTF-8 with [`Decoding::Strict`], or invalid or malformed desktop file.
+    pub fn from_bytes(
+        input: &[u8],
+        decoding: Decoding,
+    ) -> Result<DesktopEntry<'static>, ParseError> {
+        Self::from_bytes_with_options(input, decoding, &ParseOptions::default())
     }
 
-    #[test]
-    fn shoul_parse_empty_line() {
-        assert_eq!(Ok(("\n", None)), parse_empty_line("\n"))
+    /// Parses a desktop file from bytes with the given options.
+    ///
+    /// See [`DesktopEntry::from_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Invalid UTF-8 with [`Decoding::Strict`], or invalid or malformed desktop file.
+    pub fn from_bytes_with_options(
+        input: &[u8],
+        decoding: Decoding,
+        options: &ParseOptions,
+    ) -> Result<DesktopEntry<'static>, ParseError> {
+        let (text, warnings) = decode::decode(input, decoding)?;
+
+        let mut document = DesktopEntry::parse_with_options(&text, options)?.into_owned();
+
+        document.warnings.splice(0..0, warnings);
+
+        Ok(document)
     }
 
-    #[test]
-    fn shoul_parse_empty_line_whitespace() {
-        assert_eq!(Ok(("\n", Some(Cow::from("  ")))), parse_empty_line("  \n"))
+    /// Reads and parses a desktop file.
+    ///
+    /// See [`loader::load`] to parse the file with options.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or it's invalid or malformed.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<DesktopEntry<'static>, Error> {
+        loader::load(path, &ParseOptions::default())
     }
 
-    #[test]
-    fn shoul_parse_group_header() {
-        assert_eq!(
-            Ok(("", Cow::from("header"))),
-            parse_group_header("[header]")
-        );
+    /// Reads and parses a desktop file from a reader, like the standard input.
+    ///
+    /// The input must be UTF-8, the byte order mark is skipped.
+    ///
+    /// # Errors
+    ///
+    /// If the input can't be read, or it's invalid or malformed.
+    pub fn from_reader(reader: impl Read) -> Result<DesktopEntry<'static>, Error> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
     }
 
-    #[test]
-    fn shoul_parse_entry() {
-        assert_eq!(
-            Ok((
-                "",
-                (
-                    Key::Simple(Cow::from("Ke1")),
-                    Value::String(Cow::from("Value"))
-                )
-            )),
-            parse_entry("Ke1=Value")
-        );
+    /// Reads and parses a desktop file from a reader with the given options.
+    ///
+    /// The input is read up to the [`Limits::max_file_size`], a larger input is rejected without
+    /// reading the rest. See [`DesktopEntry::from_reader`].
+    ///
+    /// # Errors
+    ///
+    /// If the input can't be read, it exceeds the file size limit, or it's invalid or malformed.
+    pub fn from_reader_with_options(
+        reader: impl Read,
+        options: &ParseOptions,
+    ) -> Result<DesktopEntry<'static>, Error> {
+        let input = decode::read_limited(reader, options.limits.max_file_size)?;
+
+        Ok(Self::from_bytes_with_options(
+            &input,
+            Decoding::Strict,
+            options,
+        )?)
     }
 
-    #[test]
-    fn shoul_parse_key() {
-        assert_eq!(Ok(("", Key::Simple(Cow::from("Ke1")))), parse_key("Ke1"));
+    /// Returns the line ending style of the parsed file.
+    ///
+    /// It's used when writing the document to preserve the original style.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
     }
 
-    #[test]
-    fn shoul_parse_localized_key() {
-        assert_eq!(
-            Ok((
-                "",
-                Key::Localized {
-                    key: Cow::from("Ke1"),
-                    locale: Locale {
-                        lang: Cow::from("sr"),
-                        country: Some(Cow::from("YU")),
-                        encoding: Some(Cow::from("UTF-8")),
-                        modifier: Some(Cow::from("Latin"))
-                    }
-                }
-            )),
-            parse_key("Ke1[sr_YU.UTF-8@Latin]")
-        );
+    /// Sets the line ending style used when writing the document.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
     }
 
-    fn example_file_groups() -> IndexMap<Cow<'static, str>, EntryMap<'static, 'static>> {
-        indexmap! {
-            Cow::from("Desktop Entry") => indexmap! {
-                Key::Simple(Cow::from("Version")) => Value::Numeric(1.0),
-                Key::Simple(Cow::from("Type")) => Value::String(Cow::from("Application")),
-                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Foo Viewer")),
-                Key::Simple(Cow::from("Comment")) => Value::String(Cow::from("The best viewer for Foo objects available!")),
-                Key::Simple(Cow::from("TryExec")) => Value::String(Cow::from("fooview")),
-                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview %F")),
-                Key::Simple(Cow::from("Icon")) => Value::String(Cow::from("fooview")),
-                Key::Simple(Cow::from("MimeType")) => Value::String(Cow::from("image/x-foo;")),
-                Key::Simple(Cow::from("Actions")) => Value::String(Cow::from("Gallery;Create;")),
-            },
-            Cow::from("Desktop Action Gallery") => indexmap! {
-                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview --gallery")),
-                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Browse Gallery")),
-            },
-            Cow::from("Desktop Action Create") => indexmap! {
-                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview --create-new")),
-                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Create a new Foo!")),
-                Key::Simple(Cow::from("Icon")) => Value::String(Cow::from("fooview-new")),
-            },
+    /// Returns true if the parsed file started with a UTF-8 byte order mark, which is skipped.
+    pub fn has_byte_order_mark(&self) -> bool {
+        self.byte_order_mark
+    }
+
+    /// Returns the comments and empty lines before the header of a group.
+    pub fn group_comments(&self, header: &str) -> &[Comment<'a>] {
+        self.comments.before_group(header)
+    }
+
+    /// Returns the comments and empty lines before an entry of a group.
+    pub fn entry_comments(&self, header: &str, key: &Key) -> &[Comment<'a>] {
+        self.comments.before_entry(header, key)
+    }
+
+    /// Returns the comments and empty lines after the last entry of the document.
+    pub fn trailing_comments(&self) -> &[Comment<'a>] {
+        self.comments.tail()
+    }
+
+    /// Sets the comments and empty lines written before the header of a group.
+    ///
+    /// The comments are written only if the group is in the document. Returns the previous
+    /// comments.
+    pub fn set_group_comments(
+        &mut self,
+        header: impl Into<Cow<'a, str>>,
+        comments: Vec<Comment<'a>>,
+    ) -> Vec<Comment<'a>> {
+        self.comments
+            .groups
+            .insert(header.into(), comments)
+            .unwrap_or_default()
+    }
+
+    /// Sets the comments and empty lines written before an entry of a group.
+    ///
+    /// The comments are written only if the entry is in the document. Returns the previous
+    /// comments.
+    pub fn set_entry_comments(
+        &mut self,
+        header: impl Into<Cow<'a, str>>,
+        key: Key<'a>,
+        comments: Vec<Comment<'a>>,
+    ) -> Vec<Comment<'a>> {
+        self.comments
+            .entries
+            .entry(header.into())
+            .or_default()
+            .insert(key, comments)
+            .unwrap_or_default()
+    }
+
+    /// Sets the comments and empty lines written after the last entry of the document.
+    ///
+    /// Returns the previous comments.
+    pub fn set_trailing_comments(&mut self, comments: Vec<Comment<'a>>) -> Vec<Comment<'a>> {
+        std::mem::replace(&mut self.comments.tail, comments)
+    }
+
+    /// Converts the document into one owning all its strings, not borrowing the input.
+    pub fn into_owned(self) -> DesktopEntry<'static> {
+        DesktopEntry {
+            groups: self
+                .groups
+                .into_iter()
+                .map(|(header, entries)| {
+                    let entries = entries
+                        .into_iter()
+                        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                        .collect();
+
+                    (Cow::Owned(header.into_owned()), entries)
+                })
+                .collect(),
+            comments: self.comments.into_owned(),
+            sources: self.sources.into_owned(),
+            line_ending: self.line_ending,
+            byte_order_mark: self.byte_order_mark,
+            warnings: self.warnings,
         }
     }
 
-    #[cfg(feature = "keep-comments")]
-    #[test]
-    fn should_parse_example_file_with_comments() {
-        let example_file = include_str!("../example/file.desktop");
+    /// Returns the warnings for the recoverable issues found while parsing.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
 
-        let (rest, desktop_entry) = parse_desktop_entry(example_file).unwrap();
+    /// Returns the groups with their entries, in the order of the file.
+    pub fn groups(&self) -> impl Iterator<Item = (&str, &EntryMap<'a, 'a>)> {
+        self.groups
+            .iter()
+            .map(|(header, entries)| (header.as_ref(), entries))
+    }
 
-        assert_eq!("", rest);
+    /// Returns the entries of a group.
+    pub fn group(&self, header: &str) -> Option<&EntryMap<'a, 'a>> {
+        self.groups.get(header)
+    }
 
-        let expected = DesktopEntry {
-            groups: example_file_groups(),
-            comments: indexmap! {
-                0 => Comment::Comment(Cow::from("# Example file from the spec")),
-                11 => Comment::EmptyLine{white_space:None},
-                15 => Comment::EmptyLine{white_space: None},
-            },
-        };
+    /// Returns the non localized value of a key in a group.
+    pub fn get(&self, header: &str, key: &str) -> Option<&Value<'a>> {
+        let entries = self.group(header)?;
 
-        assert_eq!(expected, desktop_entry)
+        get_simple(entries, key)
     }
 
-    #[cfg(not(feature = "keep-comments"))]
-    #[test]
-    fn should_parse_example_file_with_comments() {
-        let example_file = include_str!("../example/file.desktop");
+    /// Checks if the application has a single main window, with `SingleMainWindow=true`.
+    ///
+    /// Launchers shouldn't offer to open a new window of these applications.
+    pub fn single_main_window(&self) -> bool {
+        self.boolean("SingleMainWindow")
+    }
 
-        let (rest, desktop_entry) = parse_desktop_entry(example_file).unwrap();
+    /// Checks if the application prefers to run on the discrete GPU, with
+    /// `PrefersNonDefaultGPU=true`.
+    pub fn prefers_non_default_gpu(&self) -> bool {
+        self.boolean("PrefersNonDefaultGPU")
+    }
 
-        assert_eq!("", rest);
+    /// Checks if the application is launched with D-Bus activation, with
+    /// `DBusActivatable=true`.
+    pub fn dbus_activatable(&self) -> bool {
+        self.boolean("DBusActivatable")
+    }
 
-        let expected = DesktopEntry {
-            groups: example_file_groups(),
+    /// Checks if a boolean key of the `[Desktop Entry]` group is `true`, a missing or invalid
+    /// value is `false`.
+    fn boolean(&self, key: &str) -> bool {
+        matches!(
+            self.get(DESKTOP_ENTRY_GROUP, key),
+            Some(Value::Boolean(true))
+        )
+    }
+
+    /// Returns the value of a key that best matches the locale.
+    ///
+    /// Fallbacks to the non localized value if no translation matches or the locale is
+    /// [`None`].
+    pub fn get_localized(
+        &self,
+        header: &str,
+        key: &str,
+        locale: Option<&Locale>,
+    ) -> Option<&Value<'a>> {
+        let entries = self.group(header)?;
+
+        locale
+            .and_then(|locale| {
+                entries
+                    .iter()
+                    .filter_map(|(entry_key, value)| match entry_key {
+                        Key::Localized {
+                            key: entry_key,
+                            locale: entry_locale,
+                        } if entry_key == key => {
+                            locale.match_key(entry_locale).map(|level| (level, value))
+                        }
+                        _ => None,
+                    })
+                    // Keep the first of the best matches
+                    .rev()
+                    .max_by_key(|(level, _)| *level)
+                    .map(|(_, value)| value)
+            })
+            .or_else(|| get_simple(entries, key))
+    }
+
+    /// Returns the default value of a key followed by all its translations.
+    ///
+    /// The default value has a [`None`] locale, the translations are in the same order as in the
+    /// group.
+    pub fn localized_values(
+        &self,
+        header: &str,
+        key: &str,
+    ) -> IndexMap<Option<&Locale<'a>>, &Value<'a>> {
+        let Some(entries) = self.group(header) else {
+            return IndexMap::new();
         };
 
-        assert_eq!(expected, desktop_entry)
+        get_simple(entries, key)
+            .map(|value| (None, value))
+            .into_iter()
+            .chain(
+                entries
+                    .iter()
+                    .filter_map(|(entry_key, value)| match entry_key {
+                        Key::Localized {
+                            key: entry_key,
+                            locale,
+                        } if entry_key == key => Some((Some(locale), value)),
+                        _ => None,
+                    }),
+            )
+            .collect()
     }
 
-    #[test]
-    fn should_parse_string() {
-        assert_eq!(Ok(("", Cow::from("foo bar"))), parse_string("foo bar"));
+    /// Returns the locales used in the localized keys of all the groups.
+    ///
+    /// The locales are in the order of their first use in the document.
+    pub fn locales(&self) -> IndexSet<&Locale<'a>> {
+        self.groups
+            .values()
+            .flat_map(|entries| entries.keys().filter_map(Key::locale))
+            .collect()
+    }
+
+    /// Sets the value of a key for a locale, or the default value if the locale is [`None`].
+    ///
+    /// The group is created if missing. Returns the previous value, if any.
+    ///
+    /// A translation with a locale that [normalizes](Locale::normalize) to the same one is
+    /// replaced, like the `Name[de_DE.UTF-8]` when setting the `Name` for `de_DE`.
+    pub fn set_localized(
+        &mut self,
+        header: impl Into<Cow<'a, str>>,
+        key: impl Into<Cow<'a, str>>,
+        locale: Option<Locale<'a>>,
+        value: Value<'a>,
+    ) -> Option<Value<'a>> {
+        let entries = self.groups.entry(header.into()).or_default();
+        let key = key.into();
+
+        if let Some(locale) = &locale {
+            let normalized = locale.normalize();
+
+            let existing = entries.keys().position(|entry_key| match entry_key {
+                Key::Localized {
+                    key: entry_key,
+                    locale: entry_locale,
+                } => *entry_key == key && entry_locale.normalize() == normalized,
+                Key::Simple(_) => false,
+            });
 
-        assert_eq!(Ok(("", Cow::from("foo 'bar'"))), parse_string("foo 'bar'"));
+            if let Some((_, previous)) = existing.and_then(|index| entries.get_index_mut(index)) {
+                return Some(std::mem::replace(previous, value));
+            }
+        }
+
+        let key = match locale {
+            Some(locale) => Key::Localized { key, locale },
+            None => Key::Simple(key),
+        };
+
+        entries.insert(key, value)
     }
 
-    #[test]
-    fn should_parse_escaped_string() {
-        assert_eq!(Ok(("", Cow::from("foo \nbar"))), parse_string("foo \\nbar"));
+    /// Removes all the translations of a key in a group, keeping the default value.
+    ///
+    /// Returns the number of removed translations.
+    pub fn remove_localizations(&mut self, header: &str, key: &str) -> usize {
+        let Some(entries) = self.groups.get_mut(header) else {
+            return 0;
+        };
 
-        assert_eq!(
-            Ok(("", Cow::from("foo \t bar"))),
-            parse_string("foo \\t\\sbar")
-        );
+        let len = entries.len();
+
+        entries.retain(|entry_key, _| {
+            !matches!(entry_key, Key::Localized { key: entry_key, .. } if entry_key == key)
+        });
 
-        assert_eq!(Ok(("", Cow::from("foo;bar"))), parse_string("foo\\;bar"));
+        let removed = len - entries.len();
+
+        self.comments.retain(&self.groups);
+        self.sources.retain(&self.groups);
+
+        removed
     }
 
-    #[test]
-    fn should_parse_value() {
-        assert_eq!(
-            Ok(("", Value::String(Cow::from("foo \nbar")))),
-            parse_value("foo \\nbar")
-        );
+    /// Removes the translations of all the keys in the document, keeping the default values.
+    ///
+    /// Returns the number of removed translations.
+    pub fn remove_all_localizations(&mut self) -> usize {
+        let removed = self
+            .groups
+            .values_mut()
+            .map(|entries| {
+                let len = entries.len();
 
-        assert_eq!(Ok(("\nas", Value::Boolean(true))), parse_value("true\nas"));
-        assert_eq!(
-            Ok(("\nas", Value::Boolean(false))),
-            parse_value("false\nas")
-        );
+                entries.retain(|key, _| matches!(key, Key::Simple(_)));
+
+                len - entries.len()
+            })
+            .sum();
 
-        assert_eq!(Ok(("\nas", Value::Numeric(1.))), parse_value("1\nas"));
-        assert_eq!(Ok(("\nas", Value::Numeric(4.2))), parse_value("4.20\nas"));
-        // FIX: this is will not pass
-        // assert_eq!(Ok(("\nas", Value::Numeric(4.2))), parse_value("4,20\nas"));
+        self.comments.retain(&self.groups);
+        self.sources.retain(&self.groups);
+
+        removed
+    }
+
+    /// Removes the translations that don't match any of the locales of the chain.
+    ///
+    /// A translation is kept if a locale of the chain would use it, so `de` is kept for `de_DE`
+    /// but `de_AT` isn't. Use [`DesktopEntry::promote_locales`] to keep only the best match of
+    /// each key, as its default value.
+    ///
+    /// Returns the number of removed translations.
+    pub fn strip_locales(&mut self, keep: &[Locale]) -> usize {
+        let removed = self
+            .groups
+            .values_mut()
+            .map(|entries| {
+                let len = entries.len();
+
+                entries.retain(|key, _| {
+                    key.locale().is_none_or(|locale| {
+                        keep.iter().any(|keep| keep.match_key(locale).is_some())
+                    })
+                });
+
+                len - entries.len()
+            })
+            .sum();
+
+        self.comments.retain(&self.groups);
+        self.sources.retain(&self.groups);
+
+        removed
+    }
+
+    /// Replaces the default value of each localized key with its translation for the first
+    /// locale of the chain that matches, and removes all the translations.
+    ///
+    /// The keys without a matching translation keep their default value, so the document is
+    /// displayed in the same way for the chain.
+    ///
+    /// Returns the number of removed translations.
+    pub fn promote_locales(&mut self, locales: &[Locale]) -> usize {
+        let promoted: Vec<(Cow<'a, str>, Key<'a>, Value<'a>)> = self
+            .groups
+            .iter()
+            .flat_map(|(header, entries)| {
+                entries
+                    .keys()
+                    .filter_map(|key| match key {
+                        Key::Simple(_) => None,
+                        Key::Localized { key, .. } => Some(key),
+                    })
+                    .collect::<IndexSet<_>>()
+                    .into_iter()
+                    .filter_map(|key| {
+                        let value = self.localized_in_chain(header, key, locales)?;
+
+                        Some((header.clone(), Key::Simple(key.clone()), value.clone()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let removed = self.remove_all_localizations();
+
+        // Replaces the default values in place, or appends the missing ones
+        for (header, key, value) in promoted {
+            if let Some(entries) = self.groups.get_mut(&header) {
+                entries.insert(key, value);
+            }
+        }
+
+        removed
+    }
+
+    /// Returns the translation of a key for the first locale of the chain that matches, or the
+    /// non localized value.
+    fn localized_in_chain(
+        &self,
+        header: &str,
+        key: &str,
+        locales: &[Locale],
+    ) -> Option<&Value<'a>> {
+        let values = self.localized_values(header, key);
+
+        locales
+            .iter()
+            .find_map(|locale| {
+                values
+                    .iter()
+                    .filter_map(|(entry_locale, value)| {
+                        locale
+                            .match_key(entry_locale.as_ref()?)
+                            .map(|level| (level, *value))
+                    })
+                    // Keep the first of the best matches
+                    .rev()
+                    .max_by_key(|(level, _)| *level)
+                    .map(|(_, value)| value)
+            })
+            .or_else(|| values.get(&None).copied())
+    }
+}
+
+/// Lookup a non localized key in the entries with a borrowed key.
+fn get_simple<'a, 'b>(entries: &'b EntryMap<'a, 'a>, key: &str) -> Option<&'b Value<'a>> {
+    let index = {
+        // Reborrow the entries with the shorter lifetime of the key
+        let entries: &EntryMap = entries;
+
+        entries.get_index_of(&Key::Simple(Cow::Borrowed(key)))?
+    };
+
+    entries.get_index(index).map(|(_, value)| value)
+}
+
+/// Parses a desktop file.
+///
+/// # Errors
+///
+/// Invalid or malformed desktop file.
+pub fn parse_desktop_entry(input: &str) -> IResult<&str, DesktopEntry<'_>> {
+    parse_desktop_entry_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a desktop file with the given options.
+///
+/// With the `fast-parser` feature the file is parsed by a hand-written parser, falling back to
+/// the [`nom`] one for the errors and the options it doesn't support.
+///
+/// # Errors
+///
+/// Invalid or malformed desktop file.
+pub fn parse_desktop_entry_with_options<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, DesktopEntry<'a>> {
+    #[cfg(feature = "fast-parser")]
+    if let Some(document) = fast_parser::parse_document(input, options) {
+        return Ok(("", document));
+    }
+
+    parse_document(input, options)
+}
+
+/// Parses a desktop file returning a [`VerboseError`] with the full trace of the failure.
+///
+/// The error can be formatted with [`nom::error::convert_error`] to debug malformed files.
+///
+/// # Errors
+///
+/// Invalid or malformed desktop file.
+pub fn parse_desktop_entry_verbose<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, DesktopEntry<'a>, VerboseError<&'a str>> {
+    parse_document(input, options)
+}
+
+/// Estimates the number of entries of each group of a desktop file, from the lines following
+/// each group header.
+///
+/// The estimate is an upper bound, since it counts the comments and the empty lines too. It's
+/// used to reserve the capacity of the maps before parsing, see
+/// [`ParseOptions::reserve_capacity`].
+pub fn estimate_capacity(input: &str) -> Vec<usize> {
+    input.lines().fold(Vec::new(), |mut groups, line| {
+        match groups.last_mut() {
+            _ if line.starts_with('[') => groups.push(0),
+            Some(entries) => *entries += 1,
+            None => {}
+        }
+
+        groups
+    })
+}
+
+/// Parses a desktop file, generic over the nom error type.
+fn parse_document<'a, E>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, DesktopEntry<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    let (input, byte_order_mark) = match input.strip_prefix(BYTE_ORDER_MARK) {
+        Some(input) => (input, true),
+        None => (input, false),
+    };
+
+    let limits = &options.limits;
+
+    if Limits::exceeds(limits.max_file_size, input.len()) {
+        return Err(too_large(input, "file size"));
+    }
+
+    let capacities = if options.reserve_capacity {
+        estimate_capacity(input)
+    } else {
+        Vec::new()
+    };
+
+    let has_entry = Cell::new(false);
+    let keep_group = Cell::new(true);
+    let groups = Cell::new(0usize);
+    let entries = Cell::new(0usize);
+    let lines = Cell::new(0usize);
+    let warnings = RefCell::new(Vec::new());
+    let group_keys = RefCell::new(HashSet::new());
+
+    let result = terminated(
+        map(
+            fold_many0(
+                verify(
+                    |input| {
+                        if limits.max_line_length.is_some() {
+                            let (_, line) = not_line_ending(input)?;
+
+                            if Limits::exceeds(limits.max_line_length, line.len()) {
+                                return Err(too_large(input, "line length"));
+                            }
+                        }
+
+                        let (rest, mut line) = parse_line(input, options, keep_group.get())?;
+
+                        lines.set(lines.get() + 1);
+
+                        if options.unknown_escapes == UnknownEscapes::Preserve
+                            && matches!(line, Line::Entry { .. })
+                        {
+                            let (_, content) = not_line_ending(input)?;
+
+                            warnings.borrow_mut().extend(
+                                unknown_escape_sequences_in(options.dialect.escapes, content)
+                                    .into_iter()
+                                    .map(|sequence| ParseWarning::UnknownEscape {
+                                        line: lines.get(),
+                                        sequence: sequence.to_string(),
+                                    }),
+                            );
+                        }
+
+                        match (&line, options.dialect.duplicate_keys) {
+                            (_, DuplicateKeys::KeepLast) => {}
+                            (Line::GroupHeader(_), _) => group_keys.borrow_mut().clear(),
+                            (Line::Entry { key, .. }, duplicate_keys)
+                                if !group_keys.borrow_mut().insert(key.clone()) =>
+                            {
+                                if duplicate_keys == DuplicateKeys::Reject {
+                                    return Err(duplicate_key(input));
+                                }
+
+                                line = Line::Skipped;
+                            }
+                            _ => {}
+                        }
+
+                        let exceeded = match line {
+                            Line::GroupHeader(_) => {
+                                groups.set(groups.get() + 1);
+
+                                Limits::exceeds(limits.max_groups, groups.get())
+                                    .then_some("number of groups")
+                            }
+                            Line::Entry { .. } => {
+                                entries.set(entries.get() + 1);
+
+                                Limits::exceeds(limits.max_entries, entries.get())
+                                    .then_some("number of entries")
+                            }
+                            _ => None,
+                        };
+
+                        if let Some(limit) = exceeded {
+                            return Err(too_large(input, limit));
+                        }
+
+                        Ok((rest, line))
+                    },
+                    |line| match line {
+                        Line::GroupHeader(header) => {
+                            has_entry.set(true);
+                            keep_group.set(options.keep_group(header));
+
+                            true
+                        }
+                        Line::Entry { .. } | Line::Skipped => has_entry.get(),
+                        _ => true,
+                    },
+                ),
+                || start_document(capacities.len()),
+                |acc, line| {
+                    // The header was counted before folding the line
+                    let capacity = capacities.get(groups.get().wrapping_sub(1));
+
+                    fold_line(acc, line, options, capacity.copied().unwrap_or_default())
+                },
+            ),
+            |acc| finish_document(acc, input, byte_order_mark, warnings.take()),
+        ),
+        eof,
+    )(input);
+
+    result
+}
+
+/// Failure for a key repeated in the same group.
+fn duplicate_key<'a, E>(input: &'a str) -> nom::Err<E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    nom::Err::Failure(E::add_context(
+        input,
+        "duplicate key",
+        E::from_error_kind(input, ErrorKind::Verify),
+    ))
+}
+
+/// Failure for an exceeded limit, the context is the name of the limit.
+fn too_large<'a, E>(input: &'a str, limit: &'static str) -> nom::Err<E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    nom::Err::Failure(E::add_context(
+        input,
+        limit,
+        E::from_error_kind(input, ErrorKind::TooLarge),
+    ))
+}
+
+/// Document being folded from the lines, with the current group and the pending comments.
+type FoldState<'a> = (DesktopEntry<'a>, Option<Group<'a>>, Vec<Comment<'a>>);
+
+/// Creates the document to fold the lines into, with the capacity for the groups.
+fn start_document<'a>(groups: usize) -> FoldState<'a> {
+    let document = DesktopEntry {
+        groups: GroupMap::with_capacity(groups),
+        ..Default::default()
+    };
+
+    (document, None, Vec::new())
+}
+
+/// Adds a parsed line to the document, skipping the groups and comments filtered out.
+fn fold_line<'a>(
+    acc: FoldState<'a>,
+    line: Line<'a>,
+    options: &ParseOptions,
+    entries_capacity: usize,
+) -> FoldState<'a> {
+    match line {
+        Line::GroupHeader(header) if !options.keep_group(&header) => {
+            let (mut document, group, pending) = acc;
+
+            if let Some(group) = group {
+                document.groups.insert(group.header, group.entries);
+            }
+
+            (document, None, pending)
+        }
+        Line::Comment(_) | Line::EmptyLine { .. } if !options.keep_comments => acc,
+        _ => map_document_line(acc, line, entries_capacity),
+    }
+}
+
+/// Adds the last group and the trailing comments to the document.
+fn finish_document<'a>(
+    (mut document, group, pending): FoldState<'a>,
+    input: &str,
+    byte_order_mark: bool,
+    warnings: Vec<ParseWarning>,
+) -> DesktopEntry<'a> {
+    if let Some(group) = group {
+        document.groups.insert(group.header, group.entries);
+    }
+
+    document.comments.tail.extend(pending);
+
+    document.line_ending = LineEnding::detect(input);
+    document.byte_order_mark = byte_order_mark;
+    document.warnings = warnings;
+
+    document
+}
+
+fn map_document_line<'a>(
+    (mut document, mut group, mut pending): FoldState<'a>,
+    line: Line<'a>,
+    entries_capacity: usize,
+) -> FoldState<'a> {
+    match line {
+        Line::Comment(comment) => {
+            pending.push(Comment::Comment(comment));
+        }
+        Line::EmptyLine { white_space } => {
+            pending.push(Comment::EmptyLine { white_space });
+        }
+        Line::GroupHeader(header) => {
+            if !pending.is_empty() {
+                document
+                    .comments
+                    .groups
+                    .entry(header.clone())
+                    .or_default()
+                    .append(&mut pending);
+            }
+
+            let old_group = group.replace(Group {
+                header,
+                entries: EntryMap::with_capacity(entries_capacity),
+            });
+
+            if let Some(group) = old_group {
+                document.groups.insert(group.header, group.entries);
+            }
+        }
+        Line::Entry { key, value, source } => {
+            let group = group.as_mut().unwrap();
+
+            if let Some(text) = source {
+                document
+                    .sources
+                    .entries
+                    .entry(group.header.clone())
+                    .or_default()
+                    .insert(
+                        key.clone(),
+                        Source {
+                            text,
+                            value: value.clone(),
+                        },
+                    );
+            }
+
+            if !pending.is_empty() {
+                document
+                    .comments
+                    .entries
+                    .entry(group.header.clone())
+                    .or_default()
+                    .entry(key.clone())
+                    .or_default()
+                    .append(&mut pending);
+            }
+
+            group.entries.insert(key, value);
+        }
+        Line::Skipped => {}
+    }
+
+    (document, group, pending)
+}
+
+fn parse_line<'a, E>(
+    input: &'a str,
+    options: &ParseOptions,
+    keep_group: bool,
+) -> IResult<&'a str, Line<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    let dialect = options.dialect;
+
+    terminated(
+        alt((
+            map(parse_dialect_comment(dialect), Line::Comment),
+            map(parse_dialect_group_header(dialect), Line::GroupHeader),
+            |input| parse_filtered_entry(input, options, keep_group),
+            map(parse_empty_line, |white_space| Line::EmptyLine {
+                white_space,
+            }),
+        )),
+        context("line ending", parse_end_of_line),
+    )(input)
+}
+
+/// Parses an entry, skipping the value without parsing it if the entry is filtered out.
+fn parse_filtered_entry<'a, E>(
+    input: &'a str,
+    options: &ParseOptions,
+    keep_group: bool,
+) -> IResult<&'a str, Line<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    let dialect = options.dialect;
+
+    let (value_input, key) =
+        terminated(parse_dialect_key(dialect), parse_dialect_separator(dialect))(input)?;
+
+    if !keep_group || !options.keep_key(&key) {
+        return value(Line::Skipped, not_line_ending)(value_input);
+    }
+
+    let (rest, (key, value)) = parse_dialect_entry_with(dialect, options.unknown_escapes)(input)?;
+
+    // The text read by the value parsers, without the inline comment and the `\r`
+    let (_, text) = not_line_ending(value_input)?;
+    let text = match dialect.inline_comments {
+        true => strip_inline_comment(text, dialect.comment_chars),
+        false => text,
+    };
+    let text = text.strip_suffix('\r').unwrap_or(text);
+
+    let source = value_source(text, &value);
+
+    Ok((rest, Line::Entry { key, value, source }))
+}
+
+#[cfg(test)]
+mod test {
+    use indexmap::indexmap;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Creates a [`GroupMap`] or [`EntryMap`] with the map backend of the features.
+    macro_rules! map {
+        ($($key:expr => $value:expr),* $(,)?) => {
+            [$(($key, $value)),*].into_iter().collect()
+        };
+    }
+
+    fn example_file_groups() -> GroupMap<'static> {
+        map! {
+            Cow::from("Desktop Entry") => map! {
+                Key::Simple(Cow::from("Version")) => Value::Numeric(1.0),
+                Key::Simple(Cow::from("Type")) => Value::String(Cow::from("Application")),
+                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Foo Viewer")),
+                Key::Simple(Cow::from("Comment")) => Value::String(Cow::from("The best viewer for Foo objects available!")),
+                Key::Simple(Cow::from("TryExec")) => Value::String(Cow::from("fooview")),
+                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview %F")),
+                Key::Simple(Cow::from("Icon")) => Value::String(Cow::from("fooview")),
+                Key::Simple(Cow::from("MimeType")) => Value::String(Cow::from("image/x-foo;")),
+                Key::Simple(Cow::from("Actions")) => Value::String(Cow::from("Gallery;Create;")),
+            },
+            Cow::from("Desktop Action Gallery") => map! {
+                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview --gallery")),
+                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Browse Gallery")),
+            },
+            Cow::from("Desktop Action Create") => map! {
+                Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("fooview --create-new")),
+                Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Create a new Foo!")),
+                Key::Simple(Cow::from("Icon")) => Value::String(Cow::from("fooview-new")),
+            },
+        }
+    }
+
+    #[test]
+    fn should_parse_example_file_with_comments() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let options = ParseOptions {
+            keep_comments: true,
+            ..Default::default()
+        };
+        let (rest, desktop_entry) =
+            parse_desktop_entry_with_options(example_file, &options).unwrap();
+
+        assert_eq!("", rest);
+
+        let expected = DesktopEntry {
+            groups: example_file_groups(),
+            comments: Comments {
+                groups: indexmap! {
+                    Cow::from("Desktop Entry") => vec![
+                        Comment::Comment(Cow::from("# Example file from the spec")),
+                    ],
+                    Cow::from("Desktop Action Gallery") => vec![
+                        Comment::EmptyLine { white_space: None },
+                    ],
+                    Cow::from("Desktop Action Create") => vec![
+                        Comment::EmptyLine { white_space: None },
+                    ],
+                },
+                entries: IndexMap::new(),
+                tail: Vec::new(),
+            },
+            sources: Sources::default(),
+            line_ending: LineEnding::Lf,
+            byte_order_mark: false,
+            warnings: Vec::new(),
+        };
+
+        assert_eq!(expected, desktop_entry)
+    }
+
+    #[test]
+    fn should_reserve_capacity_from_input() {
+        let input = include_str!("../example/file.desktop");
+
+        assert_eq!(vec![10, 3, 3], estimate_capacity(input));
+
+        let desktop_entry = DesktopEntry::parse(input).unwrap();
+
+        assert!(desktop_entry.groups.capacity() >= 3);
+        assert!(desktop_entry
+            .groups
+            .values()
+            .zip(estimate_capacity(input))
+            .all(|(entries, estimate)| entries.capacity() >= estimate));
+
+        let options = ParseOptions {
+            reserve_capacity: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            desktop_entry.groups,
+            DesktopEntry::parse_with_options(input, &options)
+                .unwrap()
+                .groups
+        );
+    }
+
+    #[test]
+    fn should_parse_example_file_without_comments() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let desktop_entry = DesktopEntry::parse(example_file).unwrap();
+
+        let expected = DesktopEntry {
+            groups: example_file_groups(),
+            ..Default::default()
+        };
+
+        assert_eq!(expected, desktop_entry);
+        assert!(!desktop_entry.to_string().contains('#'));
+    }
+
+    #[test]
+    fn should_convert_action_group_names() {
+        assert_eq!("Desktop Action gallery", action_group_name("gallery"));
+        assert_eq!(
+            Some("gallery"),
+            parse_action_group_name("Desktop Action gallery")
+        );
+        assert_eq!(None, parse_action_group_name("Desktop Action "));
+        assert_eq!(None, parse_action_group_name(DESKTOP_ENTRY_GROUP));
+    }
+
+    #[test]
+    fn should_get_localized_value() {
+        let (_, desktop_entry) =
+            parse_desktop_entry("[Desktop Entry]\nName=Foo\nName[de]=Foo de\nName[de_DE]=Foo DE\n")
+                .unwrap();
+
+        let get = |locale| {
+            desktop_entry.get_localized("Desktop Entry", "Name", Locale::parse(locale).as_ref())
+        };
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("Foo DE"))),
+            get("de_de.UTF-8")
+        );
+        assert_eq!(Some(&Value::String(Cow::from("Foo de"))), get("de_AT"));
+        assert_eq!(Some(&Value::String(Cow::from("Foo"))), get("C"));
+        assert_eq!(Some(&Value::String(Cow::from("Foo"))), get("it_IT"));
+    }
+
+    #[test]
+    fn should_get_localized_values() {
+        let (_, desktop_entry) = parse_desktop_entry(
+            "[Desktop Entry]\nName[de]=Foo de\nName=Foo\nComment=Bar\nName[it]=Foo it\n",
+        )
+        .unwrap();
+
+        let values = desktop_entry
+            .localized_values("Desktop Entry", "Name")
+            .into_iter()
+            .map(|(locale, value)| (locale.cloned(), value.clone()))
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            (None, Value::String(Cow::from("Foo"))),
+            (Locale::parse("de"), Value::String(Cow::from("Foo de"))),
+            (Locale::parse("it"), Value::String(Cow::from("Foo it"))),
+        ];
+
+        assert_eq!(expected, values);
+    }
+
+    #[test]
+    fn should_list_locales() {
+        let (_, desktop_entry) = parse_desktop_entry(
+            "[Desktop Entry]\nName=Foo\nName[it]=Foo it\nName[de]=Foo de\nComment[de]=Bar de\n[Desktop Action new]\nName[de_DE@euro]=Neu\n",
+        )
+        .unwrap();
+
+        let locales = desktop_entry
+            .locales()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let expected = ["it", "de", "de_DE@euro"]
+            .into_iter()
+            .filter_map(Locale::parse)
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected, locales);
+    }
+
+    #[test]
+    fn should_strip_and_promote_locales() {
+        let input = "[Desktop Entry]\nName=Foo\nName[de]=Foo de\nName[de_AT]=Foo at\nName[it]=Foo it\nComment[it]=Bar it\nIcon=foo\n[Desktop Action new]\nName[fr]=Nouveau\nName=New\n";
+        let locales = ["de_DE", "it"]
+            .into_iter()
+            .filter_map(Locale::parse)
+            .collect::<Vec<_>>();
+
+        let (_, mut desktop_entry) = parse_desktop_entry(input).unwrap();
+
+        assert_eq!(2, desktop_entry.strip_locales(&locales));
+        assert_eq!(
+            "[Desktop Entry]\nName=Foo\nName[de]=Foo de\nName[it]=Foo it\nComment[it]=Bar it\nIcon=foo\n[Desktop Action new]\nName=New\n",
+            desktop_entry.to_string()
+        );
+
+        let (_, mut desktop_entry) = parse_desktop_entry(input).unwrap();
+
+        assert_eq!(5, desktop_entry.promote_locales(&locales));
+        assert_eq!(
+            "[Desktop Entry]\nName=Foo de\nIcon=foo\nComment=Bar it\n[Desktop Action new]\nName=New\n",
+            desktop_entry.to_string()
+        );
+    }
+
+    #[test]
+    fn should_set_and_remove_localizations() {
+        let (_, mut desktop_entry) = parse_desktop_entry(
+            "[Desktop Entry]\nName=Foo\nName[de]=Foo de\nComment=Bar\nComment[de]=Bar de\n",
+        )
+        .unwrap();
+
+        let previous = desktop_entry.set_localized(
+            "Desktop Entry",
+            "Name",
+            Locale::parse("de"),
+            Value::String(Cow::from("Neu")),
+        );
+        assert_eq!(Some(Value::String(Cow::from("Foo de"))), previous);
+
+        desktop_entry.set_localized(
+            "Desktop Entry",
+            "Name",
+            Locale::parse("it"),
+            Value::String(Cow::from("Foo it")),
+        );
+        assert_eq!(
+            3,
+            desktop_entry
+                .localized_values("Desktop Entry", "Name")
+                .len()
+        );
+
+        assert_eq!(
+            2,
+            desktop_entry.remove_localizations("Desktop Entry", "Name")
+        );
+        assert_eq!(
+            Some(&Value::String(Cow::from("Foo"))),
+            desktop_entry.get_localized("Desktop Entry", "Name", Locale::parse("de").as_ref())
+        );
+        assert_eq!(
+            Some(&Value::String(Cow::from("Bar de"))),
+            desktop_entry.get_localized("Desktop Entry", "Comment", Locale::parse("de").as_ref())
+        );
+
+        assert_eq!(1, desktop_entry.remove_all_localizations());
+        assert_eq!(2, desktop_entry.group("Desktop Entry").unwrap().len());
+    }
+
+    #[test]
+    fn should_replace_the_translation_with_the_same_normalized_locale() {
+        let (_, mut desktop_entry) =
+            parse_desktop_entry("[Desktop Entry]\nName=Foo\nName[de_DE.UTF-8]=Foo de\n").unwrap();
+
+        let previous = desktop_entry.set_localized(
+            "Desktop Entry",
+            "Name",
+            Locale::parse("de_DE"),
+            Value::String(Cow::from("Neu")),
+        );
+
+        assert_eq!(Some(Value::String(Cow::from("Foo de"))), previous);
+        assert_eq!(
+            "[Desktop Entry]\nName=Foo\nName[de_DE.UTF-8]=Neu\n",
+            desktop_entry.to_string()
+        );
+    }
+
+    #[test]
+    fn should_filter_locales() {
+        let options = ParseOptions {
+            locales: Some(vec![Locale::parse("de_DE").unwrap()]),
+            ..Default::default()
+        };
+
+        let (_, desktop_entry) = parse_desktop_entry_with_options(
+            "[Desktop Entry]\nName=Foo\nName[de]=Foo de\nName[it]=Foo it\nName[de_AT]=Foo AT\n",
+            &options,
+        )
+        .unwrap();
+
+        let expected: EntryMap = map! {
+            Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Foo")),
+            Key::Localized {
+                key: Cow::from("Name"),
+                locale: Locale::parse("de").unwrap(),
+            } => Value::String(Cow::from("Foo de")),
+        };
+
+        assert_eq!(Some(&expected), desktop_entry.group("Desktop Entry"));
+    }
+
+    #[test]
+    fn should_filter_keys_and_groups() {
+        let options = ParseOptions {
+            groups: Some(vec![Cow::from("Desktop Entry")]),
+            keys: Some(vec![Cow::from("Name"), Cow::from("Exec")]),
+            ..Default::default()
+        };
+
+        let (_, desktop_entry) = parse_desktop_entry_with_options(
+            "[Desktop Entry]\nName=Foo\nName[de]=Foo de\nComment=\\x\nExec=foo\n\n[Desktop Action Bar]\nExec=bar\n",
+            &options,
+        )
+        .unwrap();
+
+        let expected = DesktopEntry {
+            groups: map! {
+                Cow::from("Desktop Entry") => map! {
+                    Key::Simple(Cow::from("Name")) => Value::String(Cow::from("Foo")),
+                    Key::Localized {
+                        key: Cow::from("Name"),
+                        locale: Locale::parse("de").unwrap(),
+                    } => Value::String(Cow::from("Foo de")),
+                    Key::Simple(Cow::from("Exec")) => Value::String(Cow::from("foo")),
+                },
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(expected.groups, desktop_entry.groups);
+    }
+
+    #[test]
+    fn should_parse_crlf_line_endings() {
+        let example_file = include_str!("../example/file.desktop");
+        let (_, expected) = parse_desktop_entry(example_file).unwrap();
+
+        let crlf_file = example_file.replace('\n', "\r\n");
+        let (rest, mut desktop_entry) = parse_desktop_entry(&crlf_file).unwrap();
+
+        assert_eq!("", rest);
+        assert_eq!(LineEnding::CrLf, desktop_entry.line_ending());
+
+        desktop_entry.set_line_ending(LineEnding::Lf);
+        assert_eq!(expected, desktop_entry);
+    }
+
+    #[test]
+    fn should_strip_carriage_return_at_end_of_input() {
+        let (rest, desktop_entry) = parse_desktop_entry("[Desktop Entry]\r\nName=Foo\r").unwrap();
+
+        assert_eq!("", rest);
+        assert_eq!(
+            Some(&Value::String(Cow::from("Foo"))),
+            desktop_entry.get("Desktop Entry", "Name")
+        );
+    }
+
+    #[test]
+    fn should_skip_byte_order_mark() {
+        let example_file = include_str!("../example/file.desktop");
+        let (_, expected) = parse_desktop_entry(example_file).unwrap();
+
+        let bom_file = format!("\u{FEFF}{example_file}");
+        let (rest, desktop_entry) = parse_desktop_entry(&bom_file).unwrap();
+
+        assert_eq!("", rest);
+        assert!(desktop_entry.has_byte_order_mark());
+        assert_eq!(expected.groups, desktop_entry.groups);
+    }
+
+    #[test]
+    fn should_parse_from_bytes() {
+        let input = b"[Desktop Entry]\nName=Foo\nComment=Caf\xe9\n";
+
+        let err = DesktopEntry::from_bytes(input, Decoding::Strict).unwrap_err();
+
+        assert_eq!(ParseErrorKind::InvalidEncoding, err.kind());
+        assert_eq!(3, err.line());
+        assert_eq!(12, err.column());
+
+        let document = DesktopEntry::from_bytes(input, Decoding::Lossy).unwrap();
+
+        assert_eq!(
+            Some(&Value::LocaleString(Cow::from("Caf\u{FFFD}"))),
+            document.get(DESKTOP_ENTRY_GROUP, "Comment")
+        );
+        assert_eq!(
+            [ParseWarning::InvalidEncoding {
+                line: 3,
+                encoding: "UTF-8".to_string()
+            }],
+            document.warnings()
+        );
+    }
+
+    #[test]
+    fn should_read_from_path_and_reader() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("foo.desktop");
+
+        std::fs::write(&path, "\u{FEFF}[Desktop Entry]\nName=Foo\n").unwrap();
+
+        let document = DesktopEntry::from_path(&path).unwrap();
+        let converted = DesktopEntry::try_from(path.as_path()).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(document, converted);
+        assert!(document.has_byte_order_mark());
+        assert_eq!(
+            Some(&Value::String(Cow::from("Foo"))),
+            document.get(DESKTOP_ENTRY_GROUP, "Name")
+        );
+        assert!(matches!(
+            DesktopEntry::from_path(&path),
+            Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        let document = DesktopEntry::from_reader(&b"[Desktop Entry]\nName=Bar\n"[..]).unwrap();
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("Bar"))),
+            document.get(DESKTOP_ENTRY_GROUP, "Name")
+        );
+        assert!(matches!(
+            DesktopEntry::from_reader(&b"[Desktop Entry]\nName\n"[..]),
+            Err(Error::Parse(_))
+        ));
+
+        let options = ParseOptions {
+            limits: Limits {
+                max_file_size: Some(8),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The reader never ends, it's read only up to the limit
+        assert!(matches!(
+            DesktopEntry::from_reader_with_options(std::io::repeat(b'#'), &options),
+            Err(Error::Parse(err)) if err.kind() == ParseErrorKind::LimitExceeded
+        ));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn should_detect_encoding_from_bytes() {
+        let latin1 = b"[Desktop Entry]\nName=Caf\xe9\n";
+        let utf16 = "\u{FEFF}[Desktop Entry]\nName=Café\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        for input in [&latin1[..], &utf16] {
+            let document =
+                DesktopEntry::from_bytes(input, Decoding::Detect(encoding_rs::WINDOWS_1252))
+                    .unwrap();
+
+            assert_eq!(
+                Some(&Value::LocaleString(Cow::from("Café"))),
+                document.get(DESKTOP_ENTRY_GROUP, "Name")
+            );
+            assert!(document.warnings().is_empty());
+        }
+    }
+
+    #[test]
+    fn should_fail_on_entry_without_group() {
+        assert!(parse_desktop_entry("Name=Foo\n[Desktop Entry]\n").is_err());
+    }
+
+    #[test]
+    fn should_enforce_limits() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let parse = |limits| {
+            let options = ParseOptions {
+                limits,
+                ..Default::default()
+            };
+
+            match parse_desktop_entry_with_options(example_file, &options) {
+                Ok(_) => None,
+                Err(nom::Err::Failure(err)) => Some(err.code),
+                Err(err) => panic!("unexpected error {err}"),
+            }
+        };
+
+        assert_eq!(None, parse(Limits::default()));
+        assert_eq!(
+            Some(nom::error::ErrorKind::TooLarge),
+            parse(Limits {
+                max_file_size: Some(100),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Some(nom::error::ErrorKind::TooLarge),
+            parse(Limits {
+                max_line_length: Some(40),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Some(nom::error::ErrorKind::TooLarge),
+            parse(Limits {
+                max_groups: Some(2),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Some(nom::error::ErrorKind::TooLarge),
+            parse(Limits {
+                max_entries: Some(13),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            None,
+            parse(Limits {
+                max_file_size: Some(example_file.len()),
+                max_line_length: Some(50),
+                max_groups: Some(3),
+                max_entries: Some(14),
+            })
+        );
+    }
+
+    #[test]
+    fn should_return_verbose_error() {
+        let input = "[Desktop Entry]\nName=Foo\\x\n";
+
+        let Err(nom::Err::Failure(err)) =
+            parse_desktop_entry_verbose(input, &ParseOptions::default())
+        else {
+            panic!("expected a failure");
+        };
+
+        let contexts: Vec<_> = err
+            .errors
+            .iter()
+            .filter_map(|(_, kind)| match kind {
+                nom::error::VerboseErrorKind::Context(context) => Some(*context),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(vec!["unknown escape sequence", "value", "entry"], contexts);
+        assert!(nom::error::convert_error(input, err).contains("\\x"));
+    }
+
+    #[test]
+    fn should_preserve_unknown_escapes() {
+        let input = "[Desktop Entry]\nX-Path=C:\\Windows\\x\\s\\\nName=Foo\n";
+
+        assert!(parse_desktop_entry(input).is_err());
+
+        let options = ParseOptions {
+            unknown_escapes: UnknownEscapes::Preserve,
+            ..Default::default()
+        };
+        let (_, desktop_entry) = parse_desktop_entry_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("C:\\Windows\\x \\"))),
+            desktop_entry.get("Desktop Entry", "X-Path")
+        );
+        assert_eq!(
+            [
+                ParseWarning::UnknownEscape {
+                    line: 2,
+                    sequence: String::from("\\W"),
+                },
+                ParseWarning::UnknownEscape {
+                    line: 2,
+                    sequence: String::from("\\x"),
+                },
+                ParseWarning::UnknownEscape {
+                    line: 2,
+                    sequence: String::from("\\"),
+                },
+            ]
+            .as_slice(),
+            desktop_entry.warnings()
+        );
+    }
+
+    #[test]
+    fn should_parse_relaxed_ini() {
+        let input = "; settings\n[Général]\nname: Foo ; inline\n  path = /tmp/a#b # comment\n";
+
+        assert!(parse_desktop_entry(input).is_err());
+
+        let options = ParseOptions {
+            dialect: Dialect::RELAXED_INI,
+            ..Default::default()
+        };
+        let (_, desktop_entry) = parse_desktop_entry_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("Foo"))),
+            desktop_entry.get("Général", "name")
+        );
+        assert_eq!(
+            Some(&Value::String(Cow::from("/tmp/a#b"))),
+            desktop_entry.get("Général", "path")
+        );
+    }
+
+    #[test]
+    fn should_apply_duplicate_keys_policy() {
+        let input = "[Desktop Entry]\nName=Foo\nName=Bar\n[Desktop Action new]\nName=Baz\n";
+
+        let parse = |duplicate_keys| {
+            let options = ParseOptions {
+                dialect: Dialect {
+                    duplicate_keys,
+                    ..Dialect::DESKTOP_ENTRY
+                },
+                ..Default::default()
+            };
+
+            DesktopEntry::parse_with_options(input, &options).map(|document| {
+                document
+                    .get(DESKTOP_ENTRY_GROUP, "Name")
+                    .map(Value::to_string)
+            })
+        };
+
+        assert_eq!(Ok(Some("Bar".to_string())), parse(DuplicateKeys::KeepLast));
+        assert_eq!(Ok(Some("Foo".to_string())), parse(DuplicateKeys::KeepFirst));
+
+        let err = parse(DuplicateKeys::Reject).unwrap_err();
+
+        assert_eq!(ParseErrorKind::DuplicateKey, err.kind());
+        assert_eq!(3, err.line());
+    }
+
+    #[test]
+    fn should_parse_custom_dialect() {
+        let options = ParseOptions {
+            dialect: Dialect {
+                separators: ":",
+                comment_chars: "!",
+                escapes: &[('n', "\n")],
+                ..Dialect::GKEY_FILE
+            },
+            ..Default::default()
+        };
+        let (_, desktop_entry) =
+            parse_desktop_entry_with_options("! comment\n[main]\nkey: a\\nb\n", &options).unwrap();
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("a\nb"))),
+            desktop_entry.get("main", "key")
+        );
+        assert!(parse_desktop_entry_with_options("[main]\nkey: a\\sb\n", &options).is_err());
     }
 }
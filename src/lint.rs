@@ -0,0 +1,891 @@
+//! Lints for desktop files, checking style and recommendations beyond the hard validation.
+//!
+//! Each [`Rule`] has a stable code, like `DE001`, and a name, like `deprecated-key`. The level of
+//! the rules can be changed with the [`LintOptions`] to enforce project specific policies, and
+//! custom rules can be added to the built-in ones.
+
+use std::{borrow::Cow, collections::HashMap, fmt::Display, path::Path};
+
+use crate::{
+    exec::{self, Exec},
+    get_simple,
+    keys::{self, ValueType},
+    naming,
+    raw::{not_line_ending, parse_line_ending},
+    token::{tokenize, Token, TokenKind},
+    validate::Severity,
+    DesktopEntry, Value, DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+/// Keys deprecated by the specification.
+pub const DEPRECATED_KEYS: &[&str] = &[
+    "Encoding",
+    "MiniIcon",
+    "TerminalOptions",
+    "Protocols",
+    "Extensions",
+    "BinaryPattern",
+    "MapNotify",
+    "SwallowTitle",
+    "SwallowExec",
+    "SortOrder",
+    "FilePattern",
+];
+
+/// Keys added by a version of the specification after 1.0, with the version.
+pub const KEY_VERSIONS: &[(&str, &str)] = &[
+    ("DBusActivatable", "1.1"),
+    ("Implements", "1.1"),
+    ("Keywords", "1.1"),
+    ("PrefersNonDefaultGPU", "1.4"),
+    ("SingleMainWindow", "1.5"),
+];
+
+/// Level of a lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// The rule is disabled.
+    Allow,
+    /// Violations are reported as warnings.
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+impl LintLevel {
+    /// Returns the severity of the violations, or [`None`] if the rule is disabled.
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+/// Lint rule checking a parsed desktop file.
+#[derive(Debug, Clone, Copy)]
+pub struct Rule {
+    /// Stable code of the rule, like `DE001`.
+    pub code: &'static str,
+    /// Name of the rule, like `deprecated-key`.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Level of the rule if not configured in the [`LintOptions`].
+    pub default_level: LintLevel,
+    /// Checks the document, reporting the violations.
+    ///
+    /// It's [`None`] for the rules that can only be detected on the source, by their fix.
+    pub check: Option<fn(&DesktopEntry, &mut Reporter)>,
+    /// Fixes a line of the source, for the rules that can be fixed mechanically.
+    pub fix: Option<fn(&SourceLine) -> Option<Edit>>,
+}
+
+/// Violation of a lint rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lint {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub severity: Severity,
+    /// Group the violation is in, if any.
+    pub group: Option<String>,
+    /// Key the violation is about, if any.
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: ", self.severity, self.code)?;
+
+        if let Some(group) = &self.group {
+            write!(f, "[{group}] ")?;
+        }
+
+        if let Some(key) = &self.key {
+            write!(f, "{key}: ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Collects the violations of a rule.
+#[derive(Debug)]
+pub struct Reporter<'r> {
+    rule: &'r Rule,
+    severity: Severity,
+    file_id: Option<&'r str>,
+    lints: &'r mut Vec<Lint>,
+}
+
+impl Reporter<'_> {
+    /// Returns the desktop file ID of the checked document, if known.
+    pub fn file_id(&self) -> Option<&str> {
+        self.file_id
+    }
+
+    /// Reports a violation of the rule.
+    pub fn report(&mut self, group: Option<&str>, key: Option<&str>, message: impl Into<String>) {
+        self.lints.push(Lint {
+            code: self.rule.code,
+            name: self.rule.name,
+            severity: self.severity,
+            group: group.map(str::to_string),
+            key: key.map(str::to_string),
+            message: message.into(),
+        });
+    }
+}
+
+/// Options to configure the lint rules.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Levels of the rules, by code or name, overriding the default ones.
+    pub levels: HashMap<String, LintLevel>,
+    /// Custom rules checked together with the built-in [`RULES`].
+    pub rules: Vec<Rule>,
+}
+
+impl LintOptions {
+    /// Returns the configured level of the rule.
+    pub fn level(&self, rule: &Rule) -> LintLevel {
+        self.levels
+            .get(rule.code)
+            .or_else(|| self.levels.get(rule.name))
+            .copied()
+            .unwrap_or(rule.default_level)
+    }
+}
+
+/// Built-in lint rules.
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: "DE001",
+        name: "deprecated-key",
+        description: "Key deprecated by the specification.",
+        default_level: LintLevel::Warn,
+        check: Some(deprecated_key),
+        fix: Some(fix_deprecated_key),
+    },
+    Rule {
+        code: "DE002",
+        name: "unknown-key",
+        description: "Key not defined by the specification and without the `X-` prefix.",
+        default_level: LintLevel::Warn,
+        check: Some(unknown_key),
+        fix: None,
+    },
+    Rule {
+        code: "DE003",
+        name: "unknown-group",
+        description: "Group that is not an action group and doesn't have the `X-` prefix.",
+        default_level: LintLevel::Warn,
+        check: Some(unknown_group),
+        fix: None,
+    },
+    Rule {
+        code: "DE004",
+        name: "invalid-boolean",
+        description: "Value of a boolean key that is not `true` or `false`.",
+        default_level: LintLevel::Deny,
+        check: Some(invalid_boolean),
+        fix: None,
+    },
+    Rule {
+        code: "DE005",
+        name: "list-trailing-semicolon",
+        description: "List value not terminated by a `;`.",
+        default_level: LintLevel::Allow,
+        check: Some(list_trailing_semicolon),
+        fix: Some(fix_list_trailing_semicolon),
+    },
+    Rule {
+        code: "DE006",
+        name: "trailing-whitespace",
+        description: "Whitespace at the end of a line.",
+        default_level: LintLevel::Warn,
+        check: None,
+        fix: Some(fix_trailing_whitespace),
+    },
+    Rule {
+        code: "DE007",
+        name: "file-name",
+        description: "Desktop file ID not in the reverse-DNS form, like `org.example.App.desktop`.",
+        default_level: LintLevel::Warn,
+        check: Some(file_name),
+        fix: None,
+    },
+    Rule {
+        code: "DE008",
+        name: "exec-shell-field-code",
+        description: "Field code interpolated in the script of a shell invoked with `-c`.",
+        default_level: LintLevel::Allow,
+        check: Some(exec_shell_field_code),
+        fix: None,
+    },
+    Rule {
+        code: "DE009",
+        name: "exec-relative-program",
+        description: "Program of Exec not given as an absolute path.",
+        default_level: LintLevel::Allow,
+        check: Some(exec_relative_program),
+        fix: None,
+    },
+    Rule {
+        code: "DE010",
+        name: "exec-unquoted-reserved",
+        description: "Reserved character in Exec outside of a quoted argument.",
+        default_level: LintLevel::Allow,
+        check: Some(exec_unquoted_reserved),
+        fix: None,
+    },
+    Rule {
+        code: "DE011",
+        name: "key-newer-than-version",
+        description: "Key added by a version of the specification newer than the `Version` key.",
+        default_level: LintLevel::Warn,
+        check: Some(key_newer_than_version),
+        fix: None,
+    },
+];
+
+/// Returns the rule with the given code or name.
+pub fn find_rule(code_or_name: &str) -> Option<&'static Rule> {
+    RULES
+        .iter()
+        .find(|rule| rule.code == code_or_name || rule.name == code_or_name)
+}
+
+/// Checks the document with the enabled rules, returning the violations.
+///
+/// The rules about the name of the file are not checked, see [`lint_with_file_id`].
+pub fn lint(document: &DesktopEntry, options: &LintOptions) -> Vec<Lint> {
+    lint_with_file_id(document, None, options)
+}
+
+/// Checks the document with the enabled rules, with the desktop file ID of the document, like
+/// `org.example.App.desktop`.
+pub fn lint_with_file_id(
+    document: &DesktopEntry,
+    file_id: Option<&str>,
+    options: &LintOptions,
+) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    for rule in RULES.iter().chain(&options.rules) {
+        let (Some(severity), Some(check)) = (options.level(rule).severity(), rule.check) else {
+            continue;
+        };
+
+        let mut reporter = Reporter {
+            rule,
+            severity,
+            file_id,
+            lints: &mut lints,
+        };
+
+        check(document, &mut reporter);
+    }
+
+    lints
+}
+
+/// Line of the source passed to the fixes of the rules.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceLine<'a> {
+    /// Group the line is in, if any.
+    pub group: Option<&'a str>,
+    /// Text of the line, without the line ending.
+    pub text: &'a str,
+    /// Syntax tokens of the line.
+    pub tokens: &'a [Token],
+}
+
+impl SourceLine<'_> {
+    /// Returns the text of the first token of the given kind.
+    pub fn token(&self, kind: TokenKind) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|token| token.kind == kind)
+            .map(|token| token.text(self.text))
+    }
+
+    /// Returns the text of the value of an entry line, from the end of the separator.
+    pub fn value(&self) -> Option<&str> {
+        let separator = self
+            .tokens
+            .iter()
+            .find(|token| token.kind == TokenKind::Separator)?;
+
+        Some(&self.text[separator.span.end..])
+    }
+}
+
+/// Change to a line of the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Edit {
+    /// Replaces the text of the line, keeping the line ending.
+    Replace(String),
+    /// Removes the line with its line ending.
+    Remove,
+}
+
+/// Fix applied to the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fix {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Line of the fix in the input, starting from 1.
+    pub line: usize,
+    pub edit: Edit,
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.edit {
+            Edit::Replace(_) => "fixed",
+            Edit::Remove => "removed",
+        };
+
+        write!(
+            f,
+            "line {}: {action} [{}] {}",
+            self.line, self.code, self.name
+        )
+    }
+}
+
+/// Source with the fixes applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixed {
+    pub output: String,
+    /// Fixes applied, in the order of the lines.
+    pub fixes: Vec<Fix>,
+}
+
+/// Applies the fixes of the enabled rules to the source.
+///
+/// Only the fixed lines are changed, the rest of the input, including the line endings, is kept
+/// as it is. The fixes of a line are applied in the order of the rules, each one seeing the
+/// result of the previous.
+pub fn fix(input: &str, options: &LintOptions) -> Fixed {
+    let rules = RULES
+        .iter()
+        .chain(&options.rules)
+        .filter(|rule| options.level(rule) != LintLevel::Allow)
+        .filter_map(|rule| rule.fix.map(|fix| (rule, fix)))
+        .collect::<Vec<_>>();
+
+    let mut fixes = Vec::new();
+
+    let output = rewrite_lines(input, |number, group, line| {
+        let mut text = Cow::Borrowed(line);
+
+        for (rule, fix) in &rules {
+            let tokens = tokenize(&text);
+
+            let source_line = SourceLine {
+                group,
+                text: &text,
+                tokens: &tokens,
+            };
+
+            let Some(edit) = fix(&source_line) else {
+                continue;
+            };
+
+            fixes.push(Fix {
+                code: rule.code,
+                name: rule.name,
+                line: number,
+                edit: edit.clone(),
+            });
+
+            match edit {
+                Edit::Replace(replaced) => text = Cow::Owned(replaced),
+                Edit::Remove => return Some(Edit::Remove),
+            }
+        }
+
+        match text {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(text) => Some(Edit::Replace(text)),
+        }
+    });
+
+    Fixed { output, fixes }
+}
+
+/// Rewrites the lines of the source, keeping the line endings.
+///
+/// The closure receives the number of the line starting from 1, the group the line is in and its
+/// text without the line ending. The lines it doesn't edit are kept as they are.
+pub(crate) fn rewrite_lines(
+    input: &str,
+    mut rewrite: impl FnMut(usize, Option<&str>, &str) -> Option<Edit>,
+) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut group = None;
+    let mut rest = input;
+    let mut number = 0;
+
+    while !rest.is_empty() {
+        let Ok((after, line)) = not_line_ending::<()>(rest) else {
+            break;
+        };
+
+        let (after, line_ending) = parse_line_ending::<()>(after).unwrap_or(("", after));
+
+        rest = after;
+        number += 1;
+
+        if let Some(name) = tokenize(line)
+            .iter()
+            .find(|token| token.kind == TokenKind::GroupName)
+        {
+            group = Some(name.text(line));
+        }
+
+        match rewrite(number, group, line) {
+            Some(Edit::Replace(text)) => {
+                output.push_str(&text);
+                output.push_str(line_ending);
+            }
+            Some(Edit::Remove) => {}
+            None => {
+                output.push_str(line);
+                output.push_str(line_ending);
+            }
+        }
+    }
+
+    output
+}
+
+/// Returns the standard keys of the group.
+fn group_keys(group: &str) -> Option<fn(&str) -> Option<&'static keys::KeyInfo>> {
+    if group == DESKTOP_ENTRY_GROUP {
+        Some(keys::lookup)
+    } else if group.starts_with(DESKTOP_ACTION_PREFIX) {
+        Some(keys::lookup_action)
+    } else {
+        None
+    }
+}
+
+fn deprecated_key(document: &DesktopEntry, reporter: &mut Reporter) {
+    let Some(entries) = document.group(DESKTOP_ENTRY_GROUP) else {
+        return;
+    };
+
+    for key in entries.keys() {
+        if DEPRECATED_KEYS.contains(&key.name()) {
+            reporter.report(
+                Some(DESKTOP_ENTRY_GROUP),
+                Some(key.name()),
+                "deprecated key",
+            );
+        }
+    }
+
+    if let Some(Value::String(entry_type)) = document.get(DESKTOP_ENTRY_GROUP, "Type") {
+        if entry_type == "MimeType" {
+            reporter.report(
+                Some(DESKTOP_ENTRY_GROUP),
+                Some("Type"),
+                "deprecated type `MimeType`",
+            );
+        }
+    }
+}
+
+fn unknown_key(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, entries) in document.groups() {
+        let Some(lookup) = group_keys(group) else {
+            continue;
+        };
+
+        for key in entries.keys() {
+            let name = key.name();
+
+            if lookup(name).is_none() && !name.starts_with("X-") && !DEPRECATED_KEYS.contains(&name)
+            {
+                reporter.report(Some(group), Some(name), "unknown key");
+            }
+        }
+    }
+}
+
+fn unknown_group(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, _) in document.groups() {
+        if group_keys(group).is_none() && !group.starts_with("X-") {
+            reporter.report(Some(group), None, "unknown group");
+        }
+    }
+}
+
+fn invalid_boolean(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, entries) in document.groups() {
+        let Some(lookup) = group_keys(group) else {
+            continue;
+        };
+
+        for (key, value) in entries {
+            let is_boolean =
+                lookup(key.name()).is_some_and(|info| info.value_type == ValueType::Boolean);
+
+            if is_boolean && !matches!(value, Value::Boolean(_)) {
+                reporter.report(
+                    Some(group),
+                    Some(key.name()),
+                    format!("expected `true` or `false`, found `{value}`"),
+                );
+            }
+        }
+    }
+}
+
+fn list_trailing_semicolon(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, entries) in document.groups() {
+        let Some(lookup) = group_keys(group) else {
+            continue;
+        };
+
+        for (key, value) in entries {
+            let is_list = lookup(key.name()).is_some_and(|info| info.list);
+
+            match value {
+                Value::String(list) | Value::LocaleString(list)
+                    if is_list && !list.is_empty() && !list.ends_with(';') =>
+                {
+                    reporter.report(Some(group), Some(key.name()), "list not terminated by `;`");
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn file_name(_document: &DesktopEntry, reporter: &mut Reporter) {
+    let Some(id) = reporter.file_id() else {
+        return;
+    };
+
+    let issues = naming::check_id(id)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if !issues.is_empty() {
+        let message = format!("invalid file name `{id}`: {}", issues.join(", "));
+
+        reporter.report(None, None, message);
+    }
+}
+
+/// Returns the `Exec` values of the main group and of the actions, with their group.
+fn exec_values<'d>(document: &'d DesktopEntry) -> impl Iterator<Item = (&'d str, &'d str)> {
+    document.groups().filter_map(|(group, entries)| {
+        if group != DESKTOP_ENTRY_GROUP && !group.starts_with(DESKTOP_ACTION_PREFIX) {
+            return None;
+        }
+
+        match get_simple(entries, "Exec")? {
+            Value::String(command) | Value::LocaleString(command) => {
+                Some((group, command.as_ref()))
+            }
+            _ => None,
+        }
+    })
+}
+
+fn exec_shell_field_code(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, command) in exec_values(document) {
+        let Ok(exec) = Exec::parse(command) else {
+            continue;
+        };
+
+        let Some(code) = exec
+            .shell_script()
+            .and_then(|script| script.field_codes().next())
+        else {
+            continue;
+        };
+
+        reporter.report(
+            Some(group),
+            Some("Exec"),
+            format!("field code {code} in the shell script is interpreted by the shell, pass it as a positional parameter like `sh -c '... \"$1\"' sh {code}`"),
+        );
+    }
+}
+
+fn exec_relative_program(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, command) in exec_values(document) {
+        let Ok(exec) = Exec::parse(command) else {
+            continue;
+        };
+
+        let program = exec.program();
+
+        if !Path::new(program).is_absolute() {
+            reporter.report(
+                Some(group),
+                Some("Exec"),
+                format!("program `{program}` is not an absolute path"),
+            );
+        }
+    }
+}
+
+fn exec_unquoted_reserved(document: &DesktopEntry, reporter: &mut Reporter) {
+    for (group, command) in exec_values(document) {
+        for issue in exec::check_quoting(command) {
+            reporter.report(Some(group), Some("Exec"), issue.kind.to_string());
+        }
+    }
+}
+
+fn key_newer_than_version(document: &DesktopEntry, reporter: &mut Reporter) {
+    // The version can be typed as a number, like `1.5`
+    let version = match document.get(DESKTOP_ENTRY_GROUP, "Version") {
+        Some(value @ (Value::String(_) | Value::Numeric(_))) => value.to_string(),
+        _ => return,
+    };
+
+    let Some(declared) = parse_version(&version) else {
+        return;
+    };
+
+    for (key, added) in KEY_VERSIONS {
+        if document.get(DESKTOP_ENTRY_GROUP, key).is_none() {
+            continue;
+        }
+
+        if parse_version(added).is_some_and(|added| added > declared) {
+            reporter.report(
+                Some(DESKTOP_ENTRY_GROUP),
+                Some(key),
+                format!("key added in version {added}, but the file declares Version={version}"),
+            );
+        }
+    }
+}
+
+/// Parses a version of the specification, like `1.5`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+fn fix_deprecated_key(line: &SourceLine) -> Option<Edit> {
+    // The encoding is always UTF-8, any other value can't be fixed mechanically
+    let is_encoding = line.group == Some(DESKTOP_ENTRY_GROUP)
+        && line.token(TokenKind::Key) == Some("Encoding")
+        && line.value().map(str::trim_end) == Some("UTF-8");
+
+    is_encoding.then_some(Edit::Remove)
+}
+
+fn fix_list_trailing_semicolon(line: &SourceLine) -> Option<Edit> {
+    let lookup = group_keys(line.group?)?;
+    let info = lookup(line.token(TokenKind::Key)?)?;
+
+    if !info.list || line.value()?.is_empty() {
+        return None;
+    }
+
+    // An escaped `\;` doesn't terminate the list
+    let last = line.tokens.last()?;
+    if last.kind == TokenKind::Value && last.text(line.text).ends_with(';') {
+        return None;
+    }
+
+    Some(Edit::Replace(format!("{};", line.text)))
+}
+
+fn fix_trailing_whitespace(line: &SourceLine) -> Option<Edit> {
+    let trimmed = line.text.trim_end_matches([' ', '\t']);
+
+    (trimmed.len() < line.text.len()).then(|| Edit::Replace(trimmed.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_lint_example_file() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let document = DesktopEntry::parse(example_file).unwrap();
+
+        assert_eq!(Vec::<Lint>::new(), lint(&document, &LintOptions::default()));
+    }
+
+    #[test]
+    fn should_report_with_configured_levels() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nEncoding=UTF-8\nTerminal=yes\nCategories=Utility\nX-Foo=bar\n[Foo]\nBar=baz\n",
+        )
+        .unwrap();
+
+        let options = LintOptions {
+            levels: HashMap::from([
+                ("DE001".to_string(), LintLevel::Deny),
+                ("unknown-group".to_string(), LintLevel::Allow),
+                ("list-trailing-semicolon".to_string(), LintLevel::Warn),
+            ]),
+            ..Default::default()
+        };
+
+        let lints = lint(&document, &options)
+            .iter()
+            .map(Lint::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE001]: [Desktop Entry] Encoding: deprecated key",
+                "error[DE004]: [Desktop Entry] Terminal: expected `true` or `false`, found `yes`",
+                "warning[DE005]: [Desktop Entry] Categories: list not terminated by `;`",
+            ],
+            lints
+        );
+    }
+
+    #[test]
+    fn should_report_keys_newer_than_version() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nVersion=1.4\nType=Application\nName=Foo\nExec=foo\nPrefersNonDefaultGPU=true\nSingleMainWindow=true\n",
+        )
+        .unwrap();
+
+        let lints = lint(&document, &LintOptions::default())
+            .iter()
+            .map(Lint::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["warning[DE011]: [Desktop Entry] SingleMainWindow: key added in version 1.5, but the file declares Version=1.4"],
+            lints
+        );
+        assert!(document.single_main_window());
+        assert!(document.prefers_non_default_gpu());
+        assert!(!document.dbus_activatable());
+    }
+
+    #[test]
+    fn should_report_file_name() {
+        let document = DesktopEntry::parse("[Desktop Entry]\nType=Application\n").unwrap();
+        let options = LintOptions::default();
+
+        let lints = lint_with_file_id(&document, Some("foo-bar.desktop"), &options)
+            .iter()
+            .filter(|lint| lint.code == "DE007")
+            .map(Lint::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["warning[DE007]: invalid file name `foo-bar.desktop`: not in reverse-DNS form, like `org.example.App`"],
+            lints
+        );
+        assert!(
+            lint_with_file_id(&document, Some("org.example.Foo.desktop"), &options)
+                .iter()
+                .all(|lint| lint.code != "DE007")
+        );
+    }
+
+    #[test]
+    fn should_report_exec_security_issues() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=sh -c \"foo %f\"\n[Desktop Action New]\nName=New\nExec=/usr/bin/foo --new a|b\n",
+        )
+        .unwrap();
+
+        let options = LintOptions {
+            levels: HashMap::from([
+                ("exec-shell-field-code".to_string(), LintLevel::Deny),
+                ("exec-relative-program".to_string(), LintLevel::Warn),
+                ("exec-unquoted-reserved".to_string(), LintLevel::Warn),
+            ]),
+            ..Default::default()
+        };
+
+        let lints = lint(&document, &options)
+            .iter()
+            .filter(|lint| ["DE008", "DE009", "DE010"].contains(&lint.code))
+            .map(Lint::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE008]: [Desktop Entry] Exec: field code %f in the shell script is interpreted by the shell, pass it as a positional parameter like `sh -c '... \"$1\"' sh %f`",
+                "warning[DE009]: [Desktop Entry] Exec: program `sh` is not an absolute path",
+                "warning[DE010]: [Desktop Action New] Exec: reserved character `|` must be quoted",
+            ],
+            lints
+        );
+    }
+
+    #[test]
+    fn should_run_custom_rules() {
+        fn require_icon(document: &DesktopEntry, reporter: &mut Reporter) {
+            if document.get(DESKTOP_ENTRY_GROUP, "Icon").is_none() {
+                reporter.report(Some(DESKTOP_ENTRY_GROUP), Some("Icon"), "missing icon");
+            }
+        }
+
+        let document = DesktopEntry::parse("[Desktop Entry]\nType=Application\n").unwrap();
+
+        let options = LintOptions {
+            rules: vec![Rule {
+                code: "CUSTOM001",
+                name: "require-icon",
+                description: "The entry must have an icon.",
+                default_level: LintLevel::Deny,
+                check: Some(require_icon),
+                fix: None,
+            }],
+            ..Default::default()
+        };
+
+        let lints = lint(&document, &options);
+
+        assert_eq!(1, lints.len());
+        assert_eq!("require-icon", lints[0].name);
+        assert_eq!(Severity::Error, lints[0].severity);
+    }
+
+    #[test]
+    fn should_fix_source() {
+        let input = "# Comment \r\n[Desktop Entry]\r\nEncoding=UTF-8\r\nType=Application\t\r\nCategories=Utility;Development \r\nKeywords=a\\;\r\nName=Foo";
+
+        let options = LintOptions {
+            levels: HashMap::from([("list-trailing-semicolon".to_string(), LintLevel::Warn)]),
+            ..Default::default()
+        };
+
+        let fixed = fix(input, &options);
+
+        assert_eq!(
+            "# Comment\r\n[Desktop Entry]\r\nType=Application\r\nCategories=Utility;Development ;\r\nKeywords=a\\;;\r\nName=Foo",
+            fixed.output
+        );
+        assert_eq!(
+            vec![
+                "line 1: fixed [DE006] trailing-whitespace",
+                "line 3: removed [DE001] deprecated-key",
+                "line 4: fixed [DE006] trailing-whitespace",
+                "line 5: fixed [DE005] list-trailing-semicolon",
+                "line 6: fixed [DE005] list-trailing-semicolon",
+            ],
+            fixed.fixes.iter().map(Fix::to_string).collect::<Vec<_>>()
+        );
+    }
+}
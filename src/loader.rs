@@ -0,0 +1,870 @@
+//! Lookup and loading of the desktop files installed in the XDG data directories.
+
+use std::{
+    collections::HashSet,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use crate::{DesktopEntry, Error, ParseOptions};
+
+/// Extension of the desktop files of the applications.
+pub const DESKTOP_EXTENSION: &str = "desktop";
+
+/// Override of the base directories, see [`set_base_dirs`].
+static BASE_DIRS: RwLock<Option<BaseDirs>> = RwLock::new(None);
+
+/// XDG base directories, used to resolve the search paths of the loaders.
+///
+/// The directories are read from the environment, or with the `xdg` feature from the
+/// [`xdg`](https://docs.rs/xdg) crate. They can be overridden for the whole process with
+/// [`set_base_dirs`], for example in tests or sandboxed environments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BaseDirs {
+    /// User data directory `$XDG_DATA_HOME`.
+    pub data_home: Option<PathBuf>,
+    /// System data directories `$XDG_DATA_DIRS`, in order of preference.
+    pub data_dirs: Vec<PathBuf>,
+    /// User configuration directory `$XDG_CONFIG_HOME`.
+    pub config_home: Option<PathBuf>,
+    /// System configuration directories `$XDG_CONFIG_DIRS`, in order of preference.
+    pub config_dirs: Vec<PathBuf>,
+}
+
+impl BaseDirs {
+    /// Reads the base directories from the environment.
+    ///
+    /// The unset or empty variables get the defaults of the specification: `~/.local/share`,
+    /// `/usr/local/share:/usr/share`, `~/.config` and `/etc/xdg`. The relative paths are
+    /// ignored.
+    #[cfg(not(feature = "xdg"))]
+    pub fn from_env() -> Self {
+        let home = || env::var_os("HOME").map(PathBuf::from);
+
+        let user_dir = |name: &str, default: &str| {
+            env::var_os(name)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| home().map(|home| home.join(default)))
+        };
+
+        let system_dirs = |name: &str, default: &str| {
+            let dirs = env::var_os(name)
+                .filter(|dirs| !dirs.is_empty())
+                .unwrap_or_else(|| default.into());
+
+            env::split_paths(&dirs)
+                .filter(|dir| dir.is_absolute())
+                .collect()
+        };
+
+        Self {
+            data_home: user_dir("XDG_DATA_HOME", ".local/share"),
+            data_dirs: system_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share"),
+            config_home: user_dir("XDG_CONFIG_HOME", ".config"),
+            config_dirs: system_dirs("XDG_CONFIG_DIRS", "/etc/xdg"),
+        }
+    }
+
+    /// Reads the base directories with the [`xdg`](https://docs.rs/xdg) crate.
+    ///
+    /// Without a home directory only the system directories are set.
+    #[cfg(feature = "xdg")]
+    pub fn from_env() -> Self {
+        match xdg::BaseDirectories::new() {
+            Ok(dirs) => Self {
+                data_home: Some(dirs.get_data_home()),
+                data_dirs: dirs.get_data_dirs(),
+                config_home: Some(dirs.get_config_home()),
+                config_dirs: dirs.get_config_dirs(),
+            },
+            Err(_) => Self {
+                data_home: None,
+                data_dirs: vec!["/usr/local/share".into(), "/usr/share".into()],
+                config_home: None,
+                config_dirs: vec!["/etc/xdg".into()],
+            },
+        }
+    }
+
+    /// Returns the user data directory followed by the system ones.
+    pub fn all_data_dirs(&self) -> Vec<PathBuf> {
+        self.data_home
+            .iter()
+            .chain(&self.data_dirs)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the user configuration directory followed by the system ones.
+    pub fn all_config_dirs(&self) -> Vec<PathBuf> {
+        self.config_home
+            .iter()
+            .chain(&self.config_dirs)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the `applications` sub-directories of the data directories.
+    pub fn application_dirs(&self) -> Vec<PathBuf> {
+        join_all(self.all_data_dirs(), "applications")
+    }
+
+    /// Returns the `autostart` sub-directories of the configuration directories.
+    pub fn autostart_dirs(&self) -> Vec<PathBuf> {
+        join_all(self.all_config_dirs(), "autostart")
+    }
+
+    /// Returns the directories of the `mimeapps.list` files.
+    ///
+    /// The configuration directories, followed by the deprecated location in the `applications`
+    /// sub-directories of the data directories.
+    pub fn mimeapps_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = self.all_config_dirs();
+        dirs.extend(self.application_dirs());
+
+        dirs
+    }
+
+    /// Returns the base directories of the icon themes.
+    ///
+    /// The `~/.icons` directory, for backwards compatibility, the `icons` sub-directories of the
+    /// data directories and `/usr/share/pixmaps`.
+    pub fn icon_dirs(&self) -> Vec<PathBuf> {
+        env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".icons"))
+            .into_iter()
+            .chain(join_all(self.all_data_dirs(), "icons"))
+            .chain([PathBuf::from("/usr/share/pixmaps")])
+            .collect()
+    }
+
+    /// Returns the directories of the D-Bus session service files.
+    pub fn dbus_service_dirs(&self) -> Vec<PathBuf> {
+        join_all(self.all_data_dirs(), "dbus-1/services")
+    }
+}
+
+fn join_all(dirs: Vec<PathBuf>, path: &str) -> Vec<PathBuf> {
+    dirs.into_iter().map(|dir| dir.join(path)).collect()
+}
+
+/// Returns the base directories used by the loaders.
+///
+/// They are the ones set with [`set_base_dirs`], or [`BaseDirs::from_env`].
+pub fn base_dirs() -> BaseDirs {
+    BASE_DIRS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(BaseDirs::from_env)
+}
+
+/// Overrides the base directories for the whole process, [`None`] restores the ones from the
+/// environment.
+pub fn set_base_dirs(dirs: Option<BaseDirs>) {
+    *BASE_DIRS.write().unwrap_or_else(PoisonError::into_inner) = dirs;
+}
+
+/// Returns the XDG data directories, in order of preference.
+///
+/// The user directory `$XDG_DATA_HOME`, defaulting to `~/.local/share`, followed by the system
+/// directories in `$XDG_DATA_DIRS`, defaulting to `/usr/local/share:/usr/share`.
+pub fn data_dirs() -> Vec<PathBuf> {
+    base_dirs().all_data_dirs()
+}
+
+/// Returns the user data directory `$XDG_DATA_HOME`, defaulting to `~/.local/share`.
+pub fn data_home() -> Option<PathBuf> {
+    base_dirs().data_home
+}
+
+/// Returns the `applications` sub-directories of the XDG data directories, in order of preference.
+pub fn application_dirs() -> Vec<PathBuf> {
+    base_dirs().application_dirs()
+}
+
+/// Returns the directories of the D-Bus session service files in the XDG data directories, in
+/// order of preference.
+pub fn dbus_service_dirs() -> Vec<PathBuf> {
+    base_dirs().dbus_service_dirs()
+}
+
+/// Returns the desktop file ID of a file in an `applications` directory.
+///
+/// The ID is the path relative to the directory, with the `/` replaced by `-`. Returns [`None`]
+/// if the file is not inside the directory or the path is not valid UTF-8.
+pub fn desktop_file_id(applications_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(applications_dir).ok()?;
+
+    let parts = relative
+        .iter()
+        .map(|part| part.to_str())
+        .collect::<Option<Vec<_>>>()?;
+
+    (!parts.is_empty()).then(|| parts.join("-"))
+}
+
+/// Finds the desktop file with the given ID in the application directories.
+///
+/// The ID must include the `.desktop` extension. The first match in order of preference of the
+/// directories is returned.
+pub fn find_application(id: &str) -> Option<PathBuf> {
+    application_dirs()
+        .iter()
+        .find_map(|dir| find_in_dir(dir, dir, id))
+}
+
+/// Looks for the ID in the directory and its sub-directories, which add a prefix to the ID.
+fn find_in_dir(applications_dir: &Path, dir: &Path, id: &str) -> Option<PathBuf> {
+    let path = dir.join(id_suffix(applications_dir, dir, id)?);
+
+    if path.is_file() {
+        return Some(path);
+    }
+
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .find_map(|entry| find_in_dir(applications_dir, &entry.path(), id))
+}
+
+/// Returns the part of the ID not covered by the sub-directory prefix.
+fn id_suffix<'a>(applications_dir: &Path, dir: &Path, id: &'a str) -> Option<&'a str> {
+    if dir == applications_dir {
+        return Some(id);
+    }
+
+    let prefix = desktop_file_id(applications_dir, dir)?;
+
+    id.strip_prefix(&prefix)?.strip_prefix('-')
+}
+
+/// Origin of a loaded desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Position of the data directory of the file in the order of preference, starting from 0.
+    pub rank: usize,
+    /// The file is in the user data directory, see [`data_home`].
+    pub user: bool,
+    /// Modification time of the file, if supported by the platform.
+    pub modified: Option<SystemTime>,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Files with the same desktop file ID in the directories with a lower preference, overridden
+    /// by this one.
+    pub overrides: Vec<PathBuf>,
+}
+
+impl Provenance {
+    /// Reads the metadata of the file in the data directory with the given rank.
+    ///
+    /// # Errors
+    ///
+    /// If the metadata of the file can't be read.
+    pub fn new(path: impl Into<PathBuf>, rank: usize) -> io::Result<Self> {
+        let path = path.into();
+        let metadata = fs::metadata(&path)?;
+
+        let user = data_home().is_some_and(|home| path.starts_with(home));
+
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+            path,
+            rank,
+            user,
+            overrides: Vec::new(),
+        })
+    }
+}
+
+/// Reads and parses a desktop file, returning a document owning its content.
+///
+/// The file is read in a buffer, since the document must own its content. With the `mmap`
+/// feature only a `MappedFile` is parsed without copying the content.
+///
+/// # Errors
+///
+/// If the file can't be read or parsed.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+)]
+pub fn load(
+    path: impl AsRef<Path>,
+    options: &ParseOptions,
+) -> Result<DesktopEntry<'static>, Error> {
+    let result = read_and_parse(path.as_ref(), options);
+
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(document) => tracing::trace!(groups = document.groups.len(), "loaded desktop file"),
+        Err(err) => tracing::debug!(%err, "failed to load desktop file"),
+    }
+
+    result
+}
+
+fn read_and_parse(path: &Path, options: &ParseOptions) -> Result<DesktopEntry<'static>, Error> {
+    // The size is checked while reading, an input too large is not read in full
+    let content = crate::decode::read_limited(fs::File::open(path)?, options.limits.max_file_size)?;
+    let content = crate::utf8::from_utf8(&content)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let document = DesktopEntry::parse_with_options(content, options)?;
+
+    Ok(document.into_owned())
+}
+
+/// Filter of the files and directories visited by a scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanFilter {
+    /// Extensions of the files to return, without the `.`, like `desktop` or `directory`.
+    pub extensions: Vec<String>,
+    /// Descend into the sub-directories.
+    pub recursive: bool,
+    /// Follow the symbolic links to files and directories, instead of skipping them.
+    pub follow_symlinks: bool,
+    /// Patterns of the names of the files and directories to skip.
+    ///
+    /// A `*` matches any sequence of characters and a `?` a single character, like `*.bak` or
+    /// `.*`.
+    pub ignore: Vec<String>,
+}
+
+impl ScanFilter {
+    fn is_ignored(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+
+        self.ignore
+            .iter()
+            .any(|pattern| wildcard_match(pattern, name))
+    }
+
+    fn has_extension(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| self.extensions.iter().any(|ext| ext == extension))
+    }
+}
+
+impl Default for ScanFilter {
+    /// Returns the filter for the desktop files, following the symbolic links in all the
+    /// sub-directories.
+    fn default() -> Self {
+        Self {
+            extensions: vec![DESKTOP_EXTENSION.to_string()],
+            recursive: true,
+            follow_symlinks: true,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+/// Matches a name against a pattern with the `*` and `?` wildcards.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern and of the name when it was found
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => {
+                let Some((star_p, star_n)) = star else {
+                    return false;
+                };
+
+                // Let the `*` match one more character
+                p = star_p + 1;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Lazy walk of the files in a directory and its sub-directories, sorted by path.
+///
+/// The directories are read only when the walk reaches them. Each item is the path of a desktop
+/// file, or of a directory that couldn't be read with the error; the walk continues with the next
+/// entries. A missing directory is treated as empty. The files and directories are selected by a
+/// [`ScanFilter`], a directory reached again through a symbolic link is visited only once.
+#[derive(Debug)]
+pub struct DesktopFiles {
+    stack: Vec<std::vec::IntoIter<PathBuf>>,
+    filter: ScanFilter,
+    visited: HashSet<PathBuf>,
+}
+
+impl DesktopFiles {
+    /// Walks the desktop files with the [default filter](ScanFilter::default).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self::with_filter(dir, ScanFilter::default())
+    }
+
+    pub fn with_filter(dir: impl Into<PathBuf>, filter: ScanFilter) -> Self {
+        Self {
+            stack: vec![vec![dir.into()].into_iter()],
+            filter,
+            visited: HashSet::new(),
+        }
+    }
+}
+
+impl Iterator for DesktopFiles {
+    type Item = (PathBuf, io::Result<()>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(path) = self.stack.last_mut()?.next() else {
+                self.stack.pop();
+
+                continue;
+            };
+
+            // The directory of the walk is always visited
+            let is_root = self.stack.len() == 1;
+
+            if !is_root && self.filter.is_ignored(&path) {
+                continue;
+            }
+
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+
+            let is_dir = if metadata.file_type().is_symlink() {
+                if !self.filter.follow_symlinks && !is_root {
+                    continue;
+                }
+
+                path.is_dir()
+            } else {
+                metadata.is_dir()
+            };
+
+            if !is_dir {
+                if self.filter.has_extension(&path) {
+                    return Some((path, Ok(())));
+                }
+
+                continue;
+            }
+
+            if !is_root && !self.filter.recursive {
+                continue;
+            }
+
+            let entries = match fs::read_dir(&path) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Some((path, Err(err))),
+            };
+
+            let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+
+            if !self.visited.insert(canonical) {
+                continue;
+            }
+
+            let mut entries = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect::<Vec<_>>();
+            entries.sort();
+
+            self.stack.push(entries.into_iter());
+        }
+    }
+}
+
+/// Lazy scan of the desktop files in the directories, parsing one file at a time.
+///
+/// Each file is returned with its own result, so the caller can stream the documents, report
+/// the progress and choose which errors to ignore. The errors reading a directory are returned
+/// with the path of the directory.
+///
+/// ```no_run
+/// use xdg_desktop_entry::{loader, ParseOptions};
+///
+/// let options = ParseOptions::default();
+///
+/// for (path, result) in loader::scan(&loader::application_dirs(), &options) {
+///     match result {
+///         Ok(document) => println!("{}: {} groups", path.display(), document.groups().count()),
+///         Err(err) => eprintln!("{}: {err}", path.display()),
+///     }
+/// }
+/// ```
+pub fn scan<'a>(dirs: &'a [PathBuf], options: &'a ParseOptions<'a>) -> Scan<'a> {
+    scan_with_filter(dirs, ScanFilter::default(), options)
+}
+
+/// Lazy scan of the files selected by the filter, see [`scan`].
+pub fn scan_with_filter<'a>(
+    dirs: &'a [PathBuf],
+    filter: ScanFilter,
+    options: &'a ParseOptions<'a>,
+) -> Scan<'a> {
+    Scan {
+        dirs: dirs.iter(),
+        files: None,
+        filter,
+        options,
+    }
+}
+
+/// Iterator returned by [`scan`].
+#[derive(Debug)]
+pub struct Scan<'a> {
+    dirs: std::slice::Iter<'a, PathBuf>,
+    files: Option<DesktopFiles>,
+    filter: ScanFilter,
+    options: &'a ParseOptions<'a>,
+}
+
+impl<'a> Iterator for Scan<'a> {
+    type Item = (PathBuf, Result<DesktopEntry<'static>, Error>);
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let files = match &mut self.files {
+                Some(files) => files,
+                None => {
+                    let dir = self.dirs.next()?.clone();
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(dir = %dir.display(), "scanning directory");
+
+                    self.files
+                        .insert(DesktopFiles::with_filter(dir, self.filter.clone()))
+                }
+            };
+
+            let Some((path, result)) = files.next() else {
+                self.files = None;
+
+                continue;
+            };
+
+            let result = match result {
+                Ok(()) => load(&path, self.options),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(path = %path.display(), %err, "failed to read directory entry");
+
+                    Err(err.into())
+                }
+            };
+
+            return Some((path, result));
+        }
+    }
+}
+
+/// Desktop file mapped in memory, behind the `mmap` feature.
+///
+/// The documents parsed from the file borrow the mapped pages instead of copying the content, so
+/// loading many files doesn't allocate a buffer for each one. The documents returned by [`load`]
+/// own their content, they are always read in a buffer.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MappedFile {
+    path: PathBuf,
+    map: Option<memmap2::Mmap>,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    /// Maps the file in memory.
+    ///
+    /// The file must not be modified while it's mapped: the content is not copied, a change
+    /// would be seen by the parsed documents, or cause a crash if the file is truncated.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened or mapped.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = fs::File::open(&path)?;
+
+        // Empty files can't be mapped on all the platforms
+        let map = if file.metadata()?.len() == 0 {
+            None
+        } else {
+            // SAFETY: the desktop files are replaced, not modified in place, by the package
+            // managers and the editors, the requirement is documented on the function.
+            Some(unsafe { memmap2::Mmap::map(&file)? })
+        };
+
+        Ok(Self { path, map })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the content of the file.
+    ///
+    /// # Errors
+    ///
+    /// If the file is not valid UTF-8.
+    pub fn as_str(&self) -> io::Result<&str> {
+        let bytes = self.map.as_deref().unwrap_or_default();
+
+        crate::utf8::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Parses the file, returning a document borrowing the mapped content.
+    ///
+    /// # Errors
+    ///
+    /// If the file is not valid UTF-8 or can't be parsed.
+    pub fn parse(&self, options: &ParseOptions) -> Result<DesktopEntry<'_>, Error> {
+        let document = DesktopEntry::parse_with_options(self.as_str()?, options)?;
+
+        Ok(document)
+    }
+}
+
+/// Maps in memory the desktop files for bulk loading, skipping the ones that can't be mapped.
+///
+/// See [`MappedFile::open`] for the requirements on the files.
+#[cfg(feature = "mmap")]
+pub fn map_files<I>(paths: I) -> Vec<MappedFile>
+where
+    I: IntoIterator,
+    I::Item: Into<PathBuf>,
+{
+    paths
+        .into_iter()
+        .filter_map(|path| MappedFile::open(path).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_compute_desktop_file_id() {
+        let dir = Path::new("/usr/share/applications");
+
+        assert_eq!(
+            Some("kde-kate.desktop".to_string()),
+            desktop_file_id(dir, &dir.join("kde/kate.desktop"))
+        );
+        assert_eq!(
+            Some("org.example.Foo.desktop".to_string()),
+            desktop_file_id(dir, &dir.join("org.example.Foo.desktop"))
+        );
+        assert_eq!(
+            None,
+            desktop_file_id(dir, Path::new("/usr/share/foo.desktop"))
+        );
+    }
+
+    #[test]
+    fn should_scan_lazily() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let dir = root.join("applications");
+        fs::create_dir_all(dir.join("kde")).unwrap();
+
+        fs::write(dir.join("foo.desktop"), "[Desktop Entry]\nName=Foo\n").unwrap();
+        fs::write(dir.join("broken.desktop"), "Name\n").unwrap();
+        fs::write(dir.join("README"), "").unwrap();
+        fs::write(dir.join("kde/kate.desktop"), "[Desktop Entry]\nName=Kate\n").unwrap();
+
+        let options = ParseOptions::default();
+        let dirs = [dir.clone(), root.join("missing")];
+        let mut scan = scan(&dirs, &options);
+
+        let (first, result) = scan.next().unwrap();
+        let rest = scan
+            .map(|(path, result)| (path, result.is_ok()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(dir.join("broken.desktop"), first);
+        assert!(matches!(result, Err(Error::Parse(_))));
+        assert_eq!(
+            vec![
+                (dir.join("foo.desktop"), true),
+                (dir.join("kde/kate.desktop"), true),
+            ],
+            rest
+        );
+    }
+
+    #[test]
+    fn should_resolve_search_paths() {
+        let dirs = BaseDirs {
+            data_home: Some("/home/user/.local/share".into()),
+            data_dirs: vec!["/usr/share".into()],
+            config_home: Some("/home/user/.config".into()),
+            config_dirs: vec!["/etc/xdg".into()],
+        };
+
+        assert_eq!(
+            vec![
+                PathBuf::from("/home/user/.local/share/applications"),
+                PathBuf::from("/usr/share/applications"),
+            ],
+            dirs.application_dirs()
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("/home/user/.config/autostart"),
+                PathBuf::from("/etc/xdg/autostart"),
+            ],
+            dirs.autostart_dirs()
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("/home/user/.config"),
+                PathBuf::from("/etc/xdg"),
+                PathBuf::from("/home/user/.local/share/applications"),
+                PathBuf::from("/usr/share/applications"),
+            ],
+            dirs.mimeapps_dirs()
+        );
+    }
+
+    #[test]
+    fn should_match_wildcards() {
+        assert!(wildcard_match("*.bak", "foo.desktop.bak"));
+        assert!(wildcard_match(".*", ".hidden"));
+        assert!(wildcard_match("f?o*", "foo.desktop"));
+        assert!(wildcard_match("*a*b", "xaxxab"));
+        assert!(!wildcard_match("*.bak", "foo.desktop"));
+        assert!(!wildcard_match("f?o", "fo"));
+    }
+
+    #[test]
+    fn should_filter_scan() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("kde")).unwrap();
+        fs::create_dir_all(dir.join(".cache")).unwrap();
+
+        for name in [
+            "foo.desktop",
+            "games.directory",
+            "foo.desktop.bak",
+            "kde/kate.desktop",
+            ".cache/old.desktop",
+        ] {
+            fs::write(dir.join(name), "[Desktop Entry]\n").unwrap();
+        }
+
+        let walk = |filter: ScanFilter| {
+            DesktopFiles::with_filter(dir, filter)
+                .map(|(path, _)| path.strip_prefix(dir).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+
+        let all = walk(ScanFilter {
+            extensions: vec!["desktop".to_string(), "directory".to_string()],
+            ignore: vec![".*".to_string()],
+            ..ScanFilter::default()
+        });
+        let top = walk(ScanFilter {
+            recursive: false,
+            ..ScanFilter::default()
+        });
+
+        assert_eq!(
+            vec![
+                PathBuf::from("foo.desktop"),
+                PathBuf::from("games.directory"),
+                PathBuf::from("kde/kate.desktop"),
+            ],
+            all
+        );
+        assert_eq!(vec![PathBuf::from("foo.desktop")], top);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn should_parse_mapped_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        fs::write(dir.join("foo.desktop"), "[Desktop Entry]\nName=Foo\n").unwrap();
+        fs::write(dir.join("empty.desktop"), "").unwrap();
+        fs::write(dir.join("invalid.desktop"), b"[Desktop Entry]\nName=\xff\n").unwrap();
+
+        let files = map_files(
+            [
+                "foo.desktop",
+                "empty.desktop",
+                "invalid.desktop",
+                "missing.desktop",
+            ]
+            .map(|name| dir.join(name)),
+        );
+
+        let documents = files
+            .iter()
+            .map(|file| file.parse(&ParseOptions::default()).ok())
+            .collect::<Vec<_>>();
+
+        assert_eq!(3, files.len());
+        assert_eq!(dir.join("foo.desktop"), files[0].path());
+        assert_eq!(
+            Some(&crate::Value::String("Foo".into())),
+            documents[0]
+                .as_ref()
+                .and_then(|document| document.get("Desktop Entry", "Name"))
+        );
+        assert!(documents[1].is_some());
+        assert!(documents[2].is_none());
+    }
+
+    #[test]
+    fn should_load_up_to_the_file_size_limit() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("foo.desktop");
+        let content = "[Desktop Entry]\nName=Foo\n";
+
+        let options = ParseOptions {
+            limits: crate::Limits {
+                max_file_size: Some(content.len()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        fs::write(&path, format!("\u{FEFF}{content}")).unwrap();
+        assert!(load(&path, &options).is_ok());
+
+        fs::write(&path, format!("{content}\n")).unwrap();
+        let Err(Error::Parse(err)) = load(&path, &options) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(crate::ParseErrorKind::LimitExceeded, err.kind());
+    }
+}
@@ -0,0 +1,156 @@
+use std::borrow::Cow;
+
+use nom::combinator::all_consuming;
+
+use crate::raw::parse_key_locale;
+
+/// Locale of a localized key, in the form `lang_COUNTRY.ENCODING@MODIFIER`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale<'a> {
+    pub(crate) lang: Cow<'a, str>,
+    pub(crate) country: Option<Cow<'a, str>>,
+    pub(crate) encoding: Option<Cow<'a, str>>,
+    pub(crate) modifier: Option<Cow<'a, str>>,
+}
+
+impl<'a> Locale<'a> {
+    /// Parses a locale in the form used by the `LC_MESSAGES` and `LANG` environment variables.
+    ///
+    /// Returns [`None`] for malformed locales and for the `C` and `POSIX` locales, since they mean
+    /// that no translation should be used.
+    pub fn parse(input: &'a str) -> Option<Self> {
+        let (_, locale) = all_consuming(parse_key_locale::<()>)(input).ok()?;
+
+        if locale.lang == "C" || locale.lang == "POSIX" {
+            return None;
+        }
+
+        Some(locale)
+    }
+
+    /// Returns the locale of the messages from the environment.
+    ///
+    /// The first set variable between `LC_ALL`, `LC_MESSAGES` and `LANG` is used. Returns
+    /// [`None`] if none is set or for the `C` and `POSIX` locales.
+    pub fn from_env() -> Option<Locale<'static>> {
+        let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())?;
+
+        Locale::parse(&value).map(Locale::into_owned)
+    }
+
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+
+    pub fn modifier(&self) -> Option<&str> {
+        self.modifier.as_deref()
+    }
+
+    /// Returns the locale with the language lowercase, the country uppercase and without the
+    /// encoding, which is ignored when matching.
+    pub fn normalize(&self) -> Locale<'a> {
+        let lang = if self.lang.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(self.lang.to_ascii_lowercase())
+        } else {
+            self.lang.clone()
+        };
+
+        let country = self.country.as_ref().map(|country| {
+            if country.bytes().any(|b| b.is_ascii_lowercase()) {
+                Cow::Owned(country.to_ascii_uppercase())
+            } else {
+                country.clone()
+            }
+        });
+
+        Locale {
+            lang,
+            country,
+            encoding: None,
+            modifier: self.modifier.clone(),
+        }
+    }
+
+    /// Checks how well the locale of a key matches this locale.
+    ///
+    /// The key locale matches if it has the same language and, if present, the same country and
+    /// modifier. The higher the returned value the better the match, following the order of the
+    /// specification: `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`. The
+    /// encoding is always ignored.
+    pub fn match_key(&self, key: &Locale) -> Option<u8> {
+        if !self.lang.eq_ignore_ascii_case(&key.lang) {
+            return None;
+        }
+
+        let country = match (&self.country, &key.country) {
+            (_, None) => 0,
+            (Some(country), Some(key)) if country.eq_ignore_ascii_case(key) => 2,
+            (_, Some(_)) => return None,
+        };
+
+        let modifier = match (&self.modifier, &key.modifier) {
+            (_, None) => 0,
+            (Some(modifier), Some(key)) if modifier == key => 1,
+            (_, Some(_)) => return None,
+        };
+
+        Some(country + modifier)
+    }
+
+    /// Checks if the two locales are equal once normalized.
+    pub fn matches(&self, other: &Locale) -> bool {
+        self.normalize() == other.normalize()
+    }
+
+    pub fn into_owned(self) -> Locale<'static> {
+        Locale {
+            lang: Cow::Owned(self.lang.into_owned()),
+            country: self.country.map(|c| Cow::Owned(c.into_owned())),
+            encoding: self.encoding.map(|e| Cow::Owned(e.into_owned())),
+            modifier: self.modifier.map(|m| Cow::Owned(m.into_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_posix_as_no_locale() {
+        assert_eq!(None, Locale::parse("C"));
+        assert_eq!(None, Locale::parse("C.UTF-8"));
+        assert_eq!(None, Locale::parse("POSIX"));
+    }
+
+    #[test]
+    fn should_normalize_locale() {
+        let locale = Locale::parse("de_de.UTF-8").unwrap();
+
+        assert_eq!(Locale::parse("de_DE").unwrap(), locale.normalize());
+        assert!(locale.matches(&Locale::parse("DE_DE").unwrap()));
+    }
+
+    #[test]
+    fn should_match_key_locale() {
+        let locale = Locale::parse("sr_yu.UTF-8@Latin").unwrap();
+
+        let matches = ["sr_YU@Latin", "sr_YU", "sr@Latin", "sr", "en", "sr_RS"]
+            .map(|key| locale.match_key(&Locale::parse(key).unwrap()));
+
+        assert_eq!([Some(3), Some(2), Some(1), Some(0), None, None], matches);
+    }
+}
@@ -0,0 +1,674 @@
+//! Command line tool to parse, query, validate, format, migrate and launch desktop files.
+
+use std::{
+    collections::HashMap,
+    env,
+    error::Error as _,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use nom::combinator::all_consuming;
+use serde::Serialize;
+use xdg_desktop_entry::{
+    diff::unified_diff,
+    format::{self, FormatOptions},
+    json_schema,
+    launch::{self, LaunchOptions},
+    lint::{self, LintLevel, LintOptions},
+    loader, migrate,
+    raw::parse_key,
+    validate::{self, Baseline, Diagnostic, Severity, ValidateOptions},
+    DesktopEntry, Error, Key, Locale, ParseOptions, Value,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Parses a desktop file, reporting the errors and warnings.
+    Parse {
+        /// Prints the parsed document.
+        #[arg(long)]
+        dump: bool,
+        /// Prints the parsed document as JSON.
+        #[arg(long, conflicts_with = "dump")]
+        json: bool,
+        file: PathBuf,
+    },
+    /// Prints the value of a key, like `Desktop Entry/Name[de]`.
+    ///
+    /// The translation that best matches the locale is printed, or the default value if none
+    /// matches. With the `*` and `?` wildcards, like `Desktop Action */Exec`, all the matching
+    /// entries are printed.
+    Query {
+        /// Prints the value as JSON.
+        #[arg(long)]
+        json: bool,
+        file: PathBuf,
+        path: String,
+    },
+    /// Validates desktop files against the specification.
+    Validate {
+        /// Prints the diagnostics of the files as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Disables a check, by code or name.
+        #[arg(long, value_name = "CHECK")]
+        allow: Vec<String>,
+        /// Reports the issues of a check as warnings.
+        #[arg(long, value_name = "CHECK")]
+        warn: Vec<String>,
+        /// Reports the issues of a check as errors.
+        #[arg(long, value_name = "CHECK")]
+        deny: Vec<String>,
+        /// Checks that the D-Bus activatable entries have a D-Bus service file installed.
+        #[arg(long)]
+        check_dbus_services: bool,
+        /// Ignores the known issues listed in the baseline file.
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+        /// Writes the issues found to the baseline file, instead of reporting them.
+        #[arg(long, requires = "baseline")]
+        update_baseline: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Checks desktop files with the lint rules.
+    Lint {
+        /// Disables a rule, by code or name.
+        #[arg(long, value_name = "RULE")]
+        allow: Vec<String>,
+        /// Reports the violations of a rule as warnings.
+        #[arg(long, value_name = "RULE")]
+        warn: Vec<String>,
+        /// Reports the violations of a rule as errors.
+        #[arg(long, value_name = "RULE")]
+        deny: Vec<String>,
+        /// Applies the fixes of the enabled rules in place.
+        #[arg(long)]
+        fix: bool,
+        /// Prints the diff of the fixes instead of applying them.
+        #[arg(long, requires = "fix")]
+        diff: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Launches an application by desktop file ID or path, opening the files.
+    Launch {
+        /// Refuses to launch the entries with a command line that relies on a shell.
+        #[arg(long)]
+        hardened: bool,
+        /// Desktop file ID, like `org.example.Foo.desktop`, or path of the desktop file.
+        id: String,
+        files: Vec<OsString>,
+    },
+    /// Formats the layout of a desktop file, printing it to the standard output.
+    Format {
+        /// Writes the formatted file in place.
+        #[arg(short, long)]
+        write: bool,
+        file: PathBuf,
+    },
+    /// Migrates the deprecated entries of a desktop file, printing it to the standard output.
+    ///
+    /// The changes made are reported on the standard error.
+    Migrate {
+        /// Writes the migrated file in place.
+        #[arg(short, long)]
+        write: bool,
+        /// Prints the diff of the changes instead of the migrated file.
+        #[arg(long, conflicts_with = "write")]
+        diff: bool,
+        file: PathBuf,
+    },
+    /// Prints the JSON Schema of the desktop files serialized as JSON.
+    JsonSchema,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Parse { dump, json, file } => parse(&file, dump, json),
+        Command::Query { json, file, path } => query(&file, &path, json),
+        Command::Validate {
+            json,
+            allow,
+            warn,
+            deny,
+            check_dbus_services,
+            baseline,
+            update_baseline,
+            files,
+        } => {
+            let levels = levels(allow, warn, deny);
+
+            if let Some(check) = levels
+                .keys()
+                .find(|check| validate::find_check(check).is_none())
+            {
+                eprintln!("error: unknown validation check `{check}`");
+
+                return ExitCode::FAILURE;
+            }
+
+            let options = ValidateOptions {
+                levels,
+                dbus_service_dirs: check_dbus_services.then(loader::dbus_service_dirs),
+            };
+
+            match (baseline, update_baseline) {
+                (Some(path), true) => write_baseline(&path, &files, &options),
+                (Some(path), false) => {
+                    let baseline = match read_baseline(&path) {
+                        Ok(baseline) => baseline,
+                        Err(err) => {
+                            eprintln!("{}: error: {err}", path.display());
+
+                            return ExitCode::FAILURE;
+                        }
+                    };
+
+                    validate_files(&files, &options, &baseline, json)
+                }
+                (None, _) => validate_files(&files, &options, &Baseline::default(), json),
+            }
+        }
+        Command::Lint {
+            allow,
+            warn,
+            deny,
+            fix,
+            diff,
+            files,
+        } => {
+            let levels = levels(allow, warn, deny);
+
+            if let Some(rule) = levels.keys().find(|rule| lint::find_rule(rule).is_none()) {
+                eprintln!("error: unknown lint rule `{rule}`");
+
+                return ExitCode::FAILURE;
+            }
+
+            let options = LintOptions {
+                levels,
+                ..Default::default()
+            };
+
+            files.iter().fold(ExitCode::SUCCESS, |code, file| {
+                if lint_file(file, &options, fix, diff) {
+                    code
+                } else {
+                    ExitCode::FAILURE
+                }
+            })
+        }
+        Command::Launch {
+            hardened,
+            id,
+            files,
+        } => launch(&id, files, hardened),
+        Command::Format { write, file } => format(&file, write),
+        Command::Migrate { write, diff, file } => migrate(&file, write, diff),
+        Command::JsonSchema => match write!(io::stdout(), "{}", json_schema::json_schema()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {err}");
+
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+/// Collects the levels of the rules or checks passed to the `--allow`, `--warn` and `--deny`
+/// options.
+fn levels(allow: Vec<String>, warn: Vec<String>, deny: Vec<String>) -> HashMap<String, LintLevel> {
+    let levels = [
+        (allow, LintLevel::Allow),
+        (warn, LintLevel::Warn),
+        (deny, LintLevel::Deny),
+    ];
+
+    levels
+        .into_iter()
+        .flat_map(|(names, level)| names.into_iter().map(move |name| (name, level)))
+        .collect()
+}
+
+fn parse(path: &Path, dump: bool, json: bool) -> ExitCode {
+    let content = match read(path) {
+        Ok(content) => content,
+        Err(err) => return report_error(path, &err),
+    };
+
+    let document = match DesktopEntry::parse(&content) {
+        Ok(document) => document,
+        Err(err) => return report_error(path, &err.into()),
+    };
+
+    for warning in document.warnings() {
+        eprintln!("{}: warning: {warning}", path.display());
+    }
+
+    let result = if dump {
+        writeln!(io::stdout(), "{document:#?}")
+    } else if json {
+        write_json(&document)
+    } else {
+        Ok(())
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(path, &err.into()),
+    }
+}
+
+fn query(path: &Path, query: &str, json: bool) -> ExitCode {
+    if query.contains(['*', '?']) {
+        return query_all(path, query, json);
+    }
+
+    let Some((header, key)) = query.rsplit_once('/') else {
+        eprintln!("error: the query `{query}` must be in the form `Group/Key[locale]`");
+
+        return ExitCode::FAILURE;
+    };
+
+    let Ok((_, key)) = all_consuming(parse_key::<()>)(key) else {
+        eprintln!("error: invalid key `{key}`");
+
+        return ExitCode::FAILURE;
+    };
+
+    let content = match read(path) {
+        Ok(content) => content,
+        Err(err) => return report_error(path, &err),
+    };
+
+    let document = match DesktopEntry::parse(&content) {
+        Ok(document) => document,
+        Err(err) => return report_error(path, &err.into()),
+    };
+
+    let value = match &key {
+        Key::Simple(key) => document.get(header, key),
+        Key::Localized { key, locale } => document.get_localized(header, key, Some(locale)),
+    };
+
+    let Some(value) = value else {
+        eprintln!("{}: `{query}` not found", path.display());
+
+        return ExitCode::FAILURE;
+    };
+
+    let result = if json {
+        write_json(value)
+    } else {
+        match value {
+            Value::String(value) | Value::LocaleString(value) => {
+                writeln!(io::stdout(), "{value}")
+            }
+            Value::Boolean(value) => writeln!(io::stdout(), "{value}"),
+            Value::Numeric(value) => writeln!(io::stdout(), "{value:?}"),
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(path, &err.into()),
+    }
+}
+
+/// Prints all the entries matching a query with wildcards, as `Group/Key=Value` lines or a JSON
+/// object from the path to the value.
+fn query_all(path: &Path, query: &str, json: bool) -> ExitCode {
+    let content = match read(path) {
+        Ok(content) => content,
+        Err(err) => return report_error(path, &err),
+    };
+
+    let document = match DesktopEntry::parse(&content) {
+        Ok(document) => document,
+        Err(err) => return report_error(path, &err.into()),
+    };
+
+    let matches = match document.query(query) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("error: {err}");
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if matches.is_empty() {
+        eprintln!("{}: `{query}` not found", path.display());
+
+        return ExitCode::FAILURE;
+    }
+
+    let result = if json {
+        let values: serde_json::Map<String, serde_json::Value> = matches
+            .iter()
+            .map(|entry| {
+                let value = serde_json::to_value(entry.value).unwrap_or_default();
+
+                (format!("{}/{}", entry.group, entry.key), value)
+            })
+            .collect();
+
+        write_json(&values)
+    } else {
+        matches.iter().try_for_each(|entry| {
+            writeln!(
+                io::stdout(),
+                "{}/{}={}",
+                entry.group,
+                entry.key,
+                entry.value
+            )
+        })
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(path, &err.into()),
+    }
+}
+
+fn write_json(value: &impl Serialize) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+
+    writeln!(stdout)
+}
+
+/// Diagnostics of a file, for the JSON output.
+#[derive(Debug, Serialize)]
+struct FileDiagnostics<'a> {
+    file: &'a Path,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Validates the files printing the diagnostics not in the baseline, fails if any file has
+/// errors.
+fn validate_files(
+    files: &[PathBuf],
+    options: &ValidateOptions,
+    baseline: &Baseline,
+    json: bool,
+) -> ExitCode {
+    let mut code = ExitCode::SUCCESS;
+    let mut results = Vec::new();
+
+    for path in files {
+        let content = match read(path) {
+            Ok(content) => content,
+            Err(err) => {
+                code = report_error(path, &err);
+
+                continue;
+            }
+        };
+
+        let mut diagnostics = validate::validate_str_with_file_id(&content, file_id(path), options);
+        baseline.filter(&path.to_string_lossy(), &mut diagnostics);
+
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity >= Severity::Error)
+        {
+            code = ExitCode::FAILURE;
+        }
+
+        if json {
+            results.push(FileDiagnostics {
+                file: path,
+                diagnostics,
+            });
+
+            continue;
+        }
+
+        for diagnostic in &diagnostics {
+            match (diagnostic.line, diagnostic.column) {
+                (Some(line), Some(column)) => {
+                    println!("{}:{line}:{column}: {diagnostic}", path.display())
+                }
+                _ => println!("{}: {diagnostic}", path.display()),
+            }
+        }
+    }
+
+    if json {
+        if let Err(err) = write_json(&results) {
+            eprintln!("error: {err}");
+
+            return ExitCode::FAILURE;
+        }
+    }
+
+    code
+}
+
+fn read_baseline(path: &Path) -> Result<Baseline, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+
+    Ok(Baseline::parse(&content)?)
+}
+
+/// Writes the issues of the files to the baseline.
+fn write_baseline(path: &Path, files: &[PathBuf], options: &ValidateOptions) -> ExitCode {
+    let mut baseline = Baseline::default();
+
+    for file in files {
+        let content = match read(file) {
+            Ok(content) => content,
+            Err(err) => return report_error(file, &err),
+        };
+
+        let diagnostics = validate::validate_str_with_file_id(&content, file_id(file), options);
+        let file = file.to_string_lossy();
+
+        for diagnostic in diagnostics {
+            baseline.insert(&file, &diagnostic);
+        }
+    }
+
+    if let Err(err) = fs::write(path, baseline.to_string()) {
+        return report_error(path, &err.into());
+    }
+
+    println!(
+        "{}: wrote {} issues to the baseline",
+        path.display(),
+        baseline.len()
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn lint_file(path: &Path, options: &LintOptions, fix: bool, diff: bool) -> bool {
+    let mut content = match read(path) {
+        Ok(content) => content,
+        Err(err) => {
+            report_error(path, &err);
+
+            return false;
+        }
+    };
+
+    if fix {
+        let fixed = lint::fix(&content, options);
+
+        if diff {
+            let name = path.to_string_lossy();
+
+            print!("{}", unified_diff(&content, &fixed.output, &name, &name));
+        } else if !fixed.fixes.is_empty() {
+            if let Err(err) = fs::write(path, &fixed.output) {
+                report_error(path, &err.into());
+
+                return false;
+            }
+
+            for fix in &fixed.fixes {
+                println!("{}: {fix}", path.display());
+            }
+        }
+
+        content = fixed.output;
+    }
+
+    let document = match DesktopEntry::parse(&content) {
+        Ok(document) => document,
+        Err(err) => {
+            report_error(path, &err.into());
+
+            return false;
+        }
+    };
+
+    let lints = lint::lint_with_file_id(&document, file_id(path), options);
+
+    for lint in &lints {
+        println!("{}: {lint}", path.display());
+    }
+
+    lints.iter().all(|lint| lint.severity < Severity::Error)
+}
+
+fn launch(id: &str, files: Vec<OsString>, hardened: bool) -> ExitCode {
+    let path = Path::new(id);
+
+    let path = if path.is_file() {
+        path.to_path_buf()
+    } else {
+        let id = if id.ends_with(".desktop") {
+            id.to_string()
+        } else {
+            format!("{id}.desktop")
+        };
+
+        match loader::find_application(&id) {
+            Some(path) => path,
+            None => {
+                eprintln!("error: application `{id}` not found");
+
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    let document = match loader::load(&path, &ParseOptions::default()) {
+        Ok(document) => document,
+        Err(err) => return report_error(&path, &err),
+    };
+
+    let options = LaunchOptions {
+        files,
+        location: Some(path.clone()),
+        locale: Locale::from_env(),
+        hardened,
+        // Forward the token and startup ID of the launcher running the command
+        activation_token: env::var(launch::XDG_ACTIVATION_TOKEN).ok(),
+        startup_id: env::var(launch::DESKTOP_STARTUP_ID).ok(),
+        ..LaunchOptions::default()
+    };
+
+    match launch::launch_detached(&document, &options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(&path, &err),
+    }
+}
+
+fn format(path: &Path, write: bool) -> ExitCode {
+    let content = match read(path) {
+        Ok(content) => content,
+        Err(err) => return report_error(path, &err),
+    };
+
+    if let Err(err) = DesktopEntry::parse(&content) {
+        return report_error(path, &err.into());
+    }
+
+    let formatted = format::format(&content, &FormatOptions::default());
+
+    let result = if write {
+        fs::write(path, formatted)
+    } else {
+        io::stdout().write_all(formatted.as_bytes())
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(path, &err.into()),
+    }
+}
+
+fn migrate(path: &Path, write: bool, diff: bool) -> ExitCode {
+    let content = match read(path) {
+        Ok(content) => content,
+        Err(err) => return report_error(path, &err),
+    };
+
+    if let Err(err) = DesktopEntry::parse(&content) {
+        return report_error(path, &err.into());
+    }
+
+    let migrated = migrate::migrate(&content);
+
+    for migration in &migrated.migrations {
+        eprintln!("{}: {migration}", path.display());
+    }
+
+    let result = if write {
+        fs::write(path, migrated.output)
+    } else if diff {
+        let name = path.to_string_lossy();
+        let diff = unified_diff(&content, &migrated.output, &name, &name);
+
+        io::stdout().write_all(diff.as_bytes())
+    } else {
+        io::stdout().write_all(migrated.output.as_bytes())
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(path, &err.into()),
+    }
+}
+
+/// Returns the desktop file ID of a file from its name.
+fn file_id(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(Error::from)
+}
+
+fn report_error(path: &Path, err: &Error) -> ExitCode {
+    eprint!("{}: error: {err}", path.display());
+
+    if let Some(source) = err.source() {
+        eprint!(": {source}");
+    }
+
+    eprintln!();
+
+    ExitCode::FAILURE
+}
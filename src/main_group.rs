@@ -0,0 +1,274 @@
+//! Typed view of the `[Desktop Entry]` group, with the accessors of the standard keys.
+//!
+//! ```
+//! use xdg_desktop_entry::{main_group::EntryType, DesktopEntry, Locale};
+//!
+//! let document = DesktopEntry::parse(
+//!     "[Desktop Entry]\nType=Application\nName=Foo\nName[de]=Fu\nExec=foo %f\nCategories=Utility;Viewer;\nTerminal=true\n",
+//! )
+//! .unwrap();
+//!
+//! let entry = document.desktop_entry().unwrap();
+//!
+//! assert_eq!(Some(EntryType::Application), entry.entry_type());
+//! assert_eq!(Some("Fu"), entry.name(Locale::parse("de_DE").as_ref()).as_deref());
+//! assert_eq!(vec!["Utility", "Viewer"], entry.categories());
+//! assert!(entry.terminal());
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    gkeyfile::{value_text, LIST_SEPARATOR},
+    DesktopEntry, EntryMap, Locale, Value, DESKTOP_ENTRY_GROUP,
+};
+
+/// Error for a document without the `[Desktop Entry]` group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("the document has no `[{DESKTOP_ENTRY_GROUP}]` group")]
+pub struct MissingMainGroup;
+
+/// Type of a desktop entry, from the `Type` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryType {
+    Application,
+    Link,
+    Directory,
+}
+
+impl EntryType {
+    /// Parses the value of the `Type` key, [`None`] if it's not a type of the specification.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Application" => Some(EntryType::Application),
+            "Link" => Some(EntryType::Link),
+            "Directory" => Some(EntryType::Directory),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryType::Application => "Application",
+            EntryType::Link => "Link",
+            EntryType::Directory => "Directory",
+        }
+    }
+}
+
+/// View of the `[Desktop Entry]` group of a document.
+///
+/// The values are typed by their content, so the string accessors return the text of any value,
+/// like `1.5` for the `Version`. The boolean accessors are `false` if the key is missing or not
+/// a boolean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MainGroup<'d, 'a> {
+    document: &'d DesktopEntry<'a>,
+    entries: &'d EntryMap<'a, 'a>,
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Returns the typed view of the `[Desktop Entry]` group.
+    ///
+    /// # Errors
+    ///
+    /// If the document doesn't have the group.
+    pub fn desktop_entry(&self) -> Result<MainGroup<'_, 'a>, MissingMainGroup> {
+        let entries = self.group(DESKTOP_ENTRY_GROUP).ok_or(MissingMainGroup)?;
+
+        Ok(MainGroup {
+            document: self,
+            entries,
+        })
+    }
+}
+
+impl<'d, 'a> MainGroup<'d, 'a> {
+    /// Returns all the entries of the group.
+    pub fn entries(&self) -> &'d EntryMap<'a, 'a> {
+        self.entries
+    }
+
+    /// Returns the non localized value of a key.
+    pub fn get(&self, key: &str) -> Option<&'d Value<'a>> {
+        self.document.get(DESKTOP_ENTRY_GROUP, key)
+    }
+
+    /// Returns the type of the entry, [`None`] if missing or unknown.
+    pub fn entry_type(&self) -> Option<EntryType> {
+        EntryType::parse(&self.string("Type")?)
+    }
+
+    /// Returns the version of the specification the entry conforms to, like `1.5`.
+    pub fn version(&self) -> Option<Cow<'d, str>> {
+        match self.get("Version")? {
+            // Keep the decimal point of the versions like `1.0`
+            Value::Numeric(version) => Some(Cow::Owned(format!("{version:?}"))),
+            version => Some(value_text(version)),
+        }
+    }
+
+    /// Returns the name of the entry for the locale.
+    pub fn name(&self, locale: Option<&Locale>) -> Option<Cow<'d, str>> {
+        self.localized("Name", locale)
+    }
+
+    /// Returns the generic name of the entry for the locale, like "Web Browser".
+    pub fn generic_name(&self, locale: Option<&Locale>) -> Option<Cow<'d, str>> {
+        self.localized("GenericName", locale)
+    }
+
+    /// Returns the tooltip of the entry for the locale.
+    pub fn comment(&self, locale: Option<&Locale>) -> Option<Cow<'d, str>> {
+        self.localized("Comment", locale)
+    }
+
+    /// Returns the icon name or path for the locale.
+    pub fn icon(&self, locale: Option<&Locale>) -> Option<Cow<'d, str>> {
+        self.localized("Icon", locale)
+    }
+
+    /// Returns the keywords of the entry for the locale.
+    pub fn keywords(&self, locale: Option<&Locale>) -> Vec<Cow<'d, str>> {
+        split(self.localized("Keywords", locale))
+    }
+
+    pub fn no_display(&self) -> bool {
+        self.boolean("NoDisplay")
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.boolean("Hidden")
+    }
+
+    pub fn only_show_in(&self) -> Vec<Cow<'d, str>> {
+        self.list("OnlyShowIn")
+    }
+
+    pub fn not_show_in(&self) -> Vec<Cow<'d, str>> {
+        self.list("NotShowIn")
+    }
+
+    pub fn dbus_activatable(&self) -> bool {
+        self.boolean("DBusActivatable")
+    }
+
+    pub fn try_exec(&self) -> Option<Cow<'d, str>> {
+        self.string("TryExec")
+    }
+
+    pub fn exec(&self) -> Option<Cow<'d, str>> {
+        self.string("Exec")
+    }
+
+    /// Returns the working directory to run the program in.
+    pub fn path(&self) -> Option<Cow<'d, str>> {
+        self.string("Path")
+    }
+
+    pub fn terminal(&self) -> bool {
+        self.boolean("Terminal")
+    }
+
+    /// Returns the identifiers of the actions.
+    pub fn actions(&self) -> Vec<Cow<'d, str>> {
+        self.list("Actions")
+    }
+
+    pub fn mime_types(&self) -> Vec<Cow<'d, str>> {
+        self.list("MimeType")
+    }
+
+    pub fn categories(&self) -> Vec<Cow<'d, str>> {
+        self.list("Categories")
+    }
+
+    pub fn implements(&self) -> Vec<Cow<'d, str>> {
+        self.list("Implements")
+    }
+
+    pub fn startup_notify(&self) -> bool {
+        self.boolean("StartupNotify")
+    }
+
+    pub fn startup_wm_class(&self) -> Option<Cow<'d, str>> {
+        self.string("StartupWMClass")
+    }
+
+    /// Returns the URL of a `Link` entry.
+    pub fn url(&self) -> Option<Cow<'d, str>> {
+        self.string("URL")
+    }
+
+    pub fn prefers_non_default_gpu(&self) -> bool {
+        self.boolean("PrefersNonDefaultGPU")
+    }
+
+    pub fn single_main_window(&self) -> bool {
+        self.boolean("SingleMainWindow")
+    }
+
+    fn string(&self, key: &str) -> Option<Cow<'d, str>> {
+        self.get(key).map(value_text)
+    }
+
+    fn localized(&self, key: &str, locale: Option<&Locale>) -> Option<Cow<'d, str>> {
+        self.document
+            .get_localized(DESKTOP_ENTRY_GROUP, key, locale)
+            .map(value_text)
+    }
+
+    fn boolean(&self, key: &str) -> bool {
+        matches!(self.get(key), Some(Value::Boolean(true)))
+    }
+
+    fn list(&self, key: &str) -> Vec<Cow<'d, str>> {
+        split(self.string(key))
+    }
+}
+
+/// Splits a list, skipping the empty items.
+fn split(value: Option<Cow<str>>) -> Vec<Cow<str>> {
+    match value {
+        Some(Cow::Borrowed(value)) => items(value).map(Cow::Borrowed).collect(),
+        Some(Cow::Owned(value)) => items(&value)
+            .map(|item| Cow::Owned(item.to_string()))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn items(value: &str) -> impl Iterator<Item = &str> {
+    value.split(LIST_SEPARATOR).filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_read_standard_keys() {
+        let document = DesktopEntry::parse(include_str!("../example/file.desktop")).unwrap();
+
+        let entry = document.desktop_entry().unwrap();
+
+        assert_eq!(Some(EntryType::Application), entry.entry_type());
+        assert_eq!(Some("1.0"), entry.version().as_deref());
+        assert_eq!(Some("Foo Viewer"), entry.name(None).as_deref());
+        assert_eq!(Some("fooview %F"), entry.exec().as_deref());
+        assert_eq!(vec!["Gallery", "Create"], entry.actions());
+        assert_eq!(vec!["image/x-foo"], entry.mime_types());
+        assert!(entry.categories().is_empty());
+        assert!(!entry.terminal());
+        assert_eq!(None, entry.url());
+    }
+
+    #[test]
+    fn should_fail_without_main_group() {
+        let document = DesktopEntry::parse("[Desktop Action new]\nExec=foo\n").unwrap();
+
+        assert_eq!(Err(MissingMainGroup), document.desktop_entry());
+    }
+}
@@ -0,0 +1,451 @@
+//! Map of the groups and entries backed by a [`Vec`], used with the `vec-map` feature.
+//!
+//! The [`VecMap`] has the methods of the [`IndexMap`](indexmap::IndexMap) used for the
+//! [`EntryMap`](crate::EntryMap), so the rest of the API doesn't change with the feature. It
+//! stores only the key and value pairs in the order of the document, without the hashes and the
+//! index of the [`IndexMap`](indexmap::IndexMap), and looks up the keys with a linear search.
+//! The groups of a desktop file have few entries, so the lookups are still fast enough for
+//! memory constrained environments.
+//!
+//! The pairs are not sorted by key: the document must be written back in its order, and a sorted
+//! [`Vec`] would need an index of the insertion order beside it, taking back part of the memory
+//! saved. The lookups are `O(n)` instead of the `O(log n)` of a binary search.
+
+use std::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    slice, vec,
+};
+
+/// Map preserving the insertion order, backed by a [`Vec`] of pairs.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct VecMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for VecMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> VecMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+
+    pub fn into_values(self) -> impl DoubleEndedIterator<Item = V> + ExactSizeIterator {
+        self.entries.into_iter().map(|(_, value)| value)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries
+            .get_mut(index)
+            .map(|(key, value)| (&*key, value))
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(key, value)| (key, value))
+    }
+
+    /// Keeps the entries for which the predicate returns `true`, in the same order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        self.entries.retain_mut(|(key, value)| keep(key, value));
+    }
+
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&K, &V, &K, &V) -> std::cmp::Ordering) {
+        self.entries
+            .sort_by(|(k1, v1), (k2, v2)| compare(k1, v1, k2, v2));
+    }
+
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.entries.swap(a, b);
+    }
+
+    /// Removes the entry at the index, shifting the following ones.
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        (index < self.entries.len()).then(|| self.entries.remove(index))
+    }
+}
+
+impl<K: Eq, V> VecMap<K, V> {
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.entries
+            .iter()
+            .position(|(entry, _)| entry.borrow() == key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.get_index_of(key)
+            .and_then(|index| self.get_index(index))
+    }
+
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+        let (key, value) = &self.entries[index];
+
+        Some((index, key, value))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+
+        Some(&mut self.entries[index].1)
+    }
+
+    /// Inserts the value, returning the previous one. A new key is added at the end.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Inserts the value, returning the index of the key and the previous value.
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        match self.get_index_of(&key) {
+            Some(index) => (
+                index,
+                Some(std::mem::replace(&mut self.entries[index].1, value)),
+            ),
+            None => {
+                self.entries.push((key, value));
+
+                (self.entries.len() - 1, None)
+            }
+        }
+    }
+
+    /// Removes the entry of the key, shifting the following ones.
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+
+        self.shift_remove_index(index).map(|(_, value)| value)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.get_index_of(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+}
+
+/// Entry of a [`VecMap`], see [`VecMap::entry`].
+pub enum Entry<'m, K, V> {
+    Occupied(OccupiedEntry<'m, K, V>),
+    Vacant(VacantEntry<'m, K, V>),
+}
+
+pub struct OccupiedEntry<'m, K, V> {
+    map: &'m mut VecMap<K, V>,
+    index: usize,
+}
+
+pub struct VacantEntry<'m, K, V> {
+    map: &'m mut VecMap<K, V>,
+    key: K,
+}
+
+impl<'m, K, V> OccupiedEntry<'m, K, V> {
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    pub fn into_mut(self) -> &'m mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'m, K, V> VacantEntry<'m, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn index(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn insert(self, value: V) -> &'m mut V {
+        self.map.entries.push((self.key, value));
+
+        // The pair was just pushed
+        let last = self.map.entries.len() - 1;
+
+        &mut self.map.entries[last].1
+    }
+}
+
+impl<'m, K, V> Entry<'m, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'m mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'m mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'m mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+/// Iterator over the entries of a [`VecMap`].
+#[derive(Debug, Clone)]
+pub struct Iter<'m, K, V>(slice::Iter<'m, (K, V)>);
+
+impl<'m, K, V> Iterator for Iter<'m, K, V> {
+    type Item = (&'m K, &'m V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Mutable iterator over the entries of a [`VecMap`].
+#[derive(Debug)]
+pub struct IterMut<'m, K, V>(slice::IterMut<'m, (K, V)>);
+
+impl<'m, K, V> Iterator for IterMut<'m, K, V> {
+    type Item = (&'m K, &'m mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (&*key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (&*key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'m, K, V> IntoIterator for &'m VecMap<K, V> {
+    type Item = (&'m K, &'m V);
+    type IntoIter = Iter<'m, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'m, K, V> IntoIterator for &'m mut VecMap<K, V> {
+    type Item = (&'m K, &'m mut V);
+    type IntoIter = IterMut<'m, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K: Eq, V> FromIterator<(K, V)> for VecMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+
+        map.extend(iter);
+
+        map
+    }
+}
+
+impl<K: Eq, V> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+
+        self.reserve(iter.size_hint().0);
+
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Eq, V, const N: usize> From<[(K, V); N]> for VecMap<K, V> {
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_keep_insertion_order() {
+        let mut map = VecMap::from([("b", 1), ("a", 2)]);
+
+        assert_eq!(Some(2), map.insert("a", 3));
+        assert_eq!(None, map.insert("c", 4));
+        *map.entry("d").or_default() += 5;
+        *map.entry("b").or_default() += 5;
+
+        assert_eq!(
+            vec![(&"b", &6), (&"a", &3), (&"c", &4), (&"d", &5)],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(1), map.get_index_of("a"));
+        assert_eq!(Some(&4), map.get("c"));
+
+        map.retain(|key, _| *key != "a");
+
+        assert_eq!(Some(4), map.shift_remove("c"));
+        assert_eq!(vec!["b", "d"], map.keys().copied().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,220 @@
+//! Migration of desktop files to the current version of the specification.
+//!
+//! Like the fixes of the [lints](crate::lint), the migration works on the source text: only the
+//! migrated lines are changed, the comments and the layout of the file are kept.
+
+use std::{borrow::Cow, fmt::Display, ops::Range};
+
+use crate::{
+    categories,
+    exec::{check_field_codes, ExecIssueKind},
+    lint::{rewrite_lines, Edit},
+    raw::parse_escaped_string_with,
+    token::{tokenize, TokenKind},
+    UnknownEscapes, Value, DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+/// Change made by the migration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Migration {
+    /// Line of the change in the input, starting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Migration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Source migrated to the current version of the specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated {
+    pub output: String,
+    /// Changes made, in the order of the lines.
+    pub migrations: Vec<Migration>,
+}
+
+/// Rewrites the deprecated entries of a desktop file.
+///
+/// The `Encoding` key is removed, since desktop files are always UTF-8. The deprecated categories
+/// are replaced, or removed if they have no replacement, and the deprecated field codes of `Exec`
+/// are removed when they are a whole argument. A field code that is part of a bigger argument
+/// can't be removed without changing the command, so it's left to be fixed by hand.
+pub fn migrate(input: &str) -> Migrated {
+    let mut migrations = Vec::new();
+
+    let output = rewrite_lines(input, |line, group, text| {
+        let mut report = |message: String| migrations.push(Migration { line, message });
+
+        migrate_line(group?, text, &mut report)
+    });
+
+    Migrated { output, migrations }
+}
+
+fn migrate_line(group: &str, text: &str, report: &mut impl FnMut(String)) -> Option<Edit> {
+    let tokens = tokenize(text);
+
+    let mut iter = tokens.iter();
+    let key = iter.next().filter(|token| token.kind == TokenKind::Key)?;
+    // Only the entries without a locale are migrated
+    let separator = iter
+        .next()
+        .filter(|token| token.kind == TokenKind::Separator)?;
+
+    let is_main = group == DESKTOP_ENTRY_GROUP;
+    let is_action = group.starts_with(DESKTOP_ACTION_PREFIX);
+
+    let (prefix, raw_value) = text.split_at(separator.span.end);
+
+    let migrated = match key.text(text) {
+        "Encoding" if is_main => {
+            report("removed deprecated key `Encoding`".to_string());
+
+            return Some(Edit::Remove);
+        }
+        "Categories" if is_main => migrate_categories(&unescape(raw_value)?, report)?,
+        "Exec" if is_main || is_action => migrate_exec(&unescape(raw_value)?, report)?,
+        _ => return None,
+    };
+
+    let value = Value::String(Cow::Owned(migrated));
+
+    Some(Edit::Replace(format!("{prefix}{value}")))
+}
+
+fn unescape(value: &str) -> Option<Cow<'_, str>> {
+    parse_escaped_string_with::<()>(UnknownEscapes::Reject)(value)
+        .ok()
+        .map(|(_, value)| value)
+}
+
+/// Replaces the deprecated categories, returns [`None`] if there are none.
+fn migrate_categories(value: &str, report: &mut impl FnMut(String)) -> Option<String> {
+    let mut migrated = String::with_capacity(value.len());
+    let mut changed = false;
+
+    for category in value.split(';').filter(|category| !category.is_empty()) {
+        let category = match categories::replacement(category) {
+            Some(Some(replacement)) => {
+                report(format!(
+                    "replaced deprecated category `{category}` with `{replacement}`"
+                ));
+
+                changed = true;
+
+                replacement
+            }
+            Some(None) => {
+                report(format!("removed deprecated category `{category}`"));
+
+                changed = true;
+
+                continue;
+            }
+            None => category,
+        };
+
+        migrated.push_str(category);
+        migrated.push(';');
+    }
+
+    changed.then_some(migrated)
+}
+
+/// Removes the deprecated field codes that are a whole argument, returns [`None`] if there are
+/// none.
+fn migrate_exec(command: &str, report: &mut impl FnMut(String)) -> Option<String> {
+    let is_separator = |c: Option<char>| c.is_none_or(char::is_whitespace);
+
+    let removed = check_field_codes(command)
+        .into_iter()
+        .filter_map(|issue| {
+            let ExecIssueKind::DeprecatedFieldCode(code) = issue.kind else {
+                return None;
+            };
+
+            let Range { start, end } = issue.span;
+
+            let standalone = is_separator(command[..start].chars().next_back())
+                && is_separator(command[end..].chars().next());
+
+            if !standalone {
+                return None;
+            }
+
+            report(format!("removed deprecated field code `%{code}` from Exec"));
+
+            // Remove the whitespace before the argument, or after it if it's the first
+            let before = command[..start].trim_end().len();
+            let range = if before > 0 {
+                before..end
+            } else {
+                start..end + (command[end..].len() - command[end..].trim_start().len())
+            };
+
+            Some(range)
+        })
+        .collect::<Vec<_>>();
+
+    if removed.is_empty() {
+        return None;
+    }
+
+    let mut migrated = String::with_capacity(command.len());
+    let mut last = 0;
+
+    for range in removed {
+        migrated.push_str(&command[last..range.start]);
+        last = range.end;
+    }
+
+    migrated.push_str(&command[last..]);
+
+    Some(migrated)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_migrate_deprecated_entries() {
+        let input = "[Desktop Entry]\r\nEncoding=UTF-8\r\n# Categories\r\nCategories=Application;Utility;\r\nExec=foo %d --dir=%D %f %m\r\nName=Foo %d\r\n[Desktop Action New]\r\nExec=foo --new  %n\r\n";
+
+        let migrated = migrate(input);
+
+        assert_eq!(
+            "[Desktop Entry]\r\n# Categories\r\nCategories=Utility;\r\nExec=foo --dir=%D %f\r\nName=Foo %d\r\n[Desktop Action New]\r\nExec=foo --new\r\n",
+            migrated.output
+        );
+        assert_eq!(
+            vec![
+                "line 2: removed deprecated key `Encoding`",
+                "line 4: removed deprecated category `Application`",
+                "line 5: removed deprecated field code `%d` from Exec",
+                "line 5: removed deprecated field code `%m` from Exec",
+                "line 8: removed deprecated field code `%n` from Exec",
+            ],
+            migrated
+                .migrations
+                .iter()
+                .map(Migration::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_keep_current_file() {
+        let input = include_str!("../example/file.desktop");
+
+        let migrated = migrate(input);
+
+        assert_eq!(input, migrated.output);
+        assert!(migrated.migrations.is_empty());
+    }
+}
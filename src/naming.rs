@@ -0,0 +1,203 @@
+//! Naming of the desktop files, following the recommended reverse-DNS convention.
+//!
+//! The specification recommends a desktop file ID like `org.example.FooViewer.desktop`: the
+//! reversed domain name of the developer followed by the name of the application, that without
+//! the extension is a valid D-Bus well-known name.
+
+use std::fmt::Display;
+
+use crate::loader::DESKTOP_EXTENSION;
+
+/// Maximum length of a D-Bus well-known name.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Issue with the name of a desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IdIssue {
+    /// The name doesn't have the `.desktop` extension.
+    MissingExtension,
+    /// The name has less than two elements separated by `.`, so it's not in reverse-DNS form.
+    NotReverseDns,
+    /// An element between the `.` is empty.
+    EmptyElement,
+    /// An element starts with a digit.
+    LeadingDigit(String),
+    /// Character not allowed in a D-Bus well-known name.
+    InvalidCharacter(char),
+    /// The name without the extension is longer than 255 characters.
+    TooLong,
+}
+
+impl Display for IdIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdIssue::MissingExtension => write!(f, "missing the `.{DESKTOP_EXTENSION}` extension"),
+            IdIssue::NotReverseDns => write!(f, "not in reverse-DNS form, like `org.example.App`"),
+            IdIssue::EmptyElement => write!(f, "empty element between the `.`"),
+            IdIssue::LeadingDigit(element) => {
+                write!(f, "element `{element}` starts with a digit")
+            }
+            IdIssue::InvalidCharacter(c) => write!(f, "invalid character `{c}`"),
+            IdIssue::TooLong => write!(f, "longer than {MAX_NAME_LENGTH} characters"),
+        }
+    }
+}
+
+/// Checks a desktop file ID, like `org.example.App.desktop`, against the naming convention.
+///
+/// Returns the issues found, if the ID follows the convention the result is empty.
+pub fn check_id(id: &str) -> Vec<IdIssue> {
+    let name = match id
+        .strip_suffix(DESKTOP_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+    {
+        Some(name) => name,
+        None => return vec![IdIssue::MissingExtension],
+    };
+
+    let mut issues = check_name(name);
+
+    if name.split('.').count() < 2 {
+        issues.insert(0, IdIssue::NotReverseDns);
+    }
+
+    issues
+}
+
+/// Returns true if the ID follows the naming convention.
+pub fn is_valid_id(id: &str) -> bool {
+    check_id(id).is_empty()
+}
+
+/// Checks the name without the extension is a valid D-Bus well-known name.
+fn check_name(name: &str) -> Vec<IdIssue> {
+    let mut issues = Vec::new();
+
+    if name.len() > MAX_NAME_LENGTH {
+        issues.push(IdIssue::TooLong);
+    }
+
+    for element in name.split('.') {
+        match element.chars().next() {
+            None => {
+                if !issues.contains(&IdIssue::EmptyElement) {
+                    issues.push(IdIssue::EmptyElement);
+                }
+            }
+            Some(first) if first.is_ascii_digit() => {
+                issues.push(IdIssue::LeadingDigit(element.to_string()));
+            }
+            Some(_) => {}
+        }
+
+        for c in element.chars() {
+            let issue = IdIssue::InvalidCharacter(c);
+
+            if !is_name_char(c) && !issues.contains(&issue) {
+                issues.push(issue);
+            }
+        }
+    }
+
+    issues
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Derives a desktop file ID from the domain of the developer and the name of the application.
+///
+/// The domain is reversed and the name is converted to camel case, dropping the characters not
+/// allowed in the ID, so `("example.org", "Foo viewer")` gives `org.example.FooViewer.desktop`.
+/// The `-` of the domain is replaced by `_` and the elements starting with a digit are prefixed
+/// with `_`, as recommended for D-Bus names.
+///
+/// Returns [`None`] if the domain or the name don't have any valid character, or if the ID is too
+/// long.
+pub fn derive_id(domain: &str, app_name: &str) -> Option<String> {
+    let mut elements = domain
+        .rsplit('.')
+        .map(|element| {
+            element
+                .chars()
+                .filter(|c| is_name_char(*c))
+                .map(|c| if c == '-' { '_' } else { c })
+                .collect::<String>()
+        })
+        .filter(|element| !element.is_empty())
+        .collect::<Vec<_>>();
+
+    if elements.is_empty() {
+        return None;
+    }
+
+    let name = app_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect::<String>();
+
+    if name.is_empty() {
+        return None;
+    }
+
+    elements.push(name);
+
+    let name = elements
+        .into_iter()
+        .map(|element| {
+            if element.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("_{element}")
+            } else {
+                element
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+
+    (name.len() <= MAX_NAME_LENGTH).then(|| format!("{name}.{DESKTOP_EXTENSION}"))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_check_id() {
+        assert!(is_valid_id("org.example.Foo_Viewer.desktop"));
+        assert_eq!(vec![IdIssue::MissingExtension], check_id("org.example.Foo"));
+        assert_eq!(vec![IdIssue::NotReverseDns], check_id("foo.desktop"));
+        assert_eq!(
+            vec![
+                IdIssue::EmptyElement,
+                IdIssue::LeadingDigit("2d".to_string()),
+                IdIssue::InvalidCharacter(' '),
+            ],
+            check_id("org..2d.Foo Bar.desktop")
+        );
+    }
+
+    #[test]
+    fn should_derive_id() {
+        assert_eq!(
+            Some("org.example.FooViewer.desktop".to_string()),
+            derive_id("example.org", "Foo viewer")
+        );
+        assert_eq!(
+            Some("com.my_company._3DPaint.desktop".to_string()),
+            derive_id("my-company.com", "3D Paint!")
+        );
+        assert_eq!(None, derive_id("example.org", "!!"));
+        assert_eq!(None, derive_id("", "Foo"));
+    }
+}
@@ -0,0 +1,258 @@
+use std::borrow::Cow;
+
+use crate::{raw::DESKTOP_ESCAPES, Key, Locale};
+
+/// Options to customize the parsing of a desktop file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions<'o> {
+    /// Chain of locales to keep the translations for.
+    ///
+    /// If set, only the localized keys matching at least one of the locales are kept, together
+    /// with all the non localized keys. If [`None`] all the translations are kept.
+    pub locales: Option<Vec<Locale<'o>>>,
+    /// Groups to keep, all the other groups are skipped.
+    ///
+    /// If [`None`] all the groups are kept.
+    pub groups: Option<Vec<Cow<'o, str>>>,
+    /// Keys to keep, including their translations, all the other keys are skipped.
+    ///
+    /// If [`None`] all the keys are kept.
+    pub keys: Option<Vec<Cow<'o, str>>>,
+    /// Limits on the size of the parsed document.
+    pub limits: Limits,
+    /// How to handle unknown escape sequences in the values.
+    pub unknown_escapes: UnknownEscapes,
+    /// Syntax of the parsed file.
+    pub dialect: Dialect,
+    /// Keep the comments and empty lines, to write them back with the document.
+    ///
+    /// Disabled by default, enable it to edit a file without losing them. They are attached to the
+    /// group header or entry following them.
+    pub keep_comments: bool,
+    /// Reserve the capacity of the groups and entries before parsing, from the estimate of
+    /// [`estimate_capacity`](crate::estimate_capacity).
+    ///
+    /// Enabled by default, it avoids growing the maps while parsing large files.
+    pub reserve_capacity: bool,
+}
+
+impl<'o> Default for ParseOptions<'o> {
+    fn default() -> Self {
+        Self {
+            locales: None,
+            groups: None,
+            keys: None,
+            limits: Limits::default(),
+            unknown_escapes: UnknownEscapes::default(),
+            dialect: Dialect::default(),
+            keep_comments: false,
+            reserve_capacity: true,
+        }
+    }
+}
+
+/// Rules of the syntax of a key file, to parse the desktop files or the other formats with the
+/// same structure.
+///
+/// The presets are [`Dialect::DESKTOP_ENTRY`], [`Dialect::GKEY_FILE`] and
+/// [`Dialect::RELAXED_INI`], a custom dialect can start from one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dialect {
+    /// Characters separating the key from the value.
+    pub separators: &'static str,
+    /// Characters starting a comment line.
+    pub comment_chars: &'static str,
+    /// The values can end with a comment, starting with a comment character after a space.
+    ///
+    /// The inline comments are dropped.
+    pub inline_comments: bool,
+    /// The lines can start with spaces.
+    pub leading_spaces: bool,
+    /// Escape sequences of the values, the character following the `\\` and its replacement.
+    pub escapes: &'static [(char, &'static str)],
+    /// The group headers can contain only ASCII characters.
+    pub ascii_group_headers: bool,
+    /// The keys can contain only ASCII alphanumeric characters and `-`.
+    ///
+    /// Otherwise they can contain any character except the separators, the brackets and the
+    /// control characters.
+    pub restricted_keys: bool,
+    /// The values are typed by their content, as a boolean, a number or a string.
+    ///
+    /// Otherwise they are always a string.
+    pub typed_values: bool,
+    /// Handling of the keys repeated in the same group.
+    pub duplicate_keys: DuplicateKeys,
+}
+
+impl Dialect {
+    /// Syntax of the desktop entry specification.
+    pub const DESKTOP_ENTRY: Self = Self {
+        separators: "=",
+        comment_chars: "#",
+        inline_comments: false,
+        leading_spaces: false,
+        escapes: DESKTOP_ESCAPES,
+        ascii_group_headers: true,
+        restricted_keys: true,
+        typed_values: true,
+        duplicate_keys: DuplicateKeys::KeepLast,
+    };
+
+    /// Syntax of the key files of GLib, as read by `GKeyFile`.
+    ///
+    /// The values are not typed by their content, they are read with the functions of the
+    /// [`gkeyfile`](crate::gkeyfile) module. `GKeyFile` loads the files with unknown escape
+    /// sequences, use [`UnknownEscapes::Preserve`] to do the same.
+    pub const GKEY_FILE: Self = Self {
+        separators: "=",
+        comment_chars: "#",
+        inline_comments: false,
+        leading_spaces: true,
+        escapes: DESKTOP_ESCAPES,
+        ascii_group_headers: false,
+        restricted_keys: false,
+        typed_values: false,
+        duplicate_keys: DuplicateKeys::KeepLast,
+    };
+
+    /// Relaxed syntax of the generic INI files.
+    ///
+    /// The separator can also be `:`, the comments can also start with `;`, and the values can
+    /// have an inline comment.
+    pub const RELAXED_INI: Self = Self {
+        separators: "=:",
+        comment_chars: "#;",
+        inline_comments: true,
+        leading_spaces: true,
+        escapes: DESKTOP_ESCAPES,
+        ascii_group_headers: false,
+        restricted_keys: false,
+        typed_values: true,
+        duplicate_keys: DuplicateKeys::KeepLast,
+    };
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::DESKTOP_ENTRY
+    }
+}
+
+/// Options to customize the writing of a document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WriteOptions {
+    /// Order of the entries in the groups.
+    pub key_order: KeyOrder,
+    /// Handling of the non ASCII characters in the string values.
+    pub non_ascii_strings: NonAsciiPolicy,
+}
+
+/// Order of the entries of a group in the written document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum KeyOrder {
+    /// Keep the order of the document.
+    #[default]
+    Document,
+    /// Order the keys like a hand written file, so the generated files have stable diffs.
+    ///
+    /// The keys of the specification come first in its order, starting with `Type`, `Version`
+    /// and `Name`, followed by the other keys and the `X-` extensions, in the order of the
+    /// document. The translations follow their default value, sorted by locale.
+    Canonical,
+}
+
+/// Handling of the non ASCII characters in a value of type `string` when writing a document.
+///
+/// The specification allows only ASCII in the `string` values, like `Exec` or `URL`, while the
+/// `localestring` values are UTF-8. A value is a `string` if it's a [`Value::String`] or the key
+/// is a `string` of the [registry](crate::keys).
+///
+/// [`Value::String`]: crate::Value::String
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NonAsciiPolicy {
+    /// Write the value as it is, it will be read back as a `localestring`.
+    #[default]
+    Keep,
+    /// Fail with a [`WriteError::NonAsciiString`](crate::WriteError::NonAsciiString).
+    Reject,
+    /// Percent-encode the UTF-8 bytes of the non ASCII characters, like in a URL, so `é` is
+    /// written as `%C3%A9`.
+    Escape,
+    /// Replace each non ASCII character with a `?`, losing it.
+    Downgrade,
+}
+
+/// Handling of a key repeated in the same group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Keep the last value.
+    #[default]
+    KeepLast,
+    /// Keep the first value, the following ones are skipped.
+    KeepFirst,
+    /// Fail to parse the document.
+    Reject,
+}
+
+/// Handling of the escape sequences not defined by the specification, like `\\x`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnknownEscapes {
+    /// Fail to parse the value.
+    #[default]
+    Reject,
+    /// Keep the escape sequence verbatim in the value and report a
+    /// [`ParseWarning::UnknownEscape`](crate::ParseWarning::UnknownEscape).
+    Preserve,
+}
+
+/// Limits to bound the memory and time spent parsing untrusted input.
+///
+/// When a limit is exceeded the parser fails with [`nom::error::ErrorKind::TooLarge`]. A
+/// [`None`] limit is not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// Maximum size in bytes of the file.
+    pub max_file_size: Option<usize>,
+    /// Maximum length in bytes of a line, excluding the line ending.
+    pub max_line_length: Option<usize>,
+    /// Maximum number of groups in the document.
+    pub max_groups: Option<usize>,
+    /// Maximum number of entries in the document, across all groups.
+    pub max_entries: Option<usize>,
+}
+
+impl Limits {
+    pub(crate) fn exceeds(limit: Option<usize>, value: usize) -> bool {
+        limit.is_some_and(|limit| value > limit)
+    }
+}
+
+impl<'o> ParseOptions<'o> {
+    /// Checks if the group should be kept in the parsed document.
+    pub(crate) fn keep_group(&self, header: &str) -> bool {
+        self.groups
+            .as_ref()
+            .is_none_or(|groups| groups.iter().any(|group| group == header))
+    }
+
+    /// Checks if the key should be kept in the parsed document.
+    pub(crate) fn keep_key(&self, key: &Key) -> bool {
+        let name = match key {
+            Key::Simple(key) | Key::Localized { key, .. } => key,
+        };
+
+        if let Some(keys) = &self.keys {
+            if !keys.iter().any(|key| key == name) {
+                return false;
+            }
+        }
+
+        match (key, &self.locales) {
+            (Key::Simple(_), _) | (Key::Localized { .. }, None) => true,
+            (Key::Localized { locale, .. }, Some(locales)) => locales
+                .iter()
+                .any(|chain| chain.match_key(locale).is_some()),
+        }
+    }
+}
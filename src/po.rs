@@ -0,0 +1,783 @@
+//! Gettext catalogs of the translatable keys of a desktop file.
+//!
+//! The values of the `localestring` keys, like `Name`, `GenericName`, `Comment` and `Keywords`,
+//! and the names of the actions, are extracted in a PO template, so translators can use their
+//! usual tools instead of editing the desktop file. The context of each message is the key,
+//! prefixed by the group for the keys not in the main group, like `Desktop Action New/Name`.
+//!
+//! The translated catalogs are merged back in the desktop file with [`merge`], like
+//! `msgfmt --desktop` does.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Write},
+};
+
+use crate::{
+    keys::{self, ValueType},
+    lint::{rewrite_lines, Edit},
+    raw::parse_escaped_string_with,
+    token::{tokenize, TokenKind},
+    DesktopEntry, LineEnding, Locale, UnknownEscapes, Value, DESKTOP_ACTION_PREFIX,
+    DESKTOP_ENTRY_GROUP,
+};
+
+/// Message of a [`Catalog`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Message {
+    /// Group and key of the message, see the [module documentation](self).
+    pub context: String,
+    /// Untranslated value.
+    pub id: String,
+    /// Translated value, empty if not translated.
+    pub translation: String,
+    /// Locations of the message in the source files, like `foo.desktop:3`.
+    pub references: Vec<String>,
+    /// The translation needs to be reviewed.
+    pub fuzzy: bool,
+}
+
+/// Gettext catalog, written in the PO format by [`Display`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    /// Language of the translations, [`None`] for a template.
+    pub language: Option<String>,
+    pub messages: Vec<Message>,
+}
+
+impl Catalog {
+    /// Parses a catalog in the PO format.
+    ///
+    /// The language is read from the `Language` field of the header. The obsolete messages and
+    /// the plural forms are ignored.
+    pub fn parse(input: &str) -> Result<Self, PoError> {
+        let mut parser = Parser::default();
+
+        for (i, line) in input.lines().enumerate() {
+            parser.line(line.trim()).ok_or(PoError { line: i + 1 })?;
+        }
+
+        parser.finish();
+
+        Ok(parser.catalog)
+    }
+}
+
+/// Field of the message the strings are added to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    Translation,
+    /// Plural forms, not supported.
+    Ignored,
+}
+
+#[derive(Debug, Default)]
+struct Parser {
+    catalog: Catalog,
+    message: Message,
+    field: Option<Field>,
+    /// The message has a translation, the next context or id starts a new message.
+    translated: bool,
+}
+
+impl Parser {
+    fn line(&mut self, line: &str) -> Option<()> {
+        if line.is_empty() || line.starts_with("#~") {
+            return Some(());
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            if self.translated {
+                self.finish();
+            }
+
+            if let Some(references) = comment.strip_prefix(':') {
+                self.message
+                    .references
+                    .extend(references.split_whitespace().map(str::to_string));
+            } else if let Some(flags) = comment.strip_prefix(',') {
+                self.message.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+
+            return Some(());
+        }
+
+        let (field, value) = if line.starts_with('"') {
+            (self.field?, unquote(line)?)
+        } else {
+            let (keyword, value) = line.split_once(char::is_whitespace)?;
+
+            let field = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgstr" | "msgstr[0]" => Field::Translation,
+                "msgid_plural" => Field::Ignored,
+                keyword if keyword.starts_with("msgstr[") => Field::Ignored,
+                _ => return None,
+            };
+
+            if self.translated && matches!(field, Field::Context | Field::Id) {
+                self.finish();
+            }
+
+            self.translated |= field == Field::Translation;
+            self.field = Some(field);
+
+            (field, unquote(value.trim())?)
+        };
+
+        let target = match field {
+            Field::Context => &mut self.message.context,
+            Field::Id => &mut self.message.id,
+            Field::Translation => &mut self.message.translation,
+            Field::Ignored => return Some(()),
+        };
+
+        target.push_str(&value);
+
+        Some(())
+    }
+
+    /// Adds the current message to the catalog, or reads the language if it's the header.
+    fn finish(&mut self) {
+        let message = std::mem::take(&mut self.message);
+
+        self.field = None;
+
+        if !std::mem::take(&mut self.translated) {
+            return;
+        }
+
+        if message.id.is_empty() && message.context.is_empty() {
+            self.catalog.language = message
+                .translation
+                .lines()
+                .find_map(|line| line.strip_prefix("Language:"))
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty());
+        } else {
+            self.catalog.messages.push(message);
+        }
+    }
+}
+
+/// Returns the content of a quoted PO string, unescaped.
+fn unquote(value: &str) -> Option<String> {
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut unquoted = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+
+            continue;
+        }
+
+        let unescaped = match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            c => c,
+        };
+
+        unquoted.push(unescaped);
+    }
+
+    Some(unquoted)
+}
+
+/// Error for a malformed PO file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid PO file at line {line}")]
+pub struct PoError {
+    line: usize,
+}
+
+impl PoError {
+    /// Line of the error, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// Issue found merging the translations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// Line of the entry in the input, starting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Source with the translations merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged {
+    pub output: String,
+    /// Conflicts found, in the order of the lines.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merges the translations of the catalogs in the desktop file.
+///
+/// The translations are added as `Key[language]=` entries after the untranslated key, or replace
+/// the existing translation for the language. The fuzzy and empty translations, the catalogs
+/// without a language and the messages whose id doesn't match the current value are skipped.
+/// Replacing a different translation in the file, or two catalogs with different translations
+/// for the same language, are reported as conflicts: the first catalog wins.
+pub fn merge(input: &str, catalogs: &[Catalog]) -> Merged {
+    // Translations by context and id, in the order of the catalogs
+    let mut translations = HashMap::<(&str, &str), Vec<(&str, &str)>>::new();
+
+    for catalog in catalogs {
+        let Some(language) = &catalog.language else {
+            continue;
+        };
+
+        for message in &catalog.messages {
+            if message.fuzzy || message.translation.is_empty() {
+                continue;
+            }
+
+            translations
+                .entry((&message.context, &message.id))
+                .or_default()
+                .push((language, &message.translation));
+        }
+    }
+
+    let line_ending = LineEnding::detect(input).as_str();
+
+    // Untranslated value of each key and the languages already translated in the file
+    let mut values = HashMap::new();
+    let mut present = HashSet::new();
+
+    rewrite_lines(input, |_, group, line| {
+        let (group, key, locale, value) = translatable_entry(group?, line)?;
+        let context = context(group, key);
+
+        match locale {
+            Some(locale) => {
+                present.insert((context, locale.to_string()));
+            }
+            None => {
+                values.insert(context, value.into_owned());
+            }
+        }
+
+        None
+    });
+
+    let mut conflicts = Vec::new();
+
+    let output = rewrite_lines(input, |number, group, line| {
+        let (group, key, locale, value) = translatable_entry(group?, line)?;
+        let context = context(group, key);
+
+        let id = values.get(&context)?;
+        let resolved = resolve(&translations, &context, id, number, &mut conflicts);
+
+        let prefix = &line[..line.len() - line_value(line).len()];
+
+        match locale {
+            Some(locale) => {
+                let (_, translation) = resolved
+                    .into_iter()
+                    .find(|(language, _)| *language == locale)?;
+
+                if translation == value {
+                    return None;
+                }
+
+                conflicts.push(Conflict {
+                    line: number,
+                    message: format!(
+                        "replaced translation `{value}` of `{context}` for `{locale}` with `{translation}`"
+                    ),
+                });
+
+                let value = Value::LocaleString(Cow::Borrowed(translation));
+
+                Some(Edit::Replace(format!("{prefix}{value}")))
+            }
+            None => {
+                let mut text = line.to_string();
+
+                for (language, translation) in resolved {
+                    if present.contains(&(context.clone(), language.to_string())) {
+                        continue;
+                    }
+
+                    let value = Value::LocaleString(Cow::Borrowed(translation));
+
+                    write!(text, "{line_ending}{key}[{language}]={value}")
+                        .expect("writing to a string doesn't fail");
+                }
+
+                (text.len() != line.len()).then_some(Edit::Replace(text))
+            }
+        }
+    });
+
+    Merged { output, conflicts }
+}
+
+/// Returns the translation for each language, reporting the conflicts between the catalogs.
+fn resolve<'c>(
+    translations: &HashMap<(&str, &str), Vec<(&'c str, &'c str)>>,
+    context: &str,
+    id: &str,
+    line: usize,
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<(&'c str, &'c str)> {
+    let mut resolved = Vec::<(&str, &str)>::new();
+
+    for (language, translation) in translations.get(&(context, id)).into_iter().flatten() {
+        match resolved.iter().find(|(resolved, _)| resolved == language) {
+            Some((_, first)) if first != translation => conflicts.push(Conflict {
+                line,
+                message: format!(
+                    "different translations of `{context}` for `{language}`, kept `{first}`"
+                ),
+            }),
+            Some(_) => {}
+            None => resolved.push((language, translation)),
+        }
+    }
+
+    resolved
+}
+
+/// Returns the raw value of an entry line, after the separator.
+fn line_value(line: &str) -> &str {
+    tokenize(line)
+        .iter()
+        .find(|token| token.kind == TokenKind::Separator)
+        .map_or("", |separator| &line[separator.span.end..])
+}
+
+/// Extracts the translatable messages of a desktop file in a template.
+///
+/// The name of the file is used in the references of the messages.
+pub fn extract(input: &str, file_name: &str) -> Catalog {
+    let mut messages = Vec::new();
+
+    rewrite_lines(input, |number, group, line| {
+        let (group, key, locale, value) = translatable_entry(group?, line)?;
+
+        if locale.is_none() {
+            messages.push(Message {
+                context: context(group, key),
+                id: value.into_owned(),
+                references: vec![format!("{file_name}:{number}")],
+                ..Message::default()
+            });
+        }
+
+        None
+    });
+
+    Catalog {
+        language: None,
+        messages,
+    }
+}
+
+/// Extracts the translatable messages of a desktop file with the translations for the locale.
+///
+/// The translation of a message is the one with the exact locale of the key, the fallbacks are
+/// not used.
+pub fn extract_translations(input: &str, file_name: &str, locale: &Locale) -> Catalog {
+    let mut catalog = extract(input, file_name);
+    catalog.language = Some(locale.to_string());
+
+    let locale = locale.to_string();
+
+    rewrite_lines(input, |_, group, line| {
+        let (group, key, line_locale, value) = translatable_entry(group?, line)?;
+
+        if line_locale != Some(locale.as_str()) {
+            return None;
+        }
+
+        let context = context(group, key);
+
+        if let Some(message) = catalog
+            .messages
+            .iter_mut()
+            .find(|message| message.context == context)
+        {
+            message.translation = value.into_owned();
+        }
+
+        None
+    });
+
+    catalog
+}
+
+/// Extracts the translatable messages of a parsed document in a template, without references.
+pub fn extract_document(document: &DesktopEntry) -> Catalog {
+    let messages = document
+        .groups()
+        .flat_map(|(group, entries)| {
+            entries.iter().filter_map(move |(key, value)| {
+                if key.locale().is_some() || !is_translatable(group, key.name()) {
+                    return None;
+                }
+
+                let (crate::Value::String(value) | crate::Value::LocaleString(value)) = value
+                else {
+                    return None;
+                };
+
+                Some(Message {
+                    context: context(group, key.name()),
+                    id: value.to_string(),
+                    ..Message::default()
+                })
+            })
+        })
+        .collect();
+
+    Catalog {
+        language: None,
+        messages,
+    }
+}
+
+/// Returns true if the key of the group is a standard `localestring` key.
+pub fn is_translatable(group: &str, key: &str) -> bool {
+    let info = if group == DESKTOP_ENTRY_GROUP {
+        keys::lookup(key)
+    } else if group.starts_with(DESKTOP_ACTION_PREFIX) {
+        keys::lookup_action(key)
+    } else {
+        None
+    };
+
+    info.is_some_and(|info| info.value_type == ValueType::LocaleString)
+}
+
+/// Returns the context of the messages of a key.
+pub fn context(group: &str, key: &str) -> String {
+    if group == DESKTOP_ENTRY_GROUP {
+        key.to_string()
+    } else {
+        format!("{group}/{key}")
+    }
+}
+
+/// Returns the group, key, locale and unescaped value of a translatable entry line.
+pub(crate) fn translatable_entry<'a>(
+    group: &'a str,
+    line: &'a str,
+) -> Option<(&'a str, &'a str, Option<&'a str>, Cow<'a, str>)> {
+    let tokens = tokenize(line);
+
+    let key = tokens
+        .first()
+        .filter(|token| token.kind == TokenKind::Key)?
+        .text(line);
+
+    if !is_translatable(group, key) {
+        return None;
+    }
+
+    let separator = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Separator)?;
+    let locale = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Locale)
+        .filter(|token| token.span.start < separator.span.start)
+        .map(|token| token.text(line));
+
+    let (_, value) =
+        parse_escaped_string_with::<()>(UnknownEscapes::Preserve)(&line[separator.span.end..])
+            .ok()?;
+
+    Some((group, key, locale, value))
+}
+
+impl Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut header = String::from("Content-Type: text/plain; charset=UTF-8\n");
+        header.push_str("Content-Transfer-Encoding: 8bit\n");
+
+        if let Some(language) = &self.language {
+            writeln!(header, "Language: {language}")?;
+        }
+
+        f.write_str("msgid \"\"\n")?;
+        write_string(f, "msgstr", &header)?;
+
+        for message in &self.messages {
+            writeln!(f)?;
+
+            if !message.references.is_empty() {
+                writeln!(f, "#: {}", message.references.join(" "))?;
+            }
+
+            if message.fuzzy {
+                writeln!(f, "#, fuzzy")?;
+            }
+
+            write_string(f, "msgctxt", &message.context)?;
+            write_string(f, "msgid", &message.id)?;
+            write_string(f, "msgstr", &message.translation)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a keyword with a quoted string, split after the new lines.
+fn write_string(f: &mut fmt::Formatter<'_>, keyword: &str, value: &str) -> fmt::Result {
+    let lines = value.split_inclusive('\n').collect::<Vec<_>>();
+
+    if lines.len() > 1 {
+        writeln!(f, "{keyword} \"\"")?;
+    } else {
+        write!(f, "{keyword} ")?;
+    }
+
+    if lines.is_empty() {
+        return writeln!(f, "\"\"");
+    }
+
+    for line in lines {
+        f.write_char('"')?;
+
+        for c in line.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\t' => f.write_str("\\t")?,
+                '\r' => f.write_str("\\r")?,
+                c => f.write_char(c)?,
+            }
+        }
+
+        writeln!(f, "\"")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = "[Desktop Entry]\nType=Application\nName=Foo\nName[de]=Fuh\nComment=Say \"hi\"\\nnow\nKeywords=foo;bar;\nExec=foo\n\n[Desktop Action New]\nName=New window\nExec=foo --new\n";
+
+    #[test]
+    fn should_extract_template() {
+        let catalog = extract(INPUT, "foo.desktop");
+
+        assert_eq!(
+            r#"msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+"Content-Transfer-Encoding: 8bit\n"
+
+#: foo.desktop:3
+msgctxt "Name"
+msgid "Foo"
+msgstr ""
+
+#: foo.desktop:5
+msgctxt "Comment"
+msgid ""
+"Say \"hi\"\n"
+"now"
+msgstr ""
+
+#: foo.desktop:6
+msgctxt "Keywords"
+msgid "foo;bar;"
+msgstr ""
+
+#: foo.desktop:10
+msgctxt "Desktop Action New/Name"
+msgid "New window"
+msgstr ""
+"#,
+            catalog.to_string()
+        );
+        assert_eq!(
+            catalog
+                .messages
+                .iter()
+                .map(|message| (&message.context, &message.id))
+                .collect::<Vec<_>>(),
+            extract_document(&DesktopEntry::parse(INPUT).unwrap())
+                .messages
+                .iter()
+                .map(|message| (&message.context, &message.id))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_extract_translations() {
+        let locale = Locale::parse("de").unwrap();
+        let catalog = extract_translations(INPUT, "foo.desktop", &locale);
+
+        assert_eq!(Some("de".to_string()), catalog.language);
+        assert_eq!(
+            vec![
+                ("Name", "Fuh"),
+                ("Comment", ""),
+                ("Keywords", ""),
+                ("Desktop Action New/Name", "")
+            ],
+            catalog
+                .messages
+                .iter()
+                .map(|message| (message.context.as_str(), message.translation.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_parse_catalog() {
+        let catalog = Catalog::parse(
+            r#"# German translations
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+"Language: de\n"
+
+#: foo.desktop:3
+msgctxt "Name"
+msgid "Foo"
+msgstr "Fuh"
+
+#, fuzzy
+msgctxt "Comment"
+msgid ""
+"Say \"hi\"\n"
+"now"
+msgstr "Sag \"hallo\""
+
+msgid "file"
+msgid_plural "files"
+msgstr[0] "Datei"
+msgstr[1] "Dateien"
+
+#~ msgid "Old"
+#~ msgstr "Alt"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("de".to_string()), catalog.language);
+        assert_eq!(
+            vec![
+                Message {
+                    context: "Name".to_string(),
+                    id: "Foo".to_string(),
+                    translation: "Fuh".to_string(),
+                    references: vec!["foo.desktop:3".to_string()],
+                    fuzzy: false,
+                },
+                Message {
+                    context: "Comment".to_string(),
+                    id: "Say \"hi\"\nnow".to_string(),
+                    translation: "Sag \"hallo\"".to_string(),
+                    references: Vec::new(),
+                    fuzzy: true,
+                },
+                Message {
+                    context: String::new(),
+                    id: "file".to_string(),
+                    translation: "Datei".to_string(),
+                    references: Vec::new(),
+                    fuzzy: false,
+                },
+            ],
+            catalog.messages
+        );
+        assert_eq!(Err(PoError { line: 1 }), Catalog::parse("msgid Foo\n"));
+    }
+
+    #[test]
+    fn should_merge_translations() {
+        let translated = |language: &str, messages: &[(&str, &str, &str, bool)]| Catalog {
+            language: Some(language.to_string()),
+            messages: messages
+                .iter()
+                .map(|(context, id, translation, fuzzy)| Message {
+                    context: context.to_string(),
+                    id: id.to_string(),
+                    translation: translation.to_string(),
+                    fuzzy: *fuzzy,
+                    ..Message::default()
+                })
+                .collect(),
+        };
+
+        let merged = merge(
+            INPUT,
+            &[
+                translated(
+                    "de",
+                    &[
+                        ("Name", "Foo", "Fuh neu", false),
+                        ("Keywords", "foo;bar;", "fuh;bar;", false),
+                        (
+                            "Desktop Action New/Name",
+                            "New window",
+                            "Neues Fenster",
+                            true,
+                        ),
+                    ],
+                ),
+                translated(
+                    "fr",
+                    &[
+                        ("Name", "Foo", "Fou", false),
+                        ("Comment", "Old comment", "Vieux", false),
+                        (
+                            "Desktop Action New/Name",
+                            "New window",
+                            "Nouvelle fenêtre",
+                            false,
+                        ),
+                    ],
+                ),
+                translated("fr", &[("Name", "Foo", "Fou bis", false)]),
+            ],
+        );
+
+        assert_eq!(
+            "[Desktop Entry]\nType=Application\nName=Foo\nName[fr]=Fou\nName[de]=Fuh neu\nComment=Say \"hi\"\\nnow\nKeywords=foo;bar;\nKeywords[de]=fuh;bar;\nExec=foo\n\n[Desktop Action New]\nName=New window\nName[fr]=Nouvelle fenêtre\nExec=foo --new\n",
+            merged.output
+        );
+        assert_eq!(
+            vec![
+                "line 3: different translations of `Name` for `fr`, kept `Fou`",
+                "line 4: different translations of `Name` for `fr`, kept `Fou`",
+                "line 4: replaced translation `Fuh` of `Name` for `de` with `Fuh neu`",
+            ],
+            merged
+                .conflicts
+                .iter()
+                .map(Conflict::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Queries of the entries with a path in the form `Group/Key`.
+//!
+//! Both parts of the path can contain the wildcards `*`, matching any sequence of characters, and
+//! `?`, matching a single character. The key is matched with its locale in square brackets, so
+//! `Name` matches only the default value and `Name[*]` all the translations.
+//!
+//! ```
+//! use xdg_desktop_entry::DesktopEntry;
+//!
+//! let document = DesktopEntry::parse(
+//!     "[Desktop Entry]\nName=Foo\nName[de]=Fu\n[Desktop Action new]\nName=New\nExec=foo --new\n[Desktop Action open]\nName=Open\nExec=foo --open\n",
+//! )
+//! .unwrap();
+//!
+//! let commands: Vec<String> = document
+//!     .query("Desktop Action */Exec")
+//!     .unwrap()
+//!     .iter()
+//!     .map(|entry| entry.value.to_string())
+//!     .collect();
+//!
+//! assert_eq!(vec!["foo --new", "foo --open"], commands);
+//! ```
+
+use crate::{DesktopEntry, Key, Value};
+
+/// Separator of the group and the key in a path.
+pub const PATH_SEPARATOR: char = '/';
+
+/// Error for an invalid query path.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+    #[error("the query `{0}` must be in the form `Group/Key`")]
+    MissingSeparator(String),
+    #[error("the query `{0}` has an empty group or key")]
+    EmptyPart(String),
+}
+
+/// Entry matching a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryMatch<'d, 'a> {
+    pub group: &'d str,
+    pub key: &'d Key<'a>,
+    pub value: &'d Value<'a>,
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Returns the entries matching the path, like `Desktop Entry/Name[de]` or
+    /// `Desktop Action */Exec`, in the order of the document.
+    ///
+    /// The key can't contain a `/`, so the path is split at the last one.
+    ///
+    /// # Errors
+    ///
+    /// If the path doesn't have a group and a key.
+    pub fn query<'d>(&'d self, path: &str) -> Result<Vec<QueryMatch<'d, 'a>>, QueryError> {
+        let Some((group, key)) = path.rsplit_once(PATH_SEPARATOR) else {
+            return Err(QueryError::MissingSeparator(path.to_string()));
+        };
+
+        if group.is_empty() || key.is_empty() {
+            return Err(QueryError::EmptyPart(path.to_string()));
+        }
+
+        let matches = self
+            .groups()
+            .filter(|(header, _)| wildcard_match(group, header))
+            .flat_map(|(header, entries)| {
+                entries
+                    .iter()
+                    .filter(|(entry_key, _)| wildcard_match(key, &entry_key.to_string()))
+                    .map(move |(key, value)| QueryMatch {
+                        group: header,
+                        key,
+                        value,
+                    })
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+/// Checks if the text matches the pattern with the `*` and `?` wildcards.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern and of the text it was matched at
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => {
+                let Some((star_p, star_t)) = star else {
+                    return false;
+                };
+
+                // Let the `*` match one more character
+                p = star_p + 1;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_match_wildcards() {
+        assert!(wildcard_match("Desktop Action *", "Desktop Action new"));
+        assert!(wildcard_match("Name[*]", "Name[de_DE]"));
+        assert!(wildcard_match("*a*b?", "xxaxxbc"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("Name[*]", "Name"));
+        assert!(!wildcard_match("N?me", "Nme"));
+    }
+
+    #[test]
+    fn should_query_entries() {
+        let document = DesktopEntry::parse(
+            "[Desktop Entry]\nName=Foo\nName[de]=Fu\nName[it]=Fuu\nExec=foo\n[Desktop Action new]\nName=New\nExec=foo --new\n",
+        )
+        .unwrap();
+
+        let paths = |query: &str| {
+            document
+                .query(query)
+                .unwrap()
+                .iter()
+                .map(|entry| format!("{}/{}={}", entry.group, entry.key, entry.value))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec!["Desktop Entry/Name[de]=Fu"],
+            paths("Desktop Entry/Name[de]")
+        );
+        assert_eq!(
+            vec!["Desktop Entry/Name[de]=Fu", "Desktop Entry/Name[it]=Fuu"],
+            paths("Desktop Entry/Name[*]")
+        );
+        assert_eq!(
+            vec![
+                "Desktop Entry/Exec=foo",
+                "Desktop Action new/Exec=foo --new"
+            ],
+            paths("*/Exec")
+        );
+        assert!(paths("Desktop Entry/Icon").is_empty());
+        assert_eq!(
+            Err(QueryError::MissingSeparator("Name".to_string())),
+            document.query("Name")
+        );
+        assert_eq!(
+            Err(QueryError::EmptyPart("Desktop Entry/".to_string())),
+            document.query("Desktop Entry/")
+        );
+    }
+}
@@ -0,0 +1,806 @@
+//! Low-level parsers for the lines of a desktop file.
+//!
+//! The parsers are built with [`nom`] and are generic over the nom error type, so they can be
+//! reused by other crates parsing formats with the same key-file syntax. They parse a single line
+//! (or a part of it), without the line ending.
+//!
+//! The signatures and behaviour of the functions in this module follow the semantic versioning of
+//! the crate, a breaking change to them will only happen in a major release.
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, line_ending, one_of, satisfy, space0, space1},
+    combinator::{all_consuming, cut, eof, map, map_parser, opt, peek, recognize, value, verify},
+    error::{context, ContextError, ErrorKind, ParseError as NomParseError},
+    multi::many1_count,
+    number::complete::float,
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+use crate::{Dialect, Key, Locale, UnknownEscapes, Value};
+
+/// Character starting an escape sequence.
+pub const ESCAPE_CHAR: char = '\\';
+
+/// Escape sequences of the desktop entry specification, the character following the `\\` and
+/// its replacement.
+pub const DESKTOP_ESCAPES: &[(char, &str)] = &[
+    ('s', " "),
+    ('n', "\n"),
+    ('t', "\t"),
+    ('r', "\r"),
+    ('\\', "\\"),
+    (';', ";"),
+];
+
+/// Parses a line ending or the end of the input.
+pub fn parse_end_of_line<'a, E>(input: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: NomParseError<&'a str>,
+{
+    alt((parse_line_ending, eof))(input)
+}
+
+/// Parses a `\n` or `\r\n` line ending, or a `\r` at the end of the input.
+pub fn parse_line_ending<'a, E>(input: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: NomParseError<&'a str>,
+{
+    alt((line_ending, terminated(tag("\r"), eof)))(input)
+}
+
+/// Recognizes the content of the line, excluding the line ending.
+///
+/// Differently from [`nom::character::complete::not_line_ending`] a `\r` not followed by a `\n`
+/// is considered part of the line, unless it's at the end of the input.
+pub fn not_line_ending<'a, E>(input: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: NomParseError<&'a str>,
+{
+    let mut end = input.find('\n').unwrap_or(input.len());
+
+    if input[..end].ends_with('\r') {
+        end -= 1;
+    }
+
+    Ok((&input[end..], &input[..end]))
+}
+
+/// Parse the comment until the end of the line
+pub fn parse_comment<'a, E>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map(recognize(pair(char('#'), not_line_ending)), Cow::from)(input)
+}
+
+/// Parses an empty line, peeks since the line ending is handled by the document parser.
+///
+/// It will consider lines with only whitespace as empty lines.
+pub fn parse_empty_line<'a, E>(input: &'a str) -> IResult<&'a str, Option<Cow<'a, str>>, E>
+where
+    E: NomParseError<&'a str>,
+{
+    alt((
+        terminated(
+            map(space1, |white_space| Some(Cow::from(white_space))),
+            peek(parse_end_of_line),
+        ),
+        map(peek(parse_line_ending), |_| None),
+    ))(input)
+}
+
+/// Parses a group header in the form `[Group Name]`, returning the name.
+pub fn parse_group_header<'a, E>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_dialect_group_header(Dialect::DESKTOP_ENTRY)(input)
+}
+
+/// Parses an entry in the form `Key[locale]=Value`, rejecting unknown escape sequences.
+pub fn parse_entry<'a, E>(input: &'a str) -> IResult<&'a str, (Key<'a>, Value<'a>), E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_entry_with(UnknownEscapes::Reject)(input)
+}
+
+/// Parses an entry with the given handling of the unknown escape sequences.
+pub fn parse_entry_with<'a, E>(
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (Key<'a>, Value<'a>), E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_dialect_entry_with(Dialect::DESKTOP_ENTRY, unknown_escapes)
+}
+
+/// Parses the `=` between key and value, with the optional surrounding spaces.
+pub fn parse_separator<'a, E>(input: &'a str) -> IResult<&'a str, (&'a str, char, &'a str), E>
+where
+    E: NomParseError<&'a str>,
+{
+    tuple((space0, char('='), space0))(input)
+}
+
+/// Parses a key with the optional locale, like `Name` or `Name[de_DE]`.
+pub fn parse_key<'a, E>(input: &'a str) -> IResult<&'a str, Key<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "key",
+        map(
+            pair(
+                parse_key_part,
+                opt(delimited(char('['), parse_key_locale, char(']'))),
+            ),
+            |(key, opt_locale)| match opt_locale {
+                Some(locale) => Key::Localized { key, locale },
+                None => Key::Simple(key),
+            },
+        ),
+    )(input)
+}
+
+/// Parses the locale of a key in the form `lang_COUNTRY.ENCODING@MODIFIER`.
+pub fn parse_key_locale<'a, E>(input: &'a str) -> IResult<&'a str, Locale<'a>, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map(
+        tuple((
+            parse_key_part,
+            opt(preceded(char('_'), parse_key_part)),
+            opt(preceded(char('.'), parse_key_part)),
+            opt(preceded(char('@'), parse_key_part)),
+        )),
+        |(lang, country, encoding, modifier)| Locale {
+            lang,
+            country,
+            encoding,
+            modifier,
+        },
+    )(input)
+}
+
+/// Parses a part of a key or locale, made of ASCII alphanumeric characters and `-`.
+pub fn parse_key_part<'a, E>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map(
+        recognize(many1_count(satisfy(|c| {
+            c.is_ascii_alphanumeric() || c == '-'
+        }))),
+        Cow::from,
+    )(input)
+}
+
+/// Parses the spaces at the start of the line, if the dialect allows them.
+fn parse_leading_spaces<'a, E>(dialect: Dialect, input: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: NomParseError<&'a str>,
+{
+    match dialect.leading_spaces {
+        true => space0(input),
+        false => Ok((input, "")),
+    }
+}
+
+/// Parses a comment of the dialect, starting with one of its comment characters.
+pub fn parse_dialect_comment<'a, E>(
+    dialect: Dialect,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map(
+        recognize(tuple((
+            move |input| parse_leading_spaces(dialect, input),
+            one_of(dialect.comment_chars),
+            not_line_ending,
+        ))),
+        Cow::from,
+    )
+}
+
+/// Parses a group header of the dialect in the form `[Group Name]`, returning the name.
+pub fn parse_dialect_group_header<'a, E>(
+    dialect: Dialect,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    move |input| {
+        let (input, _) = parse_leading_spaces(dialect, input)?;
+
+        context(
+            "group header",
+            map(
+                delimited(
+                    char('['),
+                    // Fail for missing header content
+                    recognize(cut(many1_count(satisfy(|c| {
+                        (c.is_ascii() || !dialect.ascii_group_headers)
+                            && !c.is_control()
+                            && c != '['
+                            && c != ']'
+                    })))),
+                    // If an open `[` is not closed fail the parser
+                    cut(char(']')),
+                ),
+                Cow::from,
+            ),
+        )(input)
+    }
+}
+
+/// Parses one of the separators of the dialect between key and value, with the optional
+/// surrounding spaces.
+pub fn parse_dialect_separator<'a, E>(
+    dialect: Dialect,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (&'a str, char, &'a str), E>
+where
+    E: NomParseError<&'a str>,
+{
+    tuple((space0, one_of(dialect.separators), space0))
+}
+
+/// Parses a key of the dialect with the optional locale.
+///
+/// If the keys are not restricted, they can contain any character except the separators, the
+/// brackets and the control characters, and the trailing spaces are not part of the key.
+pub fn parse_dialect_key<'a, E>(
+    dialect: Dialect,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Key<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    move |input| {
+        let (input, _) = parse_leading_spaces(dialect, input)?;
+
+        if dialect.restricted_keys {
+            return parse_key(input);
+        }
+
+        let key_part = verify(
+            map(
+                recognize(many1_count(satisfy(|c| {
+                    !c.is_control() && c != '[' && c != ']' && !dialect.separators.contains(c)
+                }))),
+                |key: &str| key.trim_end_matches([' ', '\t']),
+            ),
+            |key: &str| !key.is_empty(),
+        );
+
+        context(
+            "key",
+            map(
+                pair(
+                    key_part,
+                    opt(delimited(char('['), parse_key_locale, char(']'))),
+                ),
+                |(key, opt_locale)| match opt_locale {
+                    Some(locale) => Key::Localized {
+                        key: Cow::from(key),
+                        locale,
+                    },
+                    None => Key::Simple(Cow::from(key)),
+                },
+            ),
+        )(input)
+    }
+}
+
+/// Parses an entry of the dialect with the given handling of the unknown escape sequences.
+pub fn parse_dialect_entry_with<'a, E>(
+    dialect: Dialect,
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (Key<'a>, Value<'a>), E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "entry",
+        separated_pair(
+            parse_dialect_key(dialect),
+            parse_dialect_separator(dialect),
+            parse_dialect_value_with(dialect, unknown_escapes),
+        ),
+    )
+}
+
+/// Parses a value of the dialect with the given handling of the unknown escape sequences.
+///
+/// The inline comment is dropped if the dialect allows them. If the values are not typed, they
+/// are always a [`Value::String`] or a [`Value::LocaleString`] if they are not ASCII.
+pub fn parse_dialect_value_with<'a, E>(
+    dialect: Dialect,
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Value<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    let escapes = dialect.escapes;
+
+    move |input| {
+        let (rest, line) = not_line_ending(input)?;
+
+        let value = match dialect.inline_comments {
+            true => strip_inline_comment(line, dialect.comment_chars),
+            false => line,
+        };
+
+        let string = |input| {
+            alt((
+                map(parse_string_in(escapes, unknown_escapes), Value::String),
+                map(
+                    parse_local_string_in(escapes, unknown_escapes),
+                    Value::LocaleString,
+                ),
+            ))(input)
+        };
+
+        let (_, value) = match dialect.typed_values {
+            true => context(
+                "value",
+                alt((
+                    map(parse_boolean, Value::Boolean),
+                    map(parse_numeric, Value::Numeric),
+                    string,
+                )),
+            )(value)?,
+            false => context("value", string)(value)?,
+        };
+
+        Ok((rest, value))
+    }
+}
+
+/// Removes the inline comment of a value, starting with one of the comment characters after a
+/// space, and the spaces before it.
+pub fn strip_inline_comment<'a>(value: &'a str, comment_chars: &str) -> &'a str {
+    let mut prev = None;
+
+    for (i, c) in value.char_indices() {
+        if comment_chars.contains(c) && matches!(prev, Some(' ' | '\t')) {
+            return value[..i].trim_end_matches([' ', '\t']);
+        }
+
+        prev = Some(c);
+    }
+
+    value
+}
+
+/// Parse all the characters until the line ending
+pub fn parse_value<'a, E>(input: &'a str) -> IResult<&'a str, Value<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_value_with(UnknownEscapes::Reject)(input)
+}
+
+/// Parses a value with the given handling of the unknown escape sequences.
+pub fn parse_value_with<'a, E>(
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Value<'a>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_dialect_value_with(Dialect::DESKTOP_ENTRY, unknown_escapes)
+}
+
+/// Returns the character escaped by `\\` followed by the input, if it's a known escape sequence.
+pub fn escaped_chars(input: char) -> Option<&'static str> {
+    escaped_chars_in(DESKTOP_ESCAPES, input)
+}
+
+/// Returns the character escaped by `\\` followed by the input in the escape table.
+pub fn escaped_chars_in(escapes: &[(char, &'static str)], input: char) -> Option<&'static str> {
+    escapes
+        .iter()
+        .find_map(|(c, escaped)| (*c == input).then_some(*escaped))
+}
+
+/// Replaces the escape sequences in the whole input.
+///
+/// Borrows the input if there are no escape sequences.
+pub fn parse_escaped_string_with<'a, E>(
+    unknown_escapes: UnknownEscapes,
+) -> impl Fn(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_escaped_string_in(DESKTOP_ESCAPES, unknown_escapes)
+}
+
+/// Replaces the escape sequences of the escape table in the whole input.
+///
+/// Borrows the input if there are no escape sequences.
+pub fn parse_escaped_string_in<'a, E>(
+    escapes: &'static [(char, &'static str)],
+    unknown_escapes: UnknownEscapes,
+) -> impl Fn(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    move |input: &'a str| {
+        let Some(start) = input.find(ESCAPE_CHAR) else {
+            return Ok(("", Cow::Borrowed(input)));
+        };
+
+        let mut escaped_string = String::with_capacity(input.len());
+        escaped_string.push_str(&input[..start]);
+
+        let mut iter = input[start..].char_indices();
+        while let Some((i, c)) = iter.next() {
+            if c != ESCAPE_CHAR {
+                escaped_string.push(c);
+
+                continue;
+            }
+
+            let next = iter.next().map(|(_, next)| next);
+
+            match (
+                next.and_then(|next| escaped_chars_in(escapes, next)),
+                unknown_escapes,
+            ) {
+                (Some(escaped), _) => escaped_string.push_str(escaped),
+                (None, UnknownEscapes::Preserve) => {
+                    escaped_string.push(ESCAPE_CHAR);
+                    escaped_string.extend(next);
+                }
+                (None, UnknownEscapes::Reject) => {
+                    let sequence = &input[start + i..];
+
+                    return Err(nom::Err::Error(E::add_context(
+                        sequence,
+                        "unknown escape sequence",
+                        E::from_error_kind(sequence, ErrorKind::Escaped),
+                    )));
+                }
+            }
+        }
+
+        Ok(("", Cow::Owned(escaped_string)))
+    }
+}
+
+/// Returns the unknown escape sequences in a line.
+pub fn unknown_escape_sequences(line: &str) -> Vec<&str> {
+    unknown_escape_sequences_in(DESKTOP_ESCAPES, line)
+}
+
+/// Returns the escape sequences in a line that are not in the escape table.
+pub fn unknown_escape_sequences_in<'a>(
+    escapes: &[(char, &'static str)],
+    line: &'a str,
+) -> Vec<&'a str> {
+    let mut sequences = Vec::new();
+    let mut iter = line.char_indices();
+
+    while let Some((i, c)) = iter.next() {
+        if c != ESCAPE_CHAR {
+            continue;
+        }
+
+        match iter.next() {
+            Some((_, next)) if escaped_chars_in(escapes, next).is_some() => {}
+            Some((j, next)) => sequences.push(&line[i..j + next.len_utf8()]),
+            None => sequences.push(&line[i..]),
+        }
+    }
+
+    sequences
+}
+
+/// Parses an ASCII string value, rejecting unknown escape sequences.
+pub fn parse_string<'a, E>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_string_with(UnknownEscapes::Reject)(input)
+}
+
+/// Parses an ASCII string value with the given handling of the unknown escape sequences.
+pub fn parse_string_with<'a, E>(
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_string_in(DESKTOP_ESCAPES, unknown_escapes)
+}
+
+/// Parses an ASCII string value with the escape table.
+pub fn parse_string_in<'a, E>(
+    escapes: &'static [(char, &'static str)],
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        verify(
+            map_parser(
+                not_line_ending,
+                cut(parse_escaped_string_in(escapes, unknown_escapes)),
+            ),
+            str::is_ascii,
+        ),
+        Cow::from,
+    )
+}
+
+/// Parses a UTF-8 localestring value, rejecting unknown escape sequences.
+pub fn parse_local_string<'a, E>(input: &'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_local_string_with(UnknownEscapes::Reject)(input)
+}
+
+/// Parses a UTF-8 localestring value with the given handling of the unknown escape sequences.
+pub fn parse_local_string_with<'a, E>(
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_local_string_in(DESKTOP_ESCAPES, unknown_escapes)
+}
+
+/// Parses a UTF-8 localestring value with the escape table.
+pub fn parse_local_string_in<'a, E>(
+    escapes: &'static [(char, &'static str)],
+    unknown_escapes: UnknownEscapes,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        map_parser(
+            not_line_ending,
+            cut(parse_escaped_string_in(escapes, unknown_escapes)),
+        ),
+        Cow::from,
+    )
+}
+
+/// Parses a `true` or `false` boolean value, the whole value must match.
+pub fn parse_boolean<'a, E>(input: &'a str) -> IResult<&'a str, bool, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map_parser(
+        not_line_ending,
+        all_consuming(alt((value(true, tag("true")), value(false, tag("false"))))),
+    )(input)
+}
+
+/// Parses a numeric value, the whole value must be a number.
+pub fn parse_numeric<'a, E>(input: &'a str) -> IResult<&'a str, f32, E>
+where
+    E: NomParseError<&'a str>,
+{
+    map_parser(not_line_ending, |value| {
+        // The exponent is cut by nom, recover so a value like `9Eu` is parsed as a string
+        all_consuming(float)(value).map_err(|err| match err {
+            nom::Err::Failure(err) => nom::Err::Error(err),
+            err => err,
+        })
+    })(input)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a str>;
+
+    #[test]
+    fn shoul_parse_comment() {
+        assert_eq!(
+            Ok(("\n", Cow::from("# Code"))),
+            parse_comment::<Error>("# Code\n")
+        )
+    }
+
+    #[test]
+    fn shoul_parse_empty_comment() {
+        assert_eq!(Ok(("", Cow::from("#"))), parse_comment::<Error>("#"))
+    }
+
+    #[test]
+    fn shoul_parse_empty_line() {
+        assert_eq!(Ok(("\n", None)), parse_empty_line::<Error>("\n"))
+    }
+
+    #[test]
+    fn shoul_parse_empty_line_whitespace() {
+        assert_eq!(
+            Ok(("\n", Some(Cow::from("  ")))),
+            parse_empty_line::<Error>("  \n")
+        )
+    }
+
+    #[test]
+    fn shoul_parse_group_header() {
+        assert_eq!(
+            Ok(("", Cow::from("header"))),
+            parse_group_header::<Error>("[header]")
+        );
+    }
+
+    #[test]
+    fn shoul_parse_entry() {
+        assert_eq!(
+            Ok((
+                "",
+                (
+                    Key::Simple(Cow::from("Ke1")),
+                    Value::String(Cow::from("Value"))
+                )
+            )),
+            parse_entry::<Error>("Ke1=Value")
+        );
+    }
+
+    #[test]
+    fn should_parse_relaxed_ini_entry() {
+        assert_eq!(
+            Ok((
+                "",
+                (
+                    Key::Simple(Cow::from("Some key")),
+                    Value::LocaleString(Cow::from("välue;b"))
+                )
+            )),
+            parse_dialect_entry_with::<Error>(Dialect::RELAXED_INI, UnknownEscapes::Reject)(
+                "  Some key : välue;b ; comment"
+            )
+        );
+        assert_eq!(
+            Ok(("", Cow::from("Grüße"))),
+            parse_dialect_group_header::<Error>(Dialect::RELAXED_INI)("[Grüße]")
+        );
+        assert_eq!("a#b", strip_inline_comment("a#b\t# c", "#;"));
+    }
+
+    #[test]
+    fn should_parse_gkeyfile_entry() {
+        assert_eq!(
+            Ok((
+                "",
+                (
+                    Key::Simple(Cow::from("a:b")),
+                    Value::String(Cow::from("1;0; # not a comment"))
+                )
+            )),
+            parse_dialect_entry_with::<Error>(Dialect::GKEY_FILE, UnknownEscapes::Reject)(
+                "  a:b = 1;0; # not a comment"
+            )
+        );
+        assert_eq!(
+            Ok(("", Cow::from("  # comment"))),
+            parse_dialect_comment::<Error>(Dialect::GKEY_FILE)("  # comment")
+        );
+        assert!(parse_dialect_comment::<Error>(Dialect::GKEY_FILE)("; comment").is_err());
+    }
+
+    #[test]
+    fn shoul_parse_key() {
+        assert_eq!(
+            Ok(("", Key::Simple(Cow::from("Ke1")))),
+            parse_key::<Error>("Ke1")
+        );
+    }
+
+    #[test]
+    fn shoul_parse_localized_key() {
+        assert_eq!(
+            Ok((
+                "",
+                Key::Localized {
+                    key: Cow::from("Ke1"),
+                    locale: Locale {
+                        lang: Cow::from("sr"),
+                        country: Some(Cow::from("YU")),
+                        encoding: Some(Cow::from("UTF-8")),
+                        modifier: Some(Cow::from("Latin"))
+                    }
+                }
+            )),
+            parse_key::<Error>("Ke1[sr_YU.UTF-8@Latin]")
+        );
+    }
+
+    #[test]
+    fn should_parse_string() {
+        assert_eq!(
+            Ok(("", Cow::from("foo bar"))),
+            parse_string::<Error>("foo bar")
+        );
+
+        assert_eq!(
+            Ok(("", Cow::from("foo 'bar'"))),
+            parse_string::<Error>("foo 'bar'")
+        );
+    }
+
+    #[test]
+    fn should_parse_escaped_string() {
+        assert_eq!(
+            Ok(("", Cow::from("foo \nbar"))),
+            parse_string::<Error>("foo \\nbar")
+        );
+
+        assert_eq!(
+            Ok(("", Cow::from("foo \t bar"))),
+            parse_string::<Error>("foo \\t\\sbar")
+        );
+
+        assert_eq!(
+            Ok(("", Cow::from("foo;bar"))),
+            parse_string::<Error>("foo\\;bar")
+        );
+    }
+
+    #[test]
+    fn should_parse_escaped_non_ascii_string() {
+        assert_eq!(
+            Ok(("", Cow::from("caffè\tlatte"))),
+            parse_local_string_with::<Error>(UnknownEscapes::Reject)("caffè\\tlatte")
+        );
+    }
+
+    #[test]
+    fn should_parse_value() {
+        assert_eq!(
+            Ok(("", Value::String(Cow::from("foo \nbar")))),
+            parse_value::<Error>("foo \\nbar")
+        );
+
+        assert_eq!(
+            Ok(("\nas", Value::Boolean(true))),
+            parse_value::<Error>("true\nas")
+        );
+        assert_eq!(
+            Ok(("\nas", Value::Boolean(false))),
+            parse_value::<Error>("false\nas")
+        );
+
+        assert_eq!(
+            Ok(("\nas", Value::Numeric(1.))),
+            parse_value::<Error>("1\nas")
+        );
+        assert_eq!(
+            Ok(("\nas", Value::Numeric(4.2))),
+            parse_value::<Error>("4.20\nas")
+        );
+        assert_eq!(
+            Ok(("\nas", Value::String(Cow::from("1password")))),
+            parse_value::<Error>("1password\nas")
+        );
+        assert_eq!(
+            Ok(("", Value::String(Cow::from("trueish")))),
+            parse_value::<Error>("trueish")
+        );
+        assert_eq!(
+            Ok(("", Value::String(Cow::from("9Eu")))),
+            parse_value::<Error>("9Eu")
+        );
+        // FIX: this is will not pass
+        // assert_eq!(Ok(("\nas", Value::Numeric(4.2))), parse_value::<Error>("4,20\nas"));
+    }
+}
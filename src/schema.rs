@@ -0,0 +1,473 @@
+//! Validation of custom key files against a schema.
+//!
+//! Formats built on the syntax of the desktop files, like the configuration of an application,
+//! declare their groups and keys with a [`Schema`]. The issues are reported with the
+//! [`Diagnostic`] of the desktop file validator, so they can be configured and located in the
+//! same way.
+//!
+//! ```
+//! use xdg_desktop_entry::{
+//!     keys::ValueType,
+//!     schema::{GroupSchema, KeySchema, Schema},
+//!     DesktopEntry,
+//! };
+//!
+//! let schema = Schema::new().group(
+//!     GroupSchema::new("Settings")
+//!         .required()
+//!         .key(KeySchema::new("Theme", ValueType::String).allowed_values(["light", "dark"]))
+//!         .key(KeySchema::new("Width", ValueType::Numeric).required()),
+//! );
+//!
+//! let document = DesktopEntry::parse("[Settings]\nTheme=blue\n").unwrap();
+//! let diagnostics = schema.validate(&document);
+//!
+//! assert_eq!(2, diagnostics.len());
+//! ```
+
+use crate::{
+    gkeyfile::value_text,
+    keys::ValueType,
+    validate::{self, check, check_localized_keys, Check, Diagnostic, ValidateOptions},
+    DesktopEntry, Key, ParseOptions, Value,
+};
+
+pub const REQUIRED_GROUP: Check = check(
+    "SC001",
+    "schema-required-group",
+    "Missing group required by the schema.",
+);
+pub const UNKNOWN_GROUP: Check = check(
+    "SC002",
+    "schema-unknown-group",
+    "Group not declared by the schema.",
+);
+pub const REQUIRED_KEY: Check = check(
+    "SC003",
+    "schema-required-key",
+    "Missing key required by the schema.",
+);
+pub const UNKNOWN_KEY: Check = check(
+    "SC004",
+    "schema-unknown-key",
+    "Key not declared by the schema.",
+);
+pub const VALUE_TYPE: Check = check(
+    "SC005",
+    "schema-value-type",
+    "Value not of the type declared by the schema.",
+);
+pub const ALLOWED_VALUE: Check = check(
+    "SC006",
+    "schema-allowed-value",
+    "Value not allowed by the schema.",
+);
+pub const NOT_LOCALIZABLE: Check = check(
+    "SC007",
+    "schema-not-localizable",
+    "Localized key with a type that can't be localized.",
+);
+
+/// All the checks performed by the schema validation, together with the
+/// [`MISSING_DEFAULT_KEY`](validate::MISSING_DEFAULT_KEY) check of the desktop files.
+pub const CHECKS: &[Check] = &[
+    REQUIRED_GROUP,
+    UNKNOWN_GROUP,
+    REQUIRED_KEY,
+    UNKNOWN_KEY,
+    VALUE_TYPE,
+    ALLOWED_VALUE,
+    NOT_LOCALIZABLE,
+];
+
+/// Schema of a key file, with the groups it can contain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub groups: Vec<GroupSchema>,
+    /// Groups not declared by the schema are allowed, otherwise they are reported as warnings.
+    pub allow_unknown_groups: bool,
+}
+
+/// Schema of a group, with the keys it can contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSchema {
+    /// Header of the group.
+    pub name: String,
+    pub required: bool,
+    pub keys: Vec<KeySchema>,
+    /// Keys not declared by the schema are allowed, otherwise they are reported as warnings.
+    pub allow_unknown_keys: bool,
+}
+
+/// Schema of a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySchema {
+    pub name: String,
+    pub value_type: ValueType,
+    /// The value is a list of values separated by `;`.
+    pub list: bool,
+    pub required: bool,
+    /// Values allowed for the key, or for each item of a list, compared as text.
+    ///
+    /// If [`None`] all the values of the type are allowed.
+    pub allowed_values: Option<Vec<String>>,
+}
+
+impl Schema {
+    /// Creates an empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a group to the schema.
+    pub fn group(mut self, group: GroupSchema) -> Self {
+        self.groups.push(group);
+
+        self
+    }
+
+    /// Allows the groups not declared by the schema.
+    pub fn allow_unknown_groups(mut self) -> Self {
+        self.allow_unknown_groups = true;
+
+        self
+    }
+
+    /// Returns the schema of a group.
+    pub fn find_group(&self, name: &str) -> Option<&GroupSchema> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    /// Validates a parsed key file against the schema, returning the issues found.
+    pub fn validate(&self, document: &DesktopEntry) -> Vec<Diagnostic> {
+        self.validate_with_options(document, &ValidateOptions::default())
+    }
+
+    /// Validates a parsed key file with the given options, returning the issues found.
+    pub fn validate_with_options(
+        &self,
+        document: &DesktopEntry,
+        options: &ValidateOptions,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for group in self.groups.iter().filter(|group| group.required) {
+            if document.group(&group.name).is_none() {
+                diagnostics.push(Diagnostic::error(
+                    &REQUIRED_GROUP,
+                    None,
+                    None,
+                    format!("missing the required [{}] group", group.name),
+                ));
+            }
+        }
+
+        for (header, _) in document.groups() {
+            match self.find_group(header) {
+                Some(group) => group.check(document, &mut diagnostics),
+                None if self.allow_unknown_groups => {}
+                None => diagnostics.push(Diagnostic::warning(
+                    &UNKNOWN_GROUP,
+                    Some(header),
+                    None,
+                    "group not declared by the schema",
+                )),
+            }
+        }
+
+        check_localized_keys(document, &mut diagnostics);
+
+        options.apply(&mut diagnostics);
+
+        diagnostics
+    }
+
+    /// Parses and validates a key file, returning the issues located in the input.
+    ///
+    /// A file that can't be parsed is reported as a single
+    /// [`PARSE_ERROR`](validate::PARSE_ERROR) diagnostic.
+    pub fn validate_str(
+        &self,
+        input: &str,
+        parse_options: &ParseOptions,
+        options: &ValidateOptions,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = match DesktopEntry::parse_with_options(input, parse_options) {
+            Ok(document) => self.validate_with_options(&document, options),
+            Err(err) => {
+                let mut diagnostics = vec![Diagnostic::from_parse_error(&err)];
+
+                options.apply(&mut diagnostics);
+
+                return diagnostics;
+            }
+        };
+
+        validate::locate(&mut diagnostics, input);
+
+        diagnostics
+    }
+}
+
+impl GroupSchema {
+    /// Creates the schema of an optional group without keys.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            required: false,
+            keys: Vec::new(),
+            allow_unknown_keys: false,
+        }
+    }
+
+    /// Makes the group required.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+
+        self
+    }
+
+    /// Adds a key to the group.
+    pub fn key(mut self, key: KeySchema) -> Self {
+        self.keys.push(key);
+
+        self
+    }
+
+    /// Allows the keys not declared by the schema.
+    pub fn allow_unknown_keys(mut self) -> Self {
+        self.allow_unknown_keys = true;
+
+        self
+    }
+
+    /// Returns the schema of a key.
+    pub fn find_key(&self, name: &str) -> Option<&KeySchema> {
+        self.keys.iter().find(|key| key.name == name)
+    }
+
+    fn check(&self, document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+        let header = self.name.as_str();
+
+        for key in self.keys.iter().filter(|key| key.required) {
+            if document.get(header, &key.name).is_none() {
+                diagnostics.push(Diagnostic::error(
+                    &REQUIRED_KEY,
+                    Some(header),
+                    Some(&key.name),
+                    "missing required key",
+                ));
+            }
+        }
+
+        let Some(entries) = document.group(header) else {
+            return;
+        };
+
+        for (key, value) in entries {
+            let Some(schema) = self.find_key(key.name()) else {
+                if !self.allow_unknown_keys {
+                    diagnostics.push(Diagnostic::warning(
+                        &UNKNOWN_KEY,
+                        Some(header),
+                        Some(&key.to_string()),
+                        "key not declared by the schema",
+                    ));
+                }
+
+                continue;
+            };
+
+            schema.check(header, key, value, diagnostics);
+        }
+    }
+}
+
+impl KeySchema {
+    /// Creates the schema of an optional key with a single value.
+    pub fn new(name: impl Into<String>, value_type: ValueType) -> Self {
+        Self {
+            name: name.into(),
+            value_type,
+            list: false,
+            required: false,
+            allowed_values: None,
+        }
+    }
+
+    /// Makes the key required.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+
+        self
+    }
+
+    /// Makes the value a list of values separated by `;`.
+    pub fn list(mut self) -> Self {
+        self.list = true;
+
+        self
+    }
+
+    /// Restricts the values of the key.
+    pub fn allowed_values<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_values = Some(values.into_iter().map(Into::into).collect());
+
+        self
+    }
+
+    fn check(&self, header: &str, key: &Key, value: &Value, diagnostics: &mut Vec<Diagnostic>) {
+        let key_name = key.to_string();
+
+        if matches!(key, Key::Localized { .. })
+            && !matches!(
+                self.value_type,
+                ValueType::LocaleString | ValueType::IconString
+            )
+        {
+            diagnostics.push(Diagnostic::error(
+                &NOT_LOCALIZABLE,
+                Some(header),
+                Some(&key_name),
+                format!("a {} can't be localized", self.value_type.as_str()),
+            ));
+        }
+
+        let text = value_text(value);
+
+        let items: Vec<&str> = match self.list {
+            true => text
+                .strip_suffix(';')
+                .unwrap_or(&text)
+                .split(';')
+                .filter(|item| !item.is_empty())
+                .collect(),
+            false => vec![&text],
+        };
+
+        for item in items {
+            if !matches_type(item, self.value_type) {
+                diagnostics.push(Diagnostic::error(
+                    &VALUE_TYPE,
+                    Some(header),
+                    Some(&key_name),
+                    format!("`{item}` is not a valid {}", self.value_type.as_str()),
+                ));
+
+                continue;
+            }
+
+            let allowed = self
+                .allowed_values
+                .as_ref()
+                .is_none_or(|allowed| allowed.iter().any(|value| value == item));
+
+            if !allowed {
+                diagnostics.push(Diagnostic::error(
+                    &ALLOWED_VALUE,
+                    Some(header),
+                    Some(&key_name),
+                    format!("value `{item}` not allowed"),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks if the text of a value is valid for the type.
+fn matches_type(text: &str, value_type: ValueType) -> bool {
+    match value_type {
+        ValueType::String => text.is_ascii(),
+        ValueType::LocaleString | ValueType::IconString => true,
+        ValueType::Boolean => matches!(text, "true" | "false"),
+        ValueType::Numeric => text.parse::<f32>().is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::Dialect;
+
+    fn schema() -> Schema {
+        Schema::new()
+            .group(
+                GroupSchema::new("Settings")
+                    .required()
+                    .key(KeySchema::new("Name", ValueType::LocaleString).required())
+                    .key(
+                        KeySchema::new("Theme", ValueType::String)
+                            .allowed_values(["light", "dark"]),
+                    )
+                    .key(KeySchema::new("Sizes", ValueType::Numeric).list())
+                    .key(KeySchema::new("Enabled", ValueType::Boolean)),
+            )
+            .group(GroupSchema::new("Plugins").allow_unknown_keys())
+    }
+
+    #[test]
+    fn should_accept_valid_file() {
+        let document = DesktopEntry::parse(
+            "[Settings]\nName=Foo\nName[de]=Fu\nTheme=dark\nSizes=1;2.5;\nEnabled=true\n[Plugins]\nX-Any=1\n",
+        )
+        .unwrap();
+
+        assert_eq!(Vec::<Diagnostic>::new(), schema().validate(&document));
+    }
+
+    #[test]
+    fn should_report_schema_issues() {
+        let input = "[Settings]\nTheme=blue\nTheme[de]=dunkel\nSizes=1;big;\nEnabled=1\nColor=red\n[Other]\nA=b\n";
+
+        let diagnostics =
+            schema().validate_str(input, &ParseOptions::default(), &ValidateOptions::default());
+
+        let issues: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.key.as_deref(), diagnostic.line))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("SC003", Some("Name"), Some(1)),
+                ("SC006", Some("Theme"), Some(2)),
+                ("SC007", Some("Theme[de]"), Some(3)),
+                ("SC006", Some("Theme[de]"), Some(3)),
+                ("SC005", Some("Sizes"), Some(4)),
+                ("SC005", Some("Enabled"), Some(5)),
+                ("SC004", Some("Color"), Some(6)),
+                ("SC002", None, Some(7)),
+            ],
+            issues
+        );
+    }
+
+    #[test]
+    fn should_validate_other_dialects() {
+        let options = ParseOptions {
+            dialect: Dialect::GKEY_FILE,
+            ..Default::default()
+        };
+
+        let diagnostics = schema().validate_str(
+            "[Settings]\nName=Foo\nEnabled=false\nSizes=3;\n",
+            &options,
+            &ValidateOptions::default(),
+        );
+
+        assert_eq!(Vec::<Diagnostic>::new(), diagnostics);
+
+        let diagnostics = schema().validate_str("", &options, &ValidateOptions::default());
+
+        assert_eq!(
+            vec!["SC001"],
+            diagnostics.iter().map(|d| d.code).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Search providers of GNOME Shell, described by the `.ini` files in the
+//! `gnome-shell/search-providers` sub-directories of the XDG data directories.
+//!
+//! The files use the syntax of the desktop files, with a `[Shell Search Provider]` group:
+//!
+//! ```ini
+//! [Shell Search Provider]
+//! DesktopId=org.gnome.Nautilus.desktop
+//! BusName=org.gnome.Nautilus
+//! ObjectPath=/org/gnome/Nautilus/SearchProvider
+//! Version=2
+//! ```
+//!
+//! The provider is queried over D-Bus on the `org.gnome.Shell.SearchProvider2` interface, and
+//! shown with the name and icon of the referenced application.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    index::{AppEntry, AppIndex},
+    loader::{self, DesktopFiles, ScanFilter},
+    DesktopEntry, Error, ParseError, ParseOptions, Value,
+};
+
+/// Group of the search provider files.
+pub const SEARCH_PROVIDER_GROUP: &str = "Shell Search Provider";
+
+/// Interface implemented by the search providers, listed in the `Implements` key of their
+/// application.
+pub const SEARCH_PROVIDER_INTERFACE: &str = "org.gnome.Shell.SearchProvider2";
+
+/// Extension of the search provider files.
+pub const SEARCH_PROVIDER_EXTENSION: &str = "ini";
+
+/// Error for an invalid search provider file.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SearchProviderError {
+    #[error("couldn't parse the search provider")]
+    Parse(#[from] ParseError),
+    #[error("missing [{SEARCH_PROVIDER_GROUP}] group")]
+    MissingGroup,
+    #[error("missing {0} key")]
+    MissingKey(&'static str),
+    #[error("unsupported version `{0}`, only version 2 is supported")]
+    UnsupportedVersion(String),
+}
+
+/// Search provider of GNOME Shell.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchProvider {
+    /// Desktop file ID of the application, like `org.gnome.Nautilus.desktop`.
+    pub desktop_id: String,
+    /// Bus name of the provider.
+    pub bus_name: String,
+    /// Object path implementing the interface.
+    pub object_path: String,
+    /// Version of the interface, `2` for `org.gnome.Shell.SearchProvider2`.
+    pub version: u32,
+    /// The provider is disabled unless enabled by the user.
+    pub default_disabled: bool,
+}
+
+impl SearchProvider {
+    /// Parses a search provider file.
+    ///
+    /// # Errors
+    ///
+    /// If the file is malformed or a required key is missing.
+    pub fn parse(input: &str) -> Result<Self, SearchProviderError> {
+        let document = DesktopEntry::parse(input)?;
+
+        Self::from_document(&document)
+    }
+
+    /// Reads the search provider from a parsed file.
+    ///
+    /// # Errors
+    ///
+    /// If the group or a required key is missing, or the version is not supported.
+    pub fn from_document(document: &DesktopEntry) -> Result<Self, SearchProviderError> {
+        if document.group(SEARCH_PROVIDER_GROUP).is_none() {
+            return Err(SearchProviderError::MissingGroup);
+        }
+
+        let string = |key: &'static str| match document.get(SEARCH_PROVIDER_GROUP, key) {
+            Some(Value::String(value) | Value::LocaleString(value)) if !value.is_empty() => {
+                Ok(value.to_string())
+            }
+            _ => Err(SearchProviderError::MissingKey(key)),
+        };
+
+        let version = match document.get(SEARCH_PROVIDER_GROUP, "Version") {
+            Some(Value::Numeric(version)) if *version == 2.0 => 2,
+            Some(Value::String(version)) if version == "2" => 2,
+            Some(version) => {
+                return Err(SearchProviderError::UnsupportedVersion(version.to_string()))
+            }
+            None => return Err(SearchProviderError::MissingKey("Version")),
+        };
+
+        Ok(Self {
+            desktop_id: string("DesktopId")?,
+            bus_name: string("BusName")?,
+            object_path: string("ObjectPath")?,
+            version,
+            default_disabled: document.get(SEARCH_PROVIDER_GROUP, "DefaultDisabled")
+                == Some(&Value::Boolean(true)),
+        })
+    }
+
+    /// Returns the application of the provider in the index.
+    pub fn app<'i>(&self, index: &'i AppIndex) -> Option<&'i AppEntry> {
+        index.get(&self.desktop_id)
+    }
+}
+
+/// Returns the `gnome-shell/search-providers` sub-directories of the XDG data directories, in
+/// order of preference.
+pub fn search_provider_dirs() -> Vec<PathBuf> {
+    loader::data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("gnome-shell/search-providers"))
+        .collect()
+}
+
+/// Loads a search provider file.
+///
+/// # Errors
+///
+/// If the file can't be read or parsed, or it's not a valid search provider.
+pub fn load(path: impl AsRef<Path>) -> Result<SearchProvider, Error> {
+    let document = loader::load(path, &ParseOptions::default())?;
+
+    SearchProvider::from_document(&document).map_err(|err| match err {
+        SearchProviderError::Parse(err) => Error::Parse(err),
+        err => Error::SearchProvider(err),
+    })
+}
+
+/// Scans the search providers in the directories, in order of preference.
+///
+/// A file hides the ones with the same name in the following directories. The files that
+/// can't be read or are invalid are skipped.
+pub fn scan_dirs(dirs: &[PathBuf]) -> Vec<(PathBuf, SearchProvider)> {
+    let mut seen = HashSet::new();
+    let mut providers = Vec::new();
+
+    let filter = ScanFilter {
+        extensions: vec![SEARCH_PROVIDER_EXTENSION.to_string()],
+        recursive: false,
+        ..ScanFilter::default()
+    };
+
+    for dir in dirs {
+        let paths = DesktopFiles::with_filter(dir, filter.clone()).filter_map(|(path, result)| {
+            result.ok()?;
+
+            Some(path)
+        });
+
+        for path in paths {
+            let Some(name) = path.file_name().map(|name| name.to_os_string()) else {
+                continue;
+            };
+
+            if !seen.insert(name) {
+                continue;
+            }
+
+            if let Ok(provider) = load(&path) {
+                providers.push((path, provider));
+            }
+        }
+    }
+
+    providers
+}
+
+/// Returns the search providers with their application in the index, skipping the providers
+/// of the applications not installed.
+pub fn with_apps<'i>(
+    providers: &[(PathBuf, SearchProvider)],
+    index: &'i AppIndex,
+) -> Vec<(SearchProvider, &'i AppEntry)> {
+    providers
+        .iter()
+        .filter_map(|(_, provider)| Some((provider.clone(), provider.app(index)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const NAUTILUS: &str = "[Shell Search Provider]\nDesktopId=org.gnome.Nautilus.desktop\nBusName=org.gnome.Nautilus\nObjectPath=/org/gnome/Nautilus/SearchProvider\nVersion=2\n";
+
+    #[test]
+    fn should_parse_search_provider() {
+        assert_eq!(
+            Ok(SearchProvider {
+                desktop_id: "org.gnome.Nautilus.desktop".to_string(),
+                bus_name: "org.gnome.Nautilus".to_string(),
+                object_path: "/org/gnome/Nautilus/SearchProvider".to_string(),
+                version: 2,
+                default_disabled: false,
+            }),
+            SearchProvider::parse(NAUTILUS)
+        );
+        assert_eq!(
+            Err(SearchProviderError::MissingKey("BusName")),
+            SearchProvider::parse(
+                "[Shell Search Provider]\nDesktopId=foo.desktop\nObjectPath=/foo\nVersion=2\n"
+            )
+        );
+        assert_eq!(
+            Err(SearchProviderError::UnsupportedVersion("1.0".to_string())),
+            SearchProvider::parse(&NAUTILUS.replace("Version=2", "Version=1"))
+        );
+        assert_eq!(
+            Err(SearchProviderError::MissingGroup),
+            SearchProvider::parse("[Desktop Entry]\nName=Foo\n")
+        );
+    }
+
+    #[test]
+    fn should_scan_search_providers_with_apps() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let user = root.join("user");
+        let system = root.join("system");
+
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&system).unwrap();
+
+        fs::write(
+            user.join("org.gnome.Nautilus.search-provider.ini"),
+            NAUTILUS.replace("Version=2", "Version=2\nDefaultDisabled=true"),
+        )
+        .unwrap();
+        fs::write(
+            system.join("org.gnome.Nautilus.search-provider.ini"),
+            NAUTILUS,
+        )
+        .unwrap();
+        fs::write(
+            system.join("org.example.Foo.search-provider.ini"),
+            NAUTILUS.replace("org.gnome.Nautilus", "org.example.Foo"),
+        )
+        .unwrap();
+        fs::write(system.join("broken.search-provider.ini"), "[Foo]\n").unwrap();
+
+        let providers = scan_dirs(&[user.clone(), system]);
+
+        assert_eq!(
+            vec![
+                user.join("org.gnome.Nautilus.search-provider.ini"),
+                root.join("system/org.example.Foo.search-provider.ini"),
+            ],
+            providers
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>()
+        );
+        assert!(providers[0].1.default_disabled);
+
+        let mut index = AppIndex::new();
+        index.insert(
+            "org.gnome.Nautilus.desktop",
+            DesktopEntry::parse("[Desktop Entry]\nType=Application\nName=Files\nExec=nautilus\n")
+                .unwrap()
+                .into_owned(),
+        );
+
+        let apps = with_apps(&providers, &index);
+
+        assert_eq!(1, apps.len());
+        assert_eq!("org.gnome.Nautilus.desktop", apps[0].1.id);
+    }
+}
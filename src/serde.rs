@@ -0,0 +1,139 @@
+//! [`Serialize`] implementations, behind the `serde` feature.
+//!
+//! A document is serialized as a map of the groups, each one a map from the key, with the locale
+//! in square brackets, to the value. The values are serialized with their type, the comments and
+//! the formatting are not serialized.
+//!
+//! The strings are serialized as read by the parser, without the escape sequences of the desktop
+//! files, since the format has its own escaping. They can be written back in a desktop file
+//! with the [`escape`](crate::escape) functions.
+//!
+//! The [`Diagnostic`]s of the validator are serialized as objects, so they can be consumed by CI
+//! and editors.
+
+use ::serde::{
+    ser::{SerializeMap, SerializeStruct, Serializer},
+    Serialize,
+};
+
+use crate::{
+    validate::{Diagnostic, Severity},
+    DesktopEntry, Key, Locale, Value,
+};
+
+impl<'a> Serialize for DesktopEntry<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.groups.len()))?;
+
+        for (header, entries) in &self.groups {
+            map.serialize_entry(header, &Entries(entries))?;
+        }
+
+        map.end()
+    }
+}
+
+/// Serializes the entries of a group as a map.
+struct Entries<'e, 'a>(&'e crate::EntryMap<'a, 'a>);
+
+impl<'e, 'a> Serialize for Entries<'e, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0)
+    }
+}
+
+impl<'a> Serialize for Key<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'a> Serialize for Locale<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'a> Serialize for Value<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::String(value) | Value::LocaleString(value) => serializer.serialize_str(value),
+            Value::Boolean(value) => serializer.serialize_bool(*value),
+            Value::Numeric(value) => serializer.serialize_f32(*value),
+        }
+    }
+}
+
+impl Serialize for Severity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for Diagnostic {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut diagnostic = serializer.serialize_struct("Diagnostic", 8)?;
+
+        diagnostic.serialize_field("code", self.code)?;
+        diagnostic.serialize_field("name", self.name)?;
+        diagnostic.serialize_field("severity", &self.severity)?;
+        diagnostic.serialize_field("message", &self.message)?;
+        diagnostic.serialize_field("group", &self.group)?;
+        diagnostic.serialize_field("key", &self.key)?;
+        diagnostic.serialize_field("line", &self.line)?;
+        diagnostic.serialize_field("column", &self.column)?;
+
+        diagnostic.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_serialize_to_json() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nVersion=1.5\nName=Foo\nName[de_DE]=Föö\nTerminal=false\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"{"Desktop Entry":{"Version":1.5,"Name":"Foo","Name[de_DE]":"Föö","Terminal":false}}"#,
+            serde_json::to_string(&desktop_entry).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_serialize_diagnostics() {
+        let diagnostics = crate::validate::validate_str("[Desktop Entry]\nType=Directory\n");
+
+        assert_eq!(
+            r#"[{"code":"DE103","name":"required-key","severity":"error","message":"missing required key","group":"Desktop Entry","key":"Name","line":1,"column":1}]"#,
+            serde_json::to_string(&diagnostics).unwrap()
+        );
+    }
+}
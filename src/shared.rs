@@ -0,0 +1,257 @@
+//! Owned model of a document generic over the string type, to share it cheaply between threads.
+//!
+//! The [`DesktopEntry`] borrows or owns its strings with a [`Cow`], so each clone copies the
+//! owned ones. The [`SharedEntry`] stores them in a [`Storage`] like [`Arc<str>`], the default, or
+//! any other reference counted or inline string, making the clones cheap.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use xdg_desktop_entry::{shared::SharedEntry, DesktopEntry};
+//!
+//! let document = DesktopEntry::parse("[Desktop Entry]\nName=Foo\nName[de]=Fu\n").unwrap();
+//! let shared: SharedEntry<Arc<str>> = SharedEntry::from(&document);
+//!
+//! let name = shared.get("Desktop Entry", "Name").unwrap();
+//!
+//! assert_eq!("Foo", name.to_string());
+//! assert!(document.groups().eq(shared.to_document().groups()));
+//! ```
+
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use indexmap::{Equivalent, IndexMap};
+
+use crate::{escape::write_escaped, DesktopEntry, Key, Locale, Value};
+
+/// String type of the [`SharedEntry`].
+///
+/// It's implemented for all the strings that can be borrowed as a [`str`] and created from one,
+/// like [`Arc<str>`], [`Box<str>`] and [`String`].
+pub trait Storage: AsRef<str> + Borrow<str> + Clone + Eq + Hash + for<'s> From<&'s str> {}
+
+impl<S> Storage for S where S: AsRef<str> + Borrow<str> + Clone + Eq + Hash + for<'s> From<&'s str> {}
+
+/// Key of a [`SharedEntry`], with the locale in the form `lang_COUNTRY.ENCODING@MODIFIER`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedKey<S = Arc<str>> {
+    pub name: S,
+    pub locale: Option<S>,
+}
+
+// The key is hashed as its strings, to be looked up with a borrowed name
+impl<S: AsRef<str>> Hash for SharedKey<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.as_ref().hash(state);
+        self.locale.as_ref().map(AsRef::as_ref).hash(state);
+    }
+}
+
+/// Borrowed [`SharedKey`] to lookup the entries.
+#[derive(Hash)]
+struct KeyRef<'k> {
+    name: &'k str,
+    locale: Option<&'k str>,
+}
+
+impl<S: AsRef<str>> Equivalent<SharedKey<S>> for KeyRef<'_> {
+    fn equivalent(&self, key: &SharedKey<S>) -> bool {
+        self.name == key.name.as_ref() && self.locale == key.locale.as_ref().map(AsRef::as_ref)
+    }
+}
+
+/// Value of a [`SharedEntry`], see [`Value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue<S = Arc<str>> {
+    String(S),
+    LocaleString(S),
+    Boolean(bool),
+    Numeric(f32),
+}
+
+impl<S: PartialEq> Eq for SharedValue<S> {}
+
+impl<S: AsRef<str>> Display for SharedValue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharedValue::String(value) | SharedValue::LocaleString(value) => {
+                write_escaped(f, value.as_ref(), false)
+            }
+            SharedValue::Boolean(value) => write!(f, "{value}"),
+            SharedValue::Numeric(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Entries of a group of a [`SharedEntry`].
+pub type SharedEntryMap<S = Arc<str>> = IndexMap<SharedKey<S>, SharedValue<S>>;
+
+/// Groups and entries of a document, with the strings stored in `S`.
+///
+/// The comments and the formatting of the document are not kept.
+#[derive(Debug, Clone)]
+pub struct SharedEntry<S = Arc<str>> {
+    groups: IndexMap<S, SharedEntryMap<S>>,
+}
+
+impl<S: Storage> PartialEq for SharedEntry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.groups == other.groups
+    }
+}
+
+impl<S: Storage> Eq for SharedEntry<S> {}
+
+impl<S: Storage> SharedEntry<S> {
+    pub fn groups(&self) -> impl Iterator<Item = (&str, &SharedEntryMap<S>)> {
+        self.groups
+            .iter()
+            .map(|(header, entries)| (header.as_ref(), entries))
+    }
+
+    pub fn group(&self, header: &str) -> Option<&SharedEntryMap<S>> {
+        self.groups.get(header)
+    }
+
+    /// Returns the non localized value of a key.
+    pub fn get(&self, header: &str, key: &str) -> Option<&SharedValue<S>> {
+        self.get_localized(header, key, None)
+    }
+
+    /// Returns the value of a key for the exact locale, like `de_DE`.
+    ///
+    /// Convert the entry with [`SharedEntry::to_document`] to find the best match of a locale.
+    pub fn get_localized(
+        &self,
+        header: &str,
+        key: &str,
+        locale: Option<&str>,
+    ) -> Option<&SharedValue<S>> {
+        self.group(header)?.get(&KeyRef { name: key, locale })
+    }
+
+    /// Returns a document borrowing the strings of the entry.
+    pub fn to_document(&self) -> DesktopEntry<'_> {
+        let mut document = DesktopEntry::default();
+
+        for (header, entries) in &self.groups {
+            let group = document
+                .groups
+                .entry(Cow::Borrowed(header.as_ref()))
+                .or_default();
+
+            for (key, value) in entries {
+                let name = Cow::Borrowed(key.name.as_ref());
+                // The locales are written from a parsed key
+                let key = match key
+                    .locale
+                    .as_ref()
+                    .and_then(|locale| Locale::parse(locale.as_ref()))
+                {
+                    Some(locale) => Key::Localized { key: name, locale },
+                    None => Key::Simple(name),
+                };
+
+                let value = match value {
+                    SharedValue::String(value) => Value::String(Cow::Borrowed(value.as_ref())),
+                    SharedValue::LocaleString(value) => {
+                        Value::LocaleString(Cow::Borrowed(value.as_ref()))
+                    }
+                    SharedValue::Boolean(value) => Value::Boolean(*value),
+                    SharedValue::Numeric(value) => Value::Numeric(*value),
+                };
+
+                group.insert(key, value);
+            }
+        }
+
+        document
+    }
+}
+
+impl<S: Storage> From<&DesktopEntry<'_>> for SharedEntry<S> {
+    fn from(document: &DesktopEntry<'_>) -> Self {
+        let groups = document
+            .groups()
+            .map(|(header, entries)| {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = SharedKey {
+                            name: S::from(key.name()),
+                            locale: key.locale().map(|locale| S::from(&locale.to_string())),
+                        };
+
+                        let value = match value {
+                            Value::String(value) => SharedValue::String(S::from(value)),
+                            Value::LocaleString(value) => SharedValue::LocaleString(S::from(value)),
+                            Value::Boolean(value) => SharedValue::Boolean(*value),
+                            Value::Numeric(value) => SharedValue::Numeric(*value),
+                        };
+
+                        (key, value)
+                    })
+                    .collect();
+
+                (S::from(header), entries)
+            })
+            .collect();
+
+        Self { groups }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_convert_documents() {
+        let document = DesktopEntry::parse(include_str!("../example/file.desktop"))
+            .unwrap()
+            .into_owned();
+
+        let shared: SharedEntry = SharedEntry::from(&document);
+        let boxed: SharedEntry<Box<str>> = SharedEntry::from(&document);
+
+        assert_eq!(
+            Some(&SharedValue::String(Arc::from("fooview %F"))),
+            shared.get("Desktop Entry", "Exec")
+        );
+        assert_eq!(
+            Some("fooview %F".to_string()),
+            boxed.get("Desktop Entry", "Exec").map(ToString::to_string)
+        );
+        assert_eq!(
+            None,
+            shared.get_localized("Desktop Entry", "Name", Some("de"))
+        );
+
+        let converted = shared.to_document();
+
+        assert!(document.groups().eq(converted.groups()));
+        assert!(document.groups().eq(boxed.to_document().groups()));
+    }
+
+    #[test]
+    fn should_share_strings_between_clones() {
+        let document = DesktopEntry::parse("[Desktop Entry]\nName=Foo\n").unwrap();
+        let shared: SharedEntry = SharedEntry::from(&document);
+
+        let clone = shared.clone();
+
+        let name = |shared: &SharedEntry| match shared.get("Desktop Entry", "Name") {
+            Some(SharedValue::String(name) | SharedValue::LocaleString(name)) => Arc::clone(name),
+            _ => panic!("missing name"),
+        };
+
+        assert!(Arc::ptr_eq(&name(&shared), &name(&clone)));
+    }
+}
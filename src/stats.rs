@@ -0,0 +1,176 @@
+//! Statistics of a document, to report and tune the memory used by the applications indexes.
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    mem::{size_of, size_of_val},
+};
+
+use crate::{Comment, DesktopEntry, EntryMap, Key, Locale, ParseWarning, Value};
+
+/// Counts and approximate memory footprint of a document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    pub groups: usize,
+    /// Entries in all the groups, including the localized ones.
+    pub keys: usize,
+    /// Entries with a locale.
+    pub localized_keys: usize,
+    /// Distinct locales of the localized keys, ignoring the encoding.
+    pub locales: usize,
+    /// Approximate number of bytes used by the document and its owned strings.
+    ///
+    /// The strings borrowed from the input are not counted.
+    pub memory: usize,
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Returns the statistics of the document.
+    pub fn stats(&self) -> Stats {
+        let mut locales = HashSet::new();
+        let mut stats = Stats {
+            groups: self.groups.len(),
+            memory: size_of::<Self>() + self.groups.capacity() * map_slot::<Cow<str>, EntryMap>(),
+            ..Default::default()
+        };
+
+        for (header, entries) in &self.groups {
+            stats.keys += entries.len();
+            stats.memory += cow_memory(header)
+                + entries.capacity() * map_slot::<Key, Value>()
+                + entries
+                    .iter()
+                    .map(|(key, value)| key_memory(key) + value_memory(value))
+                    .sum::<usize>();
+
+            for locale in entries.keys().filter_map(Key::locale) {
+                stats.localized_keys += 1;
+
+                locales.insert((&locale.lang, &locale.country, &locale.modifier));
+            }
+        }
+
+        stats.locales = locales.len();
+        stats.memory +=
+            self.comments_memory() + self.warnings.capacity() * size_of::<ParseWarning>();
+
+        stats
+    }
+
+    fn comments_memory(&self) -> usize {
+        let comments = |comments: &[Comment]| {
+            size_of_val(comments)
+                + comments
+                    .iter()
+                    .map(|comment| match comment {
+                        Comment::Comment(text) => cow_memory(text),
+                        Comment::EmptyLine { white_space } => {
+                            white_space.as_ref().map_or(0, cow_memory)
+                        }
+                    })
+                    .sum::<usize>()
+        };
+
+        let groups = self
+            .comments
+            .groups
+            .iter()
+            .map(|(header, group)| {
+                map_slot::<Cow<str>, Vec<Comment>>() + cow_memory(header) + comments(group)
+            })
+            .sum::<usize>();
+
+        let entries = self
+            .comments
+            .entries
+            .iter()
+            .flat_map(|(header, entries)| {
+                entries.iter().map(move |(key, entry)| {
+                    map_slot::<Key, Vec<Comment>>()
+                        + cow_memory(header)
+                        + key_memory(key)
+                        + comments(entry)
+                })
+            })
+            .sum::<usize>();
+
+        groups + entries + comments(&self.comments.tail)
+    }
+}
+
+/// Bytes of an entry of an index map: the key, the value, the hash and the index.
+#[cfg(not(feature = "vec-map"))]
+fn map_slot<K, V>() -> usize {
+    size_of::<(K, V)>() + 2 * size_of::<usize>()
+}
+
+/// Bytes of an entry of a [`VecMap`](crate::map::VecMap).
+#[cfg(feature = "vec-map")]
+fn map_slot<K, V>() -> usize {
+    size_of::<(K, V)>()
+}
+
+// The capacity of the owned string is only available from the `Cow`
+#[allow(clippy::ptr_arg)]
+fn cow_memory(value: &Cow<str>) -> usize {
+    match value {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(value) => value.capacity(),
+    }
+}
+
+fn key_memory(key: &Key) -> usize {
+    match key {
+        Key::Simple(key) => cow_memory(key),
+        Key::Localized { key, locale } => cow_memory(key) + locale_memory(locale),
+    }
+}
+
+fn locale_memory(locale: &Locale) -> usize {
+    cow_memory(&locale.lang)
+        + [&locale.country, &locale.encoding, &locale.modifier]
+            .into_iter()
+            .flatten()
+            .map(cow_memory)
+            .sum::<usize>()
+}
+
+fn value_memory(value: &Value) -> usize {
+    match value {
+        Value::String(value) | Value::LocaleString(value) => cow_memory(value),
+        Value::Boolean(_) | Value::Numeric(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_count_entries() {
+        let input = "# Comment\n[Desktop Entry]\nName=Foo\nName[de]=Fu\nName[de_DE.UTF-8]=Fu\nComment[de_DE]=Fu\nComment[it]=Fuu\n[Desktop Action new]\nName=New\n";
+
+        let mut document = DesktopEntry::parse(input).unwrap();
+        let stats = document.stats();
+
+        assert_eq!(
+            Stats {
+                groups: 2,
+                keys: 6,
+                localized_keys: 4,
+                locales: 3,
+                memory: stats.memory,
+            },
+            stats
+        );
+
+        // The owned strings are counted, the borrowed ones aren't
+        let value = String::with_capacity(1024) + "Foo";
+
+        document.set_localized("Desktop Entry", "Name", None, Value::String(value.into()));
+
+        assert!(document.stats().memory >= stats.memory + 1024);
+    }
+}
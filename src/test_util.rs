@@ -0,0 +1,226 @@
+//! [`proptest`] strategies and a round-trip harness for desktop files.
+//!
+//! The strategies generate the source text of the documents: escaped strings, lists with escaped
+//! separators, booleans and numbers, but also strings that only look like a number.
+//! [`assert_round_trip`] parses a document, writes it and checks that nothing was lost.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use xdg_desktop_entry::test_util::{assert_round_trip, desktop_file};
+//!
+//! proptest!(|(input in desktop_file())| {
+//!     assert_round_trip(&input)?;
+//! });
+//! ```
+
+use std::collections::HashSet;
+
+use proptest::{collection::vec, option, prelude::*, test_runner::TestCaseError};
+
+use crate::{escape, DesktopEntry, Key, ParseOptions, Value};
+
+/// Strategy for a locale, with the optional country, encoding and modifier.
+pub fn locale() -> impl Strategy<Value = String> {
+    "[a-z]{2,3}(_[A-Z]{2})?(\\.[A-Za-z0-9-]{1,8})?(@[A-Za-z0-9-]{1,8})?"
+}
+
+/// Strategy for a simple or localized key.
+pub fn key() -> impl Strategy<Value = String> {
+    ("[A-Za-z0-9-]{1,12}", option::of(locale())).prop_map(|(key, locale)| match locale {
+        Some(locale) => format!("{key}[{locale}]"),
+        None => key,
+    })
+}
+
+/// Strategy for the escaped text of a value of any type.
+pub fn value() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[ -~\t\n]{0,32}".prop_map(|value| escape::escape_value(&value).into_owned()),
+        "(?s:.){0,16}[^\\x00-\\x7F](?s:.){0,16}"
+            .prop_map(|value| escape::escape_value(&value).into_owned()),
+        list(),
+        prop_oneof![Just("true"), Just("false")].prop_map(str::to_string),
+        any::<f32>().prop_map(|value| Value::Numeric(value).to_string()),
+        numeric_string(),
+    ]
+}
+
+/// Strategy for a list of strings, the items can contain the separator.
+pub fn list() -> impl Strategy<Value = String> {
+    vec("[ -~]{0,8}", 0..4).prop_map(|items| escape::join_list(items.iter().map(String::as_str)))
+}
+
+/// Strategy for the strings that look like a number, but are not written like one, so they are
+/// written back with their source text.
+fn numeric_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[+-]?[0-9]{1,4}(\\.[0-9]{1,3})?([eE][+-]?[0-9]{1,2})?",
+        "[+-]?(nan|NaN|inf|infinity|Infinity)",
+    ]
+}
+
+/// Strategy for the header of a group.
+pub fn group_header() -> impl Strategy<Value = String> {
+    "[ -Z^-~\\\\]{1,24}"
+}
+
+/// Strategy for a comment or empty line.
+pub fn comment() -> impl Strategy<Value = String> {
+    prop_oneof!["#[^\r\n]{0,32}", "[ \t]{0,4}"]
+}
+
+/// Strategy for the source of a full document, with comments.
+///
+/// The group headers and the keys of a group are unique, so the document is written back as it
+/// was.
+pub fn desktop_file() -> impl Strategy<Value = String> {
+    let entry = (vec(comment(), 0..2), key(), value());
+    let group = (vec(comment(), 0..2), group_header(), vec(entry, 0..8));
+
+    (
+        vec(group, 0..4),
+        vec(comment(), 0..2),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(groups, tail, crlf, byte_order_mark)| {
+            let line_ending = if crlf { "\r\n" } else { "\n" };
+            let mut lines = Vec::new();
+            let mut headers = HashSet::new();
+
+            for (comments, header, entries) in groups {
+                if !headers.insert(header.clone()) {
+                    continue;
+                }
+
+                lines.extend(comments);
+                lines.push(format!("[{header}]"));
+
+                let mut keys = HashSet::new();
+
+                for (comments, key, value) in entries {
+                    if keys.insert(key.clone()) {
+                        lines.extend(comments);
+                        lines.push(format!("{key}={value}"));
+                    }
+                }
+            }
+
+            lines.extend(tail);
+
+            let bom = if byte_order_mark { "\u{FEFF}" } else { "" };
+
+            lines.iter().fold(bom.to_string(), |mut input, line| {
+                input.push_str(line);
+                input.push_str(line_ending);
+
+                input
+            })
+        })
+}
+
+/// Parses the document keeping the comments, writes it and checks that it's written back as it
+/// was and parsed to a semantically equal document.
+///
+/// The input must be written like the writer would, like the documents generated by
+/// [`desktop_file`], without the spaces around the `=`.
+///
+/// # Errors
+///
+/// Fails the test case if the input or the written document can't be parsed or are different.
+pub fn assert_round_trip(input: &str) -> Result<(), TestCaseError> {
+    let options = ParseOptions {
+        keep_comments: true,
+        ..Default::default()
+    };
+    let parse = |input| {
+        DesktopEntry::parse_with_options(input, &options)
+            .map_err(|err| TestCaseError::fail(format!("{err} in {input:?}")))
+    };
+
+    let document = parse(input)?;
+    let written = document.to_string();
+
+    prop_assert_eq!(input, &written);
+
+    let parsed = parse(&written)?;
+
+    assert_semantic_eq(&document, &parsed)?;
+    prop_assert_eq!(document.line_ending, parsed.line_ending);
+
+    Ok(())
+}
+
+/// Checks that two documents have the same groups, entries, comments and byte order mark.
+///
+/// The comments are compared with the group header or entry they are attached to. The `NaN`
+/// numeric values are equal to each other.
+///
+/// # Errors
+///
+/// Fails the test case if the documents are different.
+pub fn assert_semantic_eq(
+    expected: &DesktopEntry,
+    actual: &DesktopEntry,
+) -> Result<(), TestCaseError> {
+    let groups = |document: &DesktopEntry| {
+        document
+            .groups
+            .iter()
+            .map(|(header, entries)| {
+                (
+                    header.to_string(),
+                    entries.keys().map(Key::to_string).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    prop_assert_eq!(groups(expected), groups(actual));
+
+    for ((_, expected), (_, actual)) in expected.groups.iter().zip(&actual.groups) {
+        for ((key, expected), actual) in expected.iter().zip(actual.values()) {
+            prop_assert!(
+                value_eq(expected, actual),
+                "value of {} differs: {:?} != {:?}",
+                key,
+                expected,
+                actual
+            );
+        }
+    }
+
+    prop_assert_eq!(&expected.comments, &actual.comments);
+
+    prop_assert_eq!(expected.byte_order_mark, actual.byte_order_mark);
+
+    Ok(())
+}
+
+fn value_eq(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Numeric(expected), Value::Numeric(actual)) => {
+            expected == actual || (expected.is_nan() && actual.is_nan())
+        }
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn should_round_trip_documents(input in desktop_file()) {
+            assert_round_trip(&input)?;
+        }
+    }
+
+    #[test]
+    fn should_round_trip_lists_and_numeric_strings() {
+        let input = "[Desktop Entry]\nKeywords=a\\;b;c;\nName=007\nComment=2024\nX-N=1e3\nX-Nan=nan\nVersion=1.0\n";
+
+        assert_round_trip(input).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+//! Classification of the source of a desktop file in syntax tokens.
+//!
+//! The tokens are produced with the same parsers used for the document, so editors and
+//! highlighters can be driven by them. They cover the whole input except line endings and
+//! whitespace only lines, and are returned in order.
+
+use std::ops::Range;
+
+use nom::Offset;
+
+use crate::{
+    raw::{
+        escaped_chars, not_line_ending, parse_comment, parse_group_header, parse_key_locale,
+        parse_key_part, parse_line_ending, parse_separator, ESCAPE_CHAR,
+    },
+    BYTE_ORDER_MARK,
+};
+
+/// Kind of a [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// UTF-8 byte order mark at the start of the file.
+    ByteOrderMark,
+    /// Comment line, including the `#`.
+    Comment,
+    /// Name of a group, between the brackets of the header.
+    GroupName,
+    /// Name of the key of an entry.
+    Key,
+    /// Locale of a key, between the brackets.
+    Locale,
+    /// Brackets of a group header or of a locale.
+    Bracket,
+    /// The `=` between key and value, with the surrounding spaces.
+    Separator,
+    /// Text of a value.
+    Value,
+    /// Escape sequence in a value, like `\s`.
+    Escape,
+    /// Unknown escape sequence in a value.
+    InvalidEscape,
+    /// Content that is not a valid comment, group header or entry.
+    Invalid,
+}
+
+/// Span of the source with its [`TokenKind`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range of the token in the input.
+    pub span: Range<usize>,
+}
+
+impl Token {
+    fn new(kind: TokenKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    /// Returns the text of the token in the input it was produced from.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.span.clone()]
+    }
+}
+
+/// Splits the input in syntax tokens.
+///
+/// It never fails, the lines that can't be parsed are returned as [`TokenKind::Invalid`].
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+
+    if let Some(stripped) = rest.strip_prefix(BYTE_ORDER_MARK) {
+        tokens.push(Token::new(
+            TokenKind::ByteOrderMark,
+            0..BYTE_ORDER_MARK.len_utf8(),
+        ));
+        rest = stripped;
+    }
+
+    while !rest.is_empty() {
+        let Ok((after, line)) = not_line_ending::<()>(rest) else {
+            break;
+        };
+
+        tokenize_line(&mut tokens, input.offset(line), line);
+
+        rest = parse_line_ending::<()>(after).map_or("", |(after, _)| after);
+    }
+
+    tokens
+}
+
+/// Tokenizes a line starting at the byte offset `start` of the input.
+fn tokenize_line(tokens: &mut Vec<Token>, start: usize, line: &str) {
+    let span = |part: &str| {
+        let offset = start + line.offset(part);
+
+        offset..offset + part.len()
+    };
+
+    if line.trim_start_matches([' ', '\t']).is_empty() {
+        return;
+    }
+
+    if parse_comment::<()>(line).is_ok() {
+        tokens.push(Token::new(TokenKind::Comment, span(line)));
+
+        return;
+    }
+
+    if let Ok((rest, name)) = parse_group_header::<()>(line) {
+        let name_start = line.offset(&line[1..]);
+        let name_span = start + name_start..start + name_start + name.len();
+
+        tokens.push(Token::new(TokenKind::Bracket, start..start + 1));
+        tokens.push(Token::new(TokenKind::GroupName, name_span.clone()));
+        tokens.push(Token::new(
+            TokenKind::Bracket,
+            name_span.end..name_span.end + 1,
+        ));
+        push_invalid(tokens, span(rest));
+
+        return;
+    }
+
+    let Ok((rest, key)) = parse_key_part::<()>(line) else {
+        push_invalid(tokens, span(line));
+
+        return;
+    };
+
+    tokens.push(Token::new(TokenKind::Key, start..start + key.len()));
+
+    let mut rest = rest;
+
+    if let Some(locale) = rest.strip_prefix('[') {
+        let Ok((after, _)) = parse_key_locale::<()>(locale) else {
+            push_invalid(tokens, span(rest));
+
+            return;
+        };
+
+        let Some(after) = after.strip_prefix(']') else {
+            push_invalid(tokens, span(rest));
+
+            return;
+        };
+
+        let locale = &locale[..locale.len() - after.len() - 1];
+
+        tokens.push(Token::new(TokenKind::Bracket, span(&rest[..1])));
+        tokens.push(Token::new(TokenKind::Locale, span(locale)));
+        tokens.push(Token::new(
+            TokenKind::Bracket,
+            span(&rest[1 + locale.len()..2 + locale.len()]),
+        ));
+
+        rest = after;
+    }
+
+    let Ok((value, _)) = parse_separator::<()>(rest) else {
+        push_invalid(tokens, span(rest));
+
+        return;
+    };
+
+    tokens.push(Token::new(
+        TokenKind::Separator,
+        span(&rest[..rest.len() - value.len()]),
+    ));
+
+    tokenize_value(tokens, span(value).start, value);
+}
+
+/// Splits the value in text and escape sequences.
+fn tokenize_value(tokens: &mut Vec<Token>, start: usize, value: &str) {
+    let mut text_start = 0;
+    let mut iter = value.char_indices();
+
+    while let Some((i, c)) = iter.next() {
+        if c != ESCAPE_CHAR {
+            continue;
+        }
+
+        if text_start < i {
+            tokens.push(Token::new(TokenKind::Value, start + text_start..start + i));
+        }
+
+        let (end, kind) = match iter.next() {
+            Some((j, next)) if escaped_chars(next).is_some() => {
+                (j + next.len_utf8(), TokenKind::Escape)
+            }
+            Some((j, next)) => (j + next.len_utf8(), TokenKind::InvalidEscape),
+            None => (value.len(), TokenKind::InvalidEscape),
+        };
+
+        tokens.push(Token::new(kind, start + i..start + end));
+        text_start = end;
+    }
+
+    if text_start < value.len() {
+        tokens.push(Token::new(
+            TokenKind::Value,
+            start + text_start..start + value.len(),
+        ));
+    }
+}
+
+fn push_invalid(tokens: &mut Vec<Token>, span: Range<usize>) {
+    if !span.is_empty() {
+        tokens.push(Token::new(TokenKind::Invalid, span));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(input)
+            .into_iter()
+            .map(|token| (token.kind, token.text(input)))
+            .collect()
+    }
+
+    #[test]
+    fn should_tokenize_document() {
+        let input = "\u{FEFF}# Comment\r\n[Desktop Entry]\n\nName[de_DE] = Caffè\\sLatte\\x\\";
+
+        assert_eq!(
+            vec![
+                (TokenKind::ByteOrderMark, "\u{FEFF}"),
+                (TokenKind::Comment, "# Comment"),
+                (TokenKind::Bracket, "["),
+                (TokenKind::GroupName, "Desktop Entry"),
+                (TokenKind::Bracket, "]"),
+                (TokenKind::Key, "Name"),
+                (TokenKind::Bracket, "["),
+                (TokenKind::Locale, "de_DE"),
+                (TokenKind::Bracket, "]"),
+                (TokenKind::Separator, " = "),
+                (TokenKind::Value, "Caffè"),
+                (TokenKind::Escape, "\\s"),
+                (TokenKind::Value, "Latte"),
+                (TokenKind::InvalidEscape, "\\x"),
+                (TokenKind::InvalidEscape, "\\"),
+            ],
+            kinds(input)
+        );
+    }
+
+    #[test]
+    fn should_mark_invalid_lines() {
+        assert_eq!(
+            vec![
+                (TokenKind::Bracket, "["),
+                (TokenKind::GroupName, "Group"),
+                (TokenKind::Bracket, "]"),
+                (TokenKind::Invalid, " trailing"),
+                (TokenKind::Key, "Name"),
+                (TokenKind::Invalid, " value"),
+                (TokenKind::Invalid, "[Group"),
+            ],
+            kinds("[Group] trailing\nName value\n[Group\n")
+        );
+    }
+
+    #[test]
+    fn should_tokenize_example() {
+        let input = include_str!("../example/file.desktop");
+
+        let tokens = tokenize(input);
+
+        assert!(tokens.iter().all(|token| token.kind != TokenKind::Invalid));
+        assert_eq!(
+            vec!["Icon", "=", "fooview-new"],
+            tokens[tokens.len() - 3..]
+                .iter()
+                .map(|token| token.text(input))
+                .collect::<Vec<_>>()
+        );
+    }
+}
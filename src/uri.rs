@@ -0,0 +1,162 @@
+//! Conversion between local paths and `file://` URIs.
+//!
+//! The applications accepting URIs with `%u` and `%U` receive the local files as `file://`
+//! URIs, while the ones accepting only files with `%f` and `%F` need the local path of the URIs.
+//! The paths are percent-encoded as in RFC 3986, byte by byte.
+
+use std::path::{Path, PathBuf};
+
+/// Scheme of the local files.
+const FILE_SCHEME: &str = "file://";
+
+/// Returns the `file://` URI of an absolute path.
+///
+/// All the bytes except the unreserved characters and `/` are percent-encoded. Returns
+/// [`None`] for relative paths.
+pub fn path_to_uri(path: &Path) -> Option<String> {
+    if !path.is_absolute() {
+        return None;
+    }
+
+    let mut uri = String::from(FILE_SCHEME);
+
+    for byte in path_bytes(path) {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    Some(uri)
+}
+
+/// Returns the local path of a `file://` URI.
+///
+/// The host must be empty or `localhost`. Returns [`None`] for the other schemes, the remote
+/// hosts and the invalid percent-encodings.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = strip_prefix_ignore_case(uri, FILE_SCHEME)?;
+
+    let path = match rest.find('/') {
+        Some(0) => rest,
+        Some(start) if rest[..start].eq_ignore_ascii_case("localhost") => &rest[start..],
+        _ => return None,
+    };
+
+    // The query and fragment are not part of the path
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let bytes = percent_decode(path)?;
+
+    Some(path_from_bytes(bytes))
+}
+
+/// Checks if the string is an URI, with a scheme as in RFC 3986.
+pub fn is_uri(value: &str) -> bool {
+    let Some((scheme, _)) = value.split_once(':') else {
+        return false;
+    };
+
+    let mut chars = scheme.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Checks if the URI is a local file.
+pub fn is_local_uri(uri: &str) -> bool {
+    uri_to_path(uri).is_some()
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = value.get(..prefix.len())?;
+
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &value[prefix.len()..])
+}
+
+fn percent_decode(value: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+
+            continue;
+        }
+
+        let high = char::from(bytes.next()?).to_digit(16)?;
+        let low = char::from(bytes.next()?).to_digit(16)?;
+
+        decoded.push(u8::try_from(high << 4 | low).ok()?);
+    }
+
+    Some(decoded)
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().replace('\\', "/").into_bytes()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_convert_paths_to_uris() {
+        assert_eq!(
+            Some("file:///home/user/a%20b%23%C3%A8.txt".to_string()),
+            path_to_uri(Path::new("/home/user/a b#è.txt"))
+        );
+        assert_eq!(None, path_to_uri(Path::new("a.txt")));
+    }
+
+    #[test]
+    fn should_convert_uris_to_paths() {
+        assert_eq!(
+            Some(PathBuf::from("/home/user/a b#è.txt")),
+            uri_to_path("file:///home/user/a%20b%23%C3%A8.txt")
+        );
+        assert_eq!(
+            Some(PathBuf::from("/tmp/a")),
+            uri_to_path("FILE://localhost/tmp/a?query")
+        );
+        assert_eq!(None, uri_to_path("file://example.com/tmp/a"));
+        assert_eq!(None, uri_to_path("https://example.com/a"));
+        assert_eq!(None, uri_to_path("file:///tmp/%zz"));
+    }
+
+    #[test]
+    fn should_detect_uris() {
+        assert!(is_uri("https://example.com"));
+        assert!(is_uri("mailto:foo@example.com"));
+        assert!(!is_uri("/tmp/a:b"));
+        assert!(!is_uri("a.txt"));
+        assert!(is_local_uri("file:///tmp"));
+        assert!(!is_local_uri("sftp://host/tmp"));
+    }
+}
@@ -0,0 +1,271 @@
+//! User overrides of the installed applications.
+//!
+//! A desktop file in the user `applications` directory has precedence over the one with the same
+//! ID in the system directories, it's the standard way to customize an application without
+//! changing the installed files. An override with `Hidden=true` deletes the application for the
+//! user, see [`hide_entry`].
+//!
+//! ```no_run
+//! use xdg_desktop_entry::{edit::Change, user, Value, DESKTOP_ENTRY_GROUP};
+//!
+//! let path = user::write_override(
+//!     "org.example.App.desktop",
+//!     &[Change::set(DESKTOP_ENTRY_GROUP, "NoDisplay", Value::Boolean(true))],
+//! )?;
+//! # Ok::<(), xdg_desktop_entry::Error>(())
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    edit::{self, Change},
+    loader, DesktopEntry, Error, Value, DESKTOP_ENTRY_GROUP,
+};
+
+/// Returns the `applications` directory of the user, in `$XDG_DATA_HOME`.
+pub fn applications_dir() -> Option<PathBuf> {
+    loader::data_home().map(|home| home.join("applications"))
+}
+
+/// Writes a user override of an application with the changes applied.
+///
+/// The override is a copy of the desktop file with the highest precedence, which is the user
+/// one if it already exists, so the previous customizations are kept. Only the changed lines
+/// are edited, see [`edit::apply`]. Returns the path of the written file.
+///
+/// # Errors
+///
+/// If the application is not installed, or the desktop file can't be parsed or the override
+/// written.
+pub fn write_override(id: &str, changes: &[Change]) -> Result<PathBuf, Error> {
+    let source = loader::find_application(id).ok_or_else(|| not_found(id))?;
+    let dir = applications_dir().ok_or_else(no_home)?;
+
+    write_override_from(&source, &dir.join(id), changes)
+}
+
+/// Writes to the target a copy of the source desktop file with the changes applied.
+///
+/// See [`write_override`].
+///
+/// # Errors
+///
+/// If the source can't be read or parsed, or the target can't be written.
+pub fn write_override_from(
+    source: &Path,
+    target: &Path,
+    changes: &[Change],
+) -> Result<PathBuf, Error> {
+    let content = fs::read_to_string(source)?;
+
+    // Don't copy a file that the launchers would ignore
+    DesktopEntry::parse(&content)?;
+
+    write_atomic(target, &edit::apply(&content, changes))?;
+
+    Ok(target.to_path_buf())
+}
+
+/// Hides an application by writing a user override with `Hidden=true`.
+///
+/// This is the mechanism of the specification to delete an installed application for the user.
+/// If the user already has an override, the key is added to it so [`unhide_entry`] can restore
+/// the customizations. Returns the path of the written file.
+///
+/// # Errors
+///
+/// If the user data directory is not found or the override can't be written.
+pub fn hide_entry(id: &str) -> Result<PathBuf, Error> {
+    let dir = applications_dir().ok_or_else(no_home)?;
+
+    hide_entry_in(&dir, id)
+}
+
+/// Hides an application with an override in the given `applications` directory.
+///
+/// See [`hide_entry`].
+///
+/// # Errors
+///
+/// If the override can't be written.
+pub fn hide_entry_in(dir: &Path, id: &str) -> Result<PathBuf, Error> {
+    let path = dir.join(id);
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    let hidden = Change::set(DESKTOP_ENTRY_GROUP, "Hidden", Value::Boolean(true));
+
+    write_atomic(&path, &edit::apply(&content, &[hidden]))?;
+
+    Ok(path)
+}
+
+/// Removes the `Hidden=true` override written by [`hide_entry`].
+///
+/// The override is deleted if it has only the `Hidden` key, otherwise only the key is removed.
+/// Returns false if the application wasn't hidden by the user.
+///
+/// # Errors
+///
+/// If the user data directory is not found or the override can't be changed.
+pub fn unhide_entry(id: &str) -> Result<bool, Error> {
+    let dir = applications_dir().ok_or_else(no_home)?;
+
+    unhide_entry_in(&dir, id)
+}
+
+/// Removes the `Hidden=true` override in the given `applications` directory.
+///
+/// See [`unhide_entry`].
+///
+/// # Errors
+///
+/// If the override can't be changed.
+pub fn unhide_entry_in(dir: &Path, id: &str) -> Result<bool, Error> {
+    let path = dir.join(id);
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+
+    let document = DesktopEntry::parse(&content)?;
+
+    if document.get(DESKTOP_ENTRY_GROUP, "Hidden") != Some(&Value::Boolean(true)) {
+        return Ok(false);
+    }
+
+    let is_tombstone = document
+        .groups()
+        .all(|(header, entries)| header == DESKTOP_ENTRY_GROUP && entries.len() == 1);
+
+    if is_tombstone {
+        fs::remove_file(&path)?;
+    } else {
+        let content = edit::apply(&content, &[Change::remove(DESKTOP_ENTRY_GROUP, "Hidden")]);
+
+        write_atomic(&path, &content)?;
+    }
+
+    Ok(true)
+}
+
+/// Writes the file replacing it at once, so the readers never see a partial file.
+pub(crate) fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+
+    let temp = path.with_file_name(name);
+
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+fn not_found(id: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("application {id} not found"),
+    )
+}
+
+fn no_home() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "user data directory not found")
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_write_override_keeping_the_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let system = dir.join("system/foo.desktop");
+        let user = dir.join("user/applications/foo.desktop");
+
+        fs::create_dir_all(system.parent().unwrap()).unwrap();
+        fs::write(
+            &system,
+            "[Desktop Entry]\n# Keep me\nName=Foo\nExec=foo %U\n",
+        )
+        .unwrap();
+
+        let path = write_override_from(
+            &system,
+            &user,
+            &[
+                Change::set(
+                    DESKTOP_ENTRY_GROUP,
+                    "Exec",
+                    Value::String("foo --safe %U".into()),
+                ),
+                Change::set(DESKTOP_ENTRY_GROUP, "NoDisplay", Value::Boolean(true)),
+            ],
+        )
+        .unwrap();
+
+        let written = fs::read_to_string(&user).unwrap();
+        let original = fs::read_to_string(&system).unwrap();
+
+        assert_eq!(user, path);
+        assert_eq!(
+            "[Desktop Entry]\n# Keep me\nName=Foo\nExec=foo --safe %U\nNoDisplay=true\n",
+            written
+        );
+        assert_eq!(
+            "[Desktop Entry]\n# Keep me\nName=Foo\nExec=foo %U\n",
+            original
+        );
+    }
+
+    #[test]
+    fn should_hide_and_unhide_entries() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        let foo = hide_entry_in(dir, "foo.desktop").unwrap();
+        let tombstone = fs::read_to_string(&foo).unwrap();
+        let foo_unhidden = unhide_entry_in(dir, "foo.desktop").unwrap();
+
+        fs::write(
+            dir.join("bar.desktop"),
+            "[Desktop Entry]\nName=Bar\nNoDisplay=true\n",
+        )
+        .unwrap();
+
+        let bar = hide_entry_in(dir, "bar.desktop").unwrap();
+        let hidden = fs::read_to_string(&bar).unwrap();
+        let bar_unhidden = unhide_entry_in(dir, "bar.desktop").unwrap();
+        let restored = fs::read_to_string(&bar).unwrap();
+        let not_hidden = unhide_entry_in(dir, "bar.desktop").unwrap();
+
+        let foo_exists = foo.exists();
+
+        assert_eq!("[Desktop Entry]\nHidden=true\n", tombstone);
+        assert!(foo_unhidden);
+        assert!(!foo_exists);
+        assert_eq!(
+            "[Desktop Entry]\nName=Bar\nNoDisplay=true\nHidden=true\n",
+            hidden
+        );
+        assert!(bar_unhidden);
+        assert_eq!("[Desktop Entry]\nName=Bar\nNoDisplay=true\n", restored);
+        assert!(!not_hidden);
+    }
+}
@@ -0,0 +1,37 @@
+//! Validation of the UTF-8 input, accelerated with SIMD instructions by the `simd` feature.
+//!
+//! The [`simdutf8`] crate only validates UTF-8, the ASCII checks of the string values use
+//! [`str::is_ascii`] that already compares a word at a time.
+
+use std::str::Utf8Error;
+
+/// Converts the bytes to a string, if valid UTF-8.
+///
+/// With the `simd` feature the valid input is checked with [`simdutf8`], the invalid one is
+/// checked again by the standard library for the position of the error.
+#[cfg(feature = "simd")]
+pub(crate) fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+    simdutf8::basic::from_utf8(bytes).or_else(|_| std::str::from_utf8(bytes))
+}
+
+/// Converts the bytes to a string, if valid UTF-8.
+#[cfg(not(feature = "simd"))]
+pub(crate) fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+    std::str::from_utf8(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_validate_utf8() {
+        assert_eq!(Ok("Name=Fü"), from_utf8("Name=Fü".as_bytes()));
+
+        let err = from_utf8(b"Name=F\xFF").unwrap_err();
+
+        assert_eq!(6, err.valid_up_to());
+    }
+}
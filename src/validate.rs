@@ -0,0 +1,1335 @@
+//! Validation of a parsed desktop file against the specification.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    action_group_name, categories,
+    exec::{check_field_codes, Exec, ExecError, ExecIssueKind},
+    get_simple,
+    keys::{self, EntryTypes, ACTION_KEYS, KEYS},
+    lint::LintLevel,
+    loader::DESKTOP_EXTENSION,
+    naming, parse_action_group_name,
+    raw::escaped_chars,
+    token::{tokenize, TokenKind},
+    DesktopEntry, Key, ParseError, Value, DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+/// Types of desktop entries defined by the specification.
+pub const ENTRY_TYPES: [&str; 3] = ["Application", "Link", "Directory"];
+
+/// Extensions of the icon files, that must not be part of a themed icon name.
+const ICON_EXTENSIONS: [&str; 3] = [".png", ".svg", ".xpm"];
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The file doesn't follow a recommendation of the specification.
+    Warning,
+    /// The file is invalid.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Check performed by the validator, identified by a stable code and a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Check {
+    /// Stable code of the check, like `DE101`.
+    pub code: &'static str,
+    /// Name of the check, like `main-group`.
+    pub name: &'static str,
+    /// Short description of the issue found by the check.
+    pub description: &'static str,
+}
+
+pub const PARSE_ERROR: Check = check("DE100", "parse-error", "The file can't be parsed.");
+pub const MAIN_GROUP: Check = check(
+    "DE101",
+    "main-group",
+    "The [Desktop Entry] group must exist and be the first group.",
+);
+pub const INVALID_TYPE: Check = check("DE102", "invalid-type", "Unknown entry Type.");
+pub const REQUIRED_KEY: Check = check("DE103", "required-key", "Missing required key.");
+pub const KEY_NOT_FOR_TYPE: Check = check(
+    "DE104",
+    "key-not-for-type",
+    "Key not valid for the Type of the entry.",
+);
+pub const MISSING_EXEC: Check = check(
+    "DE105",
+    "missing-exec",
+    "Missing Exec key in an application that is not D-Bus activatable.",
+);
+pub const UNDEFINED_ACTION: Check = check(
+    "DE106",
+    "undefined-action",
+    "Action listed in Actions without its group.",
+);
+pub const UNLISTED_ACTION: Check = check(
+    "DE107",
+    "unlisted-action",
+    "Action group not listed in Actions.",
+);
+pub const MISSING_DEFAULT_KEY: Check = check(
+    "DE108",
+    "missing-default-key",
+    "Localized key without the default key.",
+);
+pub const EXEC_FIELD_CODE: Check = check(
+    "DE109",
+    "exec-field-code",
+    "Invalid or deprecated field code in Exec.",
+);
+pub const INVALID_EXEC: Check = check("DE110", "invalid-exec", "Invalid Exec command line.");
+pub const ICON_EXTENSION: Check = check(
+    "DE111",
+    "icon-extension",
+    "Themed icon name with a file extension.",
+);
+pub const MAIN_CATEGORY: Check = check(
+    "DE112",
+    "main-category",
+    "Categories without a main category.",
+);
+pub const RESERVED_CATEGORY: Check = check(
+    "DE113",
+    "reserved-category",
+    "Reserved category without OnlyShowIn.",
+);
+pub const UNKNOWN_CATEGORY: Check = check(
+    "DE114",
+    "unknown-category",
+    "Category not in the registry and without the `X-` prefix.",
+);
+pub const DBUS_NAME: Check = check(
+    "DE115",
+    "dbus-name",
+    "D-Bus activatable entry with a desktop file ID that is not a valid D-Bus name.",
+);
+pub const DBUS_SERVICE: Check = check(
+    "DE116",
+    "dbus-service",
+    "D-Bus activatable entry without the D-Bus service file.",
+);
+
+/// All the checks performed by the validator.
+pub const CHECKS: &[Check] = &[
+    PARSE_ERROR,
+    MAIN_GROUP,
+    INVALID_TYPE,
+    REQUIRED_KEY,
+    KEY_NOT_FOR_TYPE,
+    MISSING_EXEC,
+    UNDEFINED_ACTION,
+    UNLISTED_ACTION,
+    MISSING_DEFAULT_KEY,
+    EXEC_FIELD_CODE,
+    INVALID_EXEC,
+    ICON_EXTENSION,
+    MAIN_CATEGORY,
+    RESERVED_CATEGORY,
+    UNKNOWN_CATEGORY,
+    DBUS_NAME,
+    DBUS_SERVICE,
+];
+
+/// Returns the check with the given code or name.
+pub fn find_check(code_or_name: &str) -> Option<&'static Check> {
+    CHECKS
+        .iter()
+        .find(|check| check.code == code_or_name || check.name == code_or_name)
+}
+
+pub(crate) const fn check(
+    code: &'static str,
+    name: &'static str,
+    description: &'static str,
+) -> Check {
+    Check {
+        code,
+        name,
+        description,
+    }
+}
+
+/// Issue found while validating a desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// Code of the [`Check`] that found the issue.
+    pub code: &'static str,
+    /// Name of the [`Check`] that found the issue.
+    pub name: &'static str,
+    pub severity: Severity,
+    /// Group the issue is in, if any.
+    pub group: Option<String>,
+    /// Key the issue is about, if any.
+    pub key: Option<String>,
+    pub message: String,
+    /// Byte offset of the issue in the value of the key, if it's about a part of the value.
+    pub value_offset: Option<usize>,
+    /// Line of the issue, starting from 1, set by [`locate`].
+    pub line: Option<usize>,
+    /// Column of the issue in characters, starting from 1, set by [`locate`].
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    pub(crate) fn warning(
+        check: &Check,
+        group: Option<&str>,
+        key: Option<&str>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(check, group, key, message)
+        }
+    }
+
+    pub(crate) fn error(
+        check: &Check,
+        group: Option<&str>,
+        key: Option<&str>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            code: check.code,
+            name: check.name,
+            severity: Severity::Error,
+            group: group.map(str::to_string),
+            key: key.map(str::to_string),
+            message: message.into(),
+            value_offset: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    /// Creates the diagnostic for a file that can't be parsed.
+    pub fn from_parse_error(error: &ParseError) -> Self {
+        Self {
+            line: Some(error.line()),
+            column: Some(error.column()),
+            ..Self::error(&PARSE_ERROR, None, None, error.to_string())
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: ", self.severity, self.code)?;
+
+        if let Some(group) = &self.group {
+            write!(f, "[{group}] ")?;
+        }
+
+        if let Some(key) = &self.key {
+            write!(f, "{key}: ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Options to configure the validator.
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Levels of the checks, by code or name, overriding the severity of the issues found.
+    ///
+    /// The issues of the checks with [`LintLevel::Allow`] are not reported.
+    pub levels: HashMap<String, LintLevel>,
+    /// Directories of the D-Bus service files, to check that a D-Bus activatable entry has a
+    /// service to activate.
+    ///
+    /// If [`None`] the service files are not checked, see [`loader::dbus_service_dirs`] for the
+    /// directories of the system.
+    ///
+    /// [`loader::dbus_service_dirs`]: crate::loader::dbus_service_dirs
+    pub dbus_service_dirs: Option<Vec<PathBuf>>,
+}
+
+impl ValidateOptions {
+    /// Returns the configured level of the check, if any.
+    pub fn level(&self, check: &Check) -> Option<LintLevel> {
+        self.level_of(check.code, check.name)
+    }
+
+    fn level_of(&self, code: &str, name: &str) -> Option<LintLevel> {
+        self.levels
+            .get(code)
+            .or_else(|| self.levels.get(name))
+            .copied()
+    }
+
+    /// Changes the severity of the diagnostics with the configured levels.
+    pub(crate) fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        diagnostics.retain_mut(|diagnostic| {
+            let Some(level) = self.level_of(diagnostic.code, diagnostic.name) else {
+                return true;
+            };
+
+            match level.severity() {
+                Some(severity) => {
+                    diagnostic.severity = severity;
+
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+}
+
+/// Validates a parsed desktop file, returning the issues found.
+pub fn validate(document: &DesktopEntry) -> Vec<Diagnostic> {
+    validate_with_options(document, &ValidateOptions::default())
+}
+
+/// Validates a parsed desktop file with the given options, returning the issues found.
+///
+/// The checks about the desktop file ID are not performed, see [`validate_with_file_id`].
+pub fn validate_with_options(
+    document: &DesktopEntry,
+    options: &ValidateOptions,
+) -> Vec<Diagnostic> {
+    validate_with_file_id(document, None, options)
+}
+
+/// Validates a parsed desktop file with its desktop file ID, like `org.example.App.desktop`.
+pub fn validate_with_file_id(
+    document: &DesktopEntry,
+    file_id: Option<&str>,
+    options: &ValidateOptions,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = check_document(document);
+
+    if let Some(file_id) = file_id {
+        check_dbus_activatable(
+            document,
+            file_id,
+            options.dbus_service_dirs.as_deref(),
+            &mut diagnostics,
+        );
+    }
+
+    options.apply(&mut diagnostics);
+
+    diagnostics
+}
+
+fn check_document(document: &DesktopEntry) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    match document.groups.get_index_of(DESKTOP_ENTRY_GROUP) {
+        Some(0) => {}
+        Some(_) => diagnostics.push(Diagnostic::error(
+            &MAIN_GROUP,
+            Some(DESKTOP_ENTRY_GROUP),
+            None,
+            "must be the first group",
+        )),
+        None => {
+            diagnostics.push(Diagnostic::error(
+                &MAIN_GROUP,
+                None,
+                None,
+                format!("missing the required [{DESKTOP_ENTRY_GROUP}] group"),
+            ));
+
+            return diagnostics;
+        }
+    }
+
+    match document.get(DESKTOP_ENTRY_GROUP, "Type") {
+        Some(Value::String(entry_type)) if ENTRY_TYPES.contains(&entry_type.as_ref()) => {}
+        Some(value) => diagnostics.push(Diagnostic::error(
+            &INVALID_TYPE,
+            Some(DESKTOP_ENTRY_GROUP),
+            Some("Type"),
+            format!("unknown type `{value}`"),
+        )),
+        None => diagnostics.push(Diagnostic::error(
+            &REQUIRED_KEY,
+            Some(DESKTOP_ENTRY_GROUP),
+            Some("Type"),
+            "missing required key",
+        )),
+    }
+
+    check_required_keys(document, &mut diagnostics);
+    check_actions(document, &mut diagnostics);
+    check_localized_keys(document, &mut diagnostics);
+    check_icons(document, &mut diagnostics);
+    check_categories(document, &mut diagnostics);
+
+    diagnostics
+}
+
+/// Parses and validates a desktop file, returning the issues located in the input.
+///
+/// A file that can't be parsed is reported as a single [`PARSE_ERROR`] diagnostic.
+pub fn validate_str(input: &str) -> Vec<Diagnostic> {
+    validate_str_with_options(input, &ValidateOptions::default())
+}
+
+/// Parses and validates a desktop file with the given options, returning the issues located in
+/// the input.
+pub fn validate_str_with_options(input: &str, options: &ValidateOptions) -> Vec<Diagnostic> {
+    validate_str_with_file_id(input, None, options)
+}
+
+/// Parses and validates a desktop file with its desktop file ID, returning the issues located in
+/// the input.
+pub fn validate_str_with_file_id(
+    input: &str,
+    file_id: Option<&str>,
+    options: &ValidateOptions,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = match DesktopEntry::parse(input) {
+        Ok(document) => validate_with_file_id(&document, file_id, options),
+        Err(err) => {
+            let mut diagnostics = vec![Diagnostic::from_parse_error(&err)];
+
+            options.apply(&mut diagnostics);
+
+            return diagnostics;
+        }
+    };
+
+    locate(&mut diagnostics, input);
+
+    diagnostics
+}
+
+/// Known issues of the validated files, that are not reported.
+///
+/// It allows adopting the validator on a large set of files, reporting only the new issues. The
+/// baseline is stored as text, with a line for each issue containing the file, the code of the
+/// check, the group, the key and the message separated by tabs. The issues are matched without
+/// their position, so editing other lines of a file doesn't invalidate its baseline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    issues: BTreeSet<String>,
+}
+
+impl Baseline {
+    /// Number of fields of an issue in the baseline.
+    const FIELDS: usize = 5;
+
+    /// Parses a baseline, ignoring empty lines and comments starting with `#`.
+    pub fn parse(input: &str) -> Result<Self, BaselineError> {
+        let mut issues = BTreeSet::new();
+
+        for (i, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.split('\t').count() != Self::FIELDS {
+                return Err(BaselineError { line: i + 1 });
+            }
+
+            issues.insert(line.to_string());
+        }
+
+        Ok(Self { issues })
+    }
+
+    /// Adds the issue of a file to the baseline.
+    pub fn insert(&mut self, file: &str, diagnostic: &Diagnostic) {
+        self.issues.insert(Self::issue(file, diagnostic));
+    }
+
+    /// Checks if the issue of a file is in the baseline.
+    pub fn contains(&self, file: &str, diagnostic: &Diagnostic) -> bool {
+        self.issues.contains(&Self::issue(file, diagnostic))
+    }
+
+    /// Removes the diagnostics of a file that are in the baseline.
+    pub fn filter(&self, file: &str, diagnostics: &mut Vec<Diagnostic>) {
+        diagnostics.retain(|diagnostic| !self.contains(file, diagnostic));
+    }
+
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn issue(file: &str, diagnostic: &Diagnostic) -> String {
+        let fields = [
+            file,
+            diagnostic.code,
+            diagnostic.group.as_deref().unwrap_or_default(),
+            diagnostic.key.as_deref().unwrap_or_default(),
+            &diagnostic.message,
+        ];
+
+        fields
+            .iter()
+            .map(|field| escape_field(field))
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes the separators of the fields of a [`Baseline`].
+fn escape_field(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Error for a malformed [`Baseline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "invalid baseline issue at line {line}, expected {} fields separated by tabs",
+    Baseline::FIELDS
+)]
+pub struct BaselineError {
+    line: usize,
+}
+
+impl BaselineError {
+    /// Line of the error, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// Sets the line and column of the diagnostics from the input the document was parsed from.
+///
+/// An issue about a key is located at the first line of the key in the group, or at the position
+/// in its value. An issue about a missing key is located at the group header. The diagnostics
+/// that are already located, or not about a group, are left unchanged.
+pub fn locate(diagnostics: &mut [Diagnostic], input: &str) {
+    let lines = SourceLines::new(input);
+
+    for diagnostic in diagnostics {
+        if diagnostic.line.is_some() {
+            continue;
+        }
+
+        let Some(group) = &diagnostic.group else {
+            continue;
+        };
+
+        let entry = diagnostic
+            .key
+            .as_deref()
+            .and_then(|key| lines.entry(group, key));
+
+        let (line, column) = match entry {
+            Some(entry) => {
+                let column = match diagnostic.value_offset {
+                    Some(offset) => entry.value_column + unescaped_column(entry.value, offset),
+                    None => entry.key_column,
+                };
+
+                (entry.line, column)
+            }
+            None => match lines.header(group) {
+                Some(line) => (line, 1),
+                None => continue,
+            },
+        };
+
+        diagnostic.line = Some(line);
+        diagnostic.column = Some(column);
+    }
+}
+
+/// Entry line of the source.
+struct SourceEntry<'a> {
+    line: usize,
+    group: Option<&'a str>,
+    /// Key with the locale, like `Name[de]`.
+    key: &'a str,
+    key_column: usize,
+    value: &'a str,
+    value_column: usize,
+}
+
+/// Group headers and entries of the source, with their position.
+struct SourceLines<'a> {
+    headers: Vec<(usize, &'a str)>,
+    entries: Vec<SourceEntry<'a>>,
+}
+
+impl<'a> SourceLines<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut headers = Vec::new();
+        let mut entries = Vec::new();
+        let mut line_starts = vec![0];
+        line_starts.extend(input.match_indices('\n').map(|(i, _)| i + 1));
+
+        let position = |offset: usize| {
+            let line = line_starts.partition_point(|start| *start <= offset);
+            let column = input[line_starts[line - 1]..offset].chars().count() + 1;
+
+            (line, column)
+        };
+
+        let mut group = None;
+        let mut tokens = tokenize(input).into_iter().peekable();
+
+        while let Some(token) = tokens.next() {
+            match token.kind {
+                TokenKind::GroupName => {
+                    let name = token.text(input);
+
+                    headers.push((position(token.span.start).0, name));
+                    group = Some(name);
+                }
+                TokenKind::Key => {
+                    let (line, key_column) = position(token.span.start);
+                    let mut key_end = token.span.end;
+
+                    let mut value = None;
+
+                    while let Some(next) =
+                        tokens.next_if(|next| position(next.span.start).0 == line)
+                    {
+                        match next.kind {
+                            TokenKind::Bracket | TokenKind::Locale => key_end = next.span.end,
+                            TokenKind::Separator => value = Some(next.span.end),
+                            _ => {}
+                        }
+                    }
+
+                    let Some(value_start) = value else {
+                        continue;
+                    };
+
+                    let value_end = input[value_start..]
+                        .find('\n')
+                        .map_or(input.len(), |end| value_start + end);
+
+                    entries.push(SourceEntry {
+                        line,
+                        group,
+                        key: &input[token.span.start..key_end],
+                        key_column,
+                        value: input[value_start..value_end].trim_end_matches('\r'),
+                        value_column: position(value_start).1,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Self { headers, entries }
+    }
+
+    fn header(&self, group: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .find(|(_, header)| *header == group)
+            .map(|(line, _)| *line)
+    }
+
+    fn entry(&self, group: &str, key: &str) -> Option<&SourceEntry<'a>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.group == Some(group) && entry.key == key)
+    }
+}
+
+/// Returns the number of characters of the raw value before the byte offset in the unescaped
+/// value.
+fn unescaped_column(raw: &str, offset: usize) -> usize {
+    let mut unescaped = 0;
+    let mut column = 0;
+    let mut chars = raw.chars();
+
+    while unescaped < offset {
+        let Some(c) = chars.next() else {
+            break;
+        };
+
+        column += 1;
+
+        let escaped = (c == '\\')
+            .then(|| chars.clone().next())
+            .flatten()
+            .and_then(escaped_chars);
+
+        match escaped {
+            Some(escaped) => {
+                chars.next();
+                column += 1;
+                unescaped += escaped.len();
+            }
+            None => unescaped += c.len_utf8(),
+        }
+    }
+
+    column
+}
+
+/// Checks the keys required by the type of the entry, and the keys not valid for it.
+fn check_required_keys(document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let entry_type = match document.get(DESKTOP_ENTRY_GROUP, "Type") {
+        Some(Value::String(entry_type)) if ENTRY_TYPES.contains(&entry_type.as_ref()) => {
+            Some(entry_type.as_ref())
+        }
+        _ => None,
+    };
+
+    // Without a known type only the keys required by all types are checked
+    let allows = |entry_types: EntryTypes| match entry_type {
+        Some(entry_type) => entry_types.allows(entry_type),
+        None => entry_types == EntryTypes::All,
+    };
+
+    for info in KEYS {
+        // The type is checked separately
+        if info.required
+            && info.name != "Type"
+            && allows(info.entry_types)
+            && document.get(DESKTOP_ENTRY_GROUP, info.name).is_none()
+        {
+            diagnostics.push(Diagnostic::error(
+                &REQUIRED_KEY,
+                Some(DESKTOP_ENTRY_GROUP),
+                Some(info.name),
+                "missing required key",
+            ));
+        }
+    }
+
+    let dbus_activatable = document.dbus_activatable();
+
+    if entry_type == Some("Application") {
+        check_exec(document, DESKTOP_ENTRY_GROUP, dbus_activatable, diagnostics);
+    }
+
+    if let (Some(entry_type), Some(entries)) = (entry_type, document.group(DESKTOP_ENTRY_GROUP)) {
+        for key in entries.keys() {
+            let Some(info) = keys::lookup(key.name()) else {
+                continue;
+            };
+
+            if !info.entry_types.allows(entry_type) {
+                diagnostics.push(Diagnostic::error(
+                    &KEY_NOT_FOR_TYPE,
+                    Some(DESKTOP_ENTRY_GROUP),
+                    Some(&key.to_string()),
+                    format!("key not valid for Type={entry_type}"),
+                ));
+            }
+        }
+    }
+
+    for (header, _) in document.groups() {
+        if !header.starts_with(DESKTOP_ACTION_PREFIX) {
+            continue;
+        }
+
+        for info in ACTION_KEYS.iter().filter(|info| info.required) {
+            if document.get(header, info.name).is_none() {
+                diagnostics.push(Diagnostic::error(
+                    &REQUIRED_KEY,
+                    Some(header),
+                    Some(info.name),
+                    "missing required key",
+                ));
+            }
+        }
+
+        check_exec(document, header, dbus_activatable, diagnostics);
+    }
+}
+
+/// Checks that the group has the `Exec` key, unless the application is D-Bus activatable.
+fn check_exec(
+    document: &DesktopEntry,
+    header: &str,
+    dbus_activatable: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let command = match document.get(header, "Exec") {
+        Some(Value::String(command) | Value::LocaleString(command)) => command,
+        Some(_) => return,
+        None if dbus_activatable => return,
+        None => {
+            diagnostics.push(Diagnostic::error(
+                &MISSING_EXEC,
+                Some(header),
+                Some("Exec"),
+                "missing required key, unless DBusActivatable is true",
+            ));
+
+            return;
+        }
+    };
+
+    let issues = check_field_codes(command);
+
+    for issue in &issues {
+        let position = command[..issue.span.start].chars().count() + 1;
+        let message = format!("{} at position {position}", issue.kind);
+
+        let diagnostic = match issue.kind {
+            ExecIssueKind::DeprecatedFieldCode(_) => {
+                Diagnostic::warning(&EXEC_FIELD_CODE, Some(header), Some("Exec"), message)
+            }
+            _ => Diagnostic::error(&EXEC_FIELD_CODE, Some(header), Some("Exec"), message),
+        };
+
+        diagnostics.push(Diagnostic {
+            value_offset: Some(issue.span.start),
+            ..diagnostic
+        });
+    }
+
+    // Report the other issues of the command line, if not already reported
+    match Exec::parse(command) {
+        Err(ExecError::UnknownFieldCode(_) | ExecError::MissingFieldCode) if !issues.is_empty() => {
+        }
+        Err(err) => diagnostics.push(Diagnostic::error(
+            &INVALID_EXEC,
+            Some(header),
+            Some("Exec"),
+            err.to_string(),
+        )),
+        Ok(_) => {}
+    }
+}
+
+/// Checks that the `Actions` key and the action groups match.
+fn check_actions(document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let actions = match document.get(DESKTOP_ENTRY_GROUP, "Actions") {
+        Some(Value::String(actions) | Value::LocaleString(actions)) => actions
+            .split(';')
+            .filter(|action| !action.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for action in &actions {
+        let header = action_group_name(action);
+
+        if document.group(&header).is_none() {
+            diagnostics.push(Diagnostic::error(
+                &UNDEFINED_ACTION,
+                Some(DESKTOP_ENTRY_GROUP),
+                Some("Actions"),
+                format!("action `{action}` has no [{header}] group"),
+            ));
+        }
+    }
+
+    for (header, _) in document.groups() {
+        let Some(action) = parse_action_group_name(header) else {
+            continue;
+        };
+
+        if !actions.contains(&action) {
+            diagnostics.push(Diagnostic::warning(
+                &UNLISTED_ACTION,
+                Some(header),
+                None,
+                format!("action `{action}` is not listed in the Actions key"),
+            ));
+        }
+    }
+}
+
+/// Checks that the localized keys have the default key without the locale.
+pub(crate) fn check_localized_keys(document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    for (header, entries) in document.groups() {
+        for key in entries.keys() {
+            let Key::Localized { key: name, .. } = key else {
+                continue;
+            };
+
+            if get_simple(entries, name).is_none() {
+                diagnostics.push(Diagnostic::error(
+                    &MISSING_DEFAULT_KEY,
+                    Some(header),
+                    Some(&key.to_string()),
+                    format!("localized key without the default `{name}` key"),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that the themed icon names don't have an extension, which breaks the theme lookup.
+fn check_icons(document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    for (header, entries) in document.groups() {
+        if header != DESKTOP_ENTRY_GROUP && !header.starts_with(DESKTOP_ACTION_PREFIX) {
+            continue;
+        }
+
+        for (key, value) in entries {
+            let (Value::String(icon) | Value::LocaleString(icon)) = value else {
+                continue;
+            };
+
+            if key.name() != "Icon" {
+                continue;
+            }
+
+            if Path::new(icon.as_ref()).is_absolute() {
+                continue;
+            }
+
+            let extension = ICON_EXTENSIONS
+                .iter()
+                .find(|extension| icon.ends_with(*extension));
+
+            if let Some(extension) = extension {
+                diagnostics.push(Diagnostic::warning(
+                    &ICON_EXTENSION,
+                    Some(header),
+                    Some(&key.to_string()),
+                    format!("icon name `{icon}` must not have the `{extension}` extension, unless it's an absolute path"),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that the desktop file ID of a D-Bus activatable entry is a valid D-Bus name, and that it
+/// has a service file in one of the directories, if given.
+///
+/// The entry is activated through the D-Bus name equal to the ID without the extension, a mismatch
+/// silently breaks the activation.
+fn check_dbus_activatable(
+    document: &DesktopEntry,
+    file_id: &str,
+    service_dirs: Option<&[PathBuf]>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if !document.dbus_activatable() {
+        return;
+    }
+
+    let issues = naming::check_id(file_id);
+
+    if !issues.is_empty() {
+        let issues = issues.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        diagnostics.push(Diagnostic::error(
+            &DBUS_NAME,
+            Some(DESKTOP_ENTRY_GROUP),
+            Some("DBusActivatable"),
+            format!(
+                "desktop file ID `{file_id}` is not a valid D-Bus name: {}",
+                issues.join(", ")
+            ),
+        ));
+
+        return;
+    }
+
+    let Some(service_dirs) = service_dirs else {
+        return;
+    };
+
+    let name = file_id
+        .strip_suffix(DESKTOP_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+        .unwrap_or(file_id);
+    let service = format!("{name}.service");
+
+    if !service_dirs.iter().any(|dir| dir.join(&service).is_file()) {
+        diagnostics.push(Diagnostic::error(
+            &DBUS_SERVICE,
+            Some(DESKTOP_ENTRY_GROUP),
+            Some("DBusActivatable"),
+            format!("missing the D-Bus service file `{service}`"),
+        ));
+    }
+}
+
+/// Checks the `Categories` against the registry of the Desktop Menu Specification.
+fn check_categories(document: &DesktopEntry, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(Value::String(value) | Value::LocaleString(value)) =
+        document.get(DESKTOP_ENTRY_GROUP, "Categories")
+    else {
+        return;
+    };
+
+    let mut warn = |check: &Check, message: String| {
+        diagnostics.push(Diagnostic::warning(
+            check,
+            Some(DESKTOP_ENTRY_GROUP),
+            Some("Categories"),
+            message,
+        ));
+    };
+
+    let list = value
+        .split(';')
+        .filter(|category| !category.is_empty())
+        .collect::<Vec<_>>();
+
+    if !list.iter().any(|category| categories::is_main(category)) {
+        warn(&MAIN_CATEGORY, "no main category".to_string());
+    }
+
+    let only_show_in = document.get(DESKTOP_ENTRY_GROUP, "OnlyShowIn").is_some();
+
+    for category in list {
+        if categories::is_reserved(category) {
+            if !only_show_in {
+                warn(
+                    &RESERVED_CATEGORY,
+                    format!("reserved category `{category}` requires the OnlyShowIn key"),
+                );
+            }
+        } else if !categories::is_registered(category) && !category.starts_with("X-") {
+            warn(
+                &UNKNOWN_CATEGORY,
+                format!("unknown category `{category}`, extensions must have the `X-` prefix"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_validate_example_file() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let desktop_entry = DesktopEntry::parse(example_file).unwrap();
+
+        assert_eq!(Vec::<Diagnostic>::new(), validate(&desktop_entry));
+    }
+
+    #[test]
+    fn should_report_desktop_entry_not_first() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Action Foo]\nName=Foo\n[Desktop Entry]\nType=Foo\nActions=Foo;\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE101]: [Desktop Entry] must be the first group",
+                "error[DE102]: [Desktop Entry] Type: unknown type `Foo`",
+                "error[DE103]: [Desktop Entry] Name: missing required key",
+                "error[DE105]: [Desktop Action Foo] Exec: missing required key, unless DBusActivatable is true",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_report_unmatched_actions() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\nActions=New;Open;\n[Desktop Action New]\nName=New\nExec=foo --new\n[Desktop Action Close]\nName=Close\nExec=foo --close\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE106]: [Desktop Entry] Actions: action `Open` has no [Desktop Action Open] group",
+                "warning[DE107]: [Desktop Action Close] action `Close` is not listed in the Actions key",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_report_localized_key_without_default() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nExec=foo\nName[de]=Foo\nComment=Foo\nComment[it]=Foo\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE103]: [Desktop Entry] Name: missing required key",
+                "error[DE108]: [Desktop Entry] Name[de]: localized key without the default `Name` key",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_report_keys_for_type() {
+        let desktop_entry =
+            DesktopEntry::parse("[Desktop Entry]\nType=Link\nName=Foo\nTerminal=false\n").unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "error[DE103]: [Desktop Entry] URL: missing required key",
+                "error[DE104]: [Desktop Entry] Terminal: key not valid for Type=Link",
+            ],
+            diagnostics
+        );
+
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nDBusActivatable=true\n",
+        )
+        .unwrap();
+
+        assert_eq!(Vec::<Diagnostic>::new(), validate(&desktop_entry));
+    }
+
+    #[test]
+    fn should_report_exec_field_codes() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=föo %d %f %u 50% off\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "warning[DE109]: [Desktop Entry] Exec: deprecated field code `%d` at position 5",
+                "error[DE109]: [Desktop Entry] Exec: more than one of the %f, %F, %u and %U field codes at position 11",
+                "error[DE109]: [Desktop Entry] Exec: literal `%` must be escaped as `%%` at position 16",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_report_icon_extension() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Link\nName=Foo\nURL=https://example.com\nIcon=foo.png\nIcon[de]=/usr/share/pixmaps/foo.svg\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "warning[DE111]: [Desktop Entry] Icon: icon name `foo.png` must not have the `.png` extension, unless it's an absolute path",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_report_categories() {
+        let desktop_entry = DesktopEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\nCategories=TrayIcon;Foo;X-Foo;Debugger;\n",
+        )
+        .unwrap();
+
+        let diagnostics = validate(&desktop_entry)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "warning[DE112]: [Desktop Entry] Categories: no main category",
+                "warning[DE113]: [Desktop Entry] Categories: reserved category `TrayIcon` requires the OnlyShowIn key",
+                "warning[DE114]: [Desktop Entry] Categories: unknown category `Foo`, extensions must have the `X-` prefix",
+            ],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_locate_diagnostics() {
+        let input = "# Comment\n[Desktop Entry]\nType=Application\nName[de]=Foo\nExec = a\\sb %d\n\n[Desktop Action New]\nExec=new\n";
+
+        let diagnostics = validate_str(input);
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.code,
+                    diagnostic.key.as_deref(),
+                    diagnostic.line,
+                    diagnostic.column,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("DE103", Some("Name"), Some(2), Some(1)),
+                ("DE109", Some("Exec"), Some(5), Some(13)),
+                ("DE103", Some("Name"), Some(7), Some(1)),
+                ("DE107", None, Some(7), Some(1)),
+                ("DE108", Some("Name[de]"), Some(4), Some(1)),
+            ],
+            diagnostics
+        );
+        assert_eq!(
+            vec![(Some(2), Some(6))],
+            validate_str("[Desktop Entry]\nName=\\x\n")
+                .iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_override_check_levels() {
+        let options = ValidateOptions {
+            levels: HashMap::from([
+                ("icon-extension".to_string(), LintLevel::Deny),
+                ("DE112".to_string(), LintLevel::Allow),
+            ]),
+            ..Default::default()
+        };
+
+        let diagnostics = validate_str_with_options(
+            "[Desktop Entry]\nType=Application\nName=Foo\nExec=foo\nIcon=foo.png\nCategories=GTK;\n",
+            &options,
+        )
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["error[DE111]: [Desktop Entry] Icon: icon name `foo.png` must not have the `.png` extension, unless it's an absolute path"],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn should_filter_baseline_issues() {
+        let mut diagnostics =
+            validate_str("[Desktop Entry]\nType=Foo\tBar\nName=Foo\nIcon=foo.png\n");
+
+        let mut baseline = Baseline::default();
+        baseline.insert("foo.desktop", &diagnostics[0]);
+
+        let baseline = Baseline::parse(&format!("# Known issues\n\n{baseline}")).unwrap();
+
+        assert_eq!(1, baseline.len());
+        assert_eq!(
+            "foo.desktop\tDE102\tDesktop Entry\tType\tunknown type `Foo\\\\tBar`\n",
+            baseline.to_string()
+        );
+
+        baseline.filter("bar.desktop", &mut diagnostics);
+        assert_eq!(2, diagnostics.len());
+
+        baseline.filter("foo.desktop", &mut diagnostics);
+        assert_eq!(
+            vec!["DE111"],
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.code)
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Err(BaselineError { line: 2 }),
+            Baseline::parse("a\tb\tc\td\te\nfoo\n")
+        );
+    }
+
+    #[test]
+    fn should_check_dbus_activatable_name() {
+        let input = "[Desktop Entry]\nType=Application\nName=Foo\nDBusActivatable=true\n";
+        let options = ValidateOptions::default();
+
+        let diagnostics = validate_str_with_file_id(input, Some("foo-bar.desktop"), &options)
+            .iter()
+            .map(Diagnostic::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["error[DE115]: [Desktop Entry] DBusActivatable: desktop file ID `foo-bar.desktop` is not a valid D-Bus name: not in reverse-DNS form, like `org.example.App`"],
+            diagnostics
+        );
+        assert_eq!(
+            Vec::<Diagnostic>::new(),
+            validate_str_with_file_id(input, Some("org.example.Foo.desktop"), &options)
+        );
+    }
+
+    #[test]
+    fn should_check_dbus_service_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("org.example.Foo.service"), "").unwrap();
+
+        let options = ValidateOptions {
+            dbus_service_dirs: Some(vec![dir.to_path_buf()]),
+            ..Default::default()
+        };
+        let input = "[Desktop Entry]\nType=Application\nName=Foo\nDBusActivatable=true\n";
+
+        assert_eq!(
+            Vec::<Diagnostic>::new(),
+            validate_str_with_file_id(input, Some("org.example.Foo.desktop"), &options)
+        );
+
+        let diagnostics =
+            validate_str_with_file_id(input, Some("org.example.Bar.desktop"), &options);
+
+        assert_eq!(
+            vec!["error[DE116]: [Desktop Entry] DBusActivatable: missing the D-Bus service file `org.example.Bar.service`"],
+            diagnostics
+                .iter()
+                .map(Diagnostic::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            (Some(4), Some(1)),
+            (diagnostics[0].line, diagnostics[0].column)
+        );
+    }
+}
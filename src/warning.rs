@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+/// Recoverable issue found while parsing a desktop file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseWarning {
+    /// Unknown escape sequence kept verbatim in the value.
+    UnknownEscape {
+        /// Line number of the entry, starting from 1.
+        line: usize,
+        sequence: String,
+    },
+    /// Invalid characters replaced while decoding the input.
+    InvalidEncoding {
+        /// Line number with the replaced characters, starting from 1.
+        line: usize,
+        encoding: String,
+    },
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::UnknownEscape { line, sequence } => {
+                write!(f, "line {line}: unknown escape sequence `{sequence}`")
+            }
+            ParseWarning::InvalidEncoding { line, encoding } => {
+                write!(f, "line {line}: replaced invalid {encoding} characters")
+            }
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Serialization of a desktop file.
+
+use std::fmt::{self, Display, Write};
+
+use crate::{
+    escape,
+    keys::{self, ValueType},
+    Comment, DesktopEntry, EntryMap, Key, KeyOrder, Locale, NonAsciiPolicy, Value, WriteError,
+    WriteOptions, BYTE_ORDER_MARK, DESKTOP_ACTION_PREFIX, DESKTOP_ENTRY_GROUP,
+};
+
+impl<'a> Display for DesktopEntry<'a> {
+    /// Writes the document in the desktop file format.
+    ///
+    /// The groups and entries are written in their order, each preceded by the comments and empty
+    /// lines found before it, so they follow it when the document is modified. The comments are
+    /// kept only if parsed with [`keep_comments`](crate::ParseOptions::keep_comments). The lines
+    /// are terminated with the [`LineEnding`](crate::LineEnding) of the document.
+    ///
+    /// The parsed values are written with their source text, like `1` instead of `1.0`, until
+    /// they are changed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DocumentWriter {
+            document: self,
+            options: &WriteOptions::default(),
+        }
+        .fmt(f)
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Writes the document in the desktop file format with the given options.
+    ///
+    /// See the [`Display`] implementation for the default options.
+    ///
+    /// # Errors
+    ///
+    /// If a string value contains a non ASCII character with [`NonAsciiPolicy::Reject`].
+    pub fn to_string_with_options(&self, options: &WriteOptions) -> Result<String, WriteError> {
+        if options.non_ascii_strings == NonAsciiPolicy::Reject {
+            self.check_ascii_strings()?;
+        }
+
+        Ok(DocumentWriter {
+            document: self,
+            options,
+        }
+        .to_string())
+    }
+
+    /// Checks that the string values contain only ASCII characters.
+    fn check_ascii_strings(&self) -> Result<(), WriteError> {
+        for (header, entries) in &self.groups {
+            for (key, value) in entries {
+                let Some(text) = string_text(header, key, value) else {
+                    continue;
+                };
+
+                if let Some((offset, character)) = text.char_indices().find(|(_, c)| !c.is_ascii())
+                {
+                    return Err(WriteError::NonAsciiString {
+                        group: header.to_string(),
+                        key: key.to_string(),
+                        character,
+                        offset,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a document with the options.
+struct DocumentWriter<'d, 'a> {
+    document: &'d DesktopEntry<'a>,
+    options: &'d WriteOptions,
+}
+
+impl<'d, 'a> DocumentWriter<'d, 'a> {
+    fn write_comments(&self, f: &mut fmt::Formatter<'_>, comments: &[Comment]) -> fmt::Result {
+        for comment in comments {
+            write!(f, "{comment}{}", self.document.line_ending.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'d, 'a> DocumentWriter<'d, 'a> {
+    /// Returns the text of a string value with the non ASCII characters replaced by the
+    /// [`NonAsciiPolicy`], [`None`] if it's written as it is.
+    fn ascii_string(&self, header: &str, key: &Key, value: &Value) -> Option<String> {
+        let text = string_text(header, key, value).filter(|text| !text.is_ascii())?;
+
+        let replaced = match self.options.non_ascii_strings {
+            NonAsciiPolicy::Keep | NonAsciiPolicy::Reject => return None,
+            NonAsciiPolicy::Escape => text.chars().fold(String::new(), |mut text, c| {
+                if c.is_ascii() {
+                    text.push(c);
+                } else {
+                    let mut bytes = [0; 4];
+
+                    for byte in c.encode_utf8(&mut bytes).bytes() {
+                        let _ = write!(text, "%{byte:02X}");
+                    }
+                }
+
+                text
+            }),
+            NonAsciiPolicy::Downgrade => text
+                .chars()
+                .map(|c| if c.is_ascii() { c } else { '?' })
+                .collect(),
+        };
+
+        Some(replaced)
+    }
+}
+
+/// Returns the text of a value of type `string`, a [`Value::String`] or the value of a `string`
+/// key of the registry.
+fn string_text<'v>(header: &str, key: &Key, value: &'v Value) -> Option<&'v str> {
+    let info = match header {
+        DESKTOP_ENTRY_GROUP => keys::lookup(key.name()),
+        header if header.starts_with(DESKTOP_ACTION_PREFIX) => keys::lookup_action(key.name()),
+        _ => None,
+    };
+
+    match value {
+        Value::String(text) => Some(text),
+        Value::LocaleString(text)
+            if info.is_some_and(|info| info.value_type == ValueType::String) =>
+        {
+            Some(text)
+        }
+        _ => None,
+    }
+}
+
+impl<'d, 'a> Display for DocumentWriter<'d, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let document = self.document;
+        let line_ending = document.line_ending.as_str();
+
+        if document.byte_order_mark {
+            f.write_char(BYTE_ORDER_MARK)?;
+        }
+
+        for (header, entries) in &document.groups {
+            self.write_comments(f, document.group_comments(header))?;
+            write!(f, "[{header}]{line_ending}")?;
+
+            let entries = match self.options.key_order {
+                KeyOrder::Document => entries.iter().collect(),
+                KeyOrder::Canonical => canonical_order(header, entries),
+            };
+
+            for (key, value) in entries {
+                self.write_comments(f, document.entry_comments(header, key))?;
+
+                match self.ascii_string(header, key, value) {
+                    Some(text) => write!(f, "{key}={}{line_ending}", escape::escape_value(&text))?,
+                    None => match document.sources.text(header, key, value) {
+                        Some(source) => write!(f, "{key}={source}{line_ending}")?,
+                        None => write!(f, "{key}={value}{line_ending}")?,
+                    },
+                }
+            }
+        }
+
+        self.write_comments(f, document.trailing_comments())
+    }
+}
+
+/// Returns the entries of a group in the [canonical order](KeyOrder::Canonical).
+fn canonical_order<'e, 'a>(
+    header: &str,
+    entries: &'e EntryMap<'a, 'a>,
+) -> Vec<(&'e Key<'a>, &'e Value<'a>)> {
+    let registry = match header {
+        DESKTOP_ENTRY_GROUP => keys::KEYS,
+        header if header.starts_with(DESKTOP_ACTION_PREFIX) => keys::ACTION_KEYS,
+        _ => &[],
+    };
+
+    let mut sorted: Vec<_> = entries.iter().collect();
+
+    sorted.sort_by_cached_key(|(key, _)| {
+        let name = key.name();
+
+        let (class, position) = match registry.iter().position(|info| info.name == name) {
+            Some(position) => (0, position),
+            None => {
+                // Keep the unknown keys in the order of their first entry
+                let first = entries
+                    .keys()
+                    .position(|entry| entry.name() == name)
+                    .unwrap_or_default();
+
+                (if name.starts_with("X-") { 2 } else { 1 }, first)
+            }
+        };
+
+        (class, position, key.locale().map(Locale::to_string))
+    });
+
+    sorted
+}
+
+impl<'a> Display for Comment<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Comment::Comment(comment) => f.write_str(comment),
+            Comment::EmptyLine { white_space } => {
+                f.write_str(white_space.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+impl<'a> Display for Key<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Simple(key) => f.write_str(key),
+            Key::Localized { key, locale } => write!(f, "{key}[{locale}]"),
+        }
+    }
+}
+
+impl<'a> Display for Locale<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lang)?;
+
+        if let Some(country) = &self.country {
+            write!(f, "_{country}")?;
+        }
+
+        if let Some(encoding) = &self.encoding {
+            write!(f, ".{encoding}")?;
+        }
+
+        if let Some(modifier) = &self.modifier {
+            write!(f, "@{modifier}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Display for Value<'a> {
+    /// Writes the value escaping the characters that can't be written verbatim.
+    ///
+    /// Numeric values are always written with a decimal point, like `1.0`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) | Value::LocaleString(value) => {
+                escape::write_escaped(f, value, false)
+            }
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::Numeric(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Checks if the value is written as the text, without allocating the written value.
+pub(crate) fn writes_as(value: &Value, text: &str) -> bool {
+    /// Matches the written parts with the rest of the text.
+    struct Matcher<'t>(&'t str);
+
+    impl Write for Matcher<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 = self.0.strip_prefix(s).ok_or(fmt::Error)?;
+
+            Ok(())
+        }
+    }
+
+    let mut matcher = Matcher(text);
+
+    write!(matcher, "{value}").is_ok() && matcher.0.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parse_with_comments(input: &str) -> Result<DesktopEntry<'_>, crate::ParseError> {
+        let options = crate::ParseOptions {
+            keep_comments: true,
+            ..Default::default()
+        };
+
+        DesktopEntry::parse_with_options(input, &options)
+    }
+
+    #[test]
+    fn should_write_example_file() {
+        let example_file = include_str!("../example/file.desktop");
+
+        let desktop_entry = parse_with_comments(example_file).unwrap();
+
+        assert_eq!(example_file, desktop_entry.to_string());
+    }
+
+    #[test]
+    fn should_keep_comments_with_modified_entries() {
+        let input = "# Main group\n[Desktop Entry]\n# The name\nName=Foo\nName[de]=Fu\n\n# Run it\nExec=foo\n\n[Desktop Action new]\nExec=foo --new\n# End\n";
+
+        let mut desktop_entry = parse_with_comments(input).unwrap();
+
+        desktop_entry.set_localized("Desktop Entry", "Icon", None, Value::String("foo".into()));
+        desktop_entry.remove_localizations("Desktop Entry", "Name");
+        desktop_entry.groups.swap_indices(0, 1);
+
+        assert_eq!(
+            "\n[Desktop Action new]\nExec=foo --new\n# Main group\n[Desktop Entry]\n# The name\nName=Foo\n\n# Run it\nExec=foo\nIcon=foo\n# End\n",
+            desktop_entry.to_string()
+        );
+    }
+
+    #[test]
+    fn should_write_set_comments() {
+        let mut desktop_entry = parse_with_comments(
+            "[Desktop Entry]\r\nName=Foo\r\n# Old\r\nExec=foo\r\n\r\n[Desktop Action new]\r\nExec=foo --new\r\n",
+        )
+        .unwrap();
+
+        let exec = Key::Simple("Exec".into());
+
+        assert_eq!(
+            [Comment::Comment("# Old".into())],
+            desktop_entry.entry_comments("Desktop Entry", &exec)
+        );
+        assert_eq!(
+            [Comment::EmptyLine { white_space: None }],
+            desktop_entry.group_comments("Desktop Action new")
+        );
+
+        desktop_entry.set_group_comments(
+            "Desktop Entry",
+            vec![Comment::Comment("# Generated".into())],
+        );
+        desktop_entry.set_entry_comments("Desktop Entry", exec, Vec::new());
+        desktop_entry.set_trailing_comments(vec![
+            Comment::EmptyLine {
+                white_space: Some("  ".into()),
+            },
+            Comment::Comment("# End".into()),
+        ]);
+
+        assert_eq!(
+            "# Generated\r\n[Desktop Entry]\r\nName=Foo\r\nExec=foo\r\n\r\n[Desktop Action new]\r\nExec=foo --new\r\n  \r\n# End\r\n",
+            desktop_entry.to_string()
+        );
+    }
+
+    #[test]
+    fn should_write_keys_in_canonical_order() {
+        let desktop_entry = parse_with_comments(
+            "[Desktop Entry]\nX-Foo=bar\nExec=foo\nName[it]=Fuu\nCustom=1\nName=Foo\n# The type\nType=Application\nName[de]=Fu\nVersion=1.5\n[Desktop Action new]\nExec=foo --new\nName=New\n",
+        )
+        .unwrap();
+
+        let options = WriteOptions {
+            key_order: KeyOrder::Canonical,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            "[Desktop Entry]\n# The type\nType=Application\nVersion=1.5\nName=Foo\nName[de]=Fu\nName[it]=Fuu\nExec=foo\nCustom=1\nX-Foo=bar\n[Desktop Action new]\nName=New\nExec=foo --new\n",
+            desktop_entry.to_string_with_options(&options).unwrap()
+        );
+        assert_eq!(
+            Some(&Value::Numeric(1.0)),
+            desktop_entry.get("Desktop Entry", "Custom")
+        );
+    }
+
+    #[test]
+    fn should_apply_non_ascii_policy() {
+        let desktop_entry =
+            DesktopEntry::parse("[Desktop Entry]\nName=Café\nExec=café --new\n").unwrap();
+
+        let write = |non_ascii_strings| {
+            desktop_entry.to_string_with_options(&WriteOptions {
+                non_ascii_strings,
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(
+            Ok("[Desktop Entry]\nName=Café\nExec=café --new\n".to_string()),
+            write(NonAsciiPolicy::Keep)
+        );
+        assert_eq!(
+            Err(WriteError::NonAsciiString {
+                group: "Desktop Entry".to_string(),
+                key: "Exec".to_string(),
+                character: 'é',
+                offset: 3,
+            }),
+            write(NonAsciiPolicy::Reject)
+        );
+        assert_eq!(
+            Ok("[Desktop Entry]\nName=Café\nExec=caf%C3%A9 --new\n".to_string()),
+            write(NonAsciiPolicy::Escape)
+        );
+        assert_eq!(
+            Ok("[Desktop Entry]\nName=Café\nExec=caf? --new\n".to_string()),
+            write(NonAsciiPolicy::Downgrade)
+        );
+    }
+
+    #[test]
+    fn should_escape_value() {
+        let value = Value::LocaleString(Cow::from("  a\\b\n\tc d"));
+
+        assert_eq!(r"\s\sa\\b\n\tc d", value.to_string());
+    }
+
+    #[test]
+    fn should_write_back_parsed_values() {
+        let input = "\u{FEFF}[Desktop Entry]\r\nName[sr_YU.UTF-8@Latin]=\\sFoo\\;\r\nNoDisplay=true\r\nVersion=1.5\r\n";
+
+        let mut desktop_entry = DesktopEntry::parse(input).unwrap();
+
+        assert_eq!(input, desktop_entry.to_string());
+
+        desktop_entry.set_localized(
+            "Desktop Entry",
+            "Name",
+            Locale::parse("sr_YU.UTF-8@Latin"),
+            Value::String(Cow::from(" Bar;")),
+        );
+
+        assert_eq!(
+            "\u{FEFF}[Desktop Entry]\r\nName[sr_YU.UTF-8@Latin]=\\sBar;\r\nNoDisplay=true\r\nVersion=1.5\r\n",
+            desktop_entry.to_string()
+        );
+    }
+
+    #[test]
+    fn should_write_back_the_source_of_parsed_values() {
+        let input = "[Desktop Entry]\nKeywords=a\\;b;c;\nName=007\nComment=2024\nX-N=1e3\nX-Nan=nan\nX-Number=-1.50\nExec=foo\\sbar\nVersion=1.0\n";
+
+        let mut desktop_entry = DesktopEntry::parse(input).unwrap();
+
+        assert_eq!(
+            Some(&Value::String(Cow::from("a;b;c;"))),
+            desktop_entry.get("Desktop Entry", "Keywords")
+        );
+        assert_eq!(
+            Some(&Value::Numeric(7.0)),
+            desktop_entry.get("Desktop Entry", "Name")
+        );
+        assert_eq!(input, desktop_entry.to_string());
+
+        desktop_entry.set_localized("Desktop Entry", "Name", None, Value::Numeric(8.0));
+        desktop_entry.set_localized("Desktop Entry", "Comment", None, Value::Numeric(2024.0));
+
+        assert_eq!(
+            "[Desktop Entry]\nKeywords=a\\;b;c;\nName=8.0\nComment=2024\nX-N=1e3\nX-Nan=nan\nX-Number=-1.50\nExec=foo\\sbar\nVersion=1.0\n",
+            desktop_entry.to_string()
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Parses a corpus of desktop files.
+//!
+//! Set `XDG_DESKTOP_ENTRY_CORPUS` to a directory, like `/usr/share/applications`, to check the
+//! files installed on the system.
+
+use std::{env, fs};
+
+use pretty_assertions::assert_eq;
+use xdg_desktop_entry::{corpus::check_corpus, ParseOptions};
+
+#[test]
+fn should_parse_example_corpus() {
+    let report = check_corpus("example", &ParseOptions::default()).unwrap();
+
+    assert_eq!(1, report.files.len());
+    assert!(report.is_ok(), "{report}");
+}
+
+#[test]
+fn should_report_failures() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("valid.desktop"), "[Desktop Entry]\nName=Foo\n").unwrap();
+    fs::write(
+        dir.join("nested/invalid.desktop"),
+        "[Desktop Entry]\nName\n",
+    )
+    .unwrap();
+    fs::write(dir.join("ignored.txt"), "Name\n").unwrap();
+
+    let report = check_corpus(dir, &ParseOptions::default());
+
+    let report = report.unwrap();
+    let failures = report
+        .failures()
+        .map(|file| file.path.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(2, report.files.len());
+    assert_eq!(vec![dir.join("nested/invalid.desktop")], failures);
+}
+
+#[test]
+fn should_parse_system_corpus() {
+    let Some(dir) = env::var_os("XDG_DESKTOP_ENTRY_CORPUS") else {
+        return;
+    };
+
+    let report = check_corpus(dir, &ParseOptions::default()).unwrap();
+
+    print!("{report}");
+
+    assert!(report.is_ok(), "{report}");
+}